@@ -0,0 +1,243 @@
+//!
+//! Aim Driver.
+//!
+
+use alloc::rc::Rc;
+use glam::{Mat4, Quat, Vec3, Vec3A};
+
+use crate::base::{OzzError, OzzObj};
+use crate::ik_aim_job::IKAimJob;
+use crate::track::Track;
+use crate::track_sampling_job::TrackSamplingJob;
+
+///
+/// Aims a joint (like a head) at a moving target, defined by a `Track<Vec3>` curve in model-space.
+///
+/// Each update samples the target track at the current ratio, then runs `IKAimJob`. Aim parameters
+/// (forward, up, pole vector...) are set on the inner job, see `AimDriver::aim_job_mut`.
+///
+/// The target track can have gaps, ranges without target: an empty track, or non-finite (NaN) keyframe
+/// values, usually step keyframes. Through a gap the driver keeps aiming at the last valid target while
+/// the weight ramps down to 0, then ramps up again once the track has a target.
+///
+#[derive(Debug)]
+pub struct AimDriver<T = Rc<Track<Vec3>>>
+where
+    T: OzzObj<Track<Vec3>>,
+{
+    target_job: TrackSamplingJob<Vec3, T>,
+    aim_job: IKAimJob,
+    weight: f32,
+    ramp_duration: f32,
+    current_weight: f32,
+    has_target: bool,
+}
+
+impl<T> AimDriver<T>
+where
+    T: OzzObj<Track<Vec3>>,
+{
+    /// Creates an `AimDriver` of `target_track`, with a full weight and a 0.2 second ramp.
+    pub fn new(target_track: T) -> AimDriver<T> {
+        let mut target_job = TrackSamplingJob::default();
+        target_job.set_track(target_track);
+        return AimDriver {
+            target_job,
+            aim_job: IKAimJob::default(),
+            weight: 1.0,
+            ramp_duration: 0.2,
+            current_weight: 0.0,
+            has_target: false,
+        };
+    }
+
+    /// Gets target track of `AimDriver`.
+    #[inline]
+    pub fn target_track(&self) -> &T {
+        return self.target_job.track().unwrap();
+    }
+
+    /// Gets the inner `IKAimJob` of `AimDriver`.
+    #[inline]
+    pub fn aim_job(&self) -> &IKAimJob {
+        return &self.aim_job;
+    }
+
+    /// Gets the mutable inner `IKAimJob` of `AimDriver`.
+    ///
+    /// Target, joint and weight are overwritten by `update`, other parameters are kept.
+    #[inline]
+    pub fn aim_job_mut(&mut self) -> &mut IKAimJob {
+        return &mut self.aim_job;
+    }
+
+    /// Gets weight of `AimDriver`.
+    #[inline]
+    pub fn weight(&self) -> f32 {
+        return self.weight;
+    }
+
+    /// Sets weight of `AimDriver`.
+    ///
+    /// Weight of the aim correction when the track has a target, in range 0.0-1.0. See
+    /// `IKAimJob::set_weight`.
+    #[inline]
+    pub fn set_weight(&mut self, weight: f32) {
+        self.weight = weight.clamp(0.0, 1.0);
+    }
+
+    /// Gets ramp duration of `AimDriver`.
+    #[inline]
+    pub fn ramp_duration(&self) -> f32 {
+        return self.ramp_duration;
+    }
+
+    /// Sets ramp duration of `AimDriver`.
+    ///
+    /// Time in seconds to ramp the weight from 0 to 1 (or 1 to 0) when entering (or leaving) a gap of the
+    /// target track. 0 switches the weight instantly.
+    #[inline]
+    pub fn set_ramp_duration(&mut self, duration: f32) {
+        self.ramp_duration = duration.max(0.0);
+    }
+
+    /// Gets **output** current weight of `AimDriver`, the ramped weight of the last update.
+    #[inline]
+    pub fn current_weight(&self) -> f32 {
+        return self.current_weight;
+    }
+
+    /// Gets **output** has target of `AimDriver`, true if the track had a target at the last update.
+    #[inline]
+    pub fn has_target(&self) -> bool {
+        return self.has_target;
+    }
+
+    /// Gets **output** target of `AimDriver`, the last valid target sampled from the track.
+    #[inline]
+    pub fn target(&self) -> Vec3A {
+        return self.aim_job.target();
+    }
+
+    /// Gets **output** joint correction of `AimDriver`. See `IKAimJob::joint_correction`.
+    #[inline]
+    pub fn joint_correction(&self) -> Quat {
+        return self.aim_job.joint_correction();
+    }
+
+    /// Samples the target track at `ratio`, ramps the weight over `dt` seconds, then aims `joint`.
+    ///
+    /// * `ratio` - Time ratio of the target track, in range 0.0-1.0.
+    /// * `dt` - Elapsed time since the last update in seconds, for the weight ramp.
+    /// * `joint` - Model-space matrix of the aiming joint.
+    ///
+    /// Returns the local-space joint correction, see `IKAimJob::joint_correction`.
+    pub fn update(&mut self, ratio: f32, dt: f32, joint: Mat4) -> Result<Quat, OzzError> {
+        self.target_job.set_ratio(ratio);
+        self.target_job.run()?;
+        let track_has_keys = self.target_job.track().is_some_and(|track| track.obj().key_count() > 0);
+        let target = self.target_job.result();
+        self.has_target = track_has_keys && target.is_finite();
+        if self.has_target {
+            self.aim_job.set_target(target.into());
+        }
+
+        let goal = if self.has_target { self.weight } else { 0.0 };
+        if self.ramp_duration > 0.0 {
+            let step = dt.max(0.0) / self.ramp_duration;
+            self.current_weight += (goal - self.current_weight).clamp(-step, step);
+        } else {
+            self.current_weight = goal;
+        }
+
+        self.aim_job.set_joint(joint);
+        self.aim_job.set_weight(self.current_weight);
+        if self.current_weight <= 0.0 {
+            self.aim_job.clear_joint_correction();
+            return Ok(Quat::IDENTITY);
+        }
+        self.aim_job.run()?;
+        return Ok(self.aim_job.joint_correction());
+    }
+}
+
+#[cfg(test)]
+mod aim_driver_tests {
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::track::{TrackBuilder, TrackInterpolation};
+
+    fn angle_to_target(correction: Quat, head: Mat4, target: Vec3) -> f32 {
+        let forward = head.transform_vector3(correction * Vec3::X);
+        return forward.angle_between(target - head.w_axis.truncate());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_moving_target() {
+        // The target moves from the left to the right of the head, ahead of it.
+        let mut builder = TrackBuilder::new();
+        builder.add_keyframe(0.0, Vec3::new(2.0, 1.5, 2.0), TrackInterpolation::Linear);
+        builder.add_keyframe(1.0, Vec3::new(2.0, 1.5, -2.0), TrackInterpolation::Linear);
+        let head = Mat4::from_translation(Vec3::new(0.0, 1.5, 0.0));
+
+        let mut driver = AimDriver::new(Rc::new(builder.build().unwrap()));
+        driver.set_ramp_duration(0.0);
+        for ratio in [0.0, 0.25, 0.5, 0.75, 1.0] {
+            let target = Vec3::new(2.0, 1.5, 2.0 - 4.0 * ratio);
+            let correction = driver.update(ratio, 0.1, head).unwrap();
+            assert!(driver.has_target());
+            assert!(driver.target().abs_diff_eq(target.into(), 1e-5));
+            assert!(angle_to_target(correction, head, target) < 1e-3, "ratio={}", ratio);
+        }
+
+        // Half weight, the head lags halfway between its forward and the target.
+        driver.set_weight(0.5);
+        for ratio in [0.0, 0.25, 0.75, 1.0] {
+            let target = Vec3::new(2.0, 1.5, 2.0 - 4.0 * ratio);
+            let correction = driver.update(ratio, 0.1, head).unwrap();
+            let full_angle = angle_to_target(Quat::IDENTITY, head, target);
+            let angle = angle_to_target(correction, head, target);
+            assert!((angle - full_angle * 0.5).abs() < 1e-3, "ratio={}", ratio);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_gaps() {
+        // No target in range [0.5, 0.75).
+        let target = Vec3::new(2.0, 0.0, 2.0);
+        let mut builder = TrackBuilder::new();
+        builder.add_keyframe(0.0, target, TrackInterpolation::Step);
+        builder.add_keyframe(0.5, Vec3::NAN, TrackInterpolation::Step);
+        builder.add_keyframe(0.75, target, TrackInterpolation::Step);
+        let head = Mat4::IDENTITY;
+
+        let mut driver = AimDriver::new(Rc::new(builder.build().unwrap()));
+        driver.set_ramp_duration(0.2);
+        driver.update(0.0, 0.1, head).unwrap();
+        assert!((driver.current_weight() - 0.5).abs() < 1e-6);
+        driver.update(0.1, 0.1, head).unwrap();
+        assert_eq!(driver.current_weight(), 1.0);
+        driver.update(0.2, 0.5, head).unwrap();
+        assert_eq!(driver.current_weight(), 1.0);
+
+        let correction = driver.update(0.5, 0.1, head).unwrap();
+        assert!(!driver.has_target());
+        assert!(driver.target().abs_diff_eq(target.into(), 1e-6));
+        assert!((driver.current_weight() - 0.5).abs() < 1e-6);
+        let angle = angle_to_target(correction, head, target);
+        assert!((angle - std::f32::consts::FRAC_PI_8).abs() < 1e-3);
+        assert_eq!(driver.update(0.6, 0.1, head).unwrap(), Quat::IDENTITY);
+        assert_eq!(driver.current_weight(), 0.0);
+
+        driver.update(0.75, 0.1, head).unwrap();
+        assert!(driver.has_target());
+        assert!((driver.current_weight() - 0.5).abs() < 1e-6);
+
+        let mut driver = AimDriver::new(Rc::new(Track::default()));
+        assert_eq!(driver.update(0.5, 1.0, head).unwrap(), Quat::IDENTITY);
+        assert!(!driver.has_target());
+    }
+}
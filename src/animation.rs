@@ -2,20 +2,143 @@
 //! Animation data structure definition.
 //!
 
-use glam::{Quat, Vec3, Vec4};
-use std::io::Read;
-use std::mem;
-use std::simd::prelude::*;
-use std::simd::*;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::mem;
+use core::simd::prelude::*;
+use glam::{Mat4, Quat, Vec3, Vec4};
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
+
+#[cfg(feature = "std")]
+use crate::archive::{Archive, ArchiveRead, ArchiveWrite, ArchiveWriter};
+use crate::base::{OzzError, OzzObj, SKELETON_MAX_JOINTS};
+use crate::local_to_model_job::{LocalToModelJob, LocalToModelJobRef};
+#[cfg(not(feature = "std"))]
+use crate::math::F32Ext;
+use crate::math::{
+    f16_canonicalize, f16_to_f32, f32_canonicalize, f32_to_f16, fx4, ix4, simd_f16_to_f32, SoaQuat, SoaTransform,
+    SoaVec3, StdFloat,
+};
+use crate::pose_utils::{pose_difference, soa_to_aos_iter, PoseDiff};
+use crate::sampling_job::{SamplingContext, SamplingJob};
+use crate::skeleton::Skeleton;
 
-use crate::archive::{Archive, ArchiveRead};
-use crate::base::OzzError;
-use crate::math::{f16_to_f32, fx4, ix4, simd_f16_to_f32, SoaQuat, SoaVec3};
+///
+/// Quaternion quantized like ozz `Animation` rotation keys, 7 bytes worth of data.
+///
+/// The largest component (in absolute value) of the normalized quaternion is dropped, as it can be
+/// restored from the 3 others. Only its index (`largest`, 0 to 3 for x, y, z, w) and its sign (`sign`,
+/// true for negative) are kept. The 3 smallest components, in x, y, z, w order, are in [-1/sqrt(2),
+/// 1/sqrt(2)] and are quantized to signed 16 bits integers: `value = round(component * 32767 * sqrt(2))`.
+///
+/// In an animation key, `largest` and `sign` are packed in the 3 low bits of the key bit field
+/// (`largest << 1 | sign`), the 13 high bits being the track index.
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompressedQuat {
+    pub largest: u8,
+    pub sign: bool,
+    pub value: [i16; 3],
+}
+
+///
+/// Vec3 quantized like ozz `Animation` translation and scale keys.
+///
+/// Each component is stored as an IEEE 754 half float (1 sign bit, 5 exponent bits, 10 mantissa bits),
+/// in x, y, z order. Values out of half float range are saturated to infinity.
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompressedVec3 {
+    pub value: [u16; 3],
+}
+
+/// Quantizes a normalized quaternion, matching ozz animation rotation keys. See `CompressedQuat`.
+///
+/// Decompressed components are within about 3e-5 of the original ones (quantization step is 2.2e-5).
+pub fn compress_quat(quat: Quat) -> CompressedQuat {
+    const FLOAT_2_INT: f32 = 32767.0f32 * core::f32::consts::SQRT_2;
+
+    let cpnt = quat.to_array();
+    let mut largest = 0;
+    for idx in 1..4 {
+        if cpnt[idx].abs() > cpnt[largest].abs() {
+            largest = idx;
+        }
+    }
+
+    let mut value = [0i16; 3];
+    let smallest = (0..4).filter(|idx| *idx != largest);
+    for (dst, idx) in value.iter_mut().zip(smallest) {
+        *dst = (cpnt[idx] * FLOAT_2_INT + 0.5).floor().clamp(-32767.0, 32767.0) as i16;
+    }
+
+    return CompressedQuat {
+        largest: largest as u8,
+        sign: cpnt[largest] < 0.0,
+        value,
+    };
+}
+
+/// Restores a quaternion quantized by `compress_quat`, matching ozz animation rotation keys.
+pub fn decompress_quat(compressed: CompressedQuat) -> Quat {
+    const MAPPING: [[usize; 4]; 4] = [[0, 0, 1, 2], [0, 0, 1, 2], [0, 1, 0, 2], [0, 1, 2, 0]];
+
+    let largest = (compressed.largest & 0x3) as usize;
+    let mask = &MAPPING[largest];
+    let mut cmp_keys = [
+        compressed.value[mask[0]],
+        compressed.value[mask[1]],
+        compressed.value[mask[2]],
+        compressed.value[mask[3]],
+    ];
+    cmp_keys[largest] = 0;
+
+    const INT_2_FLOAT: f32 = 1.0f32 / (32767.0f32 * core::f32::consts::SQRT_2);
+    let mut cpnt = Vec4::new(
+        (cmp_keys[0] as f32) * INT_2_FLOAT,
+        (cmp_keys[1] as f32) * INT_2_FLOAT,
+        (cmp_keys[2] as f32) * INT_2_FLOAT,
+        (cmp_keys[3] as f32) * INT_2_FLOAT,
+    );
+
+    let dot = cpnt[0] * cpnt[0] + cpnt[1] * cpnt[1] + cpnt[2] * cpnt[2] + cpnt[3] * cpnt[3];
+    let ww0 = f32::max(1e-16f32, 1f32 - dot);
+    let w0 = ww0.sqrt();
+    let restored = if compressed.sign { -w0 } else { w0 };
+
+    cpnt[largest] = restored;
+    return Quat::from_vec4(cpnt);
+}
+
+/// Quantizes a vec3 to half floats, matching ozz animation translation and scale keys. See
+/// `CompressedVec3`.
+///
+/// The relative error of a component is at most 2^-11 (about 4.9e-4) in half float normal range.
+pub fn compress_vec3(vec: Vec3) -> CompressedVec3 {
+    return CompressedVec3 {
+        value: [f32_to_f16(vec.x), f32_to_f16(vec.y), f32_to_f16(vec.z)],
+    };
+}
+
+/// Restores a vec3 quantized by `compress_vec3`, matching ozz animation translation and scale keys.
+pub fn decompress_vec3(compressed: CompressedVec3) -> Vec3 {
+    return Vec3::new(
+        f16_to_f32(compressed.value[0]),
+        f16_to_f32(compressed.value[1]),
+        f16_to_f32(compressed.value[2]),
+    );
+}
 
 /// Float3 key for `Animation` track.
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", archive(check_bytes), archive_attr(repr(C)))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Float3Key {
     pub ratio: f32,
@@ -28,12 +151,17 @@ impl Float3Key {
         return Float3Key { ratio, track, value };
     }
 
+    /// Creates a key from an uncompressed value, stored as half floats.
+    pub fn compress(ratio: f32, track: u16, value: Vec3) -> Float3Key {
+        return Float3Key {
+            ratio,
+            track,
+            value: compress_vec3(value).value,
+        };
+    }
+
     pub fn decompress(&self) -> Vec3 {
-        return Vec3::new(
-            f16_to_f32(self.value[0]),
-            f16_to_f32(self.value[1]),
-            f16_to_f32(self.value[2]),
-        );
+        return decompress_vec3(CompressedVec3 { value: self.value });
     }
 
     pub fn simd_decompress(k0: &Float3Key, k1: &Float3Key, k2: &Float3Key, k3: &Float3Key, soa: &mut SoaVec3) {
@@ -43,6 +171,7 @@ impl Float3Key {
     }
 }
 
+#[cfg(feature = "std")]
 impl ArchiveRead<Float3Key> for Float3Key {
     fn read<R: Read>(archive: &mut Archive<R>) -> Result<Float3Key, OzzError> {
         let ratio: f32 = archive.read()?;
@@ -52,10 +181,20 @@ impl ArchiveRead<Float3Key> for Float3Key {
     }
 }
 
+#[cfg(feature = "std")]
+impl ArchiveWrite<Float3Key> for Float3Key {
+    fn write<W: Write>(archive: &mut ArchiveWriter<W>, val: &Float3Key) -> Result<(), OzzError> {
+        archive.write(&val.ratio)?;
+        archive.write(&val.track)?;
+        return archive.write_slice(&val.value);
+    }
+}
+
 /// Quaternion key for `Animation` track.
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", archive(check_bytes), archive_attr(repr(C)))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct QuaternionKey {
     pub ratio: f32,
@@ -75,6 +214,19 @@ impl QuaternionKey {
         };
     }
 
+    /// Creates a key from an uncompressed normalized quaternion.
+    ///
+    /// The largest component is dropped (only its sign is stored) and the 3 smallest ones are quantized
+    /// to 16 bits.
+    pub fn compress(ratio: f32, track: u16, value: Quat) -> QuaternionKey {
+        let compressed = compress_quat(value);
+        return QuaternionKey {
+            ratio,
+            bit_field: ((track & 0x1FFF) << 3) | ((compressed.largest as u16) << 1) | (compressed.sign as u16),
+            value: compressed.value,
+        };
+    }
+
     pub fn track(&self) -> u16 {
         return self.bit_field >> 3;
     }
@@ -88,32 +240,11 @@ impl QuaternionKey {
     }
 
     pub fn decompress(&self) -> Quat {
-        const MAPPING: [[usize; 4]; 4] = [[0, 0, 1, 2], [0, 0, 1, 2], [0, 1, 0, 2], [0, 1, 2, 0]];
-
-        let mask = &MAPPING[self.largest() as usize];
-        let mut cmp_keys = [
-            self.value[mask[0]],
-            self.value[mask[1]],
-            self.value[mask[2]],
-            self.value[mask[3]],
-        ];
-        cmp_keys[self.largest() as usize] = 0;
-
-        const INT_2_FLOAT: f32 = 1.0f32 / (32767.0f32 * core::f32::consts::SQRT_2);
-        let mut cpnt = Vec4::new(
-            (cmp_keys[0] as f32) * INT_2_FLOAT,
-            (cmp_keys[1] as f32) * INT_2_FLOAT,
-            (cmp_keys[2] as f32) * INT_2_FLOAT,
-            (cmp_keys[3] as f32) * INT_2_FLOAT,
-        );
-
-        let dot = cpnt[0] * cpnt[0] + cpnt[1] * cpnt[1] + cpnt[2] * cpnt[2] + cpnt[3] * cpnt[3];
-        let ww0 = f32::max(1e-16f32, 1f32 - dot);
-        let w0 = ww0.sqrt();
-        let restored = if self.sign() == 0 { w0 } else { -w0 };
-
-        cpnt[self.largest() as usize] = restored;
-        return Quat::from_vec4(cpnt);
+        return decompress_quat(CompressedQuat {
+            largest: self.largest() as u8,
+            sign: self.sign() != 0,
+            value: self.value,
+        });
     }
 
     #[rustfmt::skip]
@@ -176,6 +307,7 @@ impl QuaternionKey {
     }
 }
 
+#[cfg(feature = "std")]
 impl ArchiveRead<QuaternionKey> for QuaternionKey {
     fn read<R: Read>(archive: &mut Archive<R>) -> Result<QuaternionKey, OzzError> {
         let ratio: f32 = archive.read()?;
@@ -192,6 +324,17 @@ impl ArchiveRead<QuaternionKey> for QuaternionKey {
     }
 }
 
+#[cfg(feature = "std")]
+impl ArchiveWrite<QuaternionKey> for QuaternionKey {
+    fn write<W: Write>(archive: &mut ArchiveWriter<W>, val: &QuaternionKey) -> Result<(), OzzError> {
+        archive.write(&val.ratio)?;
+        archive.write(&val.track())?;
+        archive.write(&(val.largest() as u8))?;
+        archive.write(&(val.sign() as u8))?;
+        return archive.write_slice(&val.value);
+    }
+}
+
 ///
 /// Defines a runtime skeletal animation clip.
 ///
@@ -207,6 +350,7 @@ impl ArchiveRead<QuaternionKey> for QuaternionKey {
 ///
 #[derive(Debug, Default)]
 #[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", archive(check_bytes))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct Animation {
@@ -263,6 +407,7 @@ impl Animation {
     }
 
     /// Reads an `AnimationMeta` from an `Archive`.
+    #[cfg(feature = "std")]
     pub fn read_meta(archive: &mut Archive<impl Read>) -> Result<AnimationMeta, OzzError> {
         if archive.tag() != Self::tag() {
             return Err(OzzError::InvalidTag);
@@ -296,6 +441,7 @@ impl Animation {
     }
 
     /// Reads an `Animation` from an `Archive`.
+    #[cfg(feature = "std")]
     pub fn from_archive(archive: &mut Archive<impl Read>) -> Result<Animation, OzzError> {
         let meta = Animation::read_meta(archive)?;
 
@@ -313,8 +459,32 @@ impl Animation {
         });
     }
 
+    /// Writes an `Animation` to an `ArchiveWriter`, readable by `Animation::from_archive`.
+    #[cfg(feature = "std")]
+    pub fn to_archive(&self, archive: &mut ArchiveWriter<impl Write>) -> Result<(), OzzError> {
+        if archive.tag() != Self::tag() {
+            return Err(OzzError::InvalidTag);
+        }
+        if archive.version() != Self::version() {
+            return Err(OzzError::InvalidVersion);
+        }
+
+        archive.write(&self.duration)?;
+        archive.write(&(self.num_tracks as i32))?;
+        archive.write(&(self.name.len() as i32))?;
+        archive.write(&(self.translations.len() as i32))?;
+        archive.write(&(self.rotations.len() as i32))?;
+        archive.write(&(self.scales.len() as i32))?;
+        archive.write_slice(self.name.as_bytes())?;
+
+        archive.write_slice(&self.translations)?;
+        archive.write_slice(&self.rotations)?;
+        archive.write_slice(&self.scales)?;
+        return Ok(());
+    }
+
     /// Reads an `Animation` from a file path.
-    #[cfg(not(feature = "wasm"))]
+    #[cfg(all(feature = "std", not(feature = "wasm")))]
     pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> Result<Animation, OzzError> {
         let mut archive = Archive::from_path(path)?;
         return Animation::from_archive(&mut archive);
@@ -326,6 +496,29 @@ impl Animation {
         let mut archive = Archive::from_path(path)?;
         return Animation::from_archive(&mut archive);
     }
+
+    /// Writes an `Animation` to a file path.
+    #[cfg(all(feature = "std", not(feature = "wasm")))]
+    pub fn to_path<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), OzzError> {
+        let mut archive = ArchiveWriter::from_path(path, Self::tag(), Self::version())?;
+        return self.to_archive(&mut archive);
+    }
+
+    /// Canonicalizes duration and keyframes floats, `-0.0` becomes `0.0` and all NaN share the same bits.
+    /// Half float values of translations and scales are canonicalized the same way.
+    ///
+    /// Opt-in, archives store floats as is. Call it before `to_archive` so that animations with the same
+    /// values give the same bytes on all platforms, as expected by pipelines hashing serialized assets.
+    pub fn canonicalize_floats(&mut self) {
+        self.duration = f32_canonicalize(self.duration);
+        for key in self.translations.iter_mut().chain(self.scales.iter_mut()) {
+            key.ratio = f32_canonicalize(key.ratio);
+            key.value = key.value.map(f16_canonicalize);
+        }
+        for key in self.rotations.iter_mut() {
+            key.ratio = f32_canonicalize(key.ratio);
+        }
+    }
 }
 
 impl Animation {
@@ -377,13 +570,733 @@ impl Animation {
     pub fn scales(&self) -> &[Float3Key] {
         return &self.scales;
     }
+
+    /// Iterates the translation keys of `joint`, as `(time, value)` pairs in time order. Time is in seconds,
+    /// from 0 to the animation duration. Returns an empty iterator if `joint` is out of range.
+    pub fn iter_translation_keys(&self, joint: i16) -> impl Iterator<Item = (f32, Vec3)> + '_ {
+        let track = self.key_track(joint);
+        return (self.translations.iter())
+            .filter(move |key| Some(key.track) == track)
+            .map(|key| (key.ratio * self.duration, key.decompress()));
+    }
+
+    /// Iterates the rotation keys of `joint`, as `(time, value)` pairs in time order. Time is in seconds,
+    /// from 0 to the animation duration. Returns an empty iterator if `joint` is out of range.
+    pub fn iter_rotation_keys(&self, joint: i16) -> impl Iterator<Item = (f32, Quat)> + '_ {
+        let track = self.key_track(joint);
+        return (self.rotations.iter())
+            .filter(move |key| Some(key.track()) == track)
+            .map(|key| (key.ratio * self.duration, key.decompress()));
+    }
+
+    /// Iterates the scale keys of `joint`, as `(time, value)` pairs in time order. Time is in seconds,
+    /// from 0 to the animation duration. Returns an empty iterator if `joint` is out of range.
+    pub fn iter_scale_keys(&self, joint: i16) -> impl Iterator<Item = (f32, Vec3)> + '_ {
+        let track = self.key_track(joint);
+        return (self.scales.iter())
+            .filter(move |key| Some(key.track) == track)
+            .map(|key| (key.ratio * self.duration, key.decompress()));
+    }
+
+    /// Gets the skeleton joint of each animation track, indexed by track.
+    ///
+    /// Ozz archives don't store a mapping: tracks are ordered like the joints of the skeleton the animation
+    /// was built for, so track `i` animates joint `i`. Custom samplers or analysis can use this instead of
+    /// relying on the convention.
+    pub fn track_to_joint(&self) -> Vec<i16> {
+        return (0..self.num_tracks).map(|track| track as i16).collect();
+    }
+
+    fn key_track(&self, joint: i16) -> Option<u16> {
+        if joint < 0 || joint as usize >= self.num_tracks {
+            return None;
+        }
+        return Some(joint as u16);
+    }
+
+    /// Tests if the animation animates `joint`, i.e. the translation, rotation or scale of its track
+    /// changes over time. A constant track (like the rest pose of a joint the clip doesn't drive) isn't
+    /// animated. Returns false if `joint` is out of range.
+    pub fn animates_joint(&self, joint: i16) -> bool {
+        if joint < 0 || joint as usize >= self.num_tracks {
+            return false;
+        }
+        fn varies<T: PartialEq>(mut values: impl Iterator<Item = T>) -> bool {
+            let first = values.next();
+            return values.any(|value| Some(value) != first);
+        }
+
+        let track = joint as u16;
+        let translations = self.translations.iter().filter(|key| key.track == track);
+        // Keys of a track share their track bits, remaining bits are the compressed value.
+        let rotations = self.rotations.iter().filter(|key| key.track() == track);
+        let scales = self.scales.iter().filter(|key| key.track == track);
+        return varies(translations.map(|key| key.value))
+            || varies(rotations.map(|key| (key.bit_field, key.value)))
+            || varies(scales.map(|key| key.value));
+    }
+
+    /// Gets the joints the animation animates, in joint order, see `animates_joint`.
+    ///
+    /// It iterates all keyframes of each track, so compute it once per animation, like the joints of
+    /// `SamplingJob::run_sparse`.
+    pub fn animated_joints(&self) -> Vec<i16> {
+        return (0..self.num_tracks as i16)
+            .filter(|joint| self.animates_joint(*joint))
+            .collect();
+    }
+
+    /// Finds the nearest ancestor of `joint` (`joint` excluded) that the animation animates, see
+    /// `animates_joint`. The animation tracks are ordered like `skeleton` joints.
+    ///
+    /// Returns `None` if no ancestor is animated, or `joint` is out of range.
+    pub fn nearest_animated_ancestor(&self, skeleton: &Skeleton, joint: i16) -> Option<i16> {
+        if joint < 0 || joint as usize >= skeleton.num_joints() {
+            return None;
+        }
+        let mut parent = skeleton.joint_parent(joint);
+        while parent >= 0 {
+            if self.animates_joint(parent) {
+                return Some(parent);
+            }
+            parent = skeleton.joint_parent(parent);
+        }
+        return None;
+    }
+
+    /// Computes the distance traveled by `joint` in model-space over the clip, like a foot stride length.
+    ///
+    /// The clip is sampled `samples` times evenly from ratio 0.0 to 1.0 (both included), the travel is the
+    /// sum of the distances between consecutive model-space positions of `joint`. More samples follow
+    /// curved paths more closely. Joints without animation track keep their rest pose.
+    ///
+    /// Returns 0.0 if `samples` is less than 2, `OzzError::InvalidIndex` if `joint` is out of range, and the
+    /// sampling or local-to-model errors otherwise.
+    pub fn joint_travel(&self, skeleton: &Skeleton, joint: i16, samples: usize) -> Result<f32, OzzError> {
+        if joint < 0 || joint as usize >= skeleton.num_joints() {
+            return Err(OzzError::InvalidIndex);
+        }
+        if samples < 2 {
+            return Ok(0.0);
+        }
+
+        let mut ctx = SamplingContext::from_animation(self);
+        let mut locals = Vec::new();
+        let mut models = vec![Mat4::IDENTITY; skeleton.num_joints()];
+        let mut travel = 0.0;
+        let mut prev: Option<Vec3> = None;
+        for idx in 0..samples {
+            self.sample_pose(skeleton, idx as f32 / (samples - 1) as f32, &mut ctx, &mut locals)?;
+
+            let mut ltm_job: LocalToModelJobRef = LocalToModelJob::default();
+            ltm_job.set_skeleton(skeleton);
+            ltm_job.set_input(&locals);
+            ltm_job.set_output(&mut models);
+            ltm_job.set_to(joint);
+            ltm_job.run()?;
+
+            let position = models[joint as usize].w_axis.truncate();
+            if let Some(prev) = prev {
+                travel += position.distance(prev);
+            }
+            prev = Some(position);
+        }
+        return Ok(travel);
+    }
+
+    // Samples the local-space pose of `skeleton` at `ratio` into `locals`, joints without animation track keep
+    // their rest pose. `locals` is resized to fit both the skeleton and the animation tracks.
+    fn sample_pose(
+        &self,
+        skeleton: &Skeleton,
+        ratio: f32,
+        ctx: &mut SamplingContext,
+        locals: &mut Vec<SoaTransform>,
+    ) -> Result<(), OzzError> {
+        locals.clear();
+        locals.extend_from_slice(skeleton.joint_rest_poses());
+        locals.resize(locals.len().max(self.num_soa_tracks()), SoaTransform::default());
+        return self.sample_into(ratio, ctx, locals);
+    }
+
+    /// Computes the difference between the first (ratio 0.0) and last (ratio 1.0) poses of the clip, per joint.
+    ///
+    /// Clips meant to loop should have matching start and end poses, a large difference pops at the loop
+    /// seam. Joints without animation track keep their rest pose. See `pose_difference`.
+    ///
+    /// Returns the sampling errors, see `Animation::sample_into`.
+    pub fn loop_seam_error(&self, skeleton: &Skeleton) -> Result<PoseDiff, OzzError> {
+        let mut ctx = SamplingContext::from_animation(self);
+        let mut locals = Vec::new();
+        self.sample_pose(skeleton, 0.0, &mut ctx, &mut locals)?;
+        let first: Vec<_> = soa_to_aos_iter(&locals, skeleton.num_joints()).collect();
+        self.sample_pose(skeleton, 1.0, &mut ctx, &mut locals)?;
+        let last: Vec<_> = soa_to_aos_iter(&locals, skeleton.num_joints()).collect();
+        return Ok(pose_difference(skeleton, &first, &last));
+    }
+
+    /// Samples the animation at `ratio` into `output`, without any heap allocation.
+    ///
+    /// Like running a `SamplingJob` with the built-in interpolation, but the context and output are borrowed
+    /// from the caller: the real-time safe path for audio or game loop threads. `ratio` is clamped to range
+    /// 0.0-1.0. Reusing `ctx` for the same animation keeps its cached keyframes, like `SamplingJob`.
+    ///
+    /// * `ctx` - Sampling context, sized for the animation (see `SamplingContext::from_animation`).
+    /// * `output` - Output local-space pose, at least `num_soa_tracks` soa transforms.
+    ///
+    /// Sizes are validated before sampling, returns `OzzError::BufferSize` if `ctx` or `output` is too small.
+    pub fn sample_into(
+        &self,
+        ratio: f32,
+        ctx: &mut SamplingContext,
+        output: &mut [SoaTransform],
+    ) -> Result<(), OzzError> {
+        return SamplingJob::<&Animation, &mut [SoaTransform]>::sample_into(&self, ctx, ratio, output);
+    }
+
+    /// Optimizes the animation, removing keyframes that linear interpolation of their neighbours restores
+    /// within `tolerance`. See `optimize_with_report`.
+    pub fn optimize(&self, tolerance: f32) -> Result<Animation, OzzError> {
+        return self.optimize_with_report(tolerance).map(|(animation, _)| animation);
+    }
+
+    /// Optimizes the animation like `optimize`, and reports the error it introduced.
+    ///
+    /// Each channel (translations, rotations and scales) of each track is reduced independently, the first
+    /// and last keyframes are always kept. `tolerance` is a distance for translations and scales, and an
+    /// angle in radians for rotations. Errors are measured in local-space at the removed keyframes, against
+    /// the interpolation `SamplingJob` does between the kept keyframes (values being compressed the same
+    /// way before and after).
+    ///
+    /// Returns `OzzError::InvalidKeyframe` if the optimized keyframes can't be built into an animation.
+    pub fn optimize_with_report(&self, tolerance: f32) -> Result<(Animation, OptimizeReport), OzzError> {
+        let tolerance = tolerance.max(0.0);
+        let distance = |a: Vec3, b: Vec3| a.distance(b);
+        let angle = |a: Quat, b: Quat| 2.0 * a.dot(b).abs().min(1.0).acos();
+
+        let mut report = OptimizeReport::default();
+        let mut builder = AnimationBuilder::new(self.duration, self.num_tracks);
+        builder.set_name(&self.name);
+        for (idx, track) in builder.tracks_mut().iter_mut().enumerate() {
+            let joint = idx as i16;
+            let translations: Vec<_> = self.iter_translation_keys(joint).collect();
+            let (keys, error) = reduce_keys(&translations, tolerance, Vec3::lerp, distance);
+            report.max_translation_error = report.max_translation_error.max(error);
+            report.num_keyframes += translations.len();
+            report.removed_translations += translations.len() - keys.len();
+            track.translations = keys;
+
+            let rotations: Vec<_> = self.iter_rotation_keys(joint).collect();
+            let (keys, error) = reduce_keys(&rotations, tolerance, |a, b, t| a.lerp(b, t).normalize(), angle);
+            report.max_rotation_error = report.max_rotation_error.max(error);
+            report.num_keyframes += rotations.len();
+            report.removed_rotations += rotations.len() - keys.len();
+            track.rotations = keys;
+
+            let scales: Vec<_> = self.iter_scale_keys(joint).collect();
+            let (keys, error) = reduce_keys(&scales, tolerance, Vec3::lerp, distance);
+            report.max_scale_error = report.max_scale_error.max(error);
+            report.num_keyframes += scales.len();
+            report.removed_scales += scales.len() - keys.len();
+            track.scales = keys;
+        }
+
+        let animation = builder.build()?;
+        return Ok((animation, report));
+    }
+}
+
+// Douglas-Peucker reduction of `(time, value)` keyframes, returns the kept keyframes and the max error.
+fn reduce_keys<V: Copy>(
+    keys: &[(f32, V)],
+    tolerance: f32,
+    lerp: impl Fn(V, V, f32) -> V,
+    error: impl Fn(V, V) -> f32,
+) -> (Vec<(f32, V)>, f32) {
+    if keys.len() <= 2 {
+        return (keys.to_vec(), 0.0);
+    }
+
+    let mut kept = vec![false; keys.len()];
+    kept[0] = true;
+    kept[keys.len() - 1] = true;
+    let mut max_error: f32 = 0.0;
+    let mut ranges = vec![(0, keys.len() - 1)];
+    while let Some((first, last)) = ranges.pop() {
+        let (t0, v0) = keys[first];
+        let (t1, v1) = keys[last];
+        let mut worst = (first, 0.0);
+        for (idx, (time, value)) in keys.iter().enumerate().take(last).skip(first + 1) {
+            let err = error(lerp(v0, v1, (time - t0) / (t1 - t0)), *value);
+            if err > worst.1 {
+                worst = (idx, err);
+            }
+        }
+        if worst.1 > tolerance {
+            kept[worst.0] = true;
+            ranges.push((first, worst.0));
+            ranges.push((worst.0, last));
+        } else {
+            max_error = max_error.max(worst.1);
+        }
+    }
+    let keys = keys.iter().zip(kept).filter(|(_, kept)| *kept).map(|(key, _)| *key);
+    return (keys.collect(), max_error);
+}
+
+/// Animation keyframes, as sampled by `SamplingJob`.
+///
+/// It's implemented by `Animation` (through any `OzzObj<Animation>`, so `SamplingJob<A>` still accepts any
+/// `A: OzzObj<Animation>`), and by `ArchivedAnimation` with `rkyv` feature. So that an animation can be
+/// sampled directly from an rkyv buffer, without deserialization.
+///
+/// Archived keys are sampled in place. If rkyv archives in a byte order other than the native one (with
+/// `archive_le` or `archive_be` rkyv features), `ArchivedAnimation` keys accessors return empty buffers and
+/// `AnimationData::check_keys` fails, so that `SamplingJob` returns an error.
+pub trait AnimationData {
+    /// Unique identifier of the animation, used by `SamplingContext` to detect animation changes.
+    fn id(&self) -> u64;
+
+    /// Gets the animation clip duration.
+    fn duration(&self) -> f32;
+
+    /// Gets the number of animated tracks.
+    fn num_tracks(&self) -> usize;
+
+    /// Gets the buffer of translations keys.
+    fn translations(&self) -> &[Float3Key];
+
+    /// Gets the buffer of rotation keys.
+    fn rotations(&self) -> &[QuaternionKey];
+
+    /// Gets the buffer of scale keys.
+    fn scales(&self) -> &[Float3Key];
+
+    /// Gets the number of animated tracks (aligned to 4 * SoA).
+    #[inline]
+    fn num_aligned_tracks(&self) -> usize {
+        return (self.num_tracks() + 3) & !0x3;
+    }
+
+    /// Gets the number of SoA elements matching the number of tracks.
+    #[inline]
+    fn num_soa_tracks(&self) -> usize {
+        return self.num_tracks().div_ceil(4);
+    }
+
+    /// Checks that the keys buffers can be sampled in place.
+    ///
+    /// Returns `OzzError::Unsupported` for an `ArchivedAnimation` whose keys aren't in native byte order.
+    #[inline]
+    fn check_keys(&self) -> Result<(), OzzError> {
+        return Ok(());
+    }
+}
+
+impl<T: OzzObj<Animation>> AnimationData for T {
+    #[inline]
+    fn id(&self) -> u64 {
+        return self.obj() as *const _ as u64;
+    }
+
+    #[inline]
+    fn duration(&self) -> f32 {
+        return self.obj().duration;
+    }
+
+    #[inline]
+    fn num_tracks(&self) -> usize {
+        return self.obj().num_tracks;
+    }
+
+    #[inline]
+    fn translations(&self) -> &[Float3Key] {
+        return &self.obj().translations;
+    }
+
+    #[inline]
+    fn rotations(&self) -> &[QuaternionKey] {
+        return &self.obj().rotations;
+    }
+
+    #[inline]
+    fn scales(&self) -> &[Float3Key] {
+        return &self.obj().scales;
+    }
+}
+
+#[cfg(feature = "rkyv")]
+const _: () = {
+    use core::slice;
+    use rkyv::{from_archived, Archived};
+    use static_assertions::const_assert_eq;
+
+    const_assert_eq!(mem::size_of::<ArchivedFloat3Key>(), mem::size_of::<Float3Key>());
+    const_assert_eq!(mem::align_of::<ArchivedFloat3Key>(), mem::align_of::<Float3Key>());
+    const_assert_eq!(mem::size_of::<ArchivedQuaternionKey>(), mem::size_of::<QuaternionKey>());
+    const_assert_eq!(
+        mem::align_of::<ArchivedQuaternionKey>(),
+        mem::align_of::<QuaternionKey>()
+    );
+
+    // `Archived<u32>` is `u32` itself, unless rkyv archives in a fixed endianness.
+    #[allow(clippy::useless_conversion)]
+    fn native_byte_order() -> bool {
+        let probe: Archived<u32> = 0x01020304u32.into();
+        let bytes = unsafe { *(&probe as *const Archived<u32> as *const [u8; 4]) };
+        return bytes == 0x01020304u32.to_ne_bytes();
+    }
+
+    // Archived keys have the layout of native keys, their fields are in native byte order unless rkyv
+    // archives in a fixed endianness that differs from the target one. Keys are empty in that case, see
+    // `AnimationData::check_keys`.
+    fn native_keys<A, K>(keys: &[A]) -> &[K] {
+        if !native_byte_order() {
+            return &[];
+        }
+        return unsafe { slice::from_raw_parts(keys.as_ptr() as *const K, keys.len()) };
+    }
+
+    impl Debug for ArchivedAnimation {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            return f
+                .debug_struct("ArchivedAnimation")
+                .field("duration", &self.duration)
+                .field("num_tracks", &self.num_tracks)
+                .field("name", &self.name.as_str())
+                .finish_non_exhaustive();
+        }
+    }
+
+    impl AnimationData for ArchivedAnimation {
+        #[inline]
+        fn id(&self) -> u64 {
+            return self as *const _ as u64;
+        }
+
+        #[inline]
+        fn duration(&self) -> f32 {
+            return from_archived!(self.duration);
+        }
+
+        #[inline]
+        fn num_tracks(&self) -> usize {
+            return from_archived!(self.num_tracks) as usize;
+        }
+
+        #[inline]
+        fn translations(&self) -> &[Float3Key] {
+            return native_keys(self.translations.as_slice());
+        }
+
+        #[inline]
+        fn rotations(&self) -> &[QuaternionKey] {
+            return native_keys(self.rotations.as_slice());
+        }
+
+        #[inline]
+        fn scales(&self) -> &[Float3Key] {
+            return native_keys(self.scales.as_slice());
+        }
+
+        #[inline]
+        fn check_keys(&self) -> Result<(), OzzError> {
+            if !native_byte_order() {
+                return Err(OzzError::Unsupported(
+                    "archived animation keys aren't in native byte order".into(),
+                ));
+            }
+            return Ok(());
+        }
+    }
+
+    impl AnimationData for &ArchivedAnimation {
+        #[inline]
+        fn id(&self) -> u64 {
+            return (*self).id();
+        }
+
+        #[inline]
+        fn duration(&self) -> f32 {
+            return (*self).duration();
+        }
+
+        #[inline]
+        fn num_tracks(&self) -> usize {
+            return (*self).num_tracks();
+        }
+
+        #[inline]
+        fn translations(&self) -> &[Float3Key] {
+            return (*self).translations();
+        }
+
+        #[inline]
+        fn rotations(&self) -> &[QuaternionKey] {
+            return (*self).rotations();
+        }
+
+        #[inline]
+        fn scales(&self) -> &[Float3Key] {
+            return (*self).scales();
+        }
+
+        #[inline]
+        fn check_keys(&self) -> Result<(), OzzError> {
+            return (*self).check_keys();
+        }
+    }
+};
+
+/// Keyframes of a joint track, for `AnimationBuilder`.
+///
+/// Keyframes are `(time, value)` tuples, time is in seconds and must be in range [0, duration] and
+/// strictly ascending. An empty track uses identity values.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct JointTrack {
+    pub translations: Vec<(f32, Vec3)>,
+    pub rotations: Vec<(f32, Quat)>,
+    pub scales: Vec<(f32, Vec3)>,
+}
+
+/// Result of `Animation::optimize_with_report`.
+///
+/// Max errors are the largest deviation introduced for each channel, over all tracks: a distance for
+/// translations and scales, an angle in radians for rotations.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct OptimizeReport {
+    /// Number of keyframes of the source animation tracks.
+    pub num_keyframes: usize,
+    pub removed_translations: usize,
+    pub removed_rotations: usize,
+    pub removed_scales: usize,
+    pub max_translation_error: f32,
+    pub max_rotation_error: f32,
+    pub max_scale_error: f32,
+}
+
+impl OptimizeReport {
+    /// Gets the number of removed keyframes.
+    #[inline]
+    pub fn removed_keyframes(&self) -> usize {
+        return self.removed_translations + self.removed_rotations + self.removed_scales;
+    }
+
+    /// Gets the ratio of removed keyframes, in range 0.0-1.0.
+    #[inline]
+    pub fn saved_ratio(&self) -> f32 {
+        if self.num_keyframes == 0 {
+            return 0.0;
+        }
+        return self.removed_keyframes() as f32 / self.num_keyframes as f32;
+    }
+}
+
+///
+/// Builds a runtime `Animation` from uncompressed keyframes.
+///
+/// Tracks are ordered like the joints of the skeleton the animation is meant for. Values are compressed
+/// (half floats for translations and scales, quantized quaternions for rotations), and keyframes are
+/// sorted in the order `SamplingJob` expects.
+///
+#[derive(Debug, Default, Clone)]
+pub struct AnimationBuilder {
+    duration: f32,
+    name: String,
+    tracks: Vec<JointTrack>,
+}
+
+impl AnimationBuilder {
+    /// Creates an `AnimationBuilder` with `num_tracks` empty tracks.
+    #[inline]
+    pub fn new(duration: f32, num_tracks: usize) -> AnimationBuilder {
+        return AnimationBuilder {
+            duration,
+            name: String::new(),
+            tracks: vec![JointTrack::default(); num_tracks],
+        };
+    }
+
+    /// Gets duration of `AnimationBuilder`.
+    #[inline]
+    pub fn duration(&self) -> f32 {
+        return self.duration;
+    }
+
+    /// Sets duration of `AnimationBuilder`.
+    ///
+    /// Duration of the built animation in seconds, must be greater than 0.
+    #[inline]
+    pub fn set_duration(&mut self, duration: f32) {
+        self.duration = duration;
+    }
+
+    /// Gets name of `AnimationBuilder`.
+    #[inline]
+    pub fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    /// Sets name of `AnimationBuilder`.
+    ///
+    /// Name of the built animation.
+    #[inline]
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+
+    /// Gets tracks of `AnimationBuilder`.
+    #[inline]
+    pub fn tracks(&self) -> &[JointTrack] {
+        return &self.tracks;
+    }
+
+    /// Gets mutable tracks of `AnimationBuilder`.
+    #[inline]
+    pub fn tracks_mut(&mut self) -> &mut Vec<JointTrack> {
+        return &mut self.tracks;
+    }
+
+    /// Validates `AnimationBuilder` duration and keyframes.
+    pub fn validate(&self) -> bool {
+        fn valid_times<V>(keys: &[(f32, V)], duration: f32) -> bool {
+            let mut prev = -1.0;
+            for (time, _) in keys.iter() {
+                if !(0.0..=duration).contains(time) || *time <= prev {
+                    return false;
+                }
+                prev = *time;
+            }
+            return true;
+        }
+
+        if !(self.duration > 0.0 && self.duration.is_finite()) || self.tracks.len() > SKELETON_MAX_JOINTS as usize {
+            return false;
+        }
+        return self.tracks.iter().all(|track| {
+            valid_times(&track.translations, self.duration)
+                && valid_times(&track.rotations, self.duration)
+                && valid_times(&track.scales, self.duration)
+        });
+    }
+
+    /// Builds an `Animation` from the tracks.
+    ///
+    /// Returns `OzzError::InvalidKeyframe` if duration is not positive, there are too many tracks, or
+    /// keyframe times are out of range or not strictly ascending.
+    pub fn build(&self) -> Result<Animation, OzzError> {
+        if !self.validate() {
+            return Err(OzzError::InvalidKeyframe);
+        }
+
+        // Normalizes quaternions, and fixes up successive opposite quaternions that would fail to take
+        // the shortest path during the normalized lerp.
+        let rotations: Vec<Vec<(f32, Quat)>> = (self.tracks.iter())
+            .map(|track| {
+                let mut prev = Quat::IDENTITY;
+                return (track.rotations.iter())
+                    .map(|(time, value)| {
+                        let mut value = if value.length_squared() > 0.0 {
+                            value.normalize()
+                        } else {
+                            Quat::IDENTITY
+                        };
+                        if prev.dot(value) < 0.0 {
+                            value = -value;
+                        }
+                        prev = value;
+                        return (*time, value);
+                    })
+                    .collect();
+            })
+            .collect();
+
+        let num_aligned_tracks = (self.tracks.len() + 3) & !0x3;
+        let translations = Self::sort_keys(
+            self.tracks.iter().map(|t| t.translations.as_slice()),
+            num_aligned_tracks,
+            Vec3::ZERO,
+            self.duration,
+        );
+        let rotations = Self::sort_keys(
+            rotations.iter().map(|r| r.as_slice()),
+            num_aligned_tracks,
+            Quat::IDENTITY,
+            self.duration,
+        );
+        let scales = Self::sort_keys(
+            self.tracks.iter().map(|t| t.scales.as_slice()),
+            num_aligned_tracks,
+            Vec3::ONE,
+            self.duration,
+        );
+
+        return Ok(Animation {
+            duration: self.duration,
+            num_tracks: self.tracks.len(),
+            name: self.name.clone(),
+            translations: (translations.into_iter())
+                .map(|(ratio, track, value)| Float3Key::compress(ratio, track, value))
+                .collect(),
+            rotations: (rotations.into_iter())
+                .map(|(ratio, track, value)| QuaternionKey::compress(ratio, track, value))
+                .collect(),
+            scales: (scales.into_iter())
+                .map(|(ratio, track, value)| Float3Key::compress(ratio, track, value))
+                .collect(),
+        });
+    }
+
+    /// Adds keys at 0 and duration to every track (padding tracks included), and sorts keys by the time
+    /// of the previous key of their track, then by track. Returns `(ratio, track, value)` tuples.
+    fn sort_keys<'t, V: Copy + 't>(
+        tracks: impl Iterator<Item = &'t [(f32, V)]>,
+        num_aligned_tracks: usize,
+        identity: V,
+        duration: f32,
+    ) -> Vec<(f32, u16, V)> {
+        let mut keys = Vec::new(); // (previous key time, track, time, value)
+        let mut tracks = tracks.fuse();
+        for track in 0..num_aligned_tracks as u16 {
+            let src = tracks.next().unwrap_or(&[]);
+            match (src.first(), src.last()) {
+                (Some(first), Some(last)) if src.len() > 1 => {
+                    let mut prev = -1.0;
+                    if first.0 != 0.0 {
+                        keys.push((prev, track, 0.0, first.1));
+                        prev = 0.0;
+                    }
+                    for (time, value) in src.iter() {
+                        keys.push((prev, track, *time, *value));
+                        prev = *time;
+                    }
+                    if last.0 != duration {
+                        keys.push((prev, track, duration, last.1));
+                    }
+                }
+                _ => {
+                    let value = src.first().map(|k| k.1).unwrap_or(identity);
+                    keys.push((-1.0, track, 0.0, value));
+                    keys.push((0.0, track, duration, value));
+                }
+            }
+        }
+
+        keys.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        return (keys.into_iter())
+            .map(|(_, track, time, value)| (time / duration, track, value))
+            .collect();
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::rc::Rc;
     use wasm_bindgen_test::*;
 
     use super::*;
+    use crate::math::AosTransform;
+    use crate::skeleton::SkeletonBuilder;
 
     #[test]
     #[wasm_bindgen_test]
@@ -579,4 +1492,459 @@ mod tests {
         assert_eq!(animation.scales()[last].track, 67);
         assert_eq!(animation.scales()[last].value, [15360, 15360, 15360]);
     }
+
+    fn sample_all(animation: impl AnimationData) -> Vec<Vec<crate::math::SoaTransform>> {
+        use crate::math::SoaTransform;
+        use crate::sampling_job::{SamplingContext, SamplingJob};
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let output = Rc::new(RefCell::new(vec![SoaTransform::default(); animation.num_soa_tracks()]));
+        let mut job: SamplingJob<_> = SamplingJob::default();
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_animation(animation);
+        job.set_output(output.clone());
+
+        let mut results = Vec::new();
+        for i in 0..=10 {
+            job.set_ratio(i as f32 / 10.0);
+            job.run().unwrap();
+            results.push(output.borrow().clone());
+        }
+        return results;
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_key_compress() {
+        let value = Vec3::new(0.0711059570, -8.77380371e-05, 1.84774399e-06);
+        let key = Float3Key::compress(0.5, 3, value);
+        assert_eq!(key, Float3Key::new(0.5, 3, [11405, 34240, 31]));
+        assert_eq!(key.decompress(), value);
+
+        for value in [
+            Quat::IDENTITY,
+            Quat::from_xyzw(0.0, 0.0, 0.0, -1.0),
+            Quat::from_rotation_y(2.5),
+            Quat::from_euler(glam::EulerRot::XYZ, -0.3, 1.2, 2.9),
+        ] {
+            let key = QuaternionKey::compress(0.25, 1000, value);
+            assert_eq!(key.ratio, 0.25);
+            assert_eq!(key.track(), 1000);
+            assert!(key.decompress().abs_diff_eq(value, 1e-4));
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_compress_round_trip() {
+        let mut max_quat_err = 0.0f32;
+        let mut max_vec3_err = 0.0f32;
+        for idx in 0..1000 {
+            let x = idx as f32;
+            let quat = Quat::from_euler(
+                glam::EulerRot::XYZ,
+                (x * 0.37).sin() * 3.1,
+                (x * 0.71).cos() * 3.1,
+                x * 0.13,
+            );
+            let compressed = compress_quat(quat);
+            assert!(compressed.largest < 4);
+            let restored = decompress_quat(compressed);
+            // Same rotation, maybe the opposite quaternion.
+            let restored = if restored.dot(quat) < 0.0 { -restored } else { restored };
+            max_quat_err = max_quat_err.max((restored - quat).to_array().iter().fold(0.0, |a, b| a.max(b.abs())));
+
+            let vec3 = Vec3::new((x * 0.37).sin() * 100.0, (x * 0.71).cos(), x * 0.01 - 5.0);
+            let restored = decompress_vec3(compress_vec3(vec3));
+            let err = (restored - vec3).abs() / vec3.abs().max(Vec3::splat(6.1e-5));
+            max_vec3_err = max_vec3_err.max(err.max_element());
+        }
+        assert!(max_quat_err < 5e-5, "{}", max_quat_err);
+        assert!(max_vec3_err <= 1.0 / 2048.0, "{}", max_vec3_err);
+
+        let compressed = compress_quat(Quat::from_xyzw(0.0, 0.0, 0.0, -1.0));
+        assert_eq!(
+            compressed,
+            CompressedQuat {
+                largest: 3,
+                sign: true,
+                value: [0, 0, 0]
+            }
+        );
+        let key = QuaternionKey::compress(0.0, 5, Quat::from_rotation_y(2.5));
+        let compressed = compress_quat(Quat::from_rotation_y(2.5));
+        assert_eq!(key.largest(), compressed.largest as u16);
+        assert_eq!(key.decompress(), decompress_quat(compressed));
+        assert_eq!(compress_vec3(Vec3::new(1.0, -2.0, 0.5)).value, [0x3C00, 0xC000, 0x3800]);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_builder_validity() {
+        assert!(AnimationBuilder::new(1.0, 0).build().is_ok());
+        assert!(AnimationBuilder::new(0.0, 1).build().unwrap_err().is_invalid_keyframe());
+        assert!(AnimationBuilder::new(f32::NAN, 1)
+            .build()
+            .unwrap_err()
+            .is_invalid_keyframe());
+
+        let mut builder = AnimationBuilder::new(1.0, 1);
+        builder.tracks_mut()[0].translations.push((1.5, Vec3::ZERO));
+        assert!(builder.build().unwrap_err().is_invalid_keyframe());
+
+        let mut builder = AnimationBuilder::new(1.0, 1);
+        builder.tracks_mut()[0].rotations.push((0.5, Quat::IDENTITY));
+        builder.tracks_mut()[0].rotations.push((0.5, Quat::IDENTITY));
+        assert!(builder.build().unwrap_err().is_invalid_keyframe());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_builder() {
+        let mut builder = AnimationBuilder::new(2.0, 5);
+        builder.set_name("built");
+        builder.tracks_mut()[0].translations = vec![(0.5, Vec3::new(1.0, 0.0, 0.0)), (1.5, Vec3::new(3.0, 0.0, 0.0))];
+        builder.tracks_mut()[1].rotations = vec![(0.0, Quat::from_rotation_z(0.5)), (2.0, Quat::from_rotation_z(-0.5))];
+        builder.tracks_mut()[4].scales = vec![(1.0, Vec3::splat(2.0))];
+        let animation = builder.build().unwrap();
+        assert_eq!(animation.name(), "built");
+        assert_eq!(animation.duration(), 2.0);
+        assert_eq!(animation.num_tracks(), 5);
+        assert_eq!(animation.translations().len(), 2 * 8 + 2);
+        assert_eq!(animation.rotations().len(), 2 * 8);
+        assert_eq!(animation.scales().len(), 2 * 8);
+
+        let poses = sample_all(Rc::new(animation));
+        // Ratio 0.5 is time 1.0.
+        assert!(poses[5][0]
+            .translation
+            .col(0)
+            .abs_diff_eq(Vec3::new(2.0, 0.0, 0.0), 1e-3));
+        assert!(poses[1][0]
+            .translation
+            .col(0)
+            .abs_diff_eq(Vec3::new(1.0, 0.0, 0.0), 1e-3));
+        assert!(poses[9][0]
+            .translation
+            .col(0)
+            .abs_diff_eq(Vec3::new(3.0, 0.0, 0.0), 1e-3));
+        assert!(poses[5][0].rotation.col(1).abs_diff_eq(Quat::IDENTITY, 1e-4));
+        assert!(poses[0][0]
+            .rotation
+            .col(1)
+            .abs_diff_eq(Quat::from_rotation_z(0.5), 1e-4));
+        assert!(poses[3][1].scale.col(0).abs_diff_eq(Vec3::splat(2.0), 1e-3));
+        for pose in poses.iter() {
+            assert_eq!(pose[0].translation.col(2), Vec3::ZERO);
+            assert_eq!(pose[0].rotation.col(3), Quat::IDENTITY);
+            assert_eq!(pose[0].scale.col(1), Vec3::ONE);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_iter_keys() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        for joint in 0..animation.num_tracks() as i16 {
+            let translations: Vec<_> = animation.iter_translation_keys(joint).collect();
+            let rotations: Vec<_> = animation.iter_rotation_keys(joint).collect();
+            let scales: Vec<_> = animation.iter_scale_keys(joint).collect();
+            let times = [
+                translations.iter().map(|x| x.0).collect::<Vec<_>>(),
+                rotations.iter().map(|x| x.0).collect::<Vec<_>>(),
+                scales.iter().map(|x| x.0).collect::<Vec<_>>(),
+            ];
+            for times in times {
+                assert_eq!(times.first(), Some(&0.0));
+                assert_eq!(times.last(), Some(&animation.duration()));
+                assert!(times.windows(2).all(|x| x[0] <= x[1]));
+            }
+            assert!(rotations.iter().all(|x| x.1.is_normalized()));
+        }
+        assert_eq!(animation.iter_translation_keys(-1).count(), 0);
+        assert_eq!(animation.iter_rotation_keys(animation.num_tracks() as i16).count(), 0);
+
+        let mut builder = AnimationBuilder::new(2.0, 1);
+        builder.tracks_mut()[0].translations = vec![(0.5, Vec3::new(1.0, 0.0, 0.0)), (1.5, Vec3::new(3.0, 0.0, 0.0))];
+        let animation = builder.build().unwrap();
+        let translations: Vec<_> = animation.iter_translation_keys(0).collect();
+        assert_eq!(translations.first(), Some(&(0.0, Vec3::new(1.0, 0.0, 0.0))));
+        assert!(translations.contains(&(0.5, Vec3::new(1.0, 0.0, 0.0))));
+        assert!(translations.contains(&(1.5, Vec3::new(3.0, 0.0, 0.0))));
+        assert_eq!(translations.last(), Some(&(2.0, Vec3::new(3.0, 0.0, 0.0))));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_nearest_animated_ancestor() {
+        let mut builder = SkeletonBuilder::new();
+        let hips = builder.add_joint("hips", -1, AosTransform::IDENTITY);
+        let spine = builder.add_joint("spine", hips, AosTransform::IDENTITY);
+        let hand = builder.add_joint("hand", spine, AosTransform::IDENTITY);
+        builder.add_joint("finger", hand, AosTransform::IDENTITY);
+        let skeleton = builder.build().unwrap();
+        let [hips, spine, hand, finger] =
+            ["hips", "spine", "hand", "finger"].map(|x| skeleton.joint_by_name(x).unwrap());
+
+        let mut builder = AnimationBuilder::new(1.0, skeleton.num_joints());
+        let tracks = builder.tracks_mut();
+        tracks[spine as usize].rotations = vec![(0.0, Quat::IDENTITY), (1.0, Quat::from_rotation_x(1.0))];
+        // Constant, not animated.
+        tracks[hand as usize].translations = vec![(0.0, Vec3::X), (1.0, Vec3::X)];
+        let animation = builder.build().unwrap();
+
+        assert!(!animation.animates_joint(hips));
+        assert!(animation.animates_joint(spine));
+        assert!(!animation.animates_joint(hand));
+        assert!(!animation.animates_joint(finger));
+        assert!(!animation.animates_joint(-1));
+        assert!(!animation.animates_joint(4));
+
+        assert_eq!(animation.nearest_animated_ancestor(&skeleton, finger), Some(spine));
+        assert_eq!(animation.nearest_animated_ancestor(&skeleton, hand), Some(spine));
+        assert_eq!(animation.nearest_animated_ancestor(&skeleton, spine), None);
+        assert_eq!(animation.nearest_animated_ancestor(&skeleton, hips), None);
+        assert_eq!(animation.nearest_animated_ancestor(&skeleton, 4), None);
+
+        let playback = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        assert!(playback.animates_joint(0));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_track_to_joint() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let mapping = animation.track_to_joint();
+        assert_eq!(mapping.len(), animation.num_tracks());
+        for (track, joint) in mapping.iter().enumerate() {
+            assert!(*joint >= 0 && (*joint as usize) < skeleton.num_joints());
+            let key = animation.translations().iter().find(|key| key.track as usize == track);
+            let joint_key = animation.iter_translation_keys(*joint).next();
+            assert_eq!(key.map(|key| key.decompress()), joint_key.map(|(_, value)| value));
+        }
+        assert!(Animation::default().track_to_joint().is_empty());
+    }
+
+    // A hips root and a foot child, 1 to the side and 1 below.
+    fn hips_foot_skeleton() -> (Skeleton, i16, i16) {
+        let mut builder = SkeletonBuilder::new();
+        let hips = builder.add_joint("hips", -1, AosTransform::IDENTITY);
+        let offset = AosTransform::from_translation_rotation(Vec3::new(1.0, -1.0, 0.0), Quat::IDENTITY);
+        let foot = builder.add_joint("foot", hips, offset);
+        return (builder.build().unwrap(), hips, foot);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_joint_travel() {
+        let (skeleton, hips, foot) = hips_foot_skeleton();
+
+        // Hips move 2 forward then back, the foot travels 4.
+        let mut builder = AnimationBuilder::new(1.0, skeleton.num_joints());
+        let translations = vec![(0.0, Vec3::ZERO), (0.5, Vec3::new(0.0, 0.0, 2.0)), (1.0, Vec3::ZERO)];
+        builder.tracks_mut()[hips as usize].translations = translations;
+        builder.tracks_mut()[foot as usize].translations = vec![(0.0, Vec3::new(1.0, -1.0, 0.0))];
+        let animation = builder.build().unwrap();
+        assert!((animation.joint_travel(&skeleton, foot, 3).unwrap() - 4.0).abs() < 1e-4);
+        assert!((animation.joint_travel(&skeleton, foot, 11).unwrap() - 4.0).abs() < 1e-4);
+        // Sampling only the bounds misses the motion.
+        assert!(animation.joint_travel(&skeleton, foot, 2).unwrap() < 1e-4);
+
+        // Hips turn a quarter, the foot arc is pi / 2 long.
+        let mut builder = AnimationBuilder::new(1.0, skeleton.num_joints());
+        let quarter = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+        let rotations = vec![(0.0, Quat::IDENTITY), (1.0, quarter)];
+        builder.tracks_mut()[hips as usize].rotations = rotations;
+        builder.tracks_mut()[foot as usize].translations = vec![(0.0, Vec3::new(1.0, -1.0, 0.0))];
+        let animation = builder.build().unwrap();
+        let travel = animation.joint_travel(&skeleton, foot, 100).unwrap();
+        assert!((travel - std::f32::consts::FRAC_PI_2).abs() < 1e-2, "travel={}", travel);
+        assert!(travel < std::f32::consts::FRAC_PI_2);
+        assert!(animation.joint_travel(&skeleton, hips, 100).unwrap() < 1e-4);
+
+        assert_eq!(animation.joint_travel(&skeleton, foot, 1).unwrap(), 0.0);
+        assert!(matches!(
+            animation.joint_travel(&skeleton, 2, 10),
+            Err(OzzError::InvalidIndex)
+        ));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_loop_seam_error() {
+        let (skeleton, hips, foot) = hips_foot_skeleton();
+
+        // Hips sway and turn, then come back to the start pose.
+        let mut builder = AnimationBuilder::new(1.0, skeleton.num_joints());
+        let sway = Vec3::new(0.0, 0.0, 2.0);
+        let turn = Quat::from_rotation_y(0.8);
+        builder.tracks_mut()[hips as usize].translations = vec![(0.0, Vec3::ZERO), (0.5, sway), (1.0, Vec3::ZERO)];
+        builder.tracks_mut()[hips as usize].rotations = vec![(0.0, turn), (0.5, Quat::IDENTITY), (1.0, turn)];
+        let animation = builder.build().unwrap();
+        let seam = animation.loop_seam_error(&skeleton).unwrap();
+        assert_eq!(seam.joints.len(), skeleton.num_joints());
+        assert!(seam.max.translation < 1e-5);
+        assert!(seam.max.rotation < 1e-3);
+        assert!(seam.max.model_translation < 1e-5);
+
+        // The clip ends halfway through the sway.
+        let mut builder = AnimationBuilder::new(1.0, skeleton.num_joints());
+        builder.tracks_mut()[hips as usize].translations = vec![(0.0, Vec3::ZERO), (1.0, sway)];
+        let animation = builder.build().unwrap();
+        let seam = animation.loop_seam_error(&skeleton).unwrap();
+        assert!((seam.joints[hips as usize].translation - 2.0).abs() < 1e-4);
+        assert!((seam.joints[foot as usize].model_translation - 2.0).abs() < 1e-4);
+        assert_eq!(seam.joints[foot as usize].translation, 0.0);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_sample_into() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let mut expected = vec![SoaTransform::default(); animation.num_soa_tracks()];
+        let mut job: SamplingJob<&Animation, &mut [SoaTransform]> = SamplingJob::default();
+        job.set_animation(&animation);
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_output(&mut expected);
+
+        let mut ctx = SamplingContext::from_animation(&animation);
+        let mut output = vec![SoaTransform::default(); animation.num_soa_tracks()];
+        for ratio in [0.0, 0.2, 0.5, 0.4, 1.0, 2.0] {
+            job.set_ratio(ratio);
+            job.run().unwrap();
+            animation.sample_into(ratio, &mut ctx, &mut output).unwrap();
+            assert_eq!(output, job.output().unwrap().to_vec(), "ratio={}", ratio);
+        }
+
+        let err = animation.sample_into(0.5, &mut ctx, &mut output[..16]).unwrap_err();
+        assert!(err.is_buffer_size());
+        assert_eq!(err.to_string(), "Buffer of 16 elements, 17 expected");
+        let mut small_ctx = SamplingContext::new(4);
+        let err = animation.sample_into(0.5, &mut small_ctx, &mut output).unwrap_err();
+        assert!(err.is_buffer_size());
+        assert_eq!(err.to_string(), "Buffer of 1 elements, 17 expected");
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_optimize_with_report() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        // Only exact duplicates are removed.
+        let (lossless, report) = animation.optimize_with_report(0.0).unwrap();
+        assert_eq!(report.max_translation_error, 0.0);
+        assert_eq!(report.max_rotation_error, 0.0);
+        assert_eq!(report.removed_translations, 0);
+        assert_eq!(lossless.translations().len(), animation.translations().len());
+
+        for tolerance in [1e-3, 1e-2, 0.1] {
+            let (optimized, report) = animation.optimize_with_report(tolerance).unwrap();
+            assert!(report.max_translation_error <= tolerance);
+            assert!(report.max_rotation_error <= tolerance);
+            assert!(report.max_scale_error <= tolerance);
+            assert!(report.removed_keyframes() > 0);
+            assert!(report.saved_ratio() > 0.0 && report.saved_ratio() < 1.0);
+            let count = |a: &Animation| a.translations().len() + a.rotations().len() + a.scales().len();
+            assert_eq!(count(&optimized), count(&animation) - report.removed_keyframes());
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_builder_rebuild_file() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let time = |ratio: f32| ratio * animation.duration();
+
+        let mut builder = AnimationBuilder::new(animation.duration(), animation.num_tracks());
+        for key in animation.translations().iter() {
+            if let Some(track) = builder.tracks_mut().get_mut(key.track as usize) {
+                track.translations.push((time(key.ratio), key.decompress()));
+            }
+        }
+        for key in animation.rotations().iter() {
+            if let Some(track) = builder.tracks_mut().get_mut(key.track() as usize) {
+                track.rotations.push((time(key.ratio), key.decompress()));
+            }
+        }
+        for key in animation.scales().iter() {
+            if let Some(track) = builder.tracks_mut().get_mut(key.track as usize) {
+                track.scales.push((time(key.ratio), key.decompress()));
+            }
+        }
+        let rebuilt = builder.build().unwrap();
+        assert_eq!(rebuilt.translations().len(), animation.translations().len());
+        assert_eq!(rebuilt.rotations().len(), animation.rotations().len());
+        assert_eq!(rebuilt.scales().len(), animation.scales().len());
+
+        let expected = sample_all(&animation);
+        let poses = sample_all(&rebuilt);
+        for (pose, expected) in poses.iter().zip(expected.iter()) {
+            for (soa, expected) in pose.iter().zip(expected.iter()) {
+                for idx in 0..4 {
+                    let (t, e) = (soa.col(idx), expected.col(idx));
+                    assert!(t.translation.abs_diff_eq(e.translation, 1e-3));
+                    assert!(t.rotation.dot(e.rotation).abs() > 1.0 - 1e-4);
+                    assert!(t.scale.abs_diff_eq(e.scale, 1e-3));
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_canonicalize_floats() {
+        let mut builder = AnimationBuilder::new(1.0, 1);
+        builder.tracks_mut()[0].translations = vec![(0.0, Vec3::new(-0.0, 1.0, f32::NAN)), (1.0, Vec3::X)];
+        let mut animation = builder.build().unwrap();
+        animation.canonicalize_floats();
+        let key = animation.translations().iter().find(|key| key.track == 0).unwrap();
+        assert_eq!(key.value[0], 0);
+        assert_eq!(key.value[2], 0x7E00);
+        assert_eq!(key.decompress().y, 1.0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_serde_animation() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let json = serde_json::to_string(&animation).unwrap();
+        let animation2: Animation = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(animation2.duration(), animation.duration());
+        assert_eq!(animation2.num_tracks(), animation.num_tracks());
+        assert_eq!(animation2.name(), animation.name());
+        assert_eq!(animation2.translations(), animation.translations());
+        assert_eq!(animation2.rotations(), animation.rotations());
+        assert_eq!(animation2.scales(), animation.scales());
+        assert_eq!(sample_all(&animation2), sample_all(&animation));
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_rkyv_animation() {
+        use rkyv::ser::Serializer;
+        use rkyv::Deserialize;
+
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let mut serializer = rkyv::ser::serializers::AllocSerializer::<30720>::default();
+        serializer.serialize_value(&animation).unwrap();
+        let buf = serializer.into_serializer().into_inner();
+        let archived = rkyv::check_archived_root::<Animation>(&buf).unwrap();
+
+        // Samples from the archived animation directly.
+        assert_eq!(archived.duration(), animation.duration());
+        assert_eq!(AnimationData::num_tracks(archived), animation.num_tracks());
+        assert_eq!(archived.translations(), animation.translations());
+        assert!(archived.check_keys().is_ok());
+        assert_eq!(sample_all(archived), sample_all(&animation));
+
+        let mut deserializer = rkyv::Infallible::default();
+        let animation2: Animation = archived.deserialize(&mut deserializer).unwrap();
+        assert_eq!(animation2.name(), animation.name());
+        assert_eq!(animation2.rotations(), animation.rotations());
+        assert_eq!(animation2.scales(), animation.scales());
+    }
 }
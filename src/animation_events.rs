@@ -0,0 +1,197 @@
+//!
+//! Animation events data structure definition.
+//!
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
+
+#[cfg(feature = "std")]
+use crate::archive::{Archive, ArchiveWriter};
+#[cfg(feature = "std")]
+use crate::base::OzzError;
+
+///
+/// Named events (like footsteps or sounds) of an `Animation`, as `(ratio, name)` pairs sorted by ratio.
+///
+/// Events aren't part of ozz-animation archives. They are stored as a separate "ozz-animation_events" object,
+/// usually written after the animation in the same archive with `ArchiveWriter::write_object_header`, so that
+/// ozz-animation still reads the animation.
+///
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", archive(check_bytes))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnimationEvents {
+    events: Vec<(f32, String)>,
+}
+
+impl AnimationEvents {
+    /// `AnimationEvents` resource file tag for `Archive`.
+    #[inline]
+    pub fn tag() -> &'static str {
+        return "ozz-animation_events";
+    }
+
+    /// `AnimationEvents` resource file version for `Archive`.
+    #[inline]
+    pub fn version() -> u32 {
+        return 1;
+    }
+
+    /// Creates an empty `AnimationEvents`.
+    #[inline]
+    pub fn new() -> AnimationEvents {
+        return AnimationEvents::default();
+    }
+
+    /// Reads an `AnimationEvents` from an `Archive`.
+    #[cfg(feature = "std")]
+    pub fn from_archive(archive: &mut Archive<impl Read>) -> Result<AnimationEvents, OzzError> {
+        if archive.tag() != Self::tag() {
+            return Err(OzzError::InvalidTag);
+        }
+        if archive.version() != Self::version() {
+            return Err(OzzError::InvalidVersion);
+        }
+
+        let count: u32 = archive.read()?;
+        let mut events = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let ratio: f32 = archive.read()?;
+            let name: String = archive.read()?;
+            events.push((ratio, name));
+        }
+        return Ok(AnimationEvents { events });
+    }
+
+    /// Writes an `AnimationEvents` to an `ArchiveWriter`, readable by `AnimationEvents::from_archive`.
+    #[cfg(feature = "std")]
+    pub fn to_archive(&self, archive: &mut ArchiveWriter<impl Write>) -> Result<(), OzzError> {
+        if archive.tag() != Self::tag() {
+            return Err(OzzError::InvalidTag);
+        }
+        if archive.version() != Self::version() {
+            return Err(OzzError::InvalidVersion);
+        }
+
+        archive.write(&(self.events.len() as u32))?;
+        for (ratio, name) in self.events.iter() {
+            archive.write(ratio)?;
+            archive.write(name)?;
+        }
+        return Ok(());
+    }
+
+    /// Gets the events, as `(ratio, name)` pairs sorted by ratio.
+    #[inline]
+    pub fn events(&self) -> &[(f32, String)] {
+        return &self.events;
+    }
+
+    /// Gets the number of events.
+    #[inline]
+    pub fn len(&self) -> usize {
+        return self.events.len();
+    }
+
+    /// Is there no event.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        return self.events.is_empty();
+    }
+
+    /// Adds an event at time `ratio` (in range 0.0-1.0), keeping events sorted by ratio. Events at the same
+    /// ratio keep their insertion order.
+    pub fn add_event(&mut self, ratio: f32, name: &str) {
+        let idx = self.events.partition_point(|(event_ratio, _)| *event_ratio <= ratio);
+        self.events.insert(idx, (ratio, name.to_string()));
+    }
+
+    /// Iterates the events crossed by a time step from `prev` to `curr` ratios, in playback order.
+    ///
+    /// The range includes `prev` and excludes `curr`, so that consecutive steps yield each event exactly once.
+    /// If `curr` is lower than `prev`, the playback is considered looping: events from `prev` to the end of
+    /// the clip (1.0 included) are followed by events from the start of the clip to `curr`. No event is
+    /// crossed if `prev` equals `curr`.
+    pub fn events_in_range(&self, prev: f32, curr: f32) -> impl Iterator<Item = &(f32, String)> + '_ {
+        let begin = self.events.partition_point(|(ratio, _)| *ratio < prev);
+        let end = self.events.partition_point(|(ratio, _)| *ratio < curr);
+        let (looped, wrapped): (&[_], &[_]) = match curr < prev {
+            true => (&self.events[begin..], &self.events[..end]),
+            false => (&self.events[begin..end.max(begin)], &[]),
+        };
+        return looped.iter().chain(wrapped.iter());
+    }
+}
+
+#[cfg(test)]
+mod animation_events_tests {
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::animation::Animation;
+
+    fn footsteps() -> AnimationEvents {
+        let mut events = AnimationEvents::new();
+        events.add_event(0.75, "foot_r");
+        events.add_event(0.0, "start");
+        events.add_event(0.25, "foot_l");
+        return events;
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_events_in_range() {
+        let events = footsteps();
+        let names: Vec<_> = events.events().iter().map(|(_, name)| name.as_str()).collect();
+        assert_eq!(names, vec!["start", "foot_l", "foot_r"]);
+
+        // Looping steps yield each event exactly once per loop, across the loop seam too.
+        let mut crossed = Vec::new();
+        let mut prev = 0.0;
+        for step in 1..=30 {
+            let curr = (step as f32 * 0.1) % 1.0;
+            crossed.extend(events.events_in_range(prev, curr).map(|(_, name)| name.clone()));
+            prev = curr;
+        }
+        let expected = ["start", "foot_l", "foot_r"].repeat(3);
+        assert_eq!(crossed, expected);
+        let names: Vec<_> = events
+            .events_in_range(0.8, 0.3)
+            .map(|(_, name)| name.as_str())
+            .collect();
+        assert_eq!(names, vec!["start", "foot_l"]);
+        assert_eq!(events.events_in_range(0.25, 0.25).count(), 0);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_archive_after_animation() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let events = footsteps();
+
+        let mut writer = ArchiveWriter::to_vec(Animation::tag(), Animation::version()).unwrap();
+        animation.to_archive(&mut writer).unwrap();
+        writer
+            .write_object_header(AnimationEvents::tag(), AnimationEvents::version())
+            .unwrap();
+        events.to_archive(&mut writer).unwrap();
+        let buf = writer.into_inner();
+
+        // The animation object is unchanged, ozz-animation ignores the events object after it.
+        let file = std::fs::read("./resource/playback/animation.ozz").unwrap();
+        assert_eq!(&buf[..file.len()], &file[..]);
+
+        let mut archive = Archive::from_vec(buf).unwrap();
+        let animation2 = Animation::from_archive(&mut archive).unwrap();
+        assert_eq!(animation2.rotations(), animation.rotations());
+        archive.read_object_header().unwrap();
+        let events2 = AnimationEvents::from_archive(&mut archive).unwrap();
+        assert_eq!(events2, events);
+
+        let mut writer = ArchiveWriter::to_vec(Animation::tag(), Animation::version()).unwrap();
+        assert!(events.to_archive(&mut writer).unwrap_err().is_invalid_tag());
+    }
+}
@@ -0,0 +1,401 @@
+//!
+//! Animation Player.
+//!
+
+use alloc::rc::Rc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::animation::{Animation, AnimationData};
+use crate::base::{OzzError, OzzObj};
+#[cfg(not(feature = "std"))]
+use crate::math::F32Ext;
+use crate::math::SoaTransform;
+use crate::sampling_job::{SamplingContext, SamplingJob};
+use crate::track::Track;
+use crate::track_triggering_job::{Edge, TrackTriggeringJob};
+
+/// Offsets a looping time ratio by `phase`, wrapping around the loop.
+///
+/// `phase` is a fraction of the animation duration, any value is valid: negative phases move backward,
+/// phases out of the unit interval wrap. The result is in range 0.0-1.0 (excluded), so a time ratio of
+/// 1.0 wraps to 0.0, the same loop point.
+///
+/// Used to desync characters playing the same clip, see `AnimationPlayer::set_phase`.
+pub fn phase_ratio(ratio: f32, phase: f32) -> f32 {
+    let ratio = ratio + phase;
+    let wrapped = ratio - ratio.floor();
+    // Tiny negative ratios round to 1.0.
+    return if wrapped < 1.0 { wrapped } else { 0.0 };
+}
+
+///
+/// Animation playback controller.
+///
+/// Advances the playback time, samples the local-space pose and collects events from an optional
+/// triggering track (a float track, whose edges crossing a threshold are events, like footsteps).
+///
+/// The player owns the `SamplingContext` and the output pose. It handles looping, playback speed and
+/// pause.
+///
+#[derive(Debug)]
+pub struct AnimationPlayer<A = Rc<Animation>, T = Rc<Track<f32>>>
+where
+    A: AnimationData,
+    T: OzzObj<Track<f32>>,
+{
+    sampling_job: SamplingJob<A, Vec<SoaTransform>>,
+    triggering_job: TrackTriggeringJob<T>,
+    time_ratio: f32,
+    previous_time_ratio: f32,
+    phase: f32,
+    playback_speed: f32,
+    playing: bool,
+    looping: bool,
+    events: Vec<Edge>,
+}
+
+impl<A, T> AnimationPlayer<A, T>
+where
+    A: AnimationData,
+    T: OzzObj<Track<f32>>,
+{
+    /// Creates a playing and looping `AnimationPlayer` of `animation`, at the beginning of the animation.
+    pub fn new(animation: A) -> AnimationPlayer<A, T> {
+        let mut sampling_job = SamplingJob::default();
+        sampling_job.set_context(SamplingContext::from_animation(&animation));
+        sampling_job.set_output(vec![SoaTransform::default(); animation.num_soa_tracks()]);
+        sampling_job.set_animation(animation);
+        return AnimationPlayer {
+            sampling_job,
+            triggering_job: TrackTriggeringJob::default(),
+            time_ratio: 0.0,
+            previous_time_ratio: 0.0,
+            phase: 0.0,
+            playback_speed: 1.0,
+            playing: true,
+            looping: true,
+            events: Vec::new(),
+        };
+    }
+
+    /// Gets animation of `AnimationPlayer`.
+    #[inline]
+    pub fn animation(&self) -> &A {
+        return self.sampling_job.animation().unwrap();
+    }
+
+    /// Gets event track of `AnimationPlayer`.
+    #[inline]
+    pub fn event_track(&self) -> Option<&T> {
+        return self.triggering_job.track();
+    }
+
+    /// Sets event track of `AnimationPlayer`.
+    ///
+    /// Events are the edges of `track` crossing `threshold` during an update, see `TrackTriggeringJob`.
+    /// The track shares the animation time ratio.
+    #[inline]
+    pub fn set_event_track(&mut self, track: T, threshold: f32) {
+        self.triggering_job.set_track(track);
+        self.triggering_job.set_threshold(threshold);
+    }
+
+    /// Clears event track of `AnimationPlayer`.
+    #[inline]
+    pub fn clear_event_track(&mut self) {
+        self.triggering_job.clear_track();
+    }
+
+    /// Gets the time ratio of `AnimationPlayer`.
+    #[inline]
+    pub fn time_ratio(&self) -> f32 {
+        return self.time_ratio;
+    }
+
+    /// Sets the time ratio of `AnimationPlayer`.
+    ///
+    /// Time ratio in the unit interval 0.0-1.0. Out of range values are wrapped when looping, clamped
+    /// otherwise. Jumping to a time ratio doesn't trigger events.
+    #[inline]
+    pub fn set_time_ratio(&mut self, ratio: f32) {
+        self.time_ratio = self.wrap_ratio(ratio);
+        self.previous_time_ratio = self.time_ratio;
+    }
+
+    /// Gets the time ratio of the previous update of `AnimationPlayer`.
+    #[inline]
+    pub fn previous_time_ratio(&self) -> f32 {
+        return self.previous_time_ratio;
+    }
+
+    /// Gets the phase of `AnimationPlayer`.
+    #[inline]
+    pub fn phase(&self) -> f32 {
+        return self.phase;
+    }
+
+    /// Sets the phase of `AnimationPlayer`.
+    ///
+    /// Offset added to the time ratio when sampling the pose and the event track, wrapping around the
+    /// loop (see `phase_ratio`). 0.0 by default. `time_ratio` isn't offset. Only looping playbacks are
+    /// offset, the phase is ignored otherwise.
+    #[inline]
+    pub fn set_phase(&mut self, phase: f32) {
+        self.phase = phase;
+    }
+
+    /// Gets the playback speed of `AnimationPlayer`.
+    #[inline]
+    pub fn playback_speed(&self) -> f32 {
+        return self.playback_speed;
+    }
+
+    /// Sets the playback speed of `AnimationPlayer`.
+    ///
+    /// Multiplies the update delta time, 1.0 by default. Negative speeds play backward.
+    #[inline]
+    pub fn set_playback_speed(&mut self, speed: f32) {
+        self.playback_speed = speed;
+    }
+
+    /// Gets if `AnimationPlayer` is playing.
+    #[inline]
+    pub fn playing(&self) -> bool {
+        return self.playing;
+    }
+
+    /// Resumes the playback of `AnimationPlayer`.
+    #[inline]
+    pub fn play(&mut self) {
+        self.playing = true;
+    }
+
+    /// Pauses the playback of `AnimationPlayer`. Updates keep sampling the pose at the current time.
+    #[inline]
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Gets if `AnimationPlayer` is looping.
+    #[inline]
+    pub fn looping(&self) -> bool {
+        return self.looping;
+    }
+
+    /// Sets if `AnimationPlayer` is looping.
+    ///
+    /// When not looping, the playback stops at the animation bounds.
+    #[inline]
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    /// Gets **output** pose of `AnimationPlayer`.
+    ///
+    /// Local-space transforms sampled by the last update.
+    #[inline]
+    pub fn pose(&self) -> &[SoaTransform] {
+        return self.sampling_job.output().unwrap();
+    }
+
+    /// Gets **output** events of `AnimationPlayer`.
+    ///
+    /// Edges of the event track triggered by the last update, in playback order. Edge ratios are in
+    /// the unit interval, like the time ratio.
+    #[inline]
+    pub fn events(&self) -> core::slice::Iter<'_, Edge> {
+        return self.events.iter();
+    }
+
+    /// Clears events of `AnimationPlayer`.
+    #[inline]
+    pub fn clear_events(&mut self) {
+        self.events.clear();
+    }
+
+    /// Advances the playback by `dt` seconds, samples the pose and collects triggered events.
+    pub fn update(&mut self, dt: f32) -> Result<(), OzzError> {
+        let duration = self.animation().duration();
+        let mut to = self.time_ratio;
+        if self.playing && duration > 0.0 {
+            to += dt * self.playback_speed / duration;
+        }
+        if !self.looping {
+            to = to.clamp(0.0, 1.0);
+        }
+
+        let phase = if self.looping { self.phase } else { 0.0 };
+        self.events.clear();
+        if self.triggering_job.validate() {
+            self.triggering_job.set_from(self.time_ratio + phase);
+            self.triggering_job.set_to(to + phase);
+            for edge in self.triggering_job.run()? {
+                let ratio = edge.ratio() - edge.ratio().floor();
+                self.events.push(Edge::new(ratio, edge.rising()));
+            }
+        }
+
+        self.previous_time_ratio = self.time_ratio;
+        self.time_ratio = self.wrap_ratio(to);
+        if self.looping {
+            self.sampling_job.set_ratio(phase_ratio(self.time_ratio, self.phase));
+        } else {
+            self.sampling_job.set_ratio(self.time_ratio);
+        }
+        self.sampling_job.run()?;
+        return Ok(());
+    }
+
+    fn wrap_ratio(&self, ratio: f32) -> f32 {
+        if self.looping {
+            return ratio - ratio.floor();
+        }
+        return ratio.clamp(0.0, 1.0);
+    }
+}
+
+#[cfg(test)]
+mod animation_player_tests {
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::track::{TrackBuilder, TrackInterpolation};
+
+    fn new_player() -> AnimationPlayer {
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+        let mut player = AnimationPlayer::new(animation);
+
+        // Footsteps at 0.25 (left) and 0.75 (right).
+        let mut builder = TrackBuilder::new();
+        builder
+            .add_keyframe(0.0, 0.0, TrackInterpolation::Step)
+            .add_keyframe(0.25, 1.0, TrackInterpolation::Step)
+            .add_keyframe(0.5, 0.0, TrackInterpolation::Step)
+            .add_keyframe(0.75, 1.0, TrackInterpolation::Step)
+            .add_keyframe(0.8, 0.0, TrackInterpolation::Step);
+        player.set_event_track(Rc::new(builder.build().unwrap()), 0.5);
+        return player;
+    }
+
+    fn sample(animation: &Rc<Animation>, ratio: f32) -> Vec<SoaTransform> {
+        let mut job = SamplingJob::default();
+        job.set_animation(animation.clone());
+        job.set_context(SamplingContext::from_animation(animation.as_ref()));
+        job.set_output(vec![SoaTransform::default(); animation.num_soa_tracks()]);
+        job.set_ratio(ratio);
+        job.run().unwrap();
+        return job.output().unwrap().clone();
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_loop_events() {
+        let mut player = new_player();
+        let animation = player.animation().clone();
+        let dt = animation.duration() / 10.0;
+
+        let mut footsteps = Vec::new();
+        for frame in 1..=15 {
+            player.update(dt).unwrap();
+            let expected = (frame as f32 / 10.0).fract();
+            assert!((player.time_ratio() - expected).abs() < 1e-4, "frame={}", frame);
+
+            let expected_pose = sample(&animation, player.time_ratio());
+            assert_eq!(player.pose(), &expected_pose[..], "frame={}", frame);
+
+            for edge in player.events().filter(|edge| edge.rising()) {
+                footsteps.push((frame, edge.ratio()));
+            }
+        }
+
+        assert_eq!(footsteps.len(), 3);
+        assert_eq!(footsteps[0], (3, 0.25));
+        assert_eq!(footsteps[1], (8, 0.75));
+        assert_eq!(footsteps[2].0, 13);
+        assert!((footsteps[2].1 - 0.25).abs() < 1e-5);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_pause_speed_no_loop() {
+        let mut player = new_player();
+        let duration = player.animation().duration();
+
+        player.pause();
+        player.update(duration).unwrap();
+        assert_eq!(player.time_ratio(), 0.0);
+        assert_eq!(player.events().count(), 0);
+
+        player.play();
+        player.set_playback_speed(0.5);
+        player.update(duration).unwrap();
+        assert!((player.time_ratio() - 0.5).abs() < 1e-5);
+        assert_eq!(player.previous_time_ratio(), 0.0);
+        // The left footstep, its falling edge at 0.5 belongs to the next update.
+        assert_eq!(player.events().count(), 1);
+        assert!(player.events().all(|edge| edge.rising() && edge.ratio() == 0.25));
+
+        player.set_looping(false);
+        player.set_playback_speed(1.0);
+        player.update(duration).unwrap();
+        assert_eq!(player.time_ratio(), 1.0);
+        assert_eq!(player.events().count(), 3);
+        assert_eq!(player.events().filter(|edge| edge.rising()).count(), 1);
+
+        // Backward playback.
+        player.set_playback_speed(-1.0);
+        player.update(duration * 0.5).unwrap();
+        assert!((player.time_ratio() - 0.5).abs() < 1e-5);
+        assert_eq!(player.events().count(), 3);
+        assert!(player.events().all(|edge| edge.ratio() >= 0.5));
+
+        player.set_time_ratio(1.5);
+        assert_eq!(player.time_ratio(), 1.0);
+        player.set_looping(true);
+        player.set_time_ratio(1.25);
+        assert_eq!(player.time_ratio(), 0.25);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_phase_ratio() {
+        assert_eq!(phase_ratio(0.25, 0.0), 0.25);
+        assert_eq!(phase_ratio(0.75, 0.5), 0.25);
+        assert_eq!(phase_ratio(0.25, -0.5), 0.75);
+        assert_eq!(phase_ratio(1.0, 0.0), 0.0);
+        assert_eq!(phase_ratio(0.5, 0.5), 0.0);
+        assert!((phase_ratio(0.25, 2.5) - 0.75).abs() < 1e-6);
+        assert!((phase_ratio(0.25, -3.75) - 0.5).abs() < 1e-6);
+        assert_eq!(phase_ratio(0.0, -1e-9), 0.0);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_phase() {
+        let mut player = new_player();
+        let animation = player.animation().clone();
+        player.set_phase(0.5);
+        player.update(0.0).unwrap();
+        assert_eq!(player.time_ratio(), 0.0);
+        assert_eq!(player.pose(), &sample(&animation, 0.5)[..]);
+
+        // Crossing the right footstep at 0.75, and the left one after wrapping.
+        player.update(animation.duration() * 0.8).unwrap();
+        assert!((player.time_ratio() - 0.8).abs() < 1e-5);
+        let rising = player.events().filter(|edge| edge.rising());
+        let footsteps: Vec<_> = rising.map(|edge| edge.ratio()).collect();
+        assert_eq!(footsteps.len(), 2);
+        assert_eq!(footsteps[0], 0.75);
+        assert!((footsteps[1] - 0.25).abs() < 1e-5);
+
+        player.set_phase(-0.25);
+        player.set_time_ratio(0.0);
+        player.update(0.0).unwrap();
+        assert_eq!(player.pose(), &sample(&animation, 0.75)[..]);
+
+        player.set_looping(false);
+        player.update(0.0).unwrap();
+        assert_eq!(player.pose(), &sample(&animation, 0.0)[..]);
+    }
+}
@@ -0,0 +1,216 @@
+//!
+//! Animation Set.
+//!
+
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
+
+use crate::animation::Animation;
+#[cfg(feature = "std")]
+use crate::archive::{Archive, ArchiveWriter};
+#[cfg(feature = "std")]
+use crate::base::OzzError;
+use crate::skeleton::Skeleton;
+
+///
+/// A skeleton with its animation clips, stored in a single archive.
+///
+/// The archive is a multi-object archive: the `AnimationSet` header (the number of clips), followed by
+/// the skeleton and the animations, each with their own ozz object header. Animations are looked up by
+/// name, see `Animation::name`.
+///
+#[derive(Debug)]
+pub struct AnimationSet {
+    skeleton: Skeleton,
+    animations: Vec<Animation>,
+}
+
+impl AnimationSet {
+    /// `AnimationSet` resource file tag for `Archive`.
+    #[inline]
+    pub fn tag() -> &'static str {
+        return "ozz-animation_set";
+    }
+
+    /// `AnimationSet` resource file version for `Archive`.
+    #[inline]
+    pub fn version() -> u32 {
+        return 1;
+    }
+
+    /// Creates an `AnimationSet` of `skeleton` without animation.
+    #[inline]
+    pub fn new(skeleton: Skeleton) -> AnimationSet {
+        return AnimationSet {
+            skeleton,
+            animations: Vec::new(),
+        };
+    }
+
+    /// Reads an `AnimationSet` from an `Archive`.
+    #[cfg(feature = "std")]
+    pub fn from_archive(archive: &mut Archive<impl Read>) -> Result<AnimationSet, OzzError> {
+        if archive.tag() != Self::tag() {
+            return Err(OzzError::InvalidTag);
+        }
+        if archive.version() != Self::version() {
+            return Err(OzzError::InvalidVersion);
+        }
+
+        let num_animations: u32 = archive.read()?;
+        archive.read_object_header()?;
+        let skeleton = Skeleton::from_archive(archive)?;
+        let mut animations = Vec::with_capacity(num_animations as usize);
+        for _ in 0..num_animations {
+            archive.read_object_header()?;
+            animations.push(Animation::from_archive(archive)?);
+        }
+        return Ok(AnimationSet { skeleton, animations });
+    }
+
+    /// Writes an `AnimationSet` to an `ArchiveWriter`.
+    #[cfg(feature = "std")]
+    pub fn to_archive(&self, archive: &mut ArchiveWriter<impl Write>) -> Result<(), OzzError> {
+        if archive.tag() != Self::tag() {
+            return Err(OzzError::InvalidTag);
+        }
+        if archive.version() != Self::version() {
+            return Err(OzzError::InvalidVersion);
+        }
+
+        archive.write(&(self.animations.len() as u32))?;
+        archive.write_object_header(Skeleton::tag(), Skeleton::version())?;
+        self.skeleton.to_archive(archive)?;
+        for animation in &self.animations {
+            archive.write_object_header(Animation::tag(), Animation::version())?;
+            animation.to_archive(archive)?;
+        }
+        return Ok(());
+    }
+
+    /// Reads an `AnimationSet` from a file path.
+    #[cfg(all(feature = "std", not(feature = "wasm")))]
+    pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> Result<AnimationSet, OzzError> {
+        let mut archive = Archive::from_path(path)?;
+        return AnimationSet::from_archive(&mut archive);
+    }
+
+    // Only for wasm test in NodeJS environment.
+    #[cfg(all(feature = "wasm", feature = "nodejs"))]
+    pub fn from_path(path: &str) -> Result<AnimationSet, OzzError> {
+        let mut archive = Archive::from_path(path)?;
+        return AnimationSet::from_archive(&mut archive);
+    }
+
+    /// Writes an `AnimationSet` to a file path.
+    #[cfg(all(feature = "std", not(feature = "wasm")))]
+    pub fn to_path<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), OzzError> {
+        let mut archive = ArchiveWriter::from_path(path, Self::tag(), Self::version())?;
+        return self.to_archive(&mut archive);
+    }
+}
+
+impl AnimationSet {
+    /// Gets the skeleton of `AnimationSet`.
+    #[inline]
+    pub fn skeleton(&self) -> &Skeleton {
+        return &self.skeleton;
+    }
+
+    /// Gets the animations of `AnimationSet`.
+    #[inline]
+    pub fn animations(&self) -> &[Animation] {
+        return &self.animations;
+    }
+
+    /// Gets mutable animations of `AnimationSet`.
+    ///
+    /// Animations are expected to have unique names, and to be made for the skeleton.
+    #[inline]
+    pub fn animations_mut(&mut self) -> &mut Vec<Animation> {
+        return &mut self.animations;
+    }
+
+    /// Adds `animation` to `AnimationSet`.
+    #[inline]
+    pub fn push_animation(&mut self, animation: Animation) {
+        self.animations.push(animation);
+    }
+
+    /// Gets the animation named `name`, the first one if several animations share this name.
+    pub fn get_animation(&self, name: &str) -> Option<&Animation> {
+        return self.animations.iter().find(|animation| animation.name() == name);
+    }
+}
+
+#[cfg(test)]
+mod animation_set_tests {
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::animation::AnimationBuilder;
+
+    fn new_set() -> AnimationSet {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let mut set = AnimationSet::new(skeleton);
+        let mut animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        animation.name = "run".into();
+        set.push_animation(animation);
+        let mut builder = AnimationBuilder::new(2.0, 3);
+        builder.set_name("wave");
+        builder.tracks_mut()[1].translations = vec![(0.5, glam::Vec3::X), (1.5, glam::Vec3::Y)];
+        set.push_animation(builder.build().unwrap());
+        return set;
+    }
+
+    fn assert_animation_eq(a: &Animation, b: &Animation) {
+        assert_eq!(a.name(), b.name());
+        assert_eq!(a.duration(), b.duration());
+        assert_eq!(a.num_tracks(), b.num_tracks());
+        assert_eq!(a.translations(), b.translations());
+        assert_eq!(a.rotations(), b.rotations());
+        assert_eq!(a.scales(), b.scales());
+    }
+
+    fn assert_set_eq(a: &AnimationSet, b: &AnimationSet) {
+        assert_eq!(a.skeleton().joint_rest_poses(), b.skeleton().joint_rest_poses());
+        assert_eq!(a.skeleton().joint_parents(), b.skeleton().joint_parents());
+        assert_eq!(a.skeleton().joint_names(), b.skeleton().joint_names());
+        assert_eq!(a.animations().len(), b.animations().len());
+        for (a, b) in a.animations().iter().zip(b.animations().iter()) {
+            assert_animation_eq(a, b);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_animation_set_archive() {
+        let set = new_set();
+        let mut writer = ArchiveWriter::to_vec(AnimationSet::tag(), AnimationSet::version()).unwrap();
+        set.to_archive(&mut writer).unwrap();
+
+        let mut archive = Archive::from_vec(writer.into_inner()).unwrap();
+        let loaded = AnimationSet::from_archive(&mut archive).unwrap();
+        assert_set_eq(&loaded, &set);
+        assert_eq!(loaded.animations().len(), 2);
+        assert_animation_eq(loaded.get_animation("wave").unwrap(), &set.animations()[1]);
+        assert_animation_eq(loaded.get_animation("run").unwrap(), &set.animations()[0]);
+        assert!(loaded.get_animation("walk").is_none());
+
+        let mut archive = Archive::from_path("./resource/playback/skeleton.ozz").unwrap();
+        assert!(AnimationSet::from_archive(&mut archive).unwrap_err().is_invalid_tag());
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    #[test]
+    fn test_animation_set_path() {
+        let set = new_set();
+        let path = std::env::temp_dir().join("ozz-animation-rs-test-animation-set.ozz");
+        set.to_path(&path).unwrap();
+        let loaded = AnimationSet::from_path(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_set_eq(&loaded, &set);
+        assert_animation_eq(loaded.get_animation("run").unwrap(), set.get_animation("run").unwrap());
+    }
+}
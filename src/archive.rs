@@ -5,7 +5,7 @@
 use glam::{Quat, Vec2, Vec3, Vec4};
 #[cfg(not(feature = "wasm"))]
 use std::fs::File;
-use std::io::{Cursor, Read};
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
 #[cfg(not(feature = "wasm"))]
 use std::path::Path;
 use std::{mem, slice, str};
@@ -47,6 +47,14 @@ impl<R: Read> Archive<R> {
         return Ok(archive);
     }
 
+    /// Reads the header (tag and version) of the next object of a multi-object archive, written by
+    /// `ArchiveWriter::write_object_header`. `tag()` and `version()` are then the ones of this object.
+    pub fn read_object_header(&mut self) -> Result<(), OzzError> {
+        self.tag = self.read::<String>()?;
+        self.version = self.read::<u32>()?;
+        return Ok(());
+    }
+
     /// Reads `T` from the archive.
     pub fn read<T: ArchiveRead<T>>(&mut self) -> Result<T, OzzError> {
         return T::read(self);
@@ -74,6 +82,19 @@ impl<R: Read> Archive<R> {
     }
 }
 
+impl<R: Read + Seek> Archive<R> {
+    /// Gets the current position of the archive stream, from the start of the stream.
+    pub fn stream_position(&mut self) -> Result<u64, OzzError> {
+        return Ok(self.read.stream_position()?);
+    }
+
+    /// Seeks the archive stream to `pos`, a position returned by `stream_position`.
+    pub fn seek_to(&mut self, pos: u64) -> Result<(), OzzError> {
+        self.read.seek(SeekFrom::Start(pos))?;
+        return Ok(());
+    }
+}
+
 #[cfg(not(feature = "wasm"))]
 impl Archive<File> {
     /// Creates an `Archive` from a path.
@@ -103,6 +124,152 @@ impl Archive<Cursor<Vec<u8>>> {
             Err(err) => return Err(OzzError::Custom(err.as_string().unwrap_or("".into()))),
         };
     }
+
+    /// Creates an `Archive` from a reader of a compressed (or not) archive.
+    ///
+    /// The compression is detected by magic bytes, gzip and zlib streams are decompressed in memory before
+    /// parsing, uncompressed archives are read as is. Zstd streams, and gzip or zlib streams without the
+    /// `gzip` feature, return `OzzError::Unsupported`. Corrupted streams, or streams decompressing to more
+    /// than `DECOMPRESSED_MAX_SIZE` bytes, return an `OzzError::IO` of kind `InvalidData`.
+    pub fn from_compressed_reader(read: impl Read) -> Result<Archive<Cursor<Vec<u8>>>, OzzError> {
+        return Archive::from_compressed_reader_with_limit(read, DECOMPRESSED_MAX_SIZE);
+    }
+
+    /// Creates an `Archive` from a reader of a compressed (or not) archive, like `from_compressed_reader`.
+    ///
+    /// * `max_size` - The maximum size of the decompressed archive in bytes. Decompression stops with an
+    ///   `OzzError::IO` of kind `InvalidData` beyond this size.
+    pub fn from_compressed_reader_with_limit(
+        mut read: impl Read,
+        max_size: usize,
+    ) -> Result<Archive<Cursor<Vec<u8>>>, OzzError> {
+        let mut buf = Vec::new();
+        read.read_to_end(&mut buf)?;
+        return Archive::from_vec(decompress(buf, max_size)?);
+    }
+}
+
+/// The default maximum size of a decompressed archive, see `Archive::from_compressed_reader`.
+pub const DECOMPRESSED_MAX_SIZE: usize = 256 * 1024 * 1024;
+
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+// Uncompressed archives start with the endian tag (0 or 1), that can't be confused with a magic number.
+fn decompress(buf: Vec<u8>, max_size: usize) -> Result<Vec<u8>, OzzError> {
+    if buf.starts_with(&GZIP_MAGIC) {
+        return decompress_gzip(&buf, max_size);
+    }
+    if buf.starts_with(&ZSTD_MAGIC) {
+        return Err(OzzError::Unsupported("zstd compression".into()));
+    }
+    let is_zlib = buf.len() >= 2 && buf[0] & 0x0F == 8 && u16::from_be_bytes([buf[0], buf[1]]) % 31 == 0;
+    if is_zlib {
+        return decompress_zlib(&buf, max_size);
+    }
+    return Ok(buf);
+}
+
+#[cfg(not(feature = "gzip"))]
+fn decompress_gzip(_: &[u8], _: usize) -> Result<Vec<u8>, OzzError> {
+    return Err(OzzError::Unsupported("gzip feature disabled".into()));
+}
+
+#[cfg(not(feature = "gzip"))]
+fn decompress_zlib(_: &[u8], _: usize) -> Result<Vec<u8>, OzzError> {
+    return Err(OzzError::Unsupported("gzip feature disabled".into()));
+}
+
+#[cfg(feature = "gzip")]
+fn decompress_zlib(buf: &[u8], max_size: usize) -> Result<Vec<u8>, OzzError> {
+    use miniz_oxide::inflate::decompress_to_vec_zlib_with_limit;
+
+    return decompress_to_vec_zlib_with_limit(buf, max_size).map_err(|err| inflate_error(err, max_size));
+}
+
+// Single member gzip stream, see RFC 1952. miniz_oxide only handles raw deflate and zlib streams, the gzip
+// header and trailer are parsed here.
+#[cfg(feature = "gzip")]
+fn decompress_gzip(buf: &[u8], max_size: usize) -> Result<Vec<u8>, OzzError> {
+    use miniz_oxide::inflate::decompress_to_vec_with_limit;
+
+    const FHCRC: u8 = 0x02;
+    const FEXTRA: u8 = 0x04;
+    const FNAME: u8 = 0x08;
+    const FCOMMENT: u8 = 0x10;
+
+    if buf.len() < 18 || buf[2] != 8 {
+        return Err(invalid_data("Invalid gzip header"));
+    }
+    let flags = buf[3];
+    let mut pos = 10;
+    if flags & FEXTRA != 0 {
+        let len = buf.get(pos..pos + 2).ok_or(invalid_data("Invalid gzip header"))?;
+        pos += 2 + u16::from_le_bytes([len[0], len[1]]) as usize;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let end = (buf.get(pos..).unwrap_or(&[]).iter()).position(|x| *x == 0);
+            pos += end.ok_or(invalid_data("Invalid gzip header"))? + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        pos += 2;
+    }
+    if pos + 8 > buf.len() {
+        return Err(invalid_data("Invalid gzip header"));
+    }
+
+    let data = &buf[pos..buf.len() - 8];
+    let trailer = &buf[buf.len() - 8..];
+    let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    let size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+    let data = decompress_to_vec_with_limit(data, max_size).map_err(|err| inflate_error(err, max_size))?;
+    if crc32(&data) != crc || data.len() as u32 != size {
+        return Err(invalid_data("Invalid gzip checksum"));
+    }
+    return Ok(data);
+}
+
+#[cfg(feature = "gzip")]
+fn inflate_error(err: miniz_oxide::inflate::DecompressError, max_size: usize) -> OzzError {
+    use miniz_oxide::inflate::TINFLStatus;
+
+    if err.status == TINFLStatus::HasMoreOutput {
+        return invalid_data(&format!("Decompressed archive larger than {} bytes", max_size));
+    }
+    return invalid_data(&err.to_string());
+}
+
+#[cfg(feature = "gzip")]
+fn invalid_data(msg: &str) -> OzzError {
+    return OzzError::IO(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+}
+
+#[cfg(feature = "gzip")]
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut idx = 0;
+    while idx < 256 {
+        let mut crc = idx as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+            bit += 1;
+        }
+        table[idx] = crc;
+        idx += 1;
+    }
+    table
+};
+
+#[cfg(feature = "gzip")]
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc = (crc >> 8) ^ CRC32_TABLE[((crc ^ *byte as u32) & 0xFF) as usize];
+    }
+    return !crc;
 }
 
 impl Archive<Cursor<&[u8]>> {
@@ -212,6 +379,156 @@ impl ArchiveRead<String> for String {
     }
 }
 
+/// Implements output archive concept used to save/serialize data.
+/// Data is always written with the native endianness, `Archive` performs the conversions at
+/// loading time if needed.
+pub struct ArchiveWriter<W: Write> {
+    write: W,
+    tag: String,
+    version: u32,
+}
+
+impl<W: Write> ArchiveWriter<W> {
+    /// Creates an `ArchiveWriter`, writing the archive header.
+    /// * `tag` - The tag of the object stored in the archive.
+    /// * `version` - The version of the object stored in the archive.
+    pub fn new(write: W, tag: &str, version: u32) -> Result<ArchiveWriter<W>, OzzError> {
+        let mut archive = ArchiveWriter {
+            write,
+            tag: String::new(),
+            version: 0,
+        };
+
+        let endian_tag: u8 = if Endian::native() == Endian::Big { 0 } else { 1 };
+        archive.write(&endian_tag)?;
+        archive.write_object_header(tag, version)?;
+        return Ok(archive);
+    }
+
+    /// Writes the header (tag and version) of the next object, so that an archive can store multiple
+    /// objects one after the other. `tag()` and `version()` are then the ones of this object.
+    ///
+    /// The archive header is the header of the first object, read back with `Archive::new`. Next objects
+    /// are read back with `Archive::read_object_header`.
+    pub fn write_object_header(&mut self, tag: &str, version: u32) -> Result<(), OzzError> {
+        self.tag = tag.to_string();
+        self.version = version;
+        self.write(&self.tag.clone())?;
+        return self.write(&version);
+    }
+
+    /// Writes `T` to the archive.
+    pub fn write<T: ArchiveWrite<T>>(&mut self, val: &T) -> Result<(), OzzError> {
+        return T::write(self, val);
+    }
+
+    /// Writes `[T]` to the archive.
+    pub fn write_slice<T: ArchiveWrite<T>>(&mut self, vals: &[T]) -> Result<(), OzzError> {
+        return T::write_slice(self, vals);
+    }
+
+    /// Gets the tag of the archive.
+    pub fn tag(&self) -> &str {
+        return &self.tag;
+    }
+
+    /// Gets the version of the archive.
+    pub fn version(&self) -> u32 {
+        return self.version;
+    }
+
+    /// Consumes the archive, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        return self.write;
+    }
+}
+
+#[cfg(not(feature = "wasm"))]
+impl ArchiveWriter<File> {
+    /// Creates an `ArchiveWriter` to a path.
+    pub fn from_path<P: AsRef<Path>>(path: P, tag: &str, version: u32) -> Result<ArchiveWriter<File>, OzzError> {
+        let file = File::create(path)?;
+        return ArchiveWriter::new(file, tag, version);
+    }
+}
+
+impl ArchiveWriter<Vec<u8>> {
+    /// Creates an `ArchiveWriter` to a `Vec<u8>`.
+    pub fn to_vec(tag: &str, version: u32) -> Result<ArchiveWriter<Vec<u8>>, OzzError> {
+        return ArchiveWriter::new(Vec::new(), tag, version);
+    }
+}
+
+/// Implements `ArchiveWrite` to write `T` to ArchiveWriter.
+pub trait ArchiveWrite<T> {
+    /// Writes `T` to the archive.
+    fn write<W: Write>(archive: &mut ArchiveWriter<W>, val: &T) -> Result<(), OzzError>;
+
+    /// Writes `[T]` to the archive.
+    fn write_slice<W: Write>(archive: &mut ArchiveWriter<W>, vals: &[T]) -> Result<(), OzzError> {
+        for val in vals {
+            Self::write(archive, val)?;
+        }
+        return Ok(());
+    }
+}
+
+macro_rules! primitive_writer {
+    ($type:ty) => {
+        impl ArchiveWrite<$type> for $type {
+            fn write<W: Write>(archive: &mut ArchiveWriter<W>, val: &$type) -> Result<(), OzzError> {
+                archive.write.write_all(unsafe {
+                    slice::from_raw_parts(val as *const $type as *const u8, mem::size_of::<$type>())
+                })?;
+                return Ok(());
+            }
+        }
+    };
+}
+
+primitive_writer!(bool);
+primitive_writer!(u8);
+primitive_writer!(i8);
+primitive_writer!(u16);
+primitive_writer!(i16);
+primitive_writer!(u32);
+primitive_writer!(i32);
+primitive_writer!(u64);
+primitive_writer!(i64);
+primitive_writer!(f32);
+primitive_writer!(f64);
+
+impl ArchiveWrite<Vec2> for Vec2 {
+    fn write<W: Write>(archive: &mut ArchiveWriter<W>, val: &Vec2) -> Result<(), OzzError> {
+        return f32::write_slice(archive, &val.to_array());
+    }
+}
+
+impl ArchiveWrite<Vec3> for Vec3 {
+    fn write<W: Write>(archive: &mut ArchiveWriter<W>, val: &Vec3) -> Result<(), OzzError> {
+        return f32::write_slice(archive, &val.to_array());
+    }
+}
+
+impl ArchiveWrite<Vec4> for Vec4 {
+    fn write<W: Write>(archive: &mut ArchiveWriter<W>, val: &Vec4) -> Result<(), OzzError> {
+        return f32::write_slice(archive, &val.to_array());
+    }
+}
+
+impl ArchiveWrite<Quat> for Quat {
+    fn write<W: Write>(archive: &mut ArchiveWriter<W>, val: &Quat) -> Result<(), OzzError> {
+        return f32::write_slice(archive, &val.to_array());
+    }
+}
+
+impl ArchiveWrite<String> for String {
+    fn write<W: Write>(archive: &mut ArchiveWriter<W>, val: &String) -> Result<(), OzzError> {
+        u8::write_slice(archive, val.as_bytes())?;
+        return u8::write(archive, &0);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use wasm_bindgen_test::*;
@@ -224,4 +541,120 @@ mod tests {
         let archive = Archive::from_path("./resource/playback/animation.ozz").unwrap();
         assert_eq!(archive.endian_swap, false);
     }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_from_compressed_reader() {
+        use miniz_oxide::deflate::compress_to_vec_zlib;
+
+        let mut writer = ArchiveWriter::to_vec("ozz-test", 3).unwrap();
+        writer.write(&42u32).unwrap();
+        let raw = writer.into_inner();
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+        let mut archive = Archive::from_compressed_reader(compress_to_vec_zlib(&raw, 6).as_slice()).unwrap();
+        assert_eq!(archive.tag(), "ozz-test");
+        assert_eq!(archive.read::<u32>().unwrap(), 42);
+
+        let mut archive = Archive::from_compressed_reader(raw.as_slice()).unwrap();
+        assert_eq!(archive.read::<u32>().unwrap(), 42);
+
+        let zstd = [0x28, 0xB5, 0x2F, 0xFD, 0, 0];
+        let err = Archive::from_compressed_reader(&zstd[..]).err();
+        assert!(matches!(err, Some(OzzError::Unsupported(_))));
+        let gzip = [0x1F, 0x8B, 8, 0x08, 0, 0, 0, 0, 0, 0xFF, b'a'];
+        let err = Archive::from_compressed_reader(&gzip[..]).err();
+        assert!(matches!(err, Some(OzzError::IO(_))));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_compressed_reader_limit() {
+        use miniz_oxide::deflate::{compress_to_vec, compress_to_vec_zlib};
+
+        let mut writer = ArchiveWriter::to_vec("ozz-test", 3).unwrap();
+        writer.write_slice(&[0u8; 4096]).unwrap();
+        let raw = writer.into_inner();
+
+        let zlib = compress_to_vec_zlib(&raw, 6);
+        let mut gzip = vec![0x1F, 0x8B, 8, 0, 0, 0, 0, 0, 0, 0xFF];
+        gzip.extend(compress_to_vec(&raw, 6));
+        gzip.extend(crc32(&raw).to_le_bytes());
+        gzip.extend((raw.len() as u32).to_le_bytes());
+        assert!(zlib.len() < 1024 && gzip.len() < 1024);
+
+        for buf in [&zlib, &gzip] {
+            let err = Archive::from_compressed_reader_with_limit(buf.as_slice(), 1024).err();
+            assert!(matches!(err, Some(OzzError::IO(err)) if err.kind() == std::io::ErrorKind::InvalidData));
+            let archive = Archive::from_compressed_reader_with_limit(buf.as_slice(), raw.len()).unwrap();
+            assert_eq!(archive.tag(), "ozz-test");
+        }
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_compressed_reader_unsupported() {
+        let gzip = [0x1F, 0x8B, 8, 0, 0, 0, 0, 0, 0, 0xFF, 0x03, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let err = Archive::from_compressed_reader(&gzip[..]).err();
+        assert!(matches!(err, Some(OzzError::Unsupported(_))));
+        let zlib = [0x78, 0x9C, 0x03, 0, 0, 0, 0, 1];
+        let err = Archive::from_compressed_reader(&zlib[..]).err();
+        assert!(err.is_some_and(|err| err.is_unsupported()));
+
+        let mut writer = ArchiveWriter::to_vec("ozz-test", 3).unwrap();
+        writer.write(&42u32).unwrap();
+        let mut archive = Archive::from_compressed_reader(writer.into_inner().as_slice()).unwrap();
+        assert_eq!(archive.read::<u32>().unwrap(), 42);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_archive_writer() {
+        let mut writer = ArchiveWriter::to_vec("ozz-test", 3).unwrap();
+        writer.write(&42u32).unwrap();
+        writer.write(&-1.5f32).unwrap();
+        writer.write(&true).unwrap();
+        writer.write(&String::from("name")).unwrap();
+        writer
+            .write_slice(&[Vec3::new(1.0, 2.0, 3.0), Vec3::new(4.0, 5.0, 6.0)])
+            .unwrap();
+        writer.write(&Quat::from_xyzw(0.0, 0.0, 1.0, 0.0)).unwrap();
+
+        let mut archive = Archive::from_vec(writer.into_inner()).unwrap();
+        assert_eq!(archive.endian_swap(), false);
+        assert_eq!(archive.tag(), "ozz-test");
+        assert_eq!(archive.version(), 3);
+        assert_eq!(archive.read::<u32>().unwrap(), 42);
+        assert_eq!(archive.read::<f32>().unwrap(), -1.5);
+        assert_eq!(archive.read::<bool>().unwrap(), true);
+        assert_eq!(archive.read::<String>().unwrap(), "name");
+        assert_eq!(
+            archive.read_vec::<Vec3>(2).unwrap(),
+            vec![Vec3::new(1.0, 2.0, 3.0), Vec3::new(4.0, 5.0, 6.0)]
+        );
+        assert_eq!(archive.read::<Quat>().unwrap(), Quat::from_xyzw(0.0, 0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_archive_multi_object() {
+        let mut writer = ArchiveWriter::to_vec("ozz-test", 3).unwrap();
+        writer.write(&42u32).unwrap();
+        writer.write_object_header("ozz-test2", 5).unwrap();
+        assert_eq!(writer.tag(), "ozz-test2");
+        assert_eq!(writer.version(), 5);
+        writer.write(&-1.5f32).unwrap();
+
+        let mut archive = Archive::from_vec(writer.into_inner()).unwrap();
+        assert_eq!(archive.tag(), "ozz-test");
+        assert_eq!(archive.read::<u32>().unwrap(), 42);
+        archive.read_object_header().unwrap();
+        assert_eq!(archive.tag(), "ozz-test2");
+        assert_eq!(archive.version(), 5);
+        assert_eq!(archive.read::<f32>().unwrap(), -1.5);
+        assert!(archive.read_object_header().unwrap_err().is_io());
+    }
 }
@@ -2,13 +2,20 @@
 //! Base types, traits and utils.
 //!
 
-use std::cell::{Ref, RefCell, RefMut};
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::{Ref, RefCell, RefMut};
+use core::fmt::Debug;
+use core::hash::BuildHasher;
+#[cfg(not(feature = "std"))]
+use core::hash::SipHasher13 as DefaultHasher;
+use core::ops::{Deref, DerefMut};
+#[cfg(feature = "std")]
 use std::collections::hash_map::DefaultHasher;
-use std::fmt::Debug;
-use std::hash::BuildHasher;
-use std::ops::{Deref, DerefMut};
-use std::rc::Rc;
-use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+#[cfg(feature = "std")]
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 use thiserror::Error;
 
 /// Ozz error type.
@@ -25,11 +32,34 @@ pub enum OzzError {
     InvalidIndex,
 
     /// Std io errors.
+    #[cfg(feature = "std")]
     #[error("IO error: {0}")]
     IO(#[from] std::io::Error),
     /// Std string errors.
     #[error("Utf8 error: {0}")]
-    Utf8(#[from] std::str::Utf8Error),
+    Utf8(#[from] core::str::Utf8Error),
+
+    /// Invalid keyframes given to a builder.
+    #[error("Invalid keyframe")]
+    InvalidKeyframe,
+    /// Non-finite (NaN or infinite) value in a pose, see `validate_pose`.
+    #[error("Non-finite {component} of joint {joint}")]
+    NonFinite { joint: usize, component: &'static str },
+    /// Animation with more tracks than skeleton joints, see `SamplingJob::set_skeleton`.
+    #[error("Animation has {num_tracks} tracks, more than the {num_joints} skeleton joints")]
+    TrackCountMismatch { num_tracks: usize, num_joints: usize },
+    /// Negative or non-finite blending weight, see `validate_weights`.
+    #[error("Invalid weight {weight} at {index}")]
+    InvalidWeight { index: usize, weight: f32 },
+    /// Buffer (output or context) too small, see `Animation::sample_into`.
+    #[error("Buffer of {actual} elements, {expected} expected")]
+    BufferSize { expected: usize, actual: usize },
+    /// Names of joints (or nodes) aren't unique, see `SkeletonBuilder::build`.
+    #[error("Duplicate name {0}")]
+    DuplicateName(String),
+    /// Hierarchy of joints (or nodes) with a cycle, see `SkeletonBuilder::build`.
+    #[error("Cyclic hierarchy")]
+    CyclicHierarchy,
 
     /// Read ozz archive tag error.
     #[error("Invalid tag")]
@@ -37,6 +67,9 @@ pub enum OzzError {
     /// Read ozz archive version error.
     #[error("Invalid version")]
     InvalidVersion,
+    /// Feature not supported in this build, like a format requiring a disabled cargo feature.
+    #[error("Unsupported: {0}")]
+    Unsupported(String),
 
     /// Custom errors.
     /// Ozz-animation-rs does not generate this error (except test & nodejs), but you can use it in your own code.
@@ -59,6 +92,7 @@ impl OzzError {
         };
     }
 
+    #[cfg(feature = "std")]
     pub fn is_io(&self) -> bool {
         return match self {
             OzzError::IO(_) => true,
@@ -73,6 +107,55 @@ impl OzzError {
         };
     }
 
+    pub fn is_invalid_keyframe(&self) -> bool {
+        return match self {
+            OzzError::InvalidKeyframe => true,
+            _ => false,
+        };
+    }
+
+    pub fn is_non_finite(&self) -> bool {
+        return match self {
+            OzzError::NonFinite { .. } => true,
+            _ => false,
+        };
+    }
+
+    pub fn is_track_count_mismatch(&self) -> bool {
+        return match self {
+            OzzError::TrackCountMismatch { .. } => true,
+            _ => false,
+        };
+    }
+
+    pub fn is_invalid_weight(&self) -> bool {
+        return match self {
+            OzzError::InvalidWeight { .. } => true,
+            _ => false,
+        };
+    }
+
+    pub fn is_buffer_size(&self) -> bool {
+        return match self {
+            OzzError::BufferSize { .. } => true,
+            _ => false,
+        };
+    }
+
+    pub fn is_duplicate_name(&self) -> bool {
+        return match self {
+            OzzError::DuplicateName(_) => true,
+            _ => false,
+        };
+    }
+
+    pub fn is_cyclic_hierarchy(&self) -> bool {
+        return match self {
+            OzzError::CyclicHierarchy => true,
+            _ => false,
+        };
+    }
+
     pub fn is_invalid_tag(&self) -> bool {
         return match self {
             OzzError::InvalidTag => true,
@@ -87,6 +170,13 @@ impl OzzError {
         };
     }
 
+    pub fn is_unsupported(&self) -> bool {
+        return match self {
+            OzzError::Unsupported(_) => true,
+            _ => false,
+        };
+    }
+
     pub fn is_custom(&self) -> bool {
         return match self {
             OzzError::Custom(_) => true,
@@ -110,6 +200,7 @@ pub const SKELETON_MAX_SOA_JOINTS: i32 = (SKELETON_MAX_JOINTS + 3) / 4;
 pub const SKELETON_NO_PARENT: i32 = -1;
 
 /// A hasher builder that creates `DefaultHasher` with default keys.
+/// Without the `std` feature, it creates `SipHasher13` with default keys, the same hasher as `DefaultHasher`.
 #[derive(Debug, Default, Clone, Copy)]
 pub struct DeterministicState;
 
@@ -124,7 +215,7 @@ impl BuildHasher for DeterministicState {
     type Hasher = DefaultHasher;
 
     fn build_hasher(&self) -> DefaultHasher {
-        return DefaultHasher::default();
+        return DefaultHasher::new();
     }
 }
 
@@ -378,6 +469,7 @@ impl<'t, T> DerefMut for ObCellRefMut<'t, T> {
 // Arc<RwLock<Vec<T>>>
 //
 
+#[cfg(feature = "std")]
 impl<T: 'static + Debug + Clone> OzzBuf<T> for Arc<RwLock<Vec<T>>> {
     type Buf<'t> = ObRwLockReadGuard<'t, T>;
 
@@ -390,8 +482,10 @@ impl<T: 'static + Debug + Clone> OzzBuf<T> for Arc<RwLock<Vec<T>>> {
     }
 }
 
+#[cfg(feature = "std")]
 pub struct ObRwLockReadGuard<'t, T>(RwLockReadGuard<'t, Vec<T>>);
 
+#[cfg(feature = "std")]
 impl<'t, T> Deref for ObRwLockReadGuard<'t, T> {
     type Target = [T];
 
@@ -401,6 +495,7 @@ impl<'t, T> Deref for ObRwLockReadGuard<'t, T> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<T: 'static + Debug + Clone> OzzMutBuf<T> for Arc<RwLock<Vec<T>>> {
     type MutBuf<'t> = ObRwLockWriteGuard<'t, T>;
 
@@ -413,8 +508,10 @@ impl<T: 'static + Debug + Clone> OzzMutBuf<T> for Arc<RwLock<Vec<T>>> {
     }
 }
 
+#[cfg(feature = "std")]
 pub struct ObRwLockWriteGuard<'t, T>(RwLockWriteGuard<'t, Vec<T>>);
 
+#[cfg(feature = "std")]
 impl<'t, T> Deref for ObRwLockWriteGuard<'t, T> {
     type Target = [T];
 
@@ -424,6 +521,7 @@ impl<'t, T> Deref for ObRwLockWriteGuard<'t, T> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<'t, T> DerefMut for ObRwLockWriteGuard<'t, T> {
     #[inline(always)]
     fn deref_mut(&mut self) -> &mut Self::Target {
@@ -0,0 +1,273 @@
+//!
+//! Blend Spaces.
+//!
+
+use alloc::vec;
+use alloc::vec::Vec;
+use glam::Vec2;
+
+use crate::base::{OzzBuf, OzzError, OzzMutBuf, OzzObj};
+use crate::blending_job::BlendingJob;
+use crate::math::SoaTransform;
+use crate::skeleton::Skeleton;
+
+fn apply_weights<S, I, O>(weights: &[f32], job: &mut BlendingJob<S, I, O>) -> Result<(), OzzError>
+where
+    S: OzzObj<Skeleton>,
+    I: OzzBuf<SoaTransform>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    if job.layers().len() != weights.len() {
+        return Err(OzzError::InvalidJob);
+    }
+    for (layer, weight) in job.layers_mut().iter_mut().zip(weights) {
+        layer.weight = *weight;
+    }
+    return Ok(());
+}
+
+///
+/// 1D blend space.
+///
+/// Clips are placed at positions on a parameter axis (like a locomotion speed). A query blends the 2
+/// clips surrounding the query position linearly, queries out of the axis range are clamped to the first
+/// or last clip.
+///
+#[derive(Debug, Default, Clone)]
+pub struct BlendSpace1D {
+    positions: Vec<f32>,
+}
+
+impl BlendSpace1D {
+    /// Creates a `BlendSpace1D` from clip positions, ordered like the `BlendingJob` layers.
+    pub fn new(positions: &[f32]) -> BlendSpace1D {
+        return BlendSpace1D {
+            positions: positions.to_vec(),
+        };
+    }
+
+    /// Gets clip positions of `BlendSpace1D`.
+    #[inline]
+    pub fn positions(&self) -> &[f32] {
+        return &self.positions;
+    }
+
+    /// Gets mutable clip positions of `BlendSpace1D`.
+    #[inline]
+    pub fn positions_mut(&mut self) -> &mut Vec<f32> {
+        return &mut self.positions;
+    }
+
+    /// Computes the weight of each clip at `query`. Weights sum to 1, unless there's no clip.
+    pub fn weights(&self, query: f32) -> Vec<f32> {
+        let mut weights = vec![0.0; self.positions.len()];
+
+        // Nearest clip at or below the query, and nearest clip above the query.
+        let mut below: Option<usize> = None;
+        let mut above: Option<usize> = None;
+        for (idx, position) in self.positions.iter().enumerate() {
+            if *position <= query {
+                if below.map_or(true, |b| *position > self.positions[b]) {
+                    below = Some(idx);
+                }
+            } else if above.map_or(true, |a| *position < self.positions[a]) {
+                above = Some(idx);
+            }
+        }
+
+        match (below, above) {
+            (Some(b), Some(a)) => {
+                let alpha = (query - self.positions[b]) / (self.positions[a] - self.positions[b]);
+                weights[b] = 1.0 - alpha;
+                weights[a] = alpha;
+            }
+            (Some(idx), None) | (None, Some(idx)) => weights[idx] = 1.0,
+            (None, None) => {}
+        }
+        return weights;
+    }
+
+    /// Sets the layer weights of `job` for `query`.
+    ///
+    /// `job` layers must be the clips poses, ordered like positions. Returns `OzzError::InvalidJob`
+    /// otherwise.
+    pub fn apply<S, I, O>(&self, query: f32, job: &mut BlendingJob<S, I, O>) -> Result<(), OzzError>
+    where
+        S: OzzObj<Skeleton>,
+        I: OzzBuf<SoaTransform>,
+        O: OzzMutBuf<SoaTransform>,
+    {
+        return apply_weights(&self.weights(query), job);
+    }
+}
+
+///
+/// 2D blend space.
+///
+/// Clips are placed at positions on a parameter plane (like a locomotion direction and speed). Weights
+/// are computed with gradient band interpolation: each clip influence decreases linearly towards every
+/// other clip. A query at a clip position gives this clip only.
+///
+#[derive(Debug, Default, Clone)]
+pub struct BlendSpace2D {
+    positions: Vec<Vec2>,
+}
+
+impl BlendSpace2D {
+    /// Creates a `BlendSpace2D` from clip positions, ordered like the `BlendingJob` layers.
+    pub fn new(positions: &[Vec2]) -> BlendSpace2D {
+        return BlendSpace2D {
+            positions: positions.to_vec(),
+        };
+    }
+
+    /// Gets clip positions of `BlendSpace2D`.
+    #[inline]
+    pub fn positions(&self) -> &[Vec2] {
+        return &self.positions;
+    }
+
+    /// Gets mutable clip positions of `BlendSpace2D`.
+    #[inline]
+    pub fn positions_mut(&mut self) -> &mut Vec<Vec2> {
+        return &mut self.positions;
+    }
+
+    /// Computes the weight of each clip at `query`. Weights sum to 1, unless there's no clip.
+    pub fn weights(&self, query: Vec2) -> Vec<f32> {
+        let mut weights = vec![0.0; self.positions.len()];
+        for (i, pi) in self.positions.iter().enumerate() {
+            let mut weight = 1.0f32;
+            for (j, pj) in self.positions.iter().enumerate() {
+                let edge = *pj - *pi;
+                let len2 = edge.length_squared();
+                if i == j || len2 <= 0.0 {
+                    continue;
+                }
+                weight = weight.min(1.0 - (query - *pi).dot(edge) / len2);
+            }
+            weights[i] = weight.max(0.0);
+        }
+
+        let total: f32 = weights.iter().sum();
+        if total > 0.0 {
+            weights.iter_mut().for_each(|w| *w /= total);
+        }
+        return weights;
+    }
+
+    /// Sets the layer weights of `job` for `query`.
+    ///
+    /// `job` layers must be the clips poses, ordered like positions. Returns `OzzError::InvalidJob`
+    /// otherwise.
+    pub fn apply<S, I, O>(&self, query: Vec2, job: &mut BlendingJob<S, I, O>) -> Result<(), OzzError>
+    where
+        S: OzzObj<Skeleton>,
+        I: OzzBuf<SoaTransform>,
+        O: OzzMutBuf<SoaTransform>,
+    {
+        return apply_weights(&self.weights(query), job);
+    }
+}
+
+#[cfg(test)]
+mod blend_space_tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::animation::Animation;
+    use crate::blending_job::BlendingLayer;
+    use crate::sampling_job::{SamplingContext, SamplingJob};
+
+    fn assert_weights(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-5, "{:?} {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_weights_1d() {
+        let space = BlendSpace1D::new(&[2.0, 0.0, 1.0]);
+        assert_weights(&space.weights(0.0), &[0.0, 1.0, 0.0]);
+        assert_weights(&space.weights(0.25), &[0.0, 0.75, 0.25]);
+        assert_weights(&space.weights(1.5), &[0.5, 0.0, 0.5]);
+        assert_weights(&space.weights(-1.0), &[0.0, 1.0, 0.0]);
+        assert_weights(&space.weights(3.0), &[1.0, 0.0, 0.0]);
+        assert!(BlendSpace1D::default().weights(1.0).is_empty());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_weights_2d() {
+        let positions = [
+            Vec2::ZERO,
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 1.0),
+            Vec2::new(-1.0, 0.0),
+        ];
+        let space = BlendSpace2D::new(&positions);
+        for (idx, position) in positions.iter().enumerate() {
+            let mut expected = [0.0; 4];
+            expected[idx] = 1.0;
+            assert_weights(&space.weights(*position), &expected);
+        }
+
+        let weights = space.weights(Vec2::new(0.5, 0.0));
+        assert_weights(&weights, &[0.5, 0.5, 0.0, 0.0]);
+        let weights = space.weights(Vec2::new(0.3, 0.4));
+        assert!((weights.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+        assert!(weights[1] > 0.0 && weights[2] > 0.0 && weights[3] == 0.0);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_blend_space_1d_pose() {
+        let skeleton = Rc::new(Skeleton::from_path("./resource/blend/skeleton.ozz").unwrap());
+        let mut poses = Vec::new();
+        for path in [
+            "./resource/blend/animation1.ozz",
+            "./resource/blend/animation2.ozz",
+            "./resource/blend/animation3.ozz",
+        ] {
+            let animation = Rc::new(Animation::from_path(path).unwrap());
+            let pose = Rc::new(RefCell::new(vec![SoaTransform::default(); animation.num_soa_tracks()]));
+            let mut job = SamplingJob::default();
+            job.set_context(SamplingContext::from_animation(animation.as_ref()));
+            job.set_animation(animation);
+            job.set_output(pose.clone());
+            job.set_ratio(0.3);
+            job.run().unwrap();
+            poses.push(pose);
+        }
+
+        let output = Rc::new(RefCell::new(vec![SoaTransform::default(); skeleton.num_soa_joints()]));
+        let mut job = BlendingJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.set_output(output.clone());
+        for pose in poses.iter() {
+            job.layers_mut().push(BlendingLayer::new(pose.clone()));
+        }
+
+        let space = BlendSpace1D::new(&[0.0, 1.0, 2.0]);
+        space.apply(1.0, &mut job).unwrap();
+        job.run().unwrap();
+        for idx in 0..skeleton.num_joints() {
+            let actual = output.borrow()[idx / 4].col(idx % 4);
+            let expected = poses[1].borrow()[idx / 4].col(idx % 4);
+            assert!(
+                actual.translation.abs_diff_eq(expected.translation, 1e-6),
+                "joint={}",
+                idx
+            );
+            assert!(actual.rotation.abs_diff_eq(expected.rotation, 1e-6), "joint={}", idx);
+            assert!(actual.scale.abs_diff_eq(expected.scale, 1e-6), "joint={}", idx);
+        }
+
+        job.layers_mut().pop();
+        assert!(space.apply(1.0, &mut job).unwrap_err().is_invalid_job());
+    }
+}
@@ -2,14 +2,19 @@
 //! Blending job.
 //!
 
+use alloc::rc::Rc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::simd::prelude::*;
 use glam::Vec4;
-use std::cell::RefCell;
-use std::rc::Rc;
-use std::simd::prelude::*;
+#[cfg(feature = "std")]
 use std::sync::{Arc, RwLock};
 
 use crate::base::{OzzBuf, OzzError, OzzMutBuf, OzzObj};
-use crate::math::{fx4_from_vec4, fx4_sign, SoaQuat, SoaTransform, SoaVec3};
+use crate::joint_mask::JointMask;
+use crate::math::{fx4_from_vec4, fx4_sign, fx4_to_vec4, AosTransform, SoaQuat, SoaTransform, SoaVec3};
+use crate::pose_utils::soa_to_aos_iter;
 use crate::skeleton::Skeleton;
 
 const ZERO: f32x4 = f32x4::from_array([0.0; 4]);
@@ -61,11 +66,38 @@ impl<I: OzzBuf<SoaTransform>> BlendingLayer<I> {
         };
     }
 
+    /// Sets per joint weights of this layer from SoA packed weights, like weights computed with `f32x4`.
+    ///
+    /// Lanes map to joints like `SoaTransform`: lane `j % 4` of `joint_weights[j / 4]` is the weight of
+    /// joint `j`. Weights of the padding lanes (beyond the number of joints) are ignored. The slice should
+    /// contain at least `Skeleton::num_soa_joints` elements.
+    pub fn set_joint_weights_soa(&mut self, joint_weights: &[f32x4]) {
+        self.joint_weights.clear();
+        self.joint_weights.extend(joint_weights.iter().map(|x| fx4_to_vec4(*x)));
+    }
+
     fn joint_weight(&self, idx: usize) -> f32x4 {
         return fx4_from_vec4(self.joint_weights[idx]);
     }
 }
 
+/// Defines an override layer of blending input data (local space transforms), replacing the joints of `mask`.
+#[derive(Debug, Clone)]
+pub struct OverrideLayer<I: OzzBuf<SoaTransform>> {
+    /// Buffer to store local space transforms, that are usually outputted from a `SamplingJob`.
+    pub transform: I,
+
+    /// Joints replaced by this layer. Masked joints take `transform` values as is, whatever the weights
+    /// of the other layers.
+    pub mask: JointMask,
+}
+
+impl<I: OzzBuf<SoaTransform>> OverrideLayer<I> {
+    pub fn new(transform: I, mask: JointMask) -> OverrideLayer<I> {
+        return OverrideLayer { transform, mask };
+    }
+}
+
 /// Blending context for storing intermediate blending data in `BlendingJob`.
 #[derive(Debug, Clone)]
 pub struct BlendingContext {
@@ -73,6 +105,7 @@ pub struct BlendingContext {
     num_partial_passes: u32,
     accumulated_weight: f32,
     accumulated_weights: Vec<f32x4>,
+    soa_output: Vec<SoaTransform>,
 }
 
 impl Default for BlendingContext {
@@ -82,6 +115,7 @@ impl Default for BlendingContext {
             num_partial_passes: 0,
             accumulated_weight: 0.0,
             accumulated_weights: Vec::new(),
+            soa_output: Vec::new(),
         };
     }
 }
@@ -94,6 +128,7 @@ impl BlendingContext {
             num_partial_passes: 0,
             accumulated_weight: 0.0,
             accumulated_weights: vec![f32x4::splat(0.0); soa_joints],
+            soa_output: Vec::new(),
         };
     }
 }
@@ -112,6 +147,14 @@ impl BlendingContext {
 /// are considered as a unit weight of 1.0, allowing to mix full and partial
 /// blend operations in a single pass.
 ///
+/// Normalized and additive layers are blended in a single run: standard layers
+/// are accumulated and normalized first (completed by the rest pose below the
+/// threshold), then additive layers are applied on top of the result. Joint
+/// weights of additive layers are respected the same way.
+///
+/// Override layers are applied last, masked joints are replaced by the layer transforms, ignoring weight
+/// normalization. When several override layers mask the same joint, the last one wins.
+///
 #[derive(Debug)]
 pub struct BlendingJob<S = Rc<Skeleton>, I = Rc<RefCell<Vec<SoaTransform>>>, O = Rc<RefCell<Vec<SoaTransform>>>>
 where
@@ -124,11 +167,13 @@ where
     threshold: f32,
     layers: Vec<BlendingLayer<I>>,
     additive_layers: Vec<BlendingLayer<I>>,
+    override_layers: Vec<OverrideLayer<I>>,
     output: Option<O>,
 }
 
-pub type BlendingJobRef<'t> = BlendingJob<&'t Skeleton, &'t [SoaTransform], &'t mut SoaTransform>;
+pub type BlendingJobRef<'t> = BlendingJob<&'t Skeleton, &'t [SoaTransform], &'t mut [SoaTransform]>;
 pub type BlendingJobRc<'t> = BlendingJob<Rc<Skeleton>, Rc<RefCell<Vec<SoaTransform>>>, Rc<RefCell<Vec<SoaTransform>>>>;
+#[cfg(feature = "std")]
 pub type BlendingJobArc = BlendingJob<Arc<Skeleton>, Arc<RwLock<Vec<SoaTransform>>>, Arc<RwLock<Vec<SoaTransform>>>>;
 
 impl<S, I, O> Default for BlendingJob<S, I, O>
@@ -144,6 +189,7 @@ where
             threshold: 0.1,
             layers: Vec::new(),
             additive_layers: Vec::new(),
+            override_layers: Vec::new(),
             output: None,
         };
     }
@@ -248,6 +294,20 @@ where
         return &mut self.additive_layers;
     }
 
+    /// Gets override layers of `BlendingJob`.
+    #[inline]
+    pub fn override_layers(&self) -> &[OverrideLayer<I>] {
+        return &self.override_layers;
+    }
+
+    /// Gets mutable override layers of `BlendingJob`.
+    ///
+    /// Job input override layers, can be empty. The range of layers replacing their masked joints in the output.
+    #[inline]
+    pub fn override_layers_mut(&mut self) -> &mut Vec<OverrideLayer<I>> {
+        return &mut self.override_layers;
+    }
+
     /// Gets output of `BlendingJob`.
     #[inline]
     pub fn output(&self) -> Option<&O> {
@@ -292,6 +352,10 @@ where
                 }
             }
 
+            for layer in &self.override_layers {
+                ok &= layer.transform.buf().ok()?.len() >= skeleton.num_soa_joints();
+            }
+
             return Some(ok);
         })()
         .unwrap_or(false);
@@ -321,9 +385,60 @@ where
         Self::blend_rest_pose(skeleton, ctx, self.threshold, &mut output);
         Self::normalize(skeleton, ctx, &mut output);
         Self::add_layers(skeleton, &self.additive_layers, &mut output)?;
+        Self::replace_layers(skeleton, &self.override_layers, &mut output)?;
         return Ok(());
     }
 
+    /// Runs job's blending task, writing the blended pose to `output` in AoS format.
+    ///
+    /// For controllers keeping poses in AoS, it avoids unpacking the SoA output after blending. Layers
+    /// are blended in a SoA buffer owned by the context, transposed by the final normalization step (or
+    /// after additive and override layers if any). The job output isn't needed nor written.
+    ///
+    /// * `output` - Blended local-space transforms, ordered like skeleton's joints. Must be at least as big
+    ///   as the number of joints, `OzzError::BufferSize` otherwise.
+    pub fn run_aos(&mut self, output: &mut [AosTransform]) -> Result<(), OzzError> {
+        let skeleton = self.skeleton.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        let ctx = self.context.as_mut().ok_or(OzzError::InvalidJob)?;
+        if self.threshold <= 0.0 {
+            return Err(OzzError::InvalidJob);
+        }
+        if output.len() < skeleton.num_joints() {
+            return Err(OzzError::BufferSize {
+                expected: skeleton.num_joints(),
+                actual: output.len(),
+            });
+        }
+
+        ctx.num_partial_passes = 0;
+        ctx.num_passes = 0;
+        ctx.accumulated_weight = 0.0;
+        if ctx.accumulated_weights.len() < skeleton.num_soa_joints() {
+            ctx.accumulated_weights.resize(skeleton.num_soa_joints(), ZERO);
+        }
+        let mut soa_output = core::mem::take(&mut ctx.soa_output);
+        soa_output.resize(skeleton.num_soa_joints(), SoaTransform::default());
+
+        let res = (|| {
+            Self::blend_layers(skeleton, ctx, &self.layers, &mut soa_output)?;
+            Self::blend_rest_pose(skeleton, ctx, self.threshold, &mut soa_output);
+            if self.additive_layers.is_empty() && self.override_layers.is_empty() {
+                Self::normalize_aos(skeleton, ctx, &soa_output, output);
+            } else {
+                Self::normalize(skeleton, ctx, &mut soa_output);
+                Self::add_layers(skeleton, &self.additive_layers, &mut soa_output)?;
+                Self::replace_layers(skeleton, &self.override_layers, &mut soa_output)?;
+                let transforms = soa_to_aos_iter(&soa_output, skeleton.num_joints());
+                for (dest, transform) in output.iter_mut().zip(transforms) {
+                    *dest = transform;
+                }
+            }
+            return Ok(());
+        })();
+        ctx.soa_output = soa_output;
+        return res;
+    }
+
     fn blend_layers(
         skeleton: &Skeleton,
         ctx: &mut BlendingContext,
@@ -434,6 +549,25 @@ where
         }
     }
 
+    fn normalize_aos(skeleton: &Skeleton, ctx: &BlendingContext, input: &[SoaTransform], output: &mut [AosTransform]) {
+        let uniform_ratio = f32x4::splat(ctx.accumulated_weight.recip());
+        for (idx, src) in input.iter().enumerate().take(skeleton.num_soa_joints()) {
+            let ratio = if ctx.num_partial_passes == 0 {
+                uniform_ratio
+            } else {
+                ctx.accumulated_weights[idx].recip()
+            };
+            let normalized = SoaTransform {
+                translation: src.translation.mul_num(ratio),
+                rotation: src.rotation.normalize(),
+                scale: src.scale.mul_num(ratio),
+            };
+            for lane in 0..(skeleton.num_joints() - idx * 4).min(4) {
+                output[idx * 4 + lane] = normalized.col(lane);
+            }
+        }
+    }
+
     fn add_layers(
         skeleton: &Skeleton,
         layers: &[BlendingLayer<I>],
@@ -488,6 +622,32 @@ where
         return Ok(());
     }
 
+    fn replace_layers(
+        skeleton: &Skeleton,
+        layers: &[OverrideLayer<I>],
+        output: &mut [SoaTransform],
+    ) -> Result<(), OzzError> {
+        for layer in layers {
+            let transform = layer.transform.buf()?;
+            if transform.len() < skeleton.num_soa_joints() {
+                return Err(OzzError::InvalidJob);
+            }
+
+            for idx in 0..skeleton.num_soa_joints() {
+                let lanes = layer.mask.soa_lanes(idx);
+                if lanes == 0xF {
+                    output[idx] = transform[idx];
+                } else {
+                    for lane in (0..4).filter(|lane| lanes & (1 << lane) != 0) {
+                        output[idx].set_col(lane, &transform[idx].col(lane));
+                    }
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
     #[inline(always)]
     fn blend_1st_pass(input: &SoaTransform, weight: f32x4, output: &mut SoaTransform) {
         output.translation = input.translation.mul_num(weight);
@@ -543,8 +703,53 @@ where
     }
 }
 
+/// Fallback of `normalize_weights` when all weights are zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeightFallback {
+    /// All weights are set to `1 / n`, blending all layers evenly.
+    Uniform,
+    /// Weights are left to zero, so `BlendingJob` outputs the rest pose (see `BlendingJob::threshold`).
+    Rest,
+}
+
+/// Validates layer weights, like `BlendingLayer::weight`.
+///
+/// Returns `OzzError::InvalidWeight` on the first negative or non-finite (NaN or infinite) weight. Zero
+/// weights are valid.
+pub fn validate_weights(weights: &[f32]) -> Result<(), OzzError> {
+    return match weights.iter().position(|weight| !weight.is_finite() || *weight < 0.0) {
+        Some(index) => Err(OzzError::InvalidWeight {
+            index,
+            weight: weights[index],
+        }),
+        None => Ok(()),
+    };
+}
+
+/// Normalizes layer weights, so that they sum to 1.
+///
+/// Negative weights are considered as 0, like `BlendingJob` does. If all weights are zero, `fallback`
+/// applies. Weights are expected to be finite, see `validate_weights`.
+///
+/// Returns false if `fallback` applied.
+pub fn normalize_weights(weights: &mut [f32], fallback: WeightFallback) -> bool {
+    weights.iter_mut().for_each(|weight| *weight = weight.max(0.0));
+    let sum: f32 = weights.iter().sum();
+    if sum > 0.0 {
+        weights.iter_mut().for_each(|weight| *weight /= sum);
+        return true;
+    }
+
+    if fallback == WeightFallback::Uniform {
+        let uniform = 1.0 / weights.len() as f32;
+        weights.iter_mut().for_each(|weight| *weight = uniform);
+    }
+    return false;
+}
+
 #[cfg(test)]
 mod blending_tests {
+    use glam::{Quat, Vec3};
     use std::mem;
     use wasm_bindgen_test::*;
 
@@ -1022,6 +1227,43 @@ mod blending_tests {
         }
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_joint_weights_soa() {
+        let skeleton = new_skeleton1();
+        let mut input1 = vec![IDENTITY; 1];
+        input1[0].translation = SoaVec3::new([0.0, 1.0, 2.0, 3.0], [4.0, 5.0, 6.0, 7.0], [8.0, 9.0, 10.0, 11.0]);
+        let mut input2 = vec![IDENTITY; 1];
+        input2[0].translation = input1[0].translation.neg();
+        input2[0].rotation = SoaQuat::splat_col([0.0, 0.70710677, 0.0, 0.70710677]);
+
+        let scalar1 = [0.2, 1.0, 0.0, 0.7];
+        let scalar2 = [0.8, 0.5, 1.0, 0.0];
+        let layers = new_layers(
+            input1.clone(),
+            vec![Vec4::from_array(scalar1)],
+            input2.clone(),
+            vec![Vec4::from_array(scalar2)],
+        );
+        let mut soa_layers = new_layers(input1, Vec::new(), input2, Vec::new());
+        soa_layers[0].set_joint_weights_soa(&[f32x4::from_array(scalar1)]);
+        soa_layers[1].set_joint_weights_soa(&[f32x4::from_array(scalar2)]);
+        assert_eq!(soa_layers[0].joint_weights, layers[0].joint_weights);
+
+        let mut outputs = Vec::new();
+        for mut layers in [layers, soa_layers] {
+            layers.iter_mut().for_each(|layer| layer.weight = 0.5);
+            let mut job: BlendingJob = BlendingJob::default();
+            job.set_skeleton(skeleton.clone());
+            *job.layers_mut() = layers;
+            let output = make_buf(vec![SoaTransform::default(); skeleton.num_soa_joints()]);
+            job.set_output(output.clone());
+            job.run().unwrap();
+            outputs.push(output.take());
+        }
+        assert_eq!(outputs[0], outputs[1]);
+    }
+
     fn new_skeleton1() -> Rc<Skeleton> {
         let mut joint_rest_poses = vec![IDENTITY];
         joint_rest_poses[0].scale = SoaVec3::new([0.0, 1.0, 2.0, 3.0], [4.0, 5.0, 6.0, 7.0], [8.0, 9.0, 10.0, 11.0]);
@@ -1414,6 +1656,73 @@ mod blending_tests {
         }
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_normalized_and_additive() {
+        let skeleton = Rc::new(Skeleton::from_raw(
+            vec![IDENTITY; 1],
+            vec![0; 4],
+            JointHashMap::with_hashers(DeterministicState::new(), DeterministicState::new()),
+        ));
+
+        let mut input1 = vec![IDENTITY; 1];
+        input1[0].translation = SoaVec3::new([0.0, 1.0, 2.0, 3.0], [4.0, 5.0, 6.0, 7.0], [8.0, 9.0, 10.0, 11.0]);
+        input1[0].rotation = SoaQuat::new(
+            [0.70710677, 0.0, 0.0, 0.382683432],
+            [0.0, 0.0, 0.70710677, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+            [0.70710677, 1.0, -0.70710677, 0.9238795],
+        );
+        let mut input2 = vec![IDENTITY; 1];
+        input2[0].translation = SoaVec3::splat_col([-1.0, 2.0, 0.5]);
+        input2[0].scale = SoaVec3::splat_col([2.0, 1.0, 0.5]);
+        let mut crossfade = new_layers(input1, vec![], input2, vec![]);
+        crossfade[0].weight = 0.3;
+        crossfade[1].weight = 0.7;
+
+        let mut overlay = vec![IDENTITY; 1];
+        overlay[0].translation = SoaVec3::splat_col([0.0, 0.5, 0.0]);
+        overlay[0].rotation = SoaQuat::splat_col([0.0, 0.0, 0.38268343, 0.9238795]);
+        overlay[0].scale = SoaVec3::splat_col([1.5, 1.5, 1.5]);
+        let additive = vec![BlendingLayer {
+            transform: make_buf(overlay),
+            weight: 0.8,
+            joint_weights: vec![Vec4::new(1.0, 0.5, 0.0, 1.0)],
+        }];
+
+        // Reference, normalized blending then additive blending on top of its result.
+        let normalized = make_buf(vec![SoaTransform::default(); 1]);
+        let mut job: BlendingJob = BlendingJob::default();
+        job.set_skeleton(skeleton.clone());
+        *job.layers_mut() = crossfade.clone();
+        job.set_output(normalized.clone());
+        job.run().unwrap();
+
+        let expected = make_buf(vec![SoaTransform::default(); 1]);
+        let mut job: BlendingJob = BlendingJob::default();
+        job.set_skeleton(skeleton.clone());
+        *job.layers_mut() = vec![BlendingLayer::with_weight(normalized.clone(), 1.0)];
+        *job.additive_layers_mut() = additive.clone();
+        job.set_output(expected.clone());
+        job.run().unwrap();
+        let expected = expected.borrow()[0];
+
+        // Masked out joint is the normalized result.
+        let normalized = normalized.borrow()[0];
+        assert_eq!(expected.translation.col(2), normalized.translation.col(2));
+        assert_ne!(expected.translation.col(0), normalized.translation.col(0));
+
+        execute_test(
+            &skeleton,
+            crossfade,
+            additive,
+            vec![expected.translation],
+            vec![expected.rotation],
+            vec![expected.scale],
+            "normalized and additive",
+        );
+    }
+
     #[test]
     #[wasm_bindgen_test]
     fn test_additive_joint_weight() {
@@ -1533,4 +1842,126 @@ mod blending_tests {
             )
         };
     }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_override_layers() {
+        let skeleton = Rc::new(Skeleton::from_path("./resource/skeleton-blending.ozz").unwrap());
+        let num_soa_joints = skeleton.num_soa_joints();
+        let input = |x: f32| {
+            let mut transform = IDENTITY;
+            transform.translation = SoaVec3::splat_col([x, 0.0, 0.0]);
+            return make_buf(vec![transform; num_soa_joints]);
+        };
+        let mut mask = JointMask::new(skeleton.num_joints());
+        mask.set(1, true);
+        mask.set(2, true);
+
+        let mut job: BlendingJob = BlendingJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.layers_mut().push(BlendingLayer::with_weight(input(1.0), 1.0));
+        job.layers_mut().push(BlendingLayer::with_weight(input(2.0), 3.0));
+        job.override_layers_mut().push(OverrideLayer::new(input(10.0), mask));
+        let output = make_buf(vec![SoaTransform::default(); num_soa_joints]);
+        job.set_output(output.clone());
+        assert!(job.validate());
+
+        for weights in [[1.0, 3.0], [0.0, 5.0], [0.0, 0.0]] {
+            job.layers_mut()[0].weight = weights[0];
+            job.layers_mut()[1].weight = weights[1];
+            job.run().unwrap();
+            let out = output.borrow();
+            assert_eq!(out[0].col(1).translation, Vec3::new(10.0, 0.0, 0.0));
+            assert_eq!(out[0].col(2).translation, Vec3::new(10.0, 0.0, 0.0));
+            assert_eq!(out[0].col(1).rotation, Quat::IDENTITY);
+            assert_ne!(out[0].col(0).translation.x, 10.0);
+            assert_ne!(out[0].col(3).translation.x, 10.0);
+        }
+        job.layers_mut()[0].weight = 1.0;
+        job.layers_mut()[1].weight = 3.0;
+        job.run().unwrap();
+        assert_eq!(output.borrow()[0].col(0).translation, Vec3::new(1.75, 0.0, 0.0));
+
+        job.override_layers_mut()[0].transform = make_buf(vec![IDENTITY; num_soa_joints - 1]);
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_run_aos() {
+        let skeleton = Rc::new(Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap());
+        let num_soa_joints = skeleton.num_soa_joints();
+        let input = |x: f32, angle: f32| {
+            let mut transform = IDENTITY;
+            transform.translation = SoaVec3::splat_col([x, 1.0, 0.0]);
+            transform.rotation = SoaQuat::splat_col(Quat::from_rotation_y(angle).to_array());
+            return make_buf(vec![transform; num_soa_joints]);
+        };
+
+        let mut job: BlendingJob = BlendingJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.layers_mut().push(BlendingLayer::with_weight(input(1.0, 0.5), 0.3));
+        let mut joint_weights = vec![Vec4::new(1.0, 0.0, 0.5, 2.0); num_soa_joints];
+        joint_weights[3] = Vec4::ZERO;
+        job.layers_mut()
+            .push(BlendingLayer::with_joint_weights(input(2.0, -1.0), joint_weights));
+        job.layers_mut()[1].weight = 0.6;
+        let output = make_buf(vec![SoaTransform::default(); num_soa_joints]);
+        job.set_output(output.clone());
+
+        let mut aos = vec![AosTransform::default(); skeleton.num_joints()];
+        for additive in [false, true] {
+            if additive {
+                job.additive_layers_mut()
+                    .push(BlendingLayer::with_weight(input(0.5, 0.2), 0.5));
+            }
+            job.run().unwrap();
+            job.run_aos(&mut aos).unwrap();
+            let expected: Vec<_> = soa_to_aos_iter(&output.borrow(), skeleton.num_joints()).collect();
+            assert_eq!(aos, expected);
+        }
+
+        let num_joints = skeleton.num_joints();
+        assert!(matches!(
+            job.run_aos(&mut aos[1..]),
+            Err(OzzError::BufferSize { expected, actual }) if expected == num_joints && actual == num_joints - 1
+        ));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_validate_weights() {
+        assert!(validate_weights(&[]).is_ok());
+        assert!(validate_weights(&[0.0, 0.5, 2.0]).is_ok());
+        let err = validate_weights(&[0.5, -0.1, -1.0]).unwrap_err();
+        assert!(err.is_invalid_weight());
+        assert!(matches!(err, OzzError::InvalidWeight { index: 1, weight } if weight == -0.1));
+        assert!(matches!(
+            validate_weights(&[1.0, f32::NAN]),
+            Err(OzzError::InvalidWeight { index: 1, .. })
+        ));
+        assert!(matches!(
+            validate_weights(&[f32::INFINITY]),
+            Err(OzzError::InvalidWeight { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_normalize_weights() {
+        let mut weights = [1.0, 3.0, -2.0];
+        assert!(normalize_weights(&mut weights, WeightFallback::Rest));
+        assert_eq!(weights, [0.25, 0.75, 0.0]);
+
+        let mut weights = [0.0, -1.0, 0.0, 0.0];
+        assert!(!normalize_weights(&mut weights, WeightFallback::Uniform));
+        assert_eq!(weights, [0.25; 4]);
+
+        let mut weights = [0.0, -1.0];
+        assert!(!normalize_weights(&mut weights, WeightFallback::Rest));
+        assert_eq!(weights, [0.0; 2]);
+
+        assert!(!normalize_weights(&mut [], WeightFallback::Uniform));
+    }
 }
@@ -0,0 +1,493 @@
+//!
+//! C ABI, for engines embedding ozz-animation-rs from C/C++.
+//!
+//! Resources and jobs are exposed as opaque handles, created by `ozz_*_new`/`ozz_*_from_bytes` functions
+//! and released by the matching `ozz_*_free` function. Functions returning `i32` return `OZZ_OK` on success,
+//! or a negative error code.
+//!
+
+use glam::Mat4;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::animation::Animation;
+use crate::archive::Archive;
+use crate::blending_job::{BlendingJob, BlendingJobRc, BlendingLayer};
+use crate::local_to_model_job::{LocalToModelJob, LocalToModelJobRc};
+use crate::math::SoaTransform;
+use crate::sampling_job::{SamplingContext, SamplingJob, SamplingJobRc};
+use crate::skeleton::Skeleton;
+
+/// Success.
+pub const OZZ_OK: i32 = 0;
+/// A handle or buffer argument is null.
+pub const OZZ_ERR_NULL: i32 = -1;
+/// A job failed to run (see `OzzError::InvalidJob`).
+pub const OZZ_ERR_INVALID_JOB: i32 = -2;
+/// The caller buffer is too small.
+pub const OZZ_ERR_BUFFER_TOO_SMALL: i32 = -3;
+/// A layer index is out of range.
+pub const OZZ_ERR_INVALID_LAYER: i32 = -4;
+
+/// Opaque skeleton handle.
+pub struct OzzSkeleton(Rc<Skeleton>);
+
+/// Opaque animation handle.
+pub struct OzzAnimation(Rc<Animation>);
+
+/// Opaque handle of a sampling + local-to-model pipeline, producing model-space matrices.
+pub struct OzzSampler {
+    sampling_job: SamplingJobRc,
+    l2m_job: LocalToModelJobRc,
+    matrices: Rc<RefCell<Vec<Mat4>>>,
+}
+
+/// Opaque handle of a sampling + blending + local-to-model pipeline, producing model-space matrices of
+/// several animations blended together.
+pub struct OzzBlender {
+    skeleton: Rc<Skeleton>,
+    sampling_jobs: Vec<SamplingJobRc>,
+    blending_job: BlendingJobRc<'static>,
+    l2m_job: LocalToModelJobRc,
+    matrices: Rc<RefCell<Vec<Mat4>>>,
+}
+
+unsafe fn bytes<'t>(data: *const u8, len: usize) -> Option<&'t [u8]> {
+    if data.is_null() {
+        return None;
+    }
+    return Some(std::slice::from_raw_parts(data, len));
+}
+
+unsafe fn into_handle<T>(value: T) -> *mut T {
+    return Box::into_raw(Box::new(value));
+}
+
+unsafe fn free_handle<T>(handle: *mut T) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Loads a skeleton from the bytes of an ozz skeleton archive (.ozz file content).
+/// Returns null if the data can't be read.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes. The returned handle must be freed with `ozz_skeleton_free`.
+#[no_mangle]
+pub unsafe extern "C" fn ozz_skeleton_from_bytes(data: *const u8, len: usize) -> *mut OzzSkeleton {
+    let Some(buf) = bytes(data, len) else {
+        return std::ptr::null_mut();
+    };
+    return match Archive::from_slice(buf).and_then(|mut archive| Skeleton::from_archive(&mut archive)) {
+        Ok(skeleton) => into_handle(OzzSkeleton(Rc::new(skeleton))),
+        Err(_) => std::ptr::null_mut(),
+    };
+}
+
+/// Frees a skeleton handle. Null is ignored.
+///
+/// # Safety
+///
+/// `skeleton` must be null or a handle returned by `ozz_skeleton_from_bytes`, not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ozz_skeleton_free(skeleton: *mut OzzSkeleton) {
+    free_handle(skeleton);
+}
+
+/// Gets the number of joints of a skeleton, 0 for a null handle.
+///
+/// # Safety
+///
+/// `skeleton` must be null or a valid skeleton handle.
+#[no_mangle]
+pub unsafe extern "C" fn ozz_skeleton_num_joints(skeleton: *const OzzSkeleton) -> usize {
+    return skeleton.as_ref().map_or(0, |s| s.0.num_joints());
+}
+
+/// Loads an animation from the bytes of an ozz animation archive (.ozz file content).
+/// Returns null if the data can't be read.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes. The returned handle must be freed with `ozz_animation_free`.
+#[no_mangle]
+pub unsafe extern "C" fn ozz_animation_from_bytes(data: *const u8, len: usize) -> *mut OzzAnimation {
+    let Some(buf) = bytes(data, len) else {
+        return std::ptr::null_mut();
+    };
+    return match Archive::from_slice(buf).and_then(|mut archive| Animation::from_archive(&mut archive)) {
+        Ok(animation) => into_handle(OzzAnimation(Rc::new(animation))),
+        Err(_) => std::ptr::null_mut(),
+    };
+}
+
+/// Frees an animation handle. Null is ignored.
+///
+/// # Safety
+///
+/// `animation` must be null or a handle returned by `ozz_animation_from_bytes`, not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ozz_animation_free(animation: *mut OzzAnimation) {
+    free_handle(animation);
+}
+
+/// Gets the duration of an animation in seconds, 0 for a null handle.
+///
+/// # Safety
+///
+/// `animation` must be null or a valid animation handle.
+#[no_mangle]
+pub unsafe extern "C" fn ozz_animation_duration(animation: *const OzzAnimation) -> f32 {
+    return animation.as_ref().map_or(0.0, |a| a.0.duration());
+}
+
+/// Creates a sampler, sampling `animation` and converting the pose to model-space with `skeleton`.
+/// Returns null if a handle is null.
+///
+/// The sampler keeps its own references to the skeleton and animation, so they can be freed before it.
+///
+/// # Safety
+///
+/// `skeleton` and `animation` must be null or valid handles. The returned handle must be freed with
+/// `ozz_sampler_free`.
+#[no_mangle]
+pub unsafe extern "C" fn ozz_sampler_new(
+    skeleton: *const OzzSkeleton,
+    animation: *const OzzAnimation,
+) -> *mut OzzSampler {
+    let (Some(skeleton), Some(animation)) = (skeleton.as_ref(), animation.as_ref()) else {
+        return std::ptr::null_mut();
+    };
+    let (skeleton, animation) = (skeleton.0.clone(), animation.0.clone());
+
+    let locals = Rc::new(RefCell::new(vec![SoaTransform::default(); skeleton.num_soa_joints()]));
+    let matrices = Rc::new(RefCell::new(vec![Mat4::IDENTITY; skeleton.num_joints()]));
+
+    let mut sampling_job: SamplingJobRc = SamplingJob::default();
+    sampling_job.set_context(SamplingContext::new(animation.num_tracks()));
+    sampling_job.set_animation(animation);
+    sampling_job.set_output(locals.clone());
+
+    let mut l2m_job: LocalToModelJobRc = LocalToModelJob::default();
+    l2m_job.set_skeleton(skeleton);
+    l2m_job.set_input(locals);
+    l2m_job.set_output(matrices.clone());
+
+    return into_handle(OzzSampler {
+        sampling_job,
+        l2m_job,
+        matrices,
+    });
+}
+
+/// Frees a sampler handle. Null is ignored.
+///
+/// # Safety
+///
+/// `sampler` must be null or a handle returned by `ozz_sampler_new`, not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ozz_sampler_free(sampler: *mut OzzSampler) {
+    free_handle(sampler);
+}
+
+/// Samples the animation at `ratio` (in range [0, 1]), and computes model-space matrices.
+///
+/// # Safety
+///
+/// `sampler` must be null or a valid sampler handle.
+#[no_mangle]
+pub unsafe extern "C" fn ozz_sampler_run(sampler: *mut OzzSampler, ratio: f32) -> i32 {
+    let Some(sampler) = sampler.as_mut() else {
+        return OZZ_ERR_NULL;
+    };
+    sampler.sampling_job.set_ratio(ratio);
+    if sampler.sampling_job.run().is_err() || sampler.l2m_job.run().is_err() {
+        return OZZ_ERR_INVALID_JOB;
+    }
+    return OZZ_OK;
+}
+
+/// Copies model-space matrices of the last `ozz_sampler_run` into `out`, as 16 floats per joint in
+/// column-major order. `len` is the number of floats of `out`, at least 16 * number of joints.
+///
+/// # Safety
+///
+/// `sampler` must be null or a valid sampler handle, `out` must point to `len` writable floats.
+#[no_mangle]
+pub unsafe extern "C" fn ozz_sampler_model_matrices(sampler: *const OzzSampler, out: *mut f32, len: usize) -> i32 {
+    let Some(sampler) = sampler.as_ref() else {
+        return OZZ_ERR_NULL;
+    };
+    return copy_matrices(&sampler.matrices.borrow(), out, len);
+}
+
+unsafe fn copy_matrices(matrices: &[Mat4], out: *mut f32, len: usize) -> i32 {
+    if out.is_null() {
+        return OZZ_ERR_NULL;
+    }
+    if len < matrices.len() * 16 {
+        return OZZ_ERR_BUFFER_TOO_SMALL;
+    }
+    let out = std::slice::from_raw_parts_mut(out, len);
+    for (dst, matrix) in out.chunks_exact_mut(16).zip(matrices.iter()) {
+        matrix.write_cols_to_slice(dst);
+    }
+    return OZZ_OK;
+}
+
+/// Creates a blender without layers, blending animations of `skeleton`. Returns null if the handle is null.
+///
+/// The blender keeps its own references to the skeleton and layer animations, so they can be freed before
+/// it. Without layers (or with null weights), the blender outputs the skeleton rest pose.
+///
+/// # Safety
+///
+/// `skeleton` must be null or a valid handle. The returned handle must be freed with `ozz_blender_free`.
+#[no_mangle]
+pub unsafe extern "C" fn ozz_blender_new(skeleton: *const OzzSkeleton) -> *mut OzzBlender {
+    let Some(skeleton) = skeleton.as_ref() else {
+        return std::ptr::null_mut();
+    };
+    let skeleton = skeleton.0.clone();
+
+    let locals = Rc::new(RefCell::new(vec![SoaTransform::default(); skeleton.num_soa_joints()]));
+    let matrices = Rc::new(RefCell::new(vec![Mat4::IDENTITY; skeleton.num_joints()]));
+
+    let mut blending_job: BlendingJobRc = BlendingJob::default();
+    blending_job.set_skeleton(skeleton.clone());
+    blending_job.set_output(locals.clone());
+
+    let mut l2m_job: LocalToModelJobRc = LocalToModelJob::default();
+    l2m_job.set_skeleton(skeleton.clone());
+    l2m_job.set_input(locals);
+    l2m_job.set_output(matrices.clone());
+
+    return into_handle(OzzBlender {
+        skeleton,
+        sampling_jobs: Vec::new(),
+        blending_job,
+        l2m_job,
+        matrices,
+    });
+}
+
+/// Frees a blender handle. Null is ignored.
+///
+/// # Safety
+///
+/// `blender` must be null or a handle returned by `ozz_blender_new`, not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ozz_blender_free(blender: *mut OzzBlender) {
+    free_handle(blender);
+}
+
+/// Adds a layer sampling `animation`, with a 0 weight and ratio. Returns the index of the new layer, or a
+/// negative error code.
+///
+/// # Safety
+///
+/// `blender` and `animation` must be null or valid handles.
+#[no_mangle]
+pub unsafe extern "C" fn ozz_blender_add_layer(blender: *mut OzzBlender, animation: *const OzzAnimation) -> i32 {
+    let (Some(blender), Some(animation)) = (blender.as_mut(), animation.as_ref()) else {
+        return OZZ_ERR_NULL;
+    };
+    let (animation, num_soa_joints) = (animation.0.clone(), blender.skeleton.num_soa_joints());
+
+    let locals = Rc::new(RefCell::new(vec![SoaTransform::default(); num_soa_joints]));
+    let mut sampling_job: SamplingJobRc = SamplingJob::default();
+    sampling_job.set_context(SamplingContext::new(animation.num_tracks()));
+    sampling_job.set_animation(animation);
+    sampling_job.set_output(locals.clone());
+
+    blender.sampling_jobs.push(sampling_job);
+    blender.blending_job.layers_mut().push(BlendingLayer::new(locals));
+    return (blender.sampling_jobs.len() - 1) as i32;
+}
+
+/// Sets the sampling `ratio` (in range [0, 1]) and blending `weight` of a layer.
+///
+/// # Safety
+///
+/// `blender` must be null or a valid blender handle.
+#[no_mangle]
+pub unsafe extern "C" fn ozz_blender_set_layer(blender: *mut OzzBlender, layer: usize, ratio: f32, weight: f32) -> i32 {
+    let Some(blender) = blender.as_mut() else {
+        return OZZ_ERR_NULL;
+    };
+    let (Some(sampling_job), Some(blending_layer)) = (
+        blender.sampling_jobs.get_mut(layer),
+        blender.blending_job.layers_mut().get_mut(layer),
+    ) else {
+        return OZZ_ERR_INVALID_LAYER;
+    };
+    sampling_job.set_ratio(ratio);
+    blending_layer.weight = weight;
+    return OZZ_OK;
+}
+
+/// Samples all layers, blends them, and computes model-space matrices.
+///
+/// # Safety
+///
+/// `blender` must be null or a valid blender handle.
+#[no_mangle]
+pub unsafe extern "C" fn ozz_blender_run(blender: *mut OzzBlender) -> i32 {
+    let Some(blender) = blender.as_mut() else {
+        return OZZ_ERR_NULL;
+    };
+    for sampling_job in blender.sampling_jobs.iter_mut() {
+        if sampling_job.run().is_err() {
+            return OZZ_ERR_INVALID_JOB;
+        }
+    }
+    if blender.blending_job.run().is_err() || blender.l2m_job.run().is_err() {
+        return OZZ_ERR_INVALID_JOB;
+    }
+    return OZZ_OK;
+}
+
+/// Copies model-space matrices of the last `ozz_blender_run` into `out`, as 16 floats per joint in
+/// column-major order. `len` is the number of floats of `out`, at least 16 * number of joints.
+///
+/// # Safety
+///
+/// `blender` must be null or a valid blender handle, `out` must point to `len` writable floats.
+#[no_mangle]
+pub unsafe extern "C" fn ozz_blender_model_matrices(blender: *const OzzBlender, out: *mut f32, len: usize) -> i32 {
+    let Some(blender) = blender.as_ref() else {
+        return OZZ_ERR_NULL;
+    };
+    return copy_matrices(&blender.matrices.borrow(), out, len);
+}
+
+#[cfg(test)]
+mod capi_tests {
+    use wasm_bindgen_test::*;
+
+    use super::*;
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_capi_sampling() {
+        let skeleton_bytes = std::fs::read("./resource/playback/skeleton.ozz").unwrap();
+        let animation_bytes = std::fs::read("./resource/playback/animation.ozz").unwrap();
+
+        unsafe {
+            assert!(ozz_skeleton_from_bytes(std::ptr::null(), 0).is_null());
+            assert!(ozz_skeleton_from_bytes(animation_bytes.as_ptr(), animation_bytes.len()).is_null());
+            assert!(ozz_sampler_new(std::ptr::null(), std::ptr::null()).is_null());
+            assert_eq!(ozz_sampler_run(std::ptr::null_mut(), 0.0), OZZ_ERR_NULL);
+
+            let skeleton = ozz_skeleton_from_bytes(skeleton_bytes.as_ptr(), skeleton_bytes.len());
+            let animation = ozz_animation_from_bytes(animation_bytes.as_ptr(), animation_bytes.len());
+            assert!(!skeleton.is_null() && !animation.is_null());
+            assert_eq!(ozz_skeleton_num_joints(skeleton), 67);
+
+            let sampler = ozz_sampler_new(skeleton, animation);
+            // The sampler keeps its own references.
+            ozz_skeleton_free(skeleton);
+            ozz_animation_free(animation);
+
+            let mut out = vec![0.0f32; 67 * 16];
+            assert_eq!(
+                ozz_sampler_model_matrices(sampler, out.as_mut_ptr(), 16),
+                OZZ_ERR_BUFFER_TOO_SMALL
+            );
+            assert_eq!(ozz_sampler_run(sampler, 0.3), OZZ_OK);
+            assert_eq!(ozz_sampler_model_matrices(sampler, out.as_mut_ptr(), out.len()), OZZ_OK);
+            ozz_sampler_free(sampler);
+
+            // Same result as the native path.
+            let skeleton = Rc::new(Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap());
+            let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+            let locals = Rc::new(RefCell::new(vec![SoaTransform::default(); skeleton.num_soa_joints()]));
+            let matrices = Rc::new(RefCell::new(vec![Mat4::IDENTITY; skeleton.num_joints()]));
+            let mut sampling_job: SamplingJobRc = SamplingJob::default();
+            sampling_job.set_context(SamplingContext::new(animation.num_tracks()));
+            sampling_job.set_animation(animation);
+            sampling_job.set_output(locals.clone());
+            sampling_job.set_ratio(0.3);
+            sampling_job.run().unwrap();
+            let mut l2m_job: LocalToModelJobRc = LocalToModelJob::default();
+            l2m_job.set_skeleton(skeleton);
+            l2m_job.set_input(locals);
+            l2m_job.set_output(matrices.clone());
+            l2m_job.run().unwrap();
+
+            for (idx, matrix) in matrices.borrow().iter().enumerate() {
+                assert_eq!(&out[idx * 16..idx * 16 + 16], &matrix.to_cols_array());
+            }
+        }
+
+        // Blended layers.
+        let skeleton_bytes = std::fs::read("./resource/blend/skeleton.ozz").unwrap();
+        let paths = [
+            "./resource/blend/animation1.ozz",
+            "./resource/blend/animation2.ozz",
+            "./resource/blend/animation3.ozz",
+        ];
+        let ratios = [0.2, 0.5, 0.7];
+        let weights = [0.3, 0.7, 0.0];
+
+        let mut out = vec![0.0f32; 67 * 16];
+        unsafe {
+            assert!(ozz_blender_new(std::ptr::null()).is_null());
+            assert_eq!(ozz_blender_run(std::ptr::null_mut()), OZZ_ERR_NULL);
+
+            let skeleton = ozz_skeleton_from_bytes(skeleton_bytes.as_ptr(), skeleton_bytes.len());
+            let blender = ozz_blender_new(skeleton);
+            assert_eq!(ozz_blender_add_layer(blender, std::ptr::null()), OZZ_ERR_NULL);
+            for (idx, path) in paths.iter().enumerate() {
+                let animation_bytes = std::fs::read(path).unwrap();
+                let animation = ozz_animation_from_bytes(animation_bytes.as_ptr(), animation_bytes.len());
+                assert_eq!(ozz_blender_add_layer(blender, animation), idx as i32);
+                ozz_animation_free(animation);
+                assert_eq!(ozz_blender_set_layer(blender, idx, ratios[idx], weights[idx]), OZZ_OK);
+            }
+            ozz_skeleton_free(skeleton);
+            assert_eq!(ozz_blender_set_layer(blender, 3, 0.0, 1.0), OZZ_ERR_INVALID_LAYER);
+
+            assert_eq!(
+                ozz_blender_model_matrices(blender, out.as_mut_ptr(), 16),
+                OZZ_ERR_BUFFER_TOO_SMALL
+            );
+            assert_eq!(ozz_blender_run(blender), OZZ_OK);
+            assert_eq!(ozz_blender_model_matrices(blender, out.as_mut_ptr(), out.len()), OZZ_OK);
+            ozz_blender_free(blender);
+        }
+
+        // Same result as the native path.
+        let skeleton = Rc::new(Skeleton::from_path("./resource/blend/skeleton.ozz").unwrap());
+        let blended = Rc::new(RefCell::new(vec![SoaTransform::default(); skeleton.num_soa_joints()]));
+        let matrices = Rc::new(RefCell::new(vec![Mat4::IDENTITY; skeleton.num_joints()]));
+        let mut blending_job: BlendingJobRc = BlendingJob::default();
+        blending_job.set_skeleton(skeleton.clone());
+        blending_job.set_output(blended.clone());
+        for (idx, path) in paths.iter().enumerate() {
+            let animation = Rc::new(Animation::from_path(path).unwrap());
+            let locals = Rc::new(RefCell::new(vec![SoaTransform::default(); skeleton.num_soa_joints()]));
+            let mut sampling_job: SamplingJobRc = SamplingJob::default();
+            sampling_job.set_context(SamplingContext::new(animation.num_tracks()));
+            sampling_job.set_animation(animation);
+            sampling_job.set_output(locals.clone());
+            sampling_job.set_ratio(ratios[idx]);
+            sampling_job.run().unwrap();
+            blending_job
+                .layers_mut()
+                .push(BlendingLayer::with_weight(locals, weights[idx]));
+        }
+        blending_job.run().unwrap();
+        let mut l2m_job: LocalToModelJobRc = LocalToModelJob::default();
+        l2m_job.set_skeleton(skeleton);
+        l2m_job.set_input(blended);
+        l2m_job.set_output(matrices.clone());
+        l2m_job.run().unwrap();
+
+        for (idx, matrix) in matrices.borrow().iter().enumerate() {
+            assert_eq!(&out[idx * 16..idx * 16 + 16], &matrix.to_cols_array());
+        }
+    }
+}
@@ -0,0 +1,278 @@
+//!
+//! Clamp Pose Job.
+//!
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+#[cfg(feature = "std")]
+use std::sync::{Arc, RwLock};
+
+use crate::base::{OzzBuf, OzzError, OzzMutBuf, OzzObj};
+use crate::joint_limits::JointLimits;
+use crate::math::SoaTransform;
+
+///
+/// Clamps local-space joint rotations to their `JointLimits`.
+///
+/// Each limited joint rotation is clamped into its allowed swing-twist range, so that IK or blending
+/// can't produce impossible poses. With a `softness`, rotations are eased near the bounds instead of being
+/// projected onto them, see `JointLimit::soft_clamp`. Translations, scales and rotations of free joints are
+/// copied as is.
+///
+/// Job input and output are local-space poses, ordered like the limits.
+///
+#[derive(Debug)]
+pub struct ClampPoseJob<L = Rc<JointLimits>, I = Rc<RefCell<Vec<SoaTransform>>>, O = Rc<RefCell<Vec<SoaTransform>>>>
+where
+    L: OzzObj<JointLimits>,
+    I: OzzBuf<SoaTransform>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    limits: Option<L>,
+    input: Option<I>,
+    output: Option<O>,
+    softness: f32,
+    num_clamped: usize,
+}
+
+pub type ClampPoseJobRef<'t> = ClampPoseJob<&'t JointLimits, &'t [SoaTransform], &'t mut [SoaTransform]>;
+pub type ClampPoseJobRc = ClampPoseJob<Rc<JointLimits>, Rc<RefCell<Vec<SoaTransform>>>, Rc<RefCell<Vec<SoaTransform>>>>;
+#[cfg(feature = "std")]
+pub type ClampPoseJobArc =
+    ClampPoseJob<Arc<JointLimits>, Arc<RwLock<Vec<SoaTransform>>>, Arc<RwLock<Vec<SoaTransform>>>>;
+
+impl<L, I, O> Default for ClampPoseJob<L, I, O>
+where
+    L: OzzObj<JointLimits>,
+    I: OzzBuf<SoaTransform>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    fn default() -> ClampPoseJob<L, I, O> {
+        return ClampPoseJob {
+            limits: None,
+            input: None,
+            output: None,
+            softness: 0.0,
+            num_clamped: 0,
+        };
+    }
+}
+
+impl<L, I, O> ClampPoseJob<L, I, O>
+where
+    L: OzzObj<JointLimits>,
+    I: OzzBuf<SoaTransform>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    /// Gets limits of `ClampPoseJob`.
+    #[inline]
+    pub fn limits(&self) -> Option<&L> {
+        return self.limits.as_ref();
+    }
+
+    /// Sets limits of `ClampPoseJob`.
+    ///
+    /// Per-joint rotation limits, ordered like the input pose joints.
+    #[inline]
+    pub fn set_limits(&mut self, limits: L) {
+        self.limits = Some(limits);
+    }
+
+    /// Clears limits of `ClampPoseJob`.
+    #[inline]
+    pub fn clear_limits(&mut self) {
+        self.limits = None;
+    }
+
+    /// Gets input of `ClampPoseJob`.
+    #[inline]
+    pub fn input(&self) -> Option<&I> {
+        return self.input.as_ref();
+    }
+
+    /// Sets input of `ClampPoseJob`.
+    ///
+    /// The local-space pose to clamp, at least as many joints as limits.
+    #[inline]
+    pub fn set_input(&mut self, input: I) {
+        self.input = Some(input);
+    }
+
+    /// Clears input of `ClampPoseJob`.
+    #[inline]
+    pub fn clear_input(&mut self) {
+        self.input = None;
+    }
+
+    /// Gets output of `ClampPoseJob`.
+    #[inline]
+    pub fn output(&self) -> Option<&O> {
+        return self.output.as_ref();
+    }
+
+    /// Sets output of `ClampPoseJob`.
+    ///
+    /// The clamped local-space pose, at least as large as the input.
+    #[inline]
+    pub fn set_output(&mut self, output: O) {
+        self.output = Some(output);
+    }
+
+    /// Clears output of `ClampPoseJob`.
+    #[inline]
+    pub fn clear_output(&mut self) {
+        self.output = None;
+    }
+
+    /// Gets softness of `ClampPoseJob`.
+    #[inline]
+    pub fn softness(&self) -> f32 {
+        return self.softness;
+    }
+
+    /// Sets softness of `ClampPoseJob`.
+    ///
+    /// Angle (in radians) before the limit bounds where rotations are eased, see `JointLimit::soft_clamp`.
+    /// Default is 0, a hard projection onto the bounds. Must be positive and finite.
+    #[inline]
+    pub fn set_softness(&mut self, softness: f32) {
+        self.softness = softness;
+    }
+
+    /// Gets **output** number of clamped joints of `ClampPoseJob`.
+    ///
+    /// Number of joints whose rotation was changed during the last run.
+    #[inline]
+    pub fn num_clamped(&self) -> usize {
+        return self.num_clamped;
+    }
+
+    /// Validates `ClampPoseJob` parameters.
+    pub fn validate(&self) -> bool {
+        return (|| {
+            let limits = self.limits.as_ref()?.obj();
+            let input = self.input.as_ref()?.buf().ok()?;
+            let output = self.output.as_ref()?.buf().ok()?;
+            let mut ok = input.len() >= limits.num_joints().div_ceil(4) && output.len() >= input.len();
+            ok &= self.softness.is_finite() && self.softness >= 0.0;
+            ok &= limits.limits().iter().flatten().all(|limit| limit.is_valid());
+            return Some(ok);
+        })()
+        .unwrap_or(false);
+    }
+
+    /// Runs clamp pose job's task.
+    /// The validate job before any operation is performed.
+    pub fn run(&mut self) -> Result<(), OzzError> {
+        if !self.validate() {
+            return Err(OzzError::InvalidJob);
+        }
+        let limits = self.limits.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        let input = self.input.as_ref().ok_or(OzzError::InvalidJob)?.buf()?;
+        let mut output = self.output.as_mut().ok_or(OzzError::InvalidJob)?.mut_buf()?;
+
+        output[..input.len()].copy_from_slice(&input);
+        self.num_clamped = 0;
+        for (idx, limit) in limits.limits().iter().enumerate() {
+            let Some(limit) = limit else {
+                continue;
+            };
+            let rotation = input[idx / 4].rotation.col(idx % 4);
+            let clamped = limit.soft_clamp(rotation, self.softness);
+            if clamped != rotation {
+                output[idx / 4].rotation.set_col(idx % 4, clamped);
+                self.num_clamped += 1;
+            }
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod clamp_pose_tests {
+    use glam::{Quat, Vec3};
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::joint_limits::JointLimit;
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_validity() {
+        let limits = JointLimits::new(5);
+        let input = vec![SoaTransform::default(); 2];
+        let mut output = vec![SoaTransform::default(); 1];
+
+        let mut job: ClampPoseJobRef = ClampPoseJob::default();
+        job.set_input(&input);
+        job.set_output(&mut output);
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        job.set_limits(&limits);
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        job.set_input(&input[..1]);
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        let mut output = vec![SoaTransform::default(); 2];
+        job.set_input(&input);
+        job.set_output(&mut output);
+        assert!(job.validate());
+        job.set_softness(-0.1);
+        assert!(!job.validate());
+        job.set_softness(0.1);
+
+        let mut limits = JointLimits::new(5);
+        limits.set_limit(2, JointLimit::new(Vec3::X, 0.5, 0.4, -0.2));
+        job.set_limits(&limits);
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_clamp_pose() {
+        let mut limits = JointLimits::new(3);
+        limits.set_limit(0, JointLimit::new(Vec3::X, 0.5, -0.2, 0.2));
+        limits.set_limit(1, JointLimit::new(Vec3::X, 0.5, -0.2, 0.2));
+
+        let mut input = vec![SoaTransform::default(); 1];
+        let in_range = Quat::from_rotation_y(0.3);
+        let out_of_range = Quat::from_rotation_z(1.0);
+        let free = Quat::from_rotation_z(2.0);
+        input[0].rotation.set_col(0, in_range);
+        input[0].rotation.set_col(1, out_of_range);
+        input[0].rotation.set_col(2, free);
+        input[0].translation.set_col(1, Vec3::new(1.0, 2.0, 3.0));
+
+        let mut output = vec![SoaTransform::default(); 1];
+        let mut job: ClampPoseJobRef = ClampPoseJob::default();
+        job.set_limits(&limits);
+        job.set_input(&input);
+        job.set_output(&mut output);
+        job.run().unwrap();
+        assert_eq!(job.num_clamped(), 1);
+
+        assert_eq!(output[0].rotation.col(0), in_range);
+        assert!(output[0].rotation.col(1).abs_diff_eq(Quat::from_rotation_z(0.5), 1e-6));
+        assert_eq!(output[0].rotation.col(2), free);
+        assert_eq!(output[0].translation, input[0].translation);
+        assert_eq!(output[0].scale, input[0].scale);
+
+        let mut job: ClampPoseJobRef = ClampPoseJob::default();
+        job.set_limits(&limits);
+        job.set_input(&input);
+        job.set_output(&mut output);
+        job.set_softness(0.2);
+        job.run().unwrap();
+        assert_eq!(job.num_clamped(), 1);
+        assert_eq!(output[0].rotation.col(0), in_range);
+        let soft = output[0].rotation.col(1);
+        assert!(soft.abs_diff_eq(limits.limit(1).unwrap().soft_clamp(out_of_range, 0.2), 1e-6));
+        assert!(!soft.abs_diff_eq(Quat::from_rotation_z(0.5), 1e-3));
+    }
+}
@@ -0,0 +1,151 @@
+//!
+//! Dirty Set.
+//!
+
+use alloc::vec::Vec;
+
+use crate::base::OzzIndex;
+use crate::joint_mask::JointMask;
+use crate::skeleton::Skeleton;
+
+///
+/// Set of joints whose local transform changed since the last update, ordered like skeleton's joints.
+///
+/// A controller marks the joints it modifies, then `LocalToModelJob::run_dirty` recomputes the model-space
+/// matrices of the marked joints and their descendants only, reusing the matrices of the other joints. The
+/// set is not cleared by the job, call `DirtySet::clear` once the frame is updated.
+///
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DirtySet {
+    joints: JointMask,
+}
+
+impl DirtySet {
+    /// Creates an empty `DirtySet` of `num_joints` joints.
+    #[inline]
+    pub fn new(num_joints: usize) -> DirtySet {
+        return DirtySet {
+            joints: JointMask::new(num_joints),
+        };
+    }
+
+    /// Creates an empty `DirtySet` sized for `skeleton`.
+    #[inline]
+    pub fn from_skeleton(skeleton: &Skeleton) -> DirtySet {
+        return DirtySet::new(skeleton.num_joints());
+    }
+
+    /// Gets the number of joints of `DirtySet`.
+    #[inline]
+    pub fn num_joints(&self) -> usize {
+        return self.joints.num_joints();
+    }
+
+    /// Gets the number of marked joints.
+    #[inline]
+    pub fn count(&self) -> usize {
+        return self.joints.count();
+    }
+
+    /// Tests if no joint is marked.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        return self.joints.bits().iter().all(|x| *x == 0);
+    }
+
+    /// Tests if `joint` is marked. Joints out of range are not marked.
+    #[inline]
+    pub fn is_dirty(&self, joint: impl OzzIndex) -> bool {
+        return self.joints.contains(joint.usize());
+    }
+
+    /// Marks `joint` as changed. Joints out of range are ignored.
+    #[inline]
+    pub fn mark(&mut self, joint: impl OzzIndex) {
+        self.joints.set(joint.usize(), true);
+    }
+
+    /// Marks all joints as changed, like when the root matrix changes.
+    pub fn mark_all(&mut self) {
+        self.joints = JointMask::all(self.joints.num_joints());
+    }
+
+    /// Unmarks all joints.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.joints = JointMask::new(self.joints.num_joints());
+    }
+
+    /// Gets the marked joints as a `JointMask`.
+    #[inline]
+    pub fn joints(&self) -> &JointMask {
+        return &self.joints;
+    }
+
+    /// Gets the subtrees to update, as `[begin, end)` joint ranges of `skeleton`.
+    ///
+    /// Each range is the subtree of a marked joint without marked ancestors, marked joints inside a range
+    /// are updated with it. Ranges are sorted and disjoint.
+    pub fn dirty_ranges(&self, skeleton: &Skeleton) -> Vec<(usize, usize)> {
+        let num_joints = usize::min(self.num_joints(), skeleton.num_joints());
+        let mut ranges = Vec::new();
+        let mut joint = 0;
+        while joint < num_joints {
+            if self.joints.contains(joint) {
+                let end = skeleton.subtree_end(joint);
+                ranges.push((joint, end));
+                joint = end;
+            } else {
+                joint += 1;
+            }
+        }
+        return ranges;
+    }
+}
+
+#[cfg(test)]
+mod dirty_set_tests {
+    use wasm_bindgen_test::*;
+
+    use super::*;
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_dirty_ranges() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let left_arm = skeleton.joint_by_name("LeftArm").unwrap();
+        let end = skeleton.subtree_end(left_arm);
+
+        let mut dirty = DirtySet::from_skeleton(&skeleton);
+        assert_eq!(dirty.num_joints(), skeleton.num_joints());
+        assert!(dirty.is_empty());
+        assert!(dirty.dirty_ranges(&skeleton).is_empty());
+
+        // Joints of a marked subtree don't add ranges.
+        dirty.mark(left_arm);
+        dirty.mark(left_arm + 3);
+        dirty.mark(end - 1);
+        dirty.mark(1000);
+        assert_eq!(dirty.count(), 3);
+        assert!(dirty.is_dirty(left_arm));
+        assert!(!dirty.is_dirty(left_arm + 1));
+        assert_eq!(dirty.dirty_ranges(&skeleton), vec![(left_arm as usize, end)]);
+
+        let leaf = (0..skeleton.num_joints())
+            .rev()
+            .find(|joint| skeleton.is_leaf(*joint))
+            .unwrap();
+        dirty.mark(leaf);
+        assert_eq!(
+            dirty.dirty_ranges(&skeleton),
+            vec![(left_arm as usize, end), (leaf, leaf + 1)]
+        );
+
+        dirty.mark_all();
+        assert_eq!(dirty.count(), skeleton.num_joints());
+        assert_eq!(dirty.dirty_ranges(&skeleton), vec![(0, skeleton.num_joints())]);
+        dirty.clear();
+        assert!(dirty.is_empty());
+        assert_eq!(dirty.num_joints(), skeleton.num_joints());
+    }
+}
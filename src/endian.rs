@@ -1,4 +1,4 @@
-use std::mem;
+use core::mem;
 
 pub trait SwapEndian {
     fn swap_endian(self) -> Self;
@@ -0,0 +1,711 @@
+//!
+//! glTF import.
+//!
+//! Importers take a `GltfDocument`, a loader-agnostic copy of the glTF nodes, skins and animations. Build it
+//! from a `gltf` crate document with `GltfDocument::from_gltf`, or fill it from any other glTF loader.
+//!
+
+use glam::{Mat4, Quat, Vec3};
+use std::collections::HashMap;
+use std::ops::{Add, Mul};
+
+use crate::animation::{Animation, AnimationBuilder};
+use crate::base::OzzError;
+use crate::math::AosTransform;
+use crate::skeleton::{Skeleton, SkeletonBuilder};
+
+/// Sampling rate (in samples per second) used to resample cubic spline channels to linear keyframes.
+pub const GLTF_CUBIC_SAMPLE_RATE: f32 = 30.0;
+
+///
+/// A node of a glTF document.
+///
+/// glTF loaders (like `gltf` crate) decode nodes in their own types, copy the fields needed by
+/// ozz-animation-rs to these structures.
+///
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GltfNode {
+    /// Node name, if any.
+    pub name: Option<String>,
+    /// Indices of the children nodes.
+    pub children: Vec<usize>,
+    /// Local transform of the node, decomposed from its TRS or matrix.
+    pub transform: AosTransform,
+}
+
+///
+/// A skin of a glTF document.
+///
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GltfSkin {
+    /// Skin name, if any.
+    pub name: Option<String>,
+    /// Indices of the joint nodes.
+    pub joints: Vec<usize>,
+    /// Inverse bind matrices of the joints, ordered like `joints`. Empty if the skin has none.
+    pub inverse_bind_matrices: Vec<Mat4>,
+}
+
+/// Interpolation of a glTF animation sampler.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GltfInterpolation {
+    #[default]
+    Linear,
+    Step,
+    CubicSpline,
+}
+
+/// Output values of a glTF animation channel, by target path.
+///
+/// With `GltfInterpolation::CubicSpline`, there are 3 values per keyframe: in-tangent, value and
+/// out-tangent.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GltfChannelValues {
+    Translations(Vec<Vec3>),
+    Rotations(Vec<Quat>),
+    Scales(Vec<Vec3>),
+}
+
+///
+/// A channel of a glTF animation, with its sampler data.
+///
+/// Morph target weights channels aren't supported by ozz-animation-rs, skip them.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfChannel {
+    /// Index of the target node.
+    pub node: usize,
+    /// Interpolation of the sampler.
+    pub interpolation: GltfInterpolation,
+    /// Keyframe times in seconds, strictly ascending (sampler input).
+    pub times: Vec<f32>,
+    /// Keyframe values (sampler output).
+    pub values: GltfChannelValues,
+}
+
+///
+/// An animation of a glTF document.
+///
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GltfAnimation {
+    /// Animation name, if any.
+    pub name: Option<String>,
+    pub channels: Vec<GltfChannel>,
+}
+
+///
+/// The parts of a glTF document used by ozz-animation-rs importers.
+///
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GltfDocument {
+    pub nodes: Vec<GltfNode>,
+    pub skins: Vec<GltfSkin>,
+    pub animations: Vec<GltfAnimation>,
+}
+
+impl GltfDocument {
+    /// Gets the parent of each node, `None` for scene roots.
+    ///
+    /// Returns `OzzError::InvalidIndex` if a child index is out of range.
+    pub fn node_parents(&self) -> Result<Vec<Option<usize>>, OzzError> {
+        let mut parents = vec![None; self.nodes.len()];
+        for (idx, node) in self.nodes.iter().enumerate() {
+            for child in node.children.iter() {
+                *parents.get_mut(*child).ok_or(OzzError::InvalidIndex)? = Some(idx);
+            }
+        }
+        return Ok(parents);
+    }
+
+    /// Gets the name of a node, or a `node_{index}` name for unnamed nodes.
+    pub fn node_name(&self, node: usize) -> String {
+        return match self.nodes.get(node).and_then(|n| n.name.as_ref()) {
+            Some(name) => name.clone(),
+            None => format!("node_{}", node),
+        };
+    }
+
+    /// Finds the closest ancestor of `node` that is a joint, and the combined transform of the intermediate
+    /// nodes (that aren't joints) between them.
+    ///
+    /// Returns `OzzError::CyclicHierarchy` if the ancestors of `node` loop.
+    fn joint_ancestor(
+        &self,
+        parents: &[Option<usize>],
+        node: usize,
+        is_joint: impl Fn(usize) -> bool,
+    ) -> Result<(Option<usize>, AosTransform), OzzError> {
+        let mut offset = AosTransform::IDENTITY;
+        let mut ancestor = parents[node];
+        // An acyclic chain of ancestors can't be longer than the nodes count.
+        for _ in 0..self.nodes.len() {
+            let Some(idx) = ancestor else {
+                return Ok((None, offset));
+            };
+            if is_joint(idx) {
+                return Ok((Some(idx), offset));
+            }
+            offset = self.nodes[idx].transform.mul(&offset);
+            ancestor = parents[idx];
+        }
+        return Err(OzzError::CyclicHierarchy);
+    }
+
+    /// Copies the nodes, skins and animations of a `gltf` crate document.
+    ///
+    /// Morph target weights channels are skipped.
+    ///
+    /// * `document` - The loaded glTF document.
+    /// * `buffers` - Data of the document buffers, ordered like `document.buffers()`. The binary chunk of a
+    ///   .glb file is the first buffer.
+    ///
+    /// Returns `OzzError::InvalidIndex` if the buffer of an accessor is missing from `buffers`.
+    pub fn from_gltf(document: &::gltf::Document, buffers: &[impl AsRef<[u8]>]) -> Result<GltfDocument, OzzError> {
+        use ::gltf::animation::util::ReadOutputs;
+        use ::gltf::animation::{Interpolation, Property};
+        use ::gltf::scene::Transform;
+
+        let get_buffer = |buffer: ::gltf::Buffer| buffers.get(buffer.index()).map(|data| data.as_ref());
+        let mut doc = GltfDocument::default();
+        for node in document.nodes() {
+            let transform = match node.transform() {
+                Transform::Matrix { matrix } => AosTransform::from(Mat4::from_cols_array_2d(&matrix)),
+                Transform::Decomposed {
+                    translation,
+                    rotation,
+                    scale,
+                } => AosTransform::new(Vec3::from(translation), Quat::from_array(rotation), Vec3::from(scale)),
+            };
+            doc.nodes.push(GltfNode {
+                name: node.name().map(|name| name.to_string()),
+                children: node.children().map(|child| child.index()).collect(),
+                transform,
+            });
+        }
+
+        for skin in document.skins() {
+            let mut inverse_bind_matrices = Vec::new();
+            if skin.inverse_bind_matrices().is_some() {
+                let matrices = skin.reader(get_buffer).read_inverse_bind_matrices();
+                let matrices = matrices.ok_or(OzzError::InvalidIndex)?;
+                inverse_bind_matrices.extend(matrices.map(|m| Mat4::from_cols_array_2d(&m)));
+            }
+            doc.skins.push(GltfSkin {
+                name: skin.name().map(|name| name.to_string()),
+                joints: skin.joints().map(|joint| joint.index()).collect(),
+                inverse_bind_matrices,
+            });
+        }
+
+        for animation in document.animations() {
+            let mut channels = Vec::new();
+            for channel in animation.channels() {
+                if matches!(channel.target().property(), Property::MorphTargetWeights) {
+                    continue;
+                }
+                let reader = channel.reader(get_buffer);
+                let values = match reader.read_outputs().ok_or(OzzError::InvalidIndex)? {
+                    ReadOutputs::Translations(values) => {
+                        GltfChannelValues::Translations(values.map(Vec3::from).collect())
+                    }
+                    ReadOutputs::Rotations(values) => {
+                        GltfChannelValues::Rotations(values.into_f32().map(Quat::from_array).collect())
+                    }
+                    ReadOutputs::Scales(values) => GltfChannelValues::Scales(values.map(Vec3::from).collect()),
+                    ReadOutputs::MorphTargetWeights(_) => continue,
+                };
+                channels.push(GltfChannel {
+                    node: channel.target().node().index(),
+                    interpolation: match channel.sampler().interpolation() {
+                        Interpolation::Linear => GltfInterpolation::Linear,
+                        Interpolation::Step => GltfInterpolation::Step,
+                        Interpolation::CubicSpline => GltfInterpolation::CubicSpline,
+                    },
+                    times: reader.read_inputs().ok_or(OzzError::InvalidIndex)?.collect(),
+                    values,
+                });
+            }
+            doc.animations.push(GltfAnimation {
+                name: animation.name().map(|name| name.to_string()),
+                channels,
+            });
+        }
+        return Ok(doc);
+    }
+}
+
+impl Skeleton {
+    /// Imports a `Skeleton` from a glTF skin.
+    ///
+    /// Skin joints become skeleton joints, named after their node. A joint's parent is its closest
+    /// ancestor node that is also a joint of the skin, skins can have several roots.
+    ///
+    /// Rest poses are derived from inverse bind matrices if the skin has some. Otherwise they are the
+    /// node local transforms, combined with the transforms of the intermediate nodes that aren't joints.
+    ///
+    /// * `doc` - The glTF document.
+    /// * `skin_index` - Index of the skin in `doc.skins`.
+    ///
+    /// Returns `OzzError::InvalidIndex` if `skin_index` or node indices are out of range,
+    /// `OzzError::CyclicHierarchy` if nodes hierarchy has a cycle, and `OzzError::DuplicateName` if joint node
+    /// names aren't unique (see `SkeletonBuilder::build`).
+    pub fn from_gltf(doc: &GltfDocument, skin_index: usize) -> Result<Skeleton, OzzError> {
+        let skin = doc.skins.get(skin_index).ok_or(OzzError::InvalidIndex)?;
+        let with_ibm = !skin.inverse_bind_matrices.is_empty();
+        if with_ibm && skin.inverse_bind_matrices.len() != skin.joints.len() {
+            return Err(OzzError::InvalidIndex);
+        }
+
+        let parents = doc.node_parents()?;
+        let mut joint_of_node = vec![None; doc.nodes.len()];
+        for (joint, node) in skin.joints.iter().enumerate() {
+            *joint_of_node.get_mut(*node).ok_or(OzzError::InvalidIndex)? = Some(joint);
+        }
+
+        let mut builder = SkeletonBuilder::new();
+        for (joint, node) in skin.joints.iter().enumerate() {
+            let (parent_node, offset) = doc.joint_ancestor(&parents, *node, |idx| joint_of_node[idx].is_some())?;
+            let parent_joint = parent_node.and_then(|idx| joint_of_node[idx]);
+            let mut rest_pose = offset.mul(&doc.nodes[*node].transform);
+
+            if with_ibm {
+                let model = skin.inverse_bind_matrices[joint].inverse();
+                rest_pose = match parent_joint {
+                    Some(parent) => AosTransform::from(skin.inverse_bind_matrices[parent] * model),
+                    None => AosTransform::from(model),
+                };
+            }
+
+            let parent = parent_joint.map(|p| p as i16).unwrap_or(-1);
+            builder.add_joint(&doc.node_name(*node), parent, rest_pose);
+        }
+        return builder.build();
+    }
+}
+
+impl Animation {
+    /// Imports an `Animation` from a glTF animation.
+    ///
+    /// Channels are mapped to skeleton joints by node name (see `GltfDocument::node_name`), channels of
+    /// other nodes are ignored. Joints without a translation, rotation or scale channel use the skeleton
+    /// rest pose. Like `Skeleton::from_gltf`, the transforms of intermediate nodes that aren't joints are
+    /// combined into channel values, those nodes are expected not to be animated.
+    ///
+    /// Step keyframes are converted to pairs of linear keyframes, cubic spline channels are resampled at
+    /// `GLTF_CUBIC_SAMPLE_RATE`, resampled rotations aren't unit quaternions and are normalized by
+    /// `AnimationBuilder::build` before compression. The duration is the last keyframe time (1 second if all
+    /// keyframes are at 0).
+    ///
+    /// * `doc` - The glTF document.
+    /// * `skeleton` - The skeleton the animation is meant for, usually imported with `Skeleton::from_gltf`.
+    /// * `animation_index` - Index of the animation in `doc.animations`.
+    ///
+    /// Returns `OzzError::InvalidIndex` if `animation_index` or node indices are out of range,
+    /// `OzzError::CyclicHierarchy` if nodes hierarchy has a cycle, and `OzzError::InvalidKeyframe` if channel
+    /// times and values don't match.
+    pub fn from_gltf(doc: &GltfDocument, skeleton: &Skeleton, animation_index: usize) -> Result<Animation, OzzError> {
+        let gltf_animation = doc.animations.get(animation_index).ok_or(OzzError::InvalidIndex)?;
+        let parents = doc.node_parents()?;
+
+        let mut nodes_of_joint = HashMap::new();
+        for node in (0..doc.nodes.len()).rev() {
+            if let Some(joint) = skeleton.joint_by_name(&doc.node_name(node)) {
+                nodes_of_joint.insert(joint, node);
+            }
+        }
+        let joint_of_node = |node: usize| -> Option<usize> {
+            let joint = skeleton.joint_by_name(&doc.node_name(node))?;
+            return (nodes_of_joint.get(&joint) == Some(&node)).then_some(joint as usize);
+        };
+
+        let mut channels = Vec::new(); // (joint, channel)
+        let mut duration: f32 = 0.0;
+        for channel in gltf_animation.channels.iter() {
+            if channel.node >= doc.nodes.len() {
+                return Err(OzzError::InvalidIndex);
+            }
+            if let Some(joint) = joint_of_node(channel.node) {
+                duration = duration.max(channel.times.last().copied().unwrap_or(0.0));
+                channels.push((joint, channel));
+            }
+        }
+        if duration <= 0.0 {
+            duration = 1.0;
+        }
+
+        let mut builder = AnimationBuilder::new(duration, skeleton.num_joints());
+        builder.set_name(gltf_animation.name.as_deref().unwrap_or_default());
+        for (joint, channel) in channels {
+            let node = nodes_of_joint[&(joint as i16)];
+            let (_, offset) = doc.joint_ancestor(&parents, node, |idx| joint_of_node(idx).is_some())?;
+            let track = &mut builder.tracks_mut()[joint];
+            match &channel.values {
+                GltfChannelValues::Translations(values) => {
+                    track.translations = resample(channel, values, duration)?;
+                    (track.translations.iter_mut()).for_each(|(_, v)| *v = offset.transform_point(*v));
+                }
+                GltfChannelValues::Rotations(values) => {
+                    track.rotations = resample(channel, values, duration)?;
+                    (track.rotations.iter_mut()).for_each(|(_, v)| *v = offset.rotation * *v);
+                }
+                GltfChannelValues::Scales(values) => {
+                    track.scales = resample(channel, values, duration)?;
+                    (track.scales.iter_mut()).for_each(|(_, v)| *v = offset.scale * *v);
+                }
+            }
+        }
+
+        // Falls back to rest pose for missing channels.
+        for (joint, track) in builder.tracks_mut().iter_mut().enumerate() {
+            let rest_pose = skeleton.joint_rest_poses()[joint / 4].col(joint % 4);
+            if track.translations.is_empty() {
+                track.translations.push((0.0, rest_pose.translation));
+            }
+            if track.rotations.is_empty() {
+                track.rotations.push((0.0, rest_pose.rotation));
+            }
+            if track.scales.is_empty() {
+                track.scales.push((0.0, rest_pose.scale));
+            }
+        }
+        return builder.build();
+    }
+}
+
+/// Converts keyframes of a glTF channel to linear `(time, value)` keyframes.
+fn resample<V>(channel: &GltfChannel, values: &[V], duration: f32) -> Result<Vec<(f32, V)>, OzzError>
+where
+    V: Copy + Add<Output = V> + Mul<f32, Output = V>,
+{
+    let times = &channel.times;
+    let mut keys = Vec::new();
+    match channel.interpolation {
+        GltfInterpolation::Linear => {
+            if values.len() != times.len() {
+                return Err(OzzError::InvalidKeyframe);
+            }
+            keys.extend(times.iter().copied().zip(values.iter().copied()));
+        }
+        GltfInterpolation::Step => {
+            if values.len() != times.len() {
+                return Err(OzzError::InvalidKeyframe);
+            }
+            // Holds the value until right before the next keyframe.
+            let epsilon = duration * 1e-5;
+            for (idx, (time, value)) in times.iter().zip(values.iter()).enumerate() {
+                keys.push((*time, *value));
+                match times.get(idx + 1) {
+                    Some(next) if next - epsilon > *time => keys.push((next - epsilon, *value)),
+                    _ => {}
+                }
+            }
+        }
+        GltfInterpolation::CubicSpline => {
+            if values.len() != times.len() * 3 {
+                return Err(OzzError::InvalidKeyframe);
+            }
+            for idx in 0..times.len() {
+                let (t0, p0) = (times[idx], values[idx * 3 + 1]);
+                keys.push((t0, p0));
+                let Some(&t1) = times.get(idx + 1) else {
+                    break;
+                };
+                let (m0, p1, m1) = (values[idx * 3 + 2], values[idx * 3 + 4], values[idx * 3 + 3]);
+                let dt = t1 - t0;
+                let steps = (dt * GLTF_CUBIC_SAMPLE_RATE).ceil().max(1.0) as usize;
+                for step in 1..steps {
+                    let t = step as f32 / steps as f32;
+                    let (t2, t3) = (t * t, t * t * t);
+                    let value = p0 * (2.0 * t3 - 3.0 * t2 + 1.0)
+                        + m0 * ((t3 - 2.0 * t2 + t) * dt)
+                        + p1 * (-2.0 * t3 + 3.0 * t2)
+                        + m1 * ((t3 - t2) * dt);
+                    keys.push((t0 + t * dt, value));
+                }
+            }
+        }
+    }
+    return Ok(keys);
+}
+
+#[cfg(test)]
+pub(crate) mod gltf_tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::math::SoaTransform;
+    use crate::sampling_job::{SamplingContext, SamplingJob};
+
+    /// Loads the nodes, skins and animations of a .gltf file, whose buffers are files next to it.
+    pub(crate) fn load_gltf(path: &str) -> GltfDocument {
+        let gltf = ::gltf::Gltf::from_slice(&std::fs::read(path).unwrap()).unwrap();
+        let dir = std::path::Path::new(path).parent().unwrap();
+        let buffers: Vec<Vec<u8>> = (gltf.buffers())
+            .map(|buffer| match buffer.source() {
+                ::gltf::buffer::Source::Uri(uri) => std::fs::read(dir.join(uri)).unwrap(),
+                ::gltf::buffer::Source::Bin => gltf.blob.clone().unwrap(),
+            })
+            .collect();
+        return GltfDocument::from_gltf(&gltf, &buffers).unwrap();
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_from_gltf_missing_buffer() {
+        let gltf = ::gltf::Gltf::from_slice(&std::fs::read("./resource/gltf/skin.gltf").unwrap()).unwrap();
+        let buffers: [Vec<u8>; 0] = [];
+        assert!(matches!(
+            GltfDocument::from_gltf(&gltf, &buffers),
+            Err(OzzError::InvalidIndex)
+        ));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_skeleton_from_gltf() {
+        let doc = load_gltf("./resource/gltf/skin.gltf");
+        assert!(matches!(Skeleton::from_gltf(&doc, 1), Err(OzzError::InvalidIndex)));
+
+        let skeleton = Skeleton::from_gltf(&doc, 0).unwrap();
+        assert_eq!(skeleton.num_joints(), 4);
+        assert_eq!(skeleton.joint_by_name("hips"), Some(0));
+        assert_eq!(skeleton.joint_by_name("chest"), Some(1));
+        assert_eq!(skeleton.joint_by_name("thigh"), Some(2));
+        assert_eq!(skeleton.joint_by_name("prop"), Some(3));
+        assert_eq!(skeleton.joint_by_name("spine_helper"), None);
+        assert_eq!(skeleton.joint_parents(), &[-1, 0, 0, -1]);
+
+        // Non joint ancestors are combined into rest poses.
+        let rest_poses = skeleton.joint_rest_poses();
+        assert!(rest_poses[0]
+            .col(0)
+            .translation
+            .abs_diff_eq(Vec3::new(0.0, 1.5, 0.0), 1e-6));
+        assert!(rest_poses[0]
+            .col(1)
+            .translation
+            .abs_diff_eq(Vec3::new(0.0, 0.5, 0.0), 1e-6));
+        assert!(rest_poses[0]
+            .col(1)
+            .rotation
+            .abs_diff_eq(Quat::from_rotation_y(0.5 * core::f32::consts::PI), 1e-6));
+        assert!(rest_poses[0].col(2).scale.abs_diff_eq(Vec3::new(1.0, 2.0, 1.0), 1e-6));
+        assert!(rest_poses[0]
+            .col(3)
+            .translation
+            .abs_diff_eq(Vec3::new(1.0, 1.0, 0.0), 1e-6));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_skeleton_from_gltf_inverse_bind() {
+        let mut doc = load_gltf("./resource/gltf/skin.gltf");
+        let reference = Skeleton::from_gltf(&doc, 0).unwrap();
+
+        // Bind pose differs from nodes pose, rest poses follow inverse bind matrices.
+        let bind = [
+            Mat4::from_translation(Vec3::new(0.0, 2.0, 0.0)),
+            Mat4::from_rotation_translation(Quat::from_rotation_x(0.3), Vec3::new(0.0, 3.0, 0.0)),
+            Mat4::from_translation(Vec3::new(0.5, 2.0, 0.0)),
+            Mat4::from_translation(Vec3::new(1.0, 0.0, 1.0)),
+        ];
+        doc.skins[0].inverse_bind_matrices = bind.iter().map(|m| m.inverse()).collect();
+        let skeleton = Skeleton::from_gltf(&doc, 0).unwrap();
+        assert_eq!(skeleton.joint_parents(), reference.joint_parents());
+        assert_eq!(skeleton.joint_names(), reference.joint_names());
+
+        let rest_poses = skeleton.joint_rest_poses();
+        assert!(rest_poses[0]
+            .col(0)
+            .translation
+            .abs_diff_eq(Vec3::new(0.0, 2.0, 0.0), 1e-5));
+        assert!(rest_poses[0]
+            .col(1)
+            .translation
+            .abs_diff_eq(Vec3::new(0.0, 1.0, 0.0), 1e-5));
+        assert!(rest_poses[0]
+            .col(1)
+            .rotation
+            .abs_diff_eq(Quat::from_rotation_x(0.3), 1e-5));
+        assert!(rest_poses[0]
+            .col(2)
+            .translation
+            .abs_diff_eq(Vec3::new(0.5, 0.0, 0.0), 1e-5));
+        assert!(rest_poses[0]
+            .col(3)
+            .translation
+            .abs_diff_eq(Vec3::new(1.0, 0.0, 1.0), 1e-5));
+
+        doc.skins[0].inverse_bind_matrices.pop();
+        assert!(matches!(Skeleton::from_gltf(&doc, 0), Err(OzzError::InvalidIndex)));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_skeleton_from_gltf_errors() {
+        let node = |name: &str, children: Vec<usize>| GltfNode {
+            name: Some(name.to_string()),
+            children,
+            transform: AosTransform::IDENTITY,
+        };
+        let skin = |joints: Vec<usize>| GltfSkin {
+            joints,
+            ..Default::default()
+        };
+
+        // Non joint ancestors looping.
+        let doc = GltfDocument {
+            nodes: vec![
+                node("a", vec![1]),
+                node("b", vec![0, 3]),
+                node("c", vec![]),
+                node("d", vec![]),
+            ],
+            skins: vec![skin(vec![3])],
+            animations: Vec::new(),
+        };
+        assert!(Skeleton::from_gltf(&doc, 0).unwrap_err().is_cyclic_hierarchy());
+
+        let doc = GltfDocument {
+            nodes: vec![node("a", vec![1]), node("a", vec![])],
+            skins: vec![skin(vec![0, 1])],
+            animations: Vec::new(),
+        };
+        assert!(matches!(Skeleton::from_gltf(&doc, 0), Err(OzzError::DuplicateName(name)) if name == "a"));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_animation_from_gltf() {
+        let doc = load_gltf("./resource/gltf/skin.gltf");
+        let skeleton = Skeleton::from_gltf(&doc, 0).unwrap();
+        assert!(matches!(
+            Animation::from_gltf(&doc, &skeleton, 1),
+            Err(OzzError::InvalidIndex)
+        ));
+
+        let animation = Rc::new(Animation::from_gltf(&doc, &skeleton, 0).unwrap());
+        assert_eq!(animation.name(), "wave");
+        assert_eq!(animation.num_tracks(), skeleton.num_joints());
+        // The channel of "mesh" node, that isn't a joint, is ignored.
+        assert_eq!(animation.duration(), 1.0);
+
+        let output = Rc::new(RefCell::new(vec![SoaTransform::default(); animation.num_soa_tracks()]));
+        let mut job: SamplingJob = SamplingJob::default();
+        job.set_animation(animation.clone());
+        job.set_context(SamplingContext::new(animation.num_tracks()));
+        job.set_output(output.clone());
+        let mut sample = |ratio: f32| -> [AosTransform; 4] {
+            job.set_ratio(ratio);
+            job.run().unwrap();
+            let soa = output.borrow()[0];
+            return [soa.col(0), soa.col(1), soa.col(2), soa.col(3)];
+        };
+
+        let [hips, chest, thigh, prop] = sample(0.25);
+        // Step channel, with "Armature" node transform.
+        assert!(hips.translation.abs_diff_eq(Vec3::new(0.0, 1.5, 0.0), 1e-3));
+        // Linear channel, translation falls back to rest pose.
+        assert!(chest.rotation.abs_diff_eq(Quat::from_rotation_y(0.25), 1e-3));
+        assert!(chest.translation.abs_diff_eq(Vec3::new(0.0, 0.5, 0.0), 1e-3));
+        // Cubic spline channel, resampled.
+        assert!(thigh.scale.abs_diff_eq(Vec3::new(1.0, 1.3125, 1.0), 1e-2));
+        assert!(thigh.translation.abs_diff_eq(Vec3::new(0.1, -0.1, 0.0), 1e-3));
+        // No channel, rest pose.
+        assert!(prop.translation.abs_diff_eq(Vec3::new(1.0, 1.0, 0.0), 1e-3));
+        assert!(prop.rotation.abs_diff_eq(Quat::IDENTITY, 1e-3));
+
+        let [hips, _, _, _] = sample(0.499);
+        assert!(hips.translation.abs_diff_eq(Vec3::new(0.0, 1.5, 0.0), 1e-3));
+        let [hips, chest, thigh, _] = sample(0.75);
+        assert!(hips.translation.abs_diff_eq(Vec3::new(0.0, 1.5, 1.0), 1e-3));
+        assert!(chest.rotation.abs_diff_eq(Quat::from_rotation_y(0.75), 1e-3));
+        assert!(thigh.scale.abs_diff_eq(Vec3::new(1.0, 2.6875, 1.0), 1e-2));
+        let [_, _, thigh, _] = sample(1.0);
+        assert!(thigh.scale.abs_diff_eq(Vec3::new(1.0, 3.0, 1.0), 1e-2));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_animation_from_gltf_cubic_rotation() {
+        let channel = GltfChannel {
+            node: 0,
+            interpolation: GltfInterpolation::CubicSpline,
+            times: vec![0.0, 1.0],
+            values: GltfChannelValues::Rotations(vec![
+                Quat::IDENTITY,
+                Quat::IDENTITY,
+                Quat::from_xyzw(0.0, 2.0, 0.0, 0.0),
+                Quat::from_xyzw(0.0, -2.0, 0.0, 0.0),
+                Quat::from_rotation_y(1.0),
+                Quat::IDENTITY,
+            ]),
+        };
+        let doc = GltfDocument {
+            nodes: vec![GltfNode {
+                name: Some("root".to_string()),
+                ..Default::default()
+            }],
+            skins: vec![GltfSkin {
+                joints: vec![0],
+                ..Default::default()
+            }],
+            animations: vec![GltfAnimation {
+                name: None,
+                channels: vec![channel.clone()],
+            }],
+        };
+        let skeleton = Skeleton::from_gltf(&doc, 0).unwrap();
+        let animation = Rc::new(Animation::from_gltf(&doc, &skeleton, 0).unwrap());
+
+        let GltfChannelValues::Rotations(values) = &channel.values else {
+            unreachable!();
+        };
+        let keys = resample(&channel, values, 1.0).unwrap();
+        // Hermite interpolation leaves the unit sphere, sampled rotations are the normalized ones.
+        assert!((keys[15].1.length() - 1.0).abs() > 0.1);
+
+        let output = Rc::new(RefCell::new(vec![SoaTransform::default(); animation.num_soa_tracks()]));
+        let mut job: SamplingJob = SamplingJob::default();
+        job.set_animation(animation.clone());
+        job.set_context(SamplingContext::new(animation.num_tracks()));
+        job.set_output(output.clone());
+        job.set_ratio(keys[15].0);
+        job.run().unwrap();
+        let rotation = output.borrow()[0].col(0).rotation;
+        assert!(rotation.abs_diff_eq(keys[15].1.normalize(), 2e-3), "{:?}", rotation);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_resample() {
+        let channel = GltfChannel {
+            node: 0,
+            interpolation: GltfInterpolation::Step,
+            times: vec![0.0, 1.0],
+            values: GltfChannelValues::Translations(Vec::new()),
+        };
+        let values = [Vec3::ZERO, Vec3::ONE];
+        let keys = resample(&channel, &values, 1.0).unwrap();
+        assert_eq!(
+            keys,
+            vec![(0.0, Vec3::ZERO), (1.0 - 1e-5, Vec3::ZERO), (1.0, Vec3::ONE)]
+        );
+        assert!(resample(&channel, &values[..1], 1.0).unwrap_err().is_invalid_keyframe());
+
+        let channel = GltfChannel {
+            interpolation: GltfInterpolation::CubicSpline,
+            ..channel
+        };
+        let values = [Vec3::ZERO, Vec3::ZERO, Vec3::X, -Vec3::X, Vec3::ZERO, Vec3::ZERO];
+        let keys = resample(&channel, &values, 1.0).unwrap();
+        assert_eq!(keys.len(), 31);
+        assert_eq!(keys[0], (0.0, Vec3::ZERO));
+        assert_eq!(keys[30], (1.0, Vec3::ZERO));
+        // Tangents bend the curve.
+        assert!(keys[15].1.x > 0.0);
+        assert!(resample(&channel, &values[..5], 1.0).unwrap_err().is_invalid_keyframe());
+    }
+}
@@ -2,13 +2,15 @@
 //! Aim IK Job.
 //!
 
+use core::simd::prelude::*;
 use glam::{Mat4, Quat, Vec3A};
-use std::simd::prelude::*;
-use std::simd::StdFloat;
 
 use crate::base::OzzError;
 use crate::math::*;
 
+/// Squared distance under which target is considered to be at joint position.
+const TARGET_EPSILON2: f32 = 1e-10;
+
 ///
 /// Rotates a joint so it aims at a target.
 ///
@@ -20,7 +22,8 @@ use crate::math::*;
 /// vector should aim the target.
 ///
 /// Result is unstable if joint-to-target direction is parallel to pole vector, or if target is too
-/// close to joint position.
+/// close to joint position. If target coincides with joint position, aiming is undefined and the job
+/// outputs an identity correction.
 ///
 #[derive(Debug)]
 pub struct IKAimJob {
@@ -157,6 +160,9 @@ impl IKAimJob {
     ///
     /// Weight given to the IK correction clamped in range 0.0-1.0.
     /// This allows to blend / interpolate from no IK applied (0 weight) to full IK (1).
+    ///
+    /// Intermediate weights normalized-lerp the correction from identity to the full correction, which gives
+    /// exactly the slerp halfway rotation for a 0.5 weight.
     #[inline]
     pub fn set_weight(&mut self, weight: f32) {
         self.weight = weight;
@@ -234,7 +240,9 @@ impl IKAimJob {
 
         let offsetted_forward = Self::compute_offsetted_forward(self.forward, self.offset, joint_to_target_js);
         self.reached = offsetted_forward.is_some();
-        if !self.reached || (joint_to_target_js_len2.simd_eq(ZERO).to_bitmask() & 0x1 == 0x1) {
+        // Target (almost) coincides with joint position, aiming direction is undefined.
+        let too_close = joint_to_target_js_len2.simd_le(f32x4::splat(TARGET_EPSILON2));
+        if !self.reached || (too_close.to_bitmask() & 0x1 == 0x1) {
             self.joint_correction = QUAT_UNIT;
             return Ok(());
         }
@@ -806,4 +814,62 @@ mod ik_aim_job_tests {
         job.run().unwrap();
         assert!(job.joint_correction().abs_diff_eq(Quat::IDENTITY, 2e-3));
     }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_forward_points_at_target() {
+        let joint = Mat4::from_rotation_translation(
+            Quat::from_euler(glam::EulerRot::XYZ, 0.3, -1.2, 0.7),
+            Vec3::new(1.0, 2.0, -0.5),
+        );
+        let forward = Vec3A::new(0.0, 0.6, 0.8);
+
+        for target in [
+            Vec3A::new(3.0, 1.0, 2.0),
+            Vec3A::new(-2.0, 5.0, 0.0),
+            Vec3A::new(1.0, -4.0, -3.0),
+        ] {
+            let mut job = IKAimJob::default();
+            job.set_joint(joint);
+            job.set_forward(forward);
+            job.set_up(Vec3A::X);
+            job.set_pole_vector(Vec3A::Y);
+            job.set_target(target);
+            job.run().unwrap();
+            assert!(job.reached());
+
+            let corrected = joint * Mat4::from_quat(job.joint_correction());
+            let aim = corrected.transform_vector3a(forward).normalize();
+            let expected = (target - Vec3A::from(joint.col(3))).normalize();
+            assert!(aim.abs_diff_eq(expected, 1e-4), "{} {}", aim, expected);
+        }
+
+        // target at joint position
+        let mut job = IKAimJob::default();
+        job.set_joint(joint);
+        job.set_forward(forward);
+        job.set_target(Vec3A::from(joint.col(3)));
+        job.run().unwrap();
+        assert!(job.joint_correction().is_finite());
+        assert!(job.joint_correction().abs_diff_eq(Quat::IDENTITY, 2e-3));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_weight_slerp_halfway() {
+        let mut job = IKAimJob::default();
+        job.set_joint(Mat4::from_rotation_z(0.4));
+        job.set_target(Vec3A::new(-1.0, 2.0, 3.0));
+        job.set_forward(Vec3A::X);
+        job.set_up(Vec3A::Y);
+        job.set_pole_vector(Vec3A::Y);
+        job.run().unwrap();
+        let full = job.joint_correction();
+
+        job.set_weight(0.5);
+        job.run().unwrap();
+        assert!(job
+            .joint_correction()
+            .abs_diff_eq(Quat::IDENTITY.slerp(full, 0.5), 1e-5));
+    }
 }
@@ -0,0 +1,626 @@
+//!
+//! Chain IK Job.
+//!
+
+use alloc::vec::Vec;
+use core::simd::prelude::*;
+use glam::{Mat4, Quat, Vec3A};
+
+use crate::base::OzzError;
+use crate::math::*;
+
+///
+/// Performs inverse kinematic on a chain of N joints, using FABRIK (Forward And Backward Reaching
+/// Inverse Kinematic) algorithm.
+///
+/// The job computes the transformations (rotations) that needs to be applied to every joint of the
+/// chain such that the last joint (named end) reaches the provided target position (if possible).
+/// This is useful for tails, spines or tentacles, where a two bone IK isn't enough. The job outputs
+/// a local-space correction quaternion per chain joint.
+///
+/// Chain joints must be ancestors of each other, ordered from root to end, but don't need to be direct
+/// ancestors (joints in-between will simply remain fixed).
+///
+#[derive(Debug)]
+pub struct IKChainJob {
+    target: f32x4,
+    iterations: u32,
+    tolerance: f32,
+    weight: f32,
+    joints: Vec<Mat4>,
+    constraints: Vec<f32>,
+    stiffness: Vec<f32>,
+
+    joint_corrections: Vec<Quat>,
+    reached: bool,
+
+    positions: Vec<f32x4>,
+    previous: Vec<f32x4>,
+    directions: Vec<f32x4>,
+}
+
+impl Default for IKChainJob {
+    fn default() -> Self {
+        Self {
+            target: ZERO,
+            iterations: 16,
+            tolerance: 1e-3,
+            weight: 1.0,
+            joints: Vec::new(),
+            constraints: Vec::new(),
+            stiffness: Vec::new(),
+
+            joint_corrections: Vec::new(),
+            reached: false,
+
+            positions: Vec::new(),
+            previous: Vec::new(),
+            directions: Vec::new(),
+        }
+    }
+}
+
+impl IKChainJob {
+    /// Gets target of `IKChainJob`.
+    #[inline]
+    pub fn target(&self) -> Vec3A {
+        return fx4_to_vec3a(self.target);
+    }
+
+    /// Sets target of `IKChainJob`.
+    ///
+    /// Target IK position, in model-space. This is the position the end of the joint chain will try to reach.
+    #[inline]
+    pub fn set_target(&mut self, target: Vec3A) {
+        self.target = fx4_from_vec3a(target);
+    }
+
+    /// Gets iterations of `IKChainJob`.
+    #[inline]
+    pub fn iterations(&self) -> u32 {
+        return self.iterations;
+    }
+
+    /// Sets iterations of `IKChainJob`.
+    ///
+    /// Maximum number of FABRIK iterations (a backward and a forward pass). Default is 16.
+    ///
+    /// Job validation will fail if iterations is 0.
+    #[inline]
+    pub fn set_iterations(&mut self, iterations: u32) {
+        self.iterations = iterations;
+    }
+
+    /// Gets tolerance of `IKChainJob`.
+    #[inline]
+    pub fn tolerance(&self) -> f32 {
+        return self.tolerance;
+    }
+
+    /// Sets tolerance of `IKChainJob`.
+    ///
+    /// Distance to the target under which the end joint is considered to reach it. Iterations stop as soon
+    /// as target is reached. Default is 1e-3.
+    #[inline]
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance;
+    }
+
+    /// Gets weight of `IKChainJob`.
+    #[inline]
+    pub fn weight(&self) -> f32 {
+        return self.weight;
+    }
+
+    /// Sets weight of `IKChainJob`.
+    ///
+    /// Weight given to the IK correction clamped in range 0.0-1.0.
+    /// This allows to blend / interpolate from no IK applied (0 weight) to full IK (1).
+    ///
+    /// Like `IKTwoBoneJob`, intermediate weights normalized-lerp every joint correction from identity to the
+    /// full correction, which gives exactly the slerp halfway rotation for a 0.5 weight.
+    #[inline]
+    pub fn set_weight(&mut self, weight: f32) {
+        self.weight = weight;
+    }
+
+    /// Gets joints of `IKChainJob`.
+    #[inline]
+    pub fn joints(&self) -> &[Mat4] {
+        return &self.joints;
+    }
+
+    /// Sets joints of `IKChainJob`.
+    ///
+    /// Chain joints model-space matrices, ordered from chain root to chain end.
+    ///
+    /// Job validation will fail if there are less than 2 joints.
+    #[inline]
+    pub fn set_joints(&mut self, joints: &[Mat4]) {
+        self.joints.clear();
+        self.joints.extend_from_slice(joints);
+    }
+
+    /// Sets joints of `IKChainJob` from skeleton joint indices.
+    ///
+    /// * `model_matrices` - Model-space matrices of all skeleton joints, usually `LocalToModelJob` output.
+    /// * `chain` - Chain joint indices, ordered from chain root to chain end.
+    ///
+    /// Returns `OzzError::InvalidIndex` if a chain index is out of `model_matrices` range.
+    pub fn set_joints_from_indices(&mut self, model_matrices: &[Mat4], chain: &[i16]) -> Result<(), OzzError> {
+        self.joints.clear();
+        for idx in chain {
+            let matrix = model_matrices.get(*idx as usize).ok_or(OzzError::InvalidIndex)?;
+            self.joints.push(*matrix);
+        }
+        return Ok(());
+    }
+
+    /// Gets constraints of `IKChainJob`.
+    #[inline]
+    pub fn constraints(&self) -> &[f32] {
+        return &self.constraints;
+    }
+
+    /// Sets constraints of `IKChainJob`.
+    ///
+    /// Per chain joint angular constraints, in radian. Each value is the maximum angle a joint bone (from the
+    /// joint to the next one) can deviate from its original direction, relatively to its parent bone.
+    /// Root joint constraint is relative to its original model-space direction.
+    ///
+    /// Empty means unconstrained (default). Otherwise job validation will fail if there isn't a constraint
+    /// for every joint.
+    #[inline]
+    pub fn set_constraints(&mut self, constraints: &[f32]) {
+        self.constraints.clear();
+        self.constraints.extend_from_slice(constraints);
+    }
+
+    /// Clears constraints of `IKChainJob`.
+    #[inline]
+    pub fn clear_constraints(&mut self) {
+        self.constraints.clear();
+    }
+
+    /// Gets stiffness of `IKChainJob`.
+    #[inline]
+    pub fn stiffness(&self) -> &[f32] {
+        return &self.stiffness;
+    }
+
+    /// Sets stiffness of `IKChainJob`.
+    ///
+    /// Per chain joint stiffness, in range 0.0-1.0. Each iteration, a joint bone only rotates by `1 - stiffness`
+    /// of the rotation the solver asks for, relatively to its parent bone. So stiffness decreasing from root to
+    /// end bends spines or tails progressively, joints near the root moving less (and needing more
+    /// iterations). 0 is free, 1 keeps the joint local rotation.
+    ///
+    /// Empty means free joints (default). Otherwise job validation will fail if there isn't a stiffness for
+    /// every joint.
+    #[inline]
+    pub fn set_stiffness(&mut self, stiffness: &[f32]) {
+        self.stiffness.clear();
+        self.stiffness.extend_from_slice(stiffness);
+    }
+
+    /// Clears stiffness of `IKChainJob`.
+    #[inline]
+    pub fn clear_stiffness(&mut self) {
+        self.stiffness.clear();
+    }
+
+    /// Gets **output** joint corrections of `IKChainJob`.
+    ///
+    /// Local-space corrections to apply to chain joints in order for end joint to reach target position.
+    /// There's one correction per chain joint, end joint correction is always identity.
+    ///
+    /// These quaternions must be multiplied to the local-space quaternion of their respective joints.
+    #[inline]
+    pub fn joint_corrections(&self) -> &[Quat] {
+        return &self.joint_corrections;
+    }
+
+    /// Clears joint corrections of `IKChainJob`.
+    #[inline]
+    pub fn clear_joint_corrections(&mut self) {
+        self.joint_corrections.clear();
+    }
+
+    /// Gets **output** reached of `IKChainJob`.
+    ///
+    /// True if end joint is within tolerance of the target after solving.
+    /// Target is considered unreached if weight is less than 1.
+    #[inline]
+    pub fn reached(&self) -> bool {
+        return self.reached;
+    }
+
+    /// Clears reached of `IKChainJob`.
+    #[inline]
+    pub fn clear_reached(&mut self) {
+        self.reached = false;
+    }
+
+    /// Clears all outputs of `IKChainJob`.
+    #[inline]
+    pub fn clear_outs(&mut self) {
+        self.clear_joint_corrections();
+        self.clear_reached();
+    }
+
+    /// Validates `IKChainJob` parameters.
+    #[inline]
+    fn validate(&self) -> bool {
+        let mut ok = self.joints.len() >= 2;
+        ok &= self.iterations > 0;
+        ok &= self.constraints.is_empty() || self.constraints.len() >= self.joints.len();
+        ok &= self.stiffness.is_empty() || self.stiffness.len() >= self.joints.len();
+        return ok;
+    }
+
+    /// Runs chain IK job's task.
+    /// The validate job before any operation is performed.
+    pub fn run(&mut self) -> Result<(), OzzError> {
+        if !self.validate() {
+            return Err(OzzError::InvalidJob);
+        }
+
+        let num = self.joints.len();
+        if self.weight <= 0.0 {
+            self.joint_corrections.clear();
+            self.joint_corrections.resize(num, Quat::IDENTITY);
+            self.reached = false;
+            return Ok(());
+        }
+
+        self.positions.clear();
+        self.positions
+            .extend(self.joints.iter().map(|m| fx4_from_vec4(m.w_axis)));
+        self.directions.clear();
+        self.directions
+            .extend((0..num - 1).map(|i| self.positions[i + 1] - self.positions[i]));
+
+        let lengths: Vec<f32x4> = self
+            .directions
+            .iter()
+            .map(|d| fx4_splat_x(vec3_length2_s(*d).sqrt()))
+            .collect();
+        let chain_len = lengths.iter().fold(0.0, |acc, len| acc + len[0]);
+        let root = self.positions[0];
+        let tolerance2 = self.tolerance * self.tolerance;
+
+        self.previous.clear();
+        self.previous.extend_from_slice(&self.positions);
+
+        if vec3_length2_s(self.target - root)[0] >= chain_len * chain_len {
+            // Unreachable, stretches the chain toward the target.
+            for (i, length) in lengths.iter().enumerate() {
+                let dir = self.stiffen(i, self.target - self.positions[i]);
+                let dir = self.constrain(i, dir);
+                self.positions[i + 1] = self.positions[i] + dir * length;
+            }
+        } else {
+            for _ in 0..self.iterations {
+                if vec3_length2_s(self.positions[num - 1] - self.target)[0] <= tolerance2 {
+                    break;
+                }
+
+                // Backward pass, from end to root.
+                self.previous.copy_from_slice(&self.positions);
+                self.positions[num - 1] = self.target;
+                for (i, length) in lengths.iter().enumerate().rev() {
+                    let dir = vec3_normalize_or_zero(self.positions[i] - self.positions[i + 1]);
+                    self.positions[i] = self.positions[i + 1] + dir * length;
+                }
+
+                // Forward pass, from root to end.
+                self.positions[0] = root;
+                for (i, length) in lengths.iter().enumerate() {
+                    let dir = self.stiffen(i, self.positions[i + 1] - self.positions[i]);
+                    let dir = self.constrain(i, dir);
+                    self.positions[i + 1] = self.positions[i] + dir * length;
+                }
+            }
+        }
+
+        self.reached = vec3_length2_s(self.positions[num - 1] - self.target)[0] <= tolerance2 && self.weight >= 1.0;
+        self.compute_corrections();
+        self.weight_output();
+        return Ok(());
+    }
+
+    // Moves bone `idx` direction `dir` back toward its direction before this iteration, by its stiffness.
+    // Returns a normalized direction.
+    fn stiffen(&self, idx: usize, dir: f32x4) -> f32x4 {
+        let dir = vec3_normalize_or_zero(dir);
+        let stiffness = match self.stiffness.get(idx) {
+            Some(stiffness) if *stiffness > 0.0 => f32x4::splat(stiffness.min(1.0)),
+            _ => return dir,
+        };
+
+        // Reference direction is the previous bone direction, rotated like its parent bone.
+        let previous = self.previous[idx + 1] - self.previous[idx];
+        let reference = if idx == 0 {
+            vec3_normalize_or_zero(previous)
+        } else {
+            let parent_previous = self.previous[idx] - self.previous[idx - 1];
+            let parent_current = self.positions[idx] - self.positions[idx - 1];
+            let rot = quat_from_vectors(parent_previous, parent_current);
+            vec3_normalize_or_zero(quat_transform_vector(rot, previous))
+        };
+
+        let blended = vec3_normalize_or_zero(fx4_lerp(dir, reference, stiffness));
+        // Opposite directions, the blend is undefined.
+        if vec3_length2_s(blended)[0] == 0.0 {
+            return reference;
+        }
+        return blended;
+    }
+
+    // Constrains bone `idx` direction `dir` in the cone allowed by its constraint. Returns a normalized
+    // direction.
+    fn constrain(&self, idx: usize, dir: f32x4) -> f32x4 {
+        let dir = vec3_normalize_or_zero(dir);
+        let max_angle = match self.constraints.get(idx) {
+            Some(max_angle) if *max_angle < core::f32::consts::PI => *max_angle,
+            _ => return dir,
+        };
+
+        // Reference direction is the original bone direction, rotated like its parent bone.
+        let original = vec3_normalize_or_zero(self.directions[idx]);
+        let reference = if idx == 0 {
+            original
+        } else {
+            let parent_original = self.directions[idx - 1];
+            let parent_current = self.positions[idx] - self.positions[idx - 1];
+            let rot = quat_from_vectors(parent_original, parent_current);
+            quat_transform_vector(rot, original)
+        };
+
+        let (max_sin, max_cos) = f32_sin_cos(f32::max(max_angle, 0.0));
+        let cos = vec3_dot_s(dir, reference)[0];
+        if cos >= max_cos {
+            return dir;
+        }
+
+        let mut perp = vec3_normalize_or_zero(dir - reference * f32x4::splat(cos));
+        if vec3_length2_s(perp)[0] == 0.0 {
+            perp = vec3_normalize_or_zero(vec3_cross(reference, X_AXIS));
+            if vec3_length2_s(perp)[0] == 0.0 {
+                perp = vec3_normalize_or_zero(vec3_cross(reference, Y_AXIS));
+            }
+        }
+        return reference * f32x4::splat(max_cos) + perp * f32x4::splat(max_sin);
+    }
+
+    // Converts solved positions into local-space joint corrections.
+    fn compute_corrections(&mut self) {
+        let num = self.joints.len();
+        let mut joints: Vec<AosMat4> = self.joints.iter().map(|m| AosMat4::from(*m)).collect();
+        self.joint_corrections.clear();
+
+        for i in 0..num - 1 {
+            let inv_joint = joints[i].invert();
+            let current_js = inv_joint.transform_vector(joints[i + 1].cols[3] - joints[i].cols[3]);
+            let solved_js = inv_joint.transform_vector(self.positions[i + 1] - self.positions[i]);
+            let correction = quat_positive_w(quat_from_vectors(current_js, solved_js));
+            self.joint_corrections.push(fx4_to_quat(correction));
+
+            // Propagates the correction to the rest of the chain.
+            let corrected = joints[i].mul(&AosMat4::from(Mat4::from_quat(fx4_to_quat(correction))));
+            let delta = corrected.mul(&inv_joint);
+            joints[i] = corrected;
+            for joint in joints.iter_mut().skip(i + 1) {
+                *joint = delta.mul(joint);
+            }
+        }
+        self.joint_corrections.push(Quat::IDENTITY);
+    }
+
+    fn weight_output(&mut self) {
+        if self.weight >= 1.0 {
+            return;
+        }
+        let simd_weight = f32x4::splat(self.weight);
+        for correction in self.joint_corrections.iter_mut() {
+            let lerp = fx4_lerp(QUAT_UNIT, fx4_from_quat(*correction), simd_weight);
+            *correction = fx4_to_quat(quat_normalize(lerp));
+        }
+    }
+}
+
+#[inline]
+fn vec3_normalize_or_zero(v: f32x4) -> f32x4 {
+    let len2 = vec3_length2_s(v);
+    if len2[0] <= 0.0 {
+        return ZERO;
+    }
+    return v * fx4_splat_x(len2.sqrt().recip());
+}
+
+#[cfg(test)]
+mod ik_chain_job_tests {
+    use glam::Vec3;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+
+    fn new_chain() -> Vec<Mat4> {
+        return (0..5)
+            .map(|i| Mat4::from_translation(Vec3::new(i as f32, 0.0, 0.0)))
+            .collect();
+    }
+
+    // Applies local-space corrections and recomputes the chain, as LocalToModelJob would.
+    fn apply_corrections(joints: &[Mat4], corrections: &[Quat]) -> Vec<Mat4> {
+        let mut corrected: Vec<Mat4> = Vec::new();
+        for (i, joint) in joints.iter().enumerate() {
+            let parent = if i == 0 { Mat4::IDENTITY } else { corrected[i - 1] };
+            let parent_original = if i == 0 { Mat4::IDENTITY } else { joints[i - 1] };
+            let local = parent_original.inverse() * *joint;
+            corrected.push(parent * local * Mat4::from_quat(corrections[i]));
+        }
+        return corrected;
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_validity() {
+        let mut job = IKChainJob::default();
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        job.set_joints(&new_chain()[0..1]);
+        assert!(!job.validate());
+
+        job.set_joints(&new_chain());
+        assert!(job.validate());
+
+        job.set_iterations(0);
+        assert!(!job.validate());
+        job.set_iterations(8);
+
+        job.set_constraints(&[1.0; 3]);
+        assert!(!job.validate());
+        job.set_constraints(&[1.0; 5]);
+        assert!(job.validate());
+
+        let model = new_chain();
+        let err = job.set_joints_from_indices(&model, &[0, 2, 7]).unwrap_err();
+        assert!(matches!(err, OzzError::InvalidIndex));
+        job.set_joints_from_indices(&model, &[0, 2, 4]).unwrap();
+        assert_eq!(job.joints(), &[model[0], model[2], model[4]]);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_reachable() {
+        let joints = new_chain();
+        for target in [
+            Vec3A::new(2.0, 2.0, 1.0),
+            Vec3A::new(-1.0, 1.5, 0.5),
+            Vec3A::new(0.5, -2.0, -2.0),
+        ] {
+            let mut job = IKChainJob::default();
+            job.set_joints(&joints);
+            job.set_target(target);
+            job.set_iterations(64);
+            job.run().unwrap();
+            assert!(job.reached());
+            assert_eq!(job.joint_corrections().len(), 5);
+            assert_eq!(job.joint_corrections()[4], Quat::IDENTITY);
+
+            let corrected = apply_corrections(&joints, job.joint_corrections());
+            let end = Vec3A::from(corrected[4].col(3));
+            assert!(end.abs_diff_eq(target, 2e-3), "{} {}", end, target);
+
+            // bone lengths are preserved
+            for i in 0..4 {
+                let len = (corrected[i + 1].col(3) - corrected[i].col(3)).length();
+                assert!((len - 1.0).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_unreachable() {
+        let joints = new_chain();
+        let mut job = IKChainJob::default();
+        job.set_joints(&joints);
+        job.set_target(Vec3A::new(0.0, 10.0, 0.0));
+        job.run().unwrap();
+        assert!(!job.reached());
+
+        let corrected = apply_corrections(&joints, job.joint_corrections());
+        for (i, joint) in corrected.iter().enumerate() {
+            assert!(joint.is_finite());
+            assert!(Vec3A::from(joint.col(3)).abs_diff_eq(Vec3A::new(0.0, i as f32, 0.0), 1e-4));
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_constraints() {
+        let joints = new_chain();
+        let mut job = IKChainJob::default();
+        job.set_joints(&joints);
+        job.set_target(Vec3A::new(1.0, 2.0, 0.0));
+        job.set_constraints(&[core::f32::consts::PI, 0.2, 0.2, 0.2, 0.2]);
+        job.run().unwrap();
+
+        let corrected = apply_corrections(&joints, job.joint_corrections());
+        for i in 1..4 {
+            let parent_bone = (corrected[i].col(3) - corrected[i - 1].col(3)).truncate().normalize();
+            let bone = (corrected[i + 1].col(3) - corrected[i].col(3)).truncate().normalize();
+            assert!(parent_bone.dot(bone) >= f32::cos(0.2) - 1e-4, "joint={}", i);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_stiffness() {
+        let joints = new_chain();
+        let target = Vec3A::new(1.0, 2.5, 0.5);
+        let angle = |job: &IKChainJob, idx: usize| job.joint_corrections()[idx].angle_between(Quat::IDENTITY);
+        let mut job = IKChainJob::default();
+        job.set_joints(&joints);
+        job.set_target(target);
+        job.set_iterations(64);
+        job.run().unwrap();
+        let free = job.joint_corrections().to_vec();
+
+        // All loose solves like no stiffness.
+        job.set_stiffness(&[0.0; 5]);
+        job.run().unwrap();
+        assert_eq!(job.joint_corrections(), &free[..]);
+
+        // Stiff root, loose end.
+        job.set_stiffness(&[0.9, 0.7, 0.5, 0.3, 0.1]);
+        job.run().unwrap();
+        let progressive_root = angle(&job, 0);
+        let corrected = apply_corrections(&joints, job.joint_corrections());
+        assert!(Vec3A::from(corrected[4].col(3)).abs_diff_eq(target, 2e-3));
+
+        job.set_stiffness(&[0.5; 5]);
+        job.run().unwrap();
+        let uniform_root = angle(&job, 0);
+        assert!(progressive_root < uniform_root, "{} {}", progressive_root, uniform_root);
+
+        // All stiff, nothing moves.
+        job.set_stiffness(&[1.0; 5]);
+        job.run().unwrap();
+        assert!(!job.reached());
+        for idx in 0..5 {
+            assert!(angle(&job, idx) < 1e-3, "joint={}", idx);
+        }
+
+        job.set_stiffness(&[0.5; 3]);
+        assert!(!job.validate());
+        job.clear_stiffness();
+        assert!(job.validate());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_weight() {
+        let joints = new_chain();
+        let mut job = IKChainJob::default();
+        job.set_joints(&joints);
+        job.set_target(Vec3A::new(2.0, 2.0, 1.0));
+        job.run().unwrap();
+        let full = job.joint_corrections().to_vec();
+
+        job.set_weight(0.0);
+        job.run().unwrap();
+        assert!(!job.reached());
+        assert_eq!(job.joint_corrections(), &[Quat::IDENTITY; 5]);
+
+        job.set_weight(0.5);
+        job.run().unwrap();
+        assert!(!job.reached());
+        for (correction, full) in job.joint_corrections().iter().zip(full.iter()) {
+            assert!(correction.abs_diff_eq(Quat::IDENTITY.slerp(*full, 0.5), 1e-5));
+        }
+    }
+}
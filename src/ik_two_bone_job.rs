@@ -2,9 +2,8 @@
 //! Two bone IK job.
 //!
 
+use core::simd::prelude::*;
 use glam::{Mat4, Quat, Vec3A};
-use std::simd::prelude::*;
-use std::simd::StdFloat;
 
 use crate::base::OzzError;
 use crate::math::*;
@@ -73,6 +72,7 @@ pub struct IKTwoBoneJob {
     start_joint_correction: f32x4,
     mid_joint_correction: f32x4,
     reached: bool,
+    overreach: bool,
 }
 
 impl Default for IKTwoBoneJob {
@@ -90,6 +90,7 @@ impl Default for IKTwoBoneJob {
             start_joint_correction: QUAT_UNIT,
             mid_joint_correction: QUAT_UNIT,
             reached: false,
+            overreach: false,
         }
     }
 }
@@ -148,6 +149,57 @@ impl IKTwoBoneJob {
         self.pole_vector = fx4_from_vec3a(pole_vector);
     }
 
+    /// Sets pole vector of `IKTwoBoneJob` from a pole target position.
+    ///
+    /// Pole target is a model-space position the middle joint should point to (e.g. a knee target in front
+    /// of the character). It's converted to a pole vector using the start joint and target positions, so
+    /// start joint and target must be set before calling this function.
+    #[inline]
+    pub fn set_pole_target(&mut self, pole_target: Vec3A) {
+        let start = Vec3A::from(self.start_joint().col(3));
+        self.set_pole_vector(Self::pole_vector_from_target(start, self.target(), pole_target));
+    }
+
+    /// Computes the pole vector expected by `IKTwoBoneJob` from a pole target position.
+    ///
+    /// * `start` - Chain start position, in model-space.
+    /// * `end` - Chain end position (usually IK target), in model-space.
+    /// * `pole_target` - Position the middle joint should point to, in model-space.
+    ///
+    /// The pole vector is the normalized component of start-to-pole_target vector that is perpendicular
+    /// to the start-to-end axis. If pole target is on the start-to-end axis, start-to-pole_target direction
+    /// is returned (which is unstable, see `set_pole_vector`).
+    pub fn pole_vector_from_target(start: Vec3A, end: Vec3A, pole_target: Vec3A) -> Vec3A {
+        let to_pole = pole_target - start;
+        let axis = (end - start).normalize_or_zero();
+        let perpendicular = to_pole - axis * to_pole.dot(axis);
+        if perpendicular.length_squared() > 1e-12 {
+            return perpendicular.normalize();
+        }
+        return to_pole.normalize_or_zero();
+    }
+
+    /// Computes a default pole vector from the rest pose of the chain, the direction the middle joint bends
+    /// to in bind pose (like knees bending forward).
+    ///
+    /// * `start_joint` - Rest pose model-space matrix of the chain start joint.
+    /// * `mid_joint` - Rest pose model-space matrix of the chain middle joint.
+    /// * `end_joint` - Rest pose model-space matrix of the chain end joint.
+    ///
+    /// The pole vector is the normalized component of start-to-mid vector that is perpendicular to the
+    /// start-to-end axis, in model-space. Returns `None` if the chain is straight in rest pose, so it has no
+    /// bending direction.
+    pub fn pole_vector_from_rest_pose(start_joint: Mat4, mid_joint: Mat4, end_joint: Mat4) -> Option<Vec3A> {
+        let start = Vec3A::from(start_joint.col(3));
+        let to_mid = Vec3A::from(mid_joint.col(3)) - start;
+        let axis = (Vec3A::from(end_joint.col(3)) - start).normalize_or_zero();
+        let perpendicular = to_mid - axis * to_mid.dot(axis);
+        if perpendicular.length_squared() > 1e-12 {
+            return Some(perpendicular.normalize());
+        }
+        return None;
+    }
+
     /// Gets twist angle of `IKTwoBoneJob`.
     #[inline]
     pub fn twist_angle(&self) -> f32 {
@@ -189,6 +241,9 @@ impl IKTwoBoneJob {
     ///
     /// Weight given to the IK correction clamped in range 0.0-1.0.
     /// This allows to blend / interpolate from no IK applied (0 weight) to full IK (1).
+    ///
+    /// Intermediate weights normalized-lerp the correction from identity to the full correction, which gives
+    /// exactly the slerp halfway rotation for a 0.5 weight.
     #[inline]
     pub fn set_weight(&mut self, weight: f32) {
         self.weight = weight;
@@ -288,12 +343,28 @@ impl IKTwoBoneJob {
         self.reached = false;
     }
 
+    /// Gets **output** overreach of `IKTwoBoneJob`.
+    ///
+    /// True if target is farther than the bone chain length. The chain is then fully extended toward the
+    /// target (or nearly, depending on soften ratio).
+    #[inline]
+    pub fn overreach(&self) -> bool {
+        return self.overreach;
+    }
+
+    /// Clears overreach of `IKTwoBoneJob`.
+    #[inline]
+    pub fn clear_overreach(&mut self) {
+        self.overreach = false;
+    }
+
     /// Clears all outputs of `IKTwoBoneJob`.
     #[inline]
     pub fn clear_outs(&mut self) {
         self.clear_start_joint_correction();
         self.clear_mid_joint_correction();
         self.clear_reached();
+        self.clear_overreach();
     }
 
     /// Validates `IKTwoBoneJob` parameters.
@@ -313,12 +384,14 @@ impl IKTwoBoneJob {
             self.start_joint_correction = QUAT_UNIT;
             self.mid_joint_correction = QUAT_UNIT;
             self.reached = false;
+            self.overreach = false;
             return Ok(());
         }
 
         let setup = IKConstantSetup::new(self);
-        let (lreached, start_target_ss, start_target_ss_len2) = self.soften_target(&setup);
+        let (lreached, overreach, start_target_ss, start_target_ss_len2) = self.soften_target(&setup);
         self.reached = lreached && self.weight >= 1.0;
+        self.overreach = overreach;
 
         let mid_rot_ms = self.compute_mid_joint(&setup, start_target_ss_len2);
         let start_rot_ss = self.compute_start_joint(&setup, mid_rot_ms, start_target_ss, start_target_ss_len2);
@@ -327,7 +400,7 @@ impl IKTwoBoneJob {
         return Ok(());
     }
 
-    fn soften_target(&self, setup: &IKConstantSetup) -> (bool, f32x4, f32x4) {
+    fn soften_target(&self, setup: &IKConstantSetup) -> (bool, bool, f32x4, f32x4) {
         let start_target_original_ss = setup.inv_start_joint.transform_point(self.target);
         let start_target_original_ss_len2 = vec3_length2_s(start_target_original_ss); // [x]
         let lengths = fx4_set_z(
@@ -342,6 +415,7 @@ impl IKTwoBoneJob {
         let bones_chain_len = start_mid_ss_len + mid_end_ss_len; // [x]
         let da = bones_chain_len * fx4_clamp_or_min(f32x4::from_array([self.soften, 0.0, 0.0, 0.0]), ZERO, ONE); // [x 0 0 0] da.yzw needs to be 0
         let ds = bones_chain_len - da; // [x]
+        let overreach = start_target_original_ss_len[0] > bones_chain_len[0];
 
         let left = fx4_set_w(start_target_original_ss_len, ds); // [x y z w]
         let right = fx4_set_z(da, bone_len_diff_abs); // [x y z w]
@@ -369,7 +443,12 @@ impl IKTwoBoneJob {
             start_target_ss_len2 = start_target_original_ss_len2; // [x]
         }
 
-        return ((comp_mask & 0x5) == 0x4, start_target_ss, start_target_ss_len2);
+        return (
+            (comp_mask & 0x5) == 0x4,
+            overreach,
+            start_target_ss,
+            start_target_ss_len2,
+        );
     }
 
     fn compute_mid_joint(&self, setup: &IKConstantSetup, start_target_ss_len2: f32x4) -> f32x4 {
@@ -1001,4 +1080,195 @@ mod ik_two_bone_tests {
         assert!(job.start_joint_correction().abs_diff_eq(Quat::IDENTITY, 2e-3));
         assert!(job.mid_joint_correction().abs_diff_eq(Quat::IDENTITY, 2e-3));
     }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_end_effector_reaches_target() {
+        // A leg: hip (start), knee (mid) slightly bent forward, and foot (end).
+        let start = Mat4::from_translation(Vec3::new(0.0, 2.0, 0.0));
+        let mid = Mat4::from_translation(Vec3::new(0.0, 1.0, 0.1));
+        let end = Mat4::from_translation(Vec3::new(0.0, 0.0, 0.0));
+        let mid_axis = Vec3A::cross(
+            Vec3A::from(start.col(3)) - Vec3A::from(mid.col(3)),
+            Vec3A::from(end.col(3)) - Vec3A::from(mid.col(3)),
+        )
+        .normalize();
+
+        for target in [
+            Vec3A::new(0.3, 0.5, 0.2),
+            Vec3A::new(-0.4, 0.2, -0.3),
+            Vec3A::new(0.0, 1.5, 0.5),
+        ] {
+            let mut job = IKTwoBoneJob::default();
+            job.set_start_joint(start);
+            job.set_mid_joint(mid);
+            job.set_end_joint(end);
+            job.set_mid_axis(mid_axis);
+            job.set_pole_vector(Vec3A::Z);
+            job.set_target(target);
+            job.run().unwrap();
+            assert!(job.reached());
+
+            // Applies local-space corrections and recomputes the chain, as LocalToModelJob would.
+            let start_corrected = start * Mat4::from_quat(job.start_joint_correction());
+            let mid_corrected = start_corrected * start.inverse() * mid * Mat4::from_quat(job.mid_joint_correction());
+            let end_corrected = mid_corrected * mid.inverse() * end;
+            let foot = Vec3A::from(end_corrected.col(3));
+            assert!(foot.abs_diff_eq(target, 1e-3), "{} {}", foot, target);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_weight_slerp_halfway() {
+        let mut job = new_ik_two_bone_job();
+        job.set_pole_vector(Vec3A::Y);
+        job.set_target(Vec3A::new(0.5, -0.3, 0.8));
+        job.run().unwrap();
+        let full_start = job.start_joint_correction();
+        let full_mid = job.mid_joint_correction();
+
+        job.set_weight(0.5);
+        job.run().unwrap();
+        assert!(!job.reached());
+        assert!(job
+            .start_joint_correction()
+            .abs_diff_eq(Quat::IDENTITY.slerp(full_start, 0.5), 1e-5));
+        assert!(job
+            .mid_joint_correction()
+            .abs_diff_eq(Quat::IDENTITY.slerp(full_mid, 0.5), 1e-5));
+    }
+
+    fn end_after_corrections(job: &IKTwoBoneJob) -> Vec3A {
+        let start = job.start_joint();
+        let mid = job.mid_joint();
+        let start_corrected = start * Mat4::from_quat(job.start_joint_correction());
+        let mid_corrected = start_corrected * start.inverse() * mid * Mat4::from_quat(job.mid_joint_correction());
+        return Vec3A::from((mid_corrected * mid.inverse() * job.end_joint()).col(3));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_overreach() {
+        let mut job = new_ik_two_bone_job();
+        job.set_pole_vector(Vec3A::Y);
+
+        job.set_target(Vec3A::new(1.5, 0.5, 0.0));
+        job.run().unwrap();
+        assert!(job.reached());
+        assert!(!job.overreach());
+
+        for target in [Vec3A::new(3.0, 0.0, 0.0), Vec3A::new(10.0, 5.0, -20.0)] {
+            job.set_target(target);
+            job.run().unwrap();
+            assert!(!job.reached());
+            assert!(job.overreach());
+            assert!(job.start_joint_correction().is_finite());
+            assert!(job.mid_joint_correction().is_finite());
+
+            // fully extended toward target
+            let end = end_after_corrections(&job);
+            assert!(end.abs_diff_eq(target.normalize() * 2.0, 2e-3), "{} {}", end, target);
+        }
+
+        job.set_weight(0.0);
+        job.run().unwrap();
+        assert!(!job.overreach());
+        job.set_weight(1.0);
+        job.run().unwrap();
+        assert!(job.overreach());
+        job.clear_outs();
+        assert!(!job.overreach());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_over_fold() {
+        // Bones of length 2 and 1, chain can't fold closer than 1 to start joint.
+        let start = Mat4::IDENTITY;
+        let mid = Mat4::from_rotation_translation(Quat::from_axis_angle(Vec3::Z, consts::FRAC_PI_2), Vec3::Y * 2.0);
+        let end = Mat4::from_translation(Vec3::X + Vec3::Y * 2.0);
+
+        let mut job = IKTwoBoneJob::default();
+        job.set_start_joint(start);
+        job.set_mid_joint(mid);
+        job.set_end_joint(end);
+        job.set_mid_axis(Vec3A::Z);
+        job.set_pole_vector(Vec3A::Y);
+
+        for target in [Vec3A::new(0.2, 0.3, 0.0), Vec3A::new(0.1, 0.0, 0.05)] {
+            job.set_target(target);
+            job.run().unwrap();
+            assert!(!job.overreach());
+            assert!(job.start_joint_correction().is_finite());
+            assert!(job.mid_joint_correction().is_finite());
+
+            // fully folded toward target
+            let end = end_after_corrections(&job);
+            assert!(end.is_finite());
+            assert!((end.length() - 1.0).abs() < 2e-3, "{}", end);
+            assert!(end.normalize().dot(target.normalize()) > 0.999, "{} {}", end, target);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_pole_target() {
+        let pole = IKTwoBoneJob::pole_vector_from_target(
+            Vec3A::new(0.0, 2.0, 0.0),
+            Vec3A::new(0.0, 0.0, 0.0),
+            Vec3A::new(1.0, 1.0, 3.0),
+        );
+        assert!(pole.abs_diff_eq(Vec3A::new(1.0, 0.0, 3.0) / 10.0f32.sqrt(), 1e-6));
+
+        // pole target on the start-end axis
+        let pole = IKTwoBoneJob::pole_vector_from_target(Vec3A::ZERO, Vec3A::X, Vec3A::new(3.0, 0.0, 0.0));
+        assert_eq!(pole, Vec3A::X);
+
+        // pole target matches pole vector
+        let mut job = new_ik_two_bone_job();
+        job.set_target(Vec3A::new(1.0, 0.0, 1.0));
+        job.set_pole_target(Vec3A::new(0.5, 0.0, 0.5) + Vec3A::Z * 4.0 - Vec3A::X * 4.0);
+        assert!(job
+            .pole_vector()
+            .abs_diff_eq(Vec3A::new(-1.0, 0.0, 1.0).normalize(), 1e-6));
+        job.run().unwrap();
+        let corrections = (job.start_joint_correction(), job.mid_joint_correction());
+
+        job.set_pole_vector(Vec3A::new(-1.0, 0.0, 1.0).normalize());
+        job.run().unwrap();
+        assert_eq!(corrections, (job.start_joint_correction(), job.mid_joint_correction()));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_pole_vector_from_rest_pose() {
+        let mut job = new_ik_two_bone_job();
+        let (start, mid, end) = (job.start_joint(), job.mid_joint(), job.end_joint());
+        let pole = IKTwoBoneJob::pole_vector_from_rest_pose(start, mid, end).unwrap();
+        assert!(pole.abs_diff_eq(Vec3A::new(-1.0, 1.0, 0.0).normalize(), 1e-6));
+
+        // Mid joint bends toward the rest pose bending direction, for a closer and rotated target.
+        let bend = |job: &IKTwoBoneJob| {
+            let mid = job.start_joint_correction() * Vec3A::from(job.mid_joint().col(3));
+            let axis = job.target().normalize();
+            return (mid - axis * mid.dot(axis)).normalize();
+        };
+        for target in [Vec3A::new(0.8, 0.8, 0.0), Vec3A::new(0.6, 0.9, 0.4)] {
+            job.set_target(target);
+            job.set_pole_vector(pole);
+            job.run().unwrap();
+            assert!(job.reached());
+            let expected = IKTwoBoneJob::pole_vector_from_target(Vec3A::ZERO, target, pole);
+            assert!(bend(&job).abs_diff_eq(expected, 1e-3));
+
+            job.set_pole_vector(-pole);
+            job.run().unwrap();
+            assert!(bend(&job).abs_diff_eq(-expected, 1e-3));
+        }
+
+        // Straight chain has no bending direction.
+        let end = Mat4::from_translation(Vec3::Y * 2.0);
+        assert_eq!(IKTwoBoneJob::pole_vector_from_rest_pose(start, mid, end), None);
+    }
 }
@@ -0,0 +1,155 @@
+//!
+//! Keyframe Interpolator.
+//!
+
+use core::fmt::Debug;
+use core::simd::prelude::*;
+
+use crate::math::{SoaQuat, SoaVec3};
+use crate::track::TrackValue;
+
+///
+/// Keyframe interpolation, between the `prev` and `next` keys bracketing the sampled time.
+///
+/// `t` is the ratio between both keys, in range 0.0-1.0. Sampling jobs use `LinearInterpolator` by
+/// default, a custom interpolator can be set to change the curve shape (easing, stepped keys...).
+///
+/// `TrackSamplingJob` uses `Interpolator<V>` with one `f32` ratio. `SamplingJob` interpolates 4 joints at
+/// once, with one ratio per lane, see `SoaInterpolator`.
+///
+pub trait Interpolator<V, R = f32>: Debug + Send + Sync {
+    /// Interpolates between `prev` and `next` at ratio `t`.
+    fn interpolate(&self, prev: &V, next: &V, t: R) -> V;
+}
+
+///
+/// Interpolator of `SamplingJob`, for soa translations, rotations and scales.
+///
+/// Implemented by all types implementing both `Interpolator<SoaVec3, f32x4>` and
+/// `Interpolator<SoaQuat, f32x4>`.
+///
+pub trait SoaInterpolator: Interpolator<SoaVec3, f32x4> + Interpolator<SoaQuat, f32x4> {}
+
+impl<I: Interpolator<SoaVec3, f32x4> + Interpolator<SoaQuat, f32x4>> SoaInterpolator for I {}
+
+///
+/// The built-in interpolation.
+///
+/// Linear interpolation for translations, scales and track values. Normalized lerp for rotations, see
+/// `TrackValue::lerp`.
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LinearInterpolator;
+
+impl<V: TrackValue> Interpolator<V> for LinearInterpolator {
+    #[inline]
+    fn interpolate(&self, prev: &V, next: &V, t: f32) -> V {
+        return V::lerp(*prev, *next, t);
+    }
+}
+
+impl Interpolator<SoaVec3, f32x4> for LinearInterpolator {
+    #[inline]
+    fn interpolate(&self, prev: &SoaVec3, next: &SoaVec3, t: f32x4) -> SoaVec3 {
+        return SoaVec3::lerp(prev, next, t);
+    }
+}
+
+impl Interpolator<SoaQuat, f32x4> for LinearInterpolator {
+    #[inline]
+    fn interpolate(&self, prev: &SoaQuat, next: &SoaQuat, t: f32x4) -> SoaQuat {
+        return SoaQuat::nlerp(prev, next, t);
+    }
+}
+
+#[cfg(test)]
+mod interpolator_tests {
+    use glam::{Quat, Vec3};
+    use std::sync::Arc;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::animation::AnimationBuilder;
+    use crate::math::SoaTransform;
+    use crate::sampling_job::{SamplingContext, SamplingJob, SamplingJobRef};
+    use crate::track::{TrackBuilder, TrackInterpolation};
+    use crate::track_sampling_job::{TrackSamplingJob, TrackSamplingJobRef};
+
+    // Holds the previous key value.
+    #[derive(Debug)]
+    struct StepInterpolator;
+
+    impl<V: Clone, R> Interpolator<V, R> for StepInterpolator {
+        fn interpolate(&self, prev: &V, _: &V, _: R) -> V {
+            return prev.clone();
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_track_interpolator() {
+        let mut builder = TrackBuilder::new();
+        builder.add_keyframe(0.0, 0.0f32, TrackInterpolation::Linear);
+        builder.add_keyframe(0.5, 1.0, TrackInterpolation::Linear);
+        builder.add_keyframe(1.0, 3.0, TrackInterpolation::Linear);
+        let track = builder.build().unwrap();
+
+        let mut job: TrackSamplingJobRef<f32> = TrackSamplingJob::default();
+        job.set_track(&track);
+        job.set_ratio(0.25);
+        job.run().unwrap();
+        assert_eq!(job.result(), 0.5);
+
+        job.set_interpolator(Arc::new(StepInterpolator));
+        for (ratio, expected) in [(0.0, 0.0), (0.25, 0.0), (0.5, 1.0), (0.75, 1.0), (1.0, 3.0)] {
+            job.set_ratio(ratio);
+            job.run().unwrap();
+            assert_eq!(job.result(), expected, "ratio {}", ratio);
+        }
+
+        job.set_interpolator(Arc::new(LinearInterpolator));
+        job.set_ratio(0.75);
+        job.run().unwrap();
+        assert_eq!(job.result(), 2.0);
+        job.clear_interpolator();
+        assert!(job.interpolator().is_none());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_sampling_interpolator() {
+        let mut builder = AnimationBuilder::new(1.0, 5);
+        let tracks = builder.tracks_mut();
+        tracks[1].translations = vec![(0.0, Vec3::ZERO), (0.5, Vec3::X), (1.0, Vec3::Y)];
+        tracks[4].rotations = vec![(0.0, Quat::IDENTITY), (1.0, Quat::from_rotation_z(1.0))];
+        let animation = builder.build().unwrap();
+
+        let mut output = vec![SoaTransform::default(); animation.num_soa_tracks()];
+        let mut job: SamplingJobRef = SamplingJob::default();
+        job.set_animation(&animation);
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_output(&mut output);
+        job.set_ratio(0.25);
+        job.run().unwrap();
+        let translation = job.output().unwrap()[0].col(1).translation;
+        assert!(translation.abs_diff_eq(Vec3::new(0.5, 0.0, 0.0), 1e-6));
+
+        job.set_interpolator(Arc::new(StepInterpolator));
+        job.run().unwrap();
+        assert_eq!(job.output().unwrap()[0].col(1).translation, Vec3::ZERO);
+        assert_eq!(job.output().unwrap()[1].col(0).rotation, Quat::IDENTITY);
+        job.set_ratio(0.75);
+        job.run().unwrap();
+        assert_eq!(job.output().unwrap()[0].col(1).translation, Vec3::X);
+        assert_eq!(job.output().unwrap()[1].col(0).rotation, Quat::IDENTITY);
+
+        job.set_interpolator(Arc::new(LinearInterpolator));
+        job.run().unwrap();
+        let translation = job.output().unwrap()[0].col(1).translation;
+        assert!(translation.abs_diff_eq(Vec3::new(0.5, 0.5, 0.0), 1e-6));
+        let rotation = job.output().unwrap()[1].col(0).rotation;
+        assert!(rotation.abs_diff_eq(Quat::from_rotation_z(0.75), 2e-3));
+        job.clear_interpolator();
+        assert!(job.interpolator().is_none());
+    }
+}
@@ -0,0 +1,319 @@
+//!
+//! Joint Limits.
+//!
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+use glam::{Quat, Vec3};
+
+#[cfg(not(feature = "std"))]
+use crate::math::F32Ext;
+
+///
+/// Swing-twist rotation limit of a joint, in joint local-space.
+///
+/// A local rotation is decomposed into a twist around `twist_axis` and a swing of the twist axis. The
+/// twist angle is limited to `[min_twist, max_twist]`, and the swing angle (the angle between the rest
+/// and the rotated twist axis) is limited to `max_swing`, like a cone around the twist axis.
+///
+/// A limit is valid if its angles are finite, `max_swing` is positive and `min_twist <= max_twist`, see
+/// `JointLimit::is_valid`. Invalid limits never panic, but their clamping is unspecified.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JointLimit {
+    pub twist_axis: Vec3,
+    pub max_swing: f32,
+    pub min_twist: f32,
+    pub max_twist: f32,
+}
+
+impl Default for JointLimit {
+    #[inline]
+    fn default() -> JointLimit {
+        return JointLimit::UNLIMITED;
+    }
+}
+
+impl JointLimit {
+    /// Limit allowing any rotation, twisting around the X axis (ozz joints axis).
+    pub const UNLIMITED: JointLimit = JointLimit {
+        twist_axis: Vec3::X,
+        max_swing: PI,
+        min_twist: -PI,
+        max_twist: PI,
+    };
+
+    /// Creates a `JointLimit`. Angles are in radians, check them with `is_valid`.
+    #[inline]
+    pub fn new(twist_axis: Vec3, max_swing: f32, min_twist: f32, max_twist: f32) -> JointLimit {
+        return JointLimit {
+            twist_axis,
+            max_swing,
+            min_twist,
+            max_twist,
+        };
+    }
+
+    /// Tests if the limit angles are finite, with a positive `max_swing` and `min_twist <= max_twist`.
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        return self.twist_axis.is_finite()
+            && self.max_swing.is_finite()
+            && self.max_swing >= 0.0
+            && self.min_twist.is_finite()
+            && self.max_twist.is_finite()
+            && self.min_twist <= self.max_twist;
+    }
+
+    /// Decomposes `rotation` into `(swing, twist)`, such that `rotation = swing * twist`.
+    pub fn swing_twist(&self, rotation: Quat) -> (Quat, Quat) {
+        let axis = self.twist_axis.normalize_or_zero();
+        let projected = axis * Vec3::new(rotation.x, rotation.y, rotation.z).dot(axis);
+        let twist = Quat::from_xyzw(projected.x, projected.y, projected.z, rotation.w);
+        let twist = if twist.length_squared() > 1e-12 {
+            twist.normalize()
+        } else {
+            // 180 degrees swing, twist is undefined.
+            Quat::IDENTITY
+        };
+        return (rotation * twist.conjugate(), twist);
+    }
+
+    /// Tests if `rotation` is in the limit range.
+    pub fn contains(&self, rotation: Quat) -> bool {
+        let (swing, twist) = self.swing_twist(rotation);
+        let twist_angle = Self::signed_angle(twist, self.twist_axis);
+        return Self::angle(swing) <= self.max_swing && (self.min_twist..=self.max_twist).contains(&twist_angle);
+    }
+
+    /// Projects `rotation` into the limit range. A rotation already in range is returned unchanged.
+    #[inline]
+    pub fn clamp(&self, rotation: Quat) -> Quat {
+        return self.soft_clamp(rotation, 0.0);
+    }
+
+    /// Soft clamps `rotation` into the limit range.
+    ///
+    /// Swing and twist angles within `softness` (in radians) of a bound are eased, approaching the bound
+    /// asymptotically instead of stopping at it. So the result stays in range, and changes smoothly as
+    /// `rotation` crosses the bound. `softness` is reduced to half the twist range (and to `max_swing`) if
+    /// it's larger. A `softness` of 0 is the hard projection of `clamp`.
+    pub fn soft_clamp(&self, rotation: Quat, softness: f32) -> Quat {
+        let softness = softness.max(0.0);
+        if softness == 0.0 && self.contains(rotation) {
+            return rotation;
+        }
+
+        let (mut swing, twist) = self.swing_twist(rotation);
+        let max_swing = self.max_swing.max(0.0);
+        let swing_angle = Self::angle(swing);
+        let limited_swing = soft_max(swing_angle, max_swing, softness.min(max_swing));
+        if limited_swing < swing_angle {
+            let swing_axis = Vec3::new(swing.x, swing.y, swing.z).normalize_or_zero() * swing.w.signum();
+            swing = Quat::from_axis_angle(swing_axis, limited_swing);
+        }
+
+        // `max` and `min` don't panic on inverted or NaN bounds, unlike `clamp`.
+        let axis = self.twist_axis.normalize_or_zero();
+        let (min_twist, max_twist) = (self.min_twist, self.max_twist.max(self.min_twist));
+        let twist_softness = softness.min((max_twist - min_twist) * 0.5);
+        let twist_angle = Self::signed_angle(twist, axis);
+        let limited_twist = -soft_max(
+            -soft_max(twist_angle, max_twist, twist_softness),
+            -min_twist,
+            twist_softness,
+        );
+        if limited_swing >= swing_angle && limited_twist == twist_angle {
+            return rotation;
+        }
+        return swing * Quat::from_axis_angle(axis, limited_twist);
+    }
+
+    // Rotation angle in [0, PI].
+    fn angle(rotation: Quat) -> f32 {
+        return 2.0 * rotation.w.abs().min(1.0).acos();
+    }
+
+    // Rotation angle around `axis`, in [-PI, PI].
+    pub(crate) fn signed_angle(twist: Quat, axis: Vec3) -> f32 {
+        let sin = Vec3::new(twist.x, twist.y, twist.z).dot(axis.normalize_or_zero());
+        let mut angle = 2.0 * sin.atan2(twist.w);
+        if angle > PI {
+            angle -= 2.0 * PI;
+        } else if angle < -PI {
+            angle += 2.0 * PI;
+        }
+        return angle;
+    }
+}
+
+///
+/// Per-joint rotation limits, ordered like skeleton's joints.
+///
+/// Limits are stored separately from the skeleton, joints without a limit are free.
+///
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JointLimits {
+    limits: Vec<Option<JointLimit>>,
+}
+
+impl JointLimits {
+    /// Creates `JointLimits` for `num_joints` free joints.
+    #[inline]
+    pub fn new(num_joints: usize) -> JointLimits {
+        return JointLimits {
+            limits: vec![None; num_joints],
+        };
+    }
+
+    /// Gets the number of joints of `JointLimits`.
+    #[inline]
+    pub fn num_joints(&self) -> usize {
+        return self.limits.len();
+    }
+
+    /// Gets the limit of `joint`, `None` if the joint is free or out of range.
+    #[inline]
+    pub fn limit(&self, joint: usize) -> Option<&JointLimit> {
+        return self.limits.get(joint).and_then(|limit| limit.as_ref());
+    }
+
+    /// Sets the limit of `joint`. Joints out of range are ignored.
+    #[inline]
+    pub fn set_limit(&mut self, joint: usize, limit: JointLimit) {
+        if let Some(dst) = self.limits.get_mut(joint) {
+            *dst = Some(limit);
+        }
+    }
+
+    /// Clears the limit of `joint`, freeing it.
+    #[inline]
+    pub fn clear_limit(&mut self, joint: usize) {
+        if let Some(dst) = self.limits.get_mut(joint) {
+            *dst = None;
+        }
+    }
+
+    /// Gets all limits of `JointLimits`.
+    #[inline]
+    pub fn limits(&self) -> &[Option<JointLimit>] {
+        return &self.limits;
+    }
+}
+
+// Limits `angle` to `max`, angles above `max - softness` approaching `max` asymptotically.
+fn soft_max(angle: f32, max: f32, softness: f32) -> f32 {
+    let knee = max - softness;
+    if softness.is_nan() || softness <= 0.0 || angle <= knee {
+        return angle.min(max);
+    }
+    return knee + softness * (1.0 - (-(angle - knee) / softness).exp());
+}
+
+#[cfg(test)]
+mod joint_limits_tests {
+    use wasm_bindgen_test::*;
+
+    use super::*;
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_swing_twist() {
+        let limit = JointLimit::new(Vec3::X, 0.5, -0.2, 0.4);
+        let rotation = Quat::from_rotation_z(0.3) * Quat::from_rotation_x(0.25);
+        let (swing, twist) = limit.swing_twist(rotation);
+        assert!((swing * twist).abs_diff_eq(rotation, 1e-6));
+        assert!(twist.abs_diff_eq(Quat::from_rotation_x(0.25), 1e-6));
+        assert!(swing.abs_diff_eq(Quat::from_rotation_z(0.3), 1e-6));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_clamp() {
+        let limit = JointLimit::new(Vec3::X, 0.5, -0.2, 0.4);
+
+        let inside = Quat::from_rotation_y(0.3) * Quat::from_rotation_x(-0.1);
+        assert!(limit.contains(inside));
+        assert_eq!(limit.clamp(inside), inside);
+
+        let swing = limit.clamp(Quat::from_rotation_z(1.2));
+        assert!(swing.abs_diff_eq(Quat::from_rotation_z(0.5), 1e-6));
+        let swing = limit.clamp(Quat::from_rotation_z(-1.2));
+        assert!(swing.abs_diff_eq(Quat::from_rotation_z(-0.5), 1e-6));
+
+        let twist = limit.clamp(Quat::from_rotation_x(1.0));
+        assert!(twist.abs_diff_eq(Quat::from_rotation_x(0.4), 1e-6));
+        let twist = limit.clamp(-Quat::from_rotation_x(-1.0));
+        assert!(twist.abs_diff_eq(Quat::from_rotation_x(-0.2), 1e-6));
+
+        let both = limit.clamp(Quat::from_rotation_y(0.9) * Quat::from_rotation_x(0.6));
+        assert!(both.abs_diff_eq(Quat::from_rotation_y(0.5) * Quat::from_rotation_x(0.4), 1e-6));
+
+        assert!(JointLimit::UNLIMITED.contains(Quat::from_rotation_y(3.0) * Quat::from_rotation_x(-3.0)));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_soft_clamp() {
+        let limit = JointLimit::new(Vec3::X, 0.5, -0.2, 0.4);
+        let twist = |angle: f32| JointLimit::signed_angle(limit.soft_clamp(Quat::from_rotation_x(angle), 0.1), Vec3::X);
+
+        // Before the soft zone, unchanged.
+        let inside = Quat::from_rotation_x(0.1);
+        assert_eq!(limit.soft_clamp(inside, 0.1), inside);
+        // In the soft zone and beyond, eased towards the bound but never past it.
+        let mut prev = twist(0.32);
+        assert!(prev > 0.31 && prev < 0.32);
+        for angle in [0.35, 0.4, 0.6, 1.0, 2.0] {
+            let eased = twist(angle);
+            assert!(eased >= prev && eased <= 0.4 + 1e-5, "{} {}", angle, eased);
+            prev = eased;
+        }
+        assert!(twist(-1.0) > -0.2 && twist(-1.0) < -0.19);
+
+        let swing = limit.soft_clamp(Quat::from_rotation_z(1.2), 0.2);
+        let angle = 2.0 * swing.w.acos();
+        assert!(angle > 0.45 && angle < 0.5);
+        assert_eq!(
+            limit.soft_clamp(Quat::from_rotation_z(1.2), 0.0),
+            limit.clamp(Quat::from_rotation_z(1.2))
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_invalid_limit() {
+        assert!(JointLimit::UNLIMITED.is_valid());
+        let inverted = JointLimit::new(Vec3::X, 0.5, 0.4, -0.2);
+        assert!(!inverted.is_valid());
+        assert!(!JointLimit::new(Vec3::X, f32::NAN, -0.2, 0.4).is_valid());
+        assert!(!JointLimit::new(Vec3::X, -0.5, -0.2, 0.4).is_valid());
+
+        // Doesn't panic.
+        let rotation = Quat::from_rotation_x(1.0);
+        assert!(inverted.clamp(rotation).is_finite());
+        assert!(JointLimit::new(Vec3::X, 0.5, f32::NAN, f32::NAN)
+            .clamp(rotation)
+            .is_finite());
+        assert!(JointLimit::new(Vec3::X, 0.5, 0.4, -0.2)
+            .soft_clamp(rotation, 0.3)
+            .is_finite());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_joint_limits() {
+        let mut limits = JointLimits::new(3);
+        assert_eq!(limits.num_joints(), 3);
+        limits.set_limit(1, JointLimit::new(Vec3::Y, 0.1, 0.0, 0.0));
+        limits.set_limit(5, JointLimit::UNLIMITED);
+        assert!(limits.limit(0).is_none());
+        assert_eq!(limits.limit(1).unwrap().twist_axis, Vec3::Y);
+        assert!(limits.limit(5).is_none());
+        limits.clear_limit(1);
+        assert!(limits.limits().iter().all(|limit| limit.is_none()));
+    }
+}
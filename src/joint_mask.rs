@@ -0,0 +1,154 @@
+//!
+//! Joint Mask.
+//!
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::base::OzzIndex;
+use crate::skeleton::Skeleton;
+
+///
+/// Set of joints, ordered like skeleton's joints. One bit per joint (8 joints per byte).
+///
+/// Used to restrict a process to a part of the skeleton, like sampling only the face joints of a rig
+/// sharing its body skeleton. See `SamplingContext::for_joints`.
+///
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JointMask {
+    num_joints: usize,
+    bits: Vec<u8>,
+}
+
+impl JointMask {
+    /// Creates an empty `JointMask` of `num_joints` joints.
+    #[inline]
+    pub fn new(num_joints: usize) -> JointMask {
+        return JointMask {
+            num_joints,
+            bits: vec![0; num_joints.div_ceil(8)],
+        };
+    }
+
+    /// Creates a `JointMask` of `num_joints` joints, all set.
+    pub fn all(num_joints: usize) -> JointMask {
+        let mut mask = JointMask::new(num_joints);
+        (0..num_joints).for_each(|joint| mask.set(joint, true));
+        return mask;
+    }
+
+    /// Creates a `JointMask` of `num_joints` joints from raw `bits`, see `JointMask::bits`.
+    pub fn from_bits(num_joints: usize, bits: &[u8]) -> JointMask {
+        let mut mask = JointMask::new(num_joints);
+        (0..num_joints)
+            .filter(|joint| bits.get(joint / 8).is_some_and(|x| x & (1 << (joint % 8)) != 0))
+            .for_each(|joint| mask.set(joint, true));
+        return mask;
+    }
+
+    /// Creates a `JointMask` of `skeleton` joints, with `root` and all its descendants set.
+    pub fn from_hierarchy(skeleton: &Skeleton, root: impl OzzIndex) -> JointMask {
+        let mut mask = JointMask::new(skeleton.num_joints());
+        if root.usize() < skeleton.num_joints() {
+            (root.usize()..skeleton.subtree_end(root.usize())).for_each(|joint| mask.set(joint, true));
+        }
+        return mask;
+    }
+
+    /// Gets the number of joints of `JointMask`.
+    #[inline]
+    pub fn num_joints(&self) -> usize {
+        return self.num_joints;
+    }
+
+    /// Gets the number of set joints.
+    #[inline]
+    pub fn count(&self) -> usize {
+        return self.bits.iter().map(|x| x.count_ones() as usize).sum();
+    }
+
+    /// Tests if `joint` is set. Joints out of range are not set.
+    #[inline]
+    pub fn contains(&self, joint: usize) -> bool {
+        return joint < self.num_joints && self.bits[joint / 8] & (1 << (joint % 8)) != 0;
+    }
+
+    /// Sets or unsets `joint`. Joints out of range are ignored.
+    #[inline]
+    pub fn set(&mut self, joint: usize, value: bool) {
+        if joint >= self.num_joints {
+            return;
+        }
+        if value {
+            self.bits[joint / 8] |= 1 << (joint % 8);
+        } else {
+            self.bits[joint / 8] &= !(1 << (joint % 8));
+        }
+    }
+
+    /// Gets the set joints of soa joint `soa`, one bit per lane.
+    #[inline]
+    pub fn soa_lanes(&self, soa: usize) -> u8 {
+        return match self.bits.get(soa / 2) {
+            Some(bits) => (bits >> ((soa % 2) * 4)) & 0xF,
+            None => 0,
+        };
+    }
+
+    /// Gets the raw bits of `JointMask`, one bit per joint (8 joints per byte).
+    #[inline]
+    pub fn bits(&self) -> &[u8] {
+        return &self.bits;
+    }
+}
+
+#[cfg(test)]
+mod joint_mask_tests {
+    use wasm_bindgen_test::*;
+
+    use super::*;
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_joint_mask() {
+        let mut mask = JointMask::new(10);
+        assert_eq!(mask.num_joints(), 10);
+        assert_eq!(mask.count(), 0);
+
+        mask.set(1, true);
+        mask.set(6, true);
+        mask.set(9, true);
+        mask.set(10, true);
+        assert_eq!(mask.count(), 3);
+        assert!(mask.contains(6));
+        assert!(!mask.contains(7));
+        assert!(!mask.contains(10));
+        assert_eq!(mask.soa_lanes(0), 0b0010);
+        assert_eq!(mask.soa_lanes(1), 0b0100);
+        assert_eq!(mask.soa_lanes(2), 0b0010);
+        assert_eq!(mask.soa_lanes(3), 0);
+
+        mask.set(6, false);
+        assert!(!mask.contains(6));
+        assert_eq!(JointMask::all(10).count(), 10);
+        assert_eq!(JointMask::from_bits(10, mask.bits()), mask);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_from_hierarchy() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let root = skeleton.joint_by_name("LeftArm").unwrap();
+        let mask = JointMask::from_hierarchy(&skeleton, root);
+        assert_eq!(mask.count(), skeleton.subtree_end(root) - root as usize);
+        for joint in 0..skeleton.num_joints() {
+            let mut parent = joint as i16;
+            while parent >= 0 && parent != root {
+                parent = skeleton.joint_parent(parent);
+            }
+            assert_eq!(mask.contains(joint), parent == root);
+        }
+        assert_eq!(JointMask::from_hierarchy(&skeleton, 1000).count(), 0);
+    }
+}
@@ -0,0 +1,361 @@
+//!
+//! Lazy Animation.
+//!
+
+use core::sync::atomic::{AtomicU64, Ordering};
+use std::io::{Read, Seek};
+
+use crate::animation::{Animation, AnimationData, AnimationMeta, Float3Key, QuaternionKey};
+use crate::archive::{Archive, ArchiveRead};
+use crate::base::OzzError;
+
+// Sizes of the keys in ozz archives.
+const FLOAT3_KEY_SIZE: u64 = 12;
+const QUATERNION_KEY_SIZE: u64 = 14;
+
+// Ids of the loaded keyframes, so that a `SamplingContext` resets when the loaded tracks change. `Animation`
+// ids are addresses, far beyond this counter.
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+///
+/// An animation read from a seekable archive, with keyframes loaded on demand, track by track.
+///
+/// Creation reads the animation header, and the track of each keyframe, but keeps only per-track keyframe
+/// indices. `load_tracks` then reads the keyframes of the requested tracks, seeking to them in the archive.
+/// This bounds the memory of large animation packs, where only a few clips (or tracks) are active.
+///
+/// `LazyAnimation` implements `AnimationData`, so it's sampled by a `SamplingJob` like an `Animation`. Loaded
+/// tracks are sampled like the eager animation. Tracks that aren't loaded only have their first keyframes,
+/// and should be skipped (see `SamplingContext::for_joints`). Use a buffered reader (like `BufReader<File>`)
+/// to avoid small reads.
+///
+pub struct LazyAnimation<R: Read + Seek> {
+    archive: Archive<R>,
+    meta: AnimationMeta,
+    keys_position: u64,
+    loaded: Vec<bool>,
+    id: u64,
+
+    // Indices of each track keyframes, after the first keyframes of all tracks.
+    translation_indices: Vec<Vec<u32>>,
+    rotation_indices: Vec<Vec<u32>>,
+    scale_indices: Vec<Vec<u32>>,
+
+    // The first keyframes of all tracks, followed by the keyframes of loaded tracks, in archive order.
+    translations: Vec<Float3Key>,
+    rotations: Vec<QuaternionKey>,
+    scales: Vec<Float3Key>,
+}
+
+impl<R: Read + Seek> LazyAnimation<R> {
+    /// Creates a `LazyAnimation` from a reader of an animation archive.
+    pub fn from_reader(read: R) -> Result<LazyAnimation<R>, OzzError> {
+        return LazyAnimation::from_archive(Archive::new(read)?);
+    }
+
+    /// Creates a `LazyAnimation` from an `Archive`, positioned at the animation header (see
+    /// `Animation::read_meta`).
+    pub fn from_archive(mut archive: Archive<R>) -> Result<LazyAnimation<R>, OzzError> {
+        let meta = Animation::read_meta(&mut archive)?;
+        let keys_position = archive.stream_position()?;
+        let num_tracks = meta.num_tracks.max(0) as usize;
+        let num_aligned_tracks = (num_tracks + 3) & !0x3;
+
+        let mut animation = LazyAnimation {
+            archive,
+            loaded: vec![false; num_tracks],
+            id: 0,
+            translation_indices: vec![Vec::new(); num_aligned_tracks],
+            rotation_indices: vec![Vec::new(); num_aligned_tracks],
+            scale_indices: vec![Vec::new(); num_aligned_tracks],
+            translations: Vec::new(),
+            rotations: Vec::new(),
+            scales: Vec::new(),
+            meta,
+            keys_position,
+        };
+        animation.read_indices()?;
+        animation.reload()?;
+        return Ok(animation);
+    }
+
+    /// Gets the meta of `LazyAnimation`, the animation header.
+    #[inline]
+    pub fn meta(&self) -> &AnimationMeta {
+        return &self.meta;
+    }
+
+    /// Gets the animation clip duration.
+    #[inline]
+    pub fn duration(&self) -> f32 {
+        return self.meta.duration;
+    }
+
+    /// Gets the number of animated tracks.
+    #[inline]
+    pub fn num_tracks(&self) -> usize {
+        return self.loaded.len();
+    }
+
+    /// Gets animation name.
+    #[inline]
+    pub fn name(&self) -> &str {
+        return &self.meta.name;
+    }
+
+    /// Tests if the keyframes of `track` are loaded.
+    #[inline]
+    pub fn is_track_loaded(&self, track: usize) -> bool {
+        return self.loaded.get(track).copied().unwrap_or(false);
+    }
+
+    /// Gets the number of tracks with loaded keyframes.
+    #[inline]
+    pub fn num_loaded_tracks(&self) -> usize {
+        return self.loaded.iter().filter(|loaded| **loaded).count();
+    }
+
+    /// Loads the keyframes of `tracks`, in addition to the already loaded tracks.
+    ///
+    /// Returns `OzzError::InvalidIndex` if a track is out of range, no track is loaded then.
+    pub fn load_tracks(&mut self, tracks: &[usize]) -> Result<(), OzzError> {
+        if tracks.iter().any(|track| *track >= self.loaded.len()) {
+            return Err(OzzError::InvalidIndex);
+        }
+        if tracks.iter().all(|track| self.loaded[*track]) {
+            return Ok(());
+        }
+        tracks.iter().for_each(|track| self.loaded[*track] = true);
+        return self.reload();
+    }
+
+    /// Frees the keyframes of all tracks, they are loaded again by `load_tracks`.
+    pub fn unload_tracks(&mut self) -> Result<(), OzzError> {
+        self.loaded.iter_mut().for_each(|loaded| *loaded = false);
+        return self.reload();
+    }
+
+    /// Reads all the keyframes into an `Animation`, like `Animation::from_archive`. Loaded tracks aren't
+    /// changed.
+    pub fn to_animation(&mut self) -> Result<Animation, OzzError> {
+        self.archive.seek_to(self.keys_position)?;
+        let translations = self.archive.read_vec(self.meta.translation_count as usize)?;
+        let rotations = self.archive.read_vec(self.meta.rotation_count as usize)?;
+        let scales = self.archive.read_vec(self.meta.scale_count as usize)?;
+        return Ok(Animation {
+            duration: self.meta.duration,
+            num_tracks: self.loaded.len(),
+            name: self.meta.name.clone(),
+            translations,
+            rotations,
+            scales,
+        });
+    }
+
+    // Reads the track of all keyframes in one pass, recording the indices of each track keyframes.
+    fn read_indices(&mut self) -> Result<(), OzzError> {
+        let head = self.num_aligned_tracks() as u32 * 2;
+        self.archive.seek_to(self.keys_position)?;
+        for idx in 0..self.meta.translation_count as u32 {
+            let key: Float3Key = self.archive.read()?;
+            if idx >= head {
+                let indices = self.translation_indices.get_mut(key.track as usize);
+                indices.ok_or(OzzError::InvalidIndex)?.push(idx);
+            }
+        }
+        for idx in 0..self.meta.rotation_count as u32 {
+            let key: QuaternionKey = self.archive.read()?;
+            if idx >= head {
+                let indices = self.rotation_indices.get_mut(key.track() as usize);
+                indices.ok_or(OzzError::InvalidIndex)?.push(idx);
+            }
+        }
+        for idx in 0..self.meta.scale_count as u32 {
+            let key: Float3Key = self.archive.read()?;
+            if idx >= head {
+                let indices = self.scale_indices.get_mut(key.track as usize);
+                indices.ok_or(OzzError::InvalidIndex)?.push(idx);
+            }
+        }
+        return Ok(());
+    }
+
+    // Reads the first keyframes of all tracks, and the keyframes of loaded tracks.
+    fn reload(&mut self) -> Result<(), OzzError> {
+        let translations_position = self.keys_position;
+        let rotations_position = translations_position + self.meta.translation_count as u64 * FLOAT3_KEY_SIZE;
+        let scales_position = rotations_position + self.meta.rotation_count as u64 * QUATERNION_KEY_SIZE;
+
+        let indices = self.loaded_indices(&self.translation_indices, self.meta.translation_count);
+        self.translations = self.read_keys(translations_position, FLOAT3_KEY_SIZE, &indices)?;
+        let indices = self.loaded_indices(&self.rotation_indices, self.meta.rotation_count);
+        self.rotations = self.read_keys(rotations_position, QUATERNION_KEY_SIZE, &indices)?;
+        let indices = self.loaded_indices(&self.scale_indices, self.meta.scale_count);
+        self.scales = self.read_keys(scales_position, FLOAT3_KEY_SIZE, &indices)?;
+        self.id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        return Ok(());
+    }
+
+    // Sorted indices of the first keyframes and of the loaded tracks keyframes.
+    fn loaded_indices(&self, track_indices: &[Vec<u32>], count: i32) -> Vec<u32> {
+        let head = (self.num_aligned_tracks() as u32 * 2).min(count.max(0) as u32);
+        let mut indices: Vec<u32> = (0..head).collect();
+        for (track, loaded) in self.loaded.iter().enumerate() {
+            if *loaded {
+                indices.extend_from_slice(&track_indices[track]);
+            }
+        }
+        indices[head as usize..].sort_unstable();
+        return indices;
+    }
+
+    fn read_keys<T: ArchiveRead<T>>(&mut self, position: u64, size: u64, indices: &[u32]) -> Result<Vec<T>, OzzError> {
+        let mut keys = Vec::with_capacity(indices.len());
+        let mut next = None;
+        for idx in indices.iter() {
+            // Contiguous keyframes are read without seeking.
+            if next != Some(*idx) {
+                self.archive.seek_to(position + *idx as u64 * size)?;
+            }
+            keys.push(self.archive.read()?);
+            next = Some(*idx + 1);
+        }
+        return Ok(keys);
+    }
+}
+
+impl<R: Read + Seek> AnimationData for LazyAnimation<R> {
+    #[inline]
+    fn id(&self) -> u64 {
+        return self.id;
+    }
+
+    #[inline]
+    fn duration(&self) -> f32 {
+        return self.meta.duration;
+    }
+
+    #[inline]
+    fn num_tracks(&self) -> usize {
+        return self.loaded.len();
+    }
+
+    #[inline]
+    fn translations(&self) -> &[Float3Key] {
+        return &self.translations;
+    }
+
+    #[inline]
+    fn rotations(&self) -> &[QuaternionKey] {
+        return &self.rotations;
+    }
+
+    #[inline]
+    fn scales(&self) -> &[Float3Key] {
+        return &self.scales;
+    }
+}
+
+impl<R: Read + Seek> AnimationData for &LazyAnimation<R> {
+    #[inline]
+    fn id(&self) -> u64 {
+        return (*self).id();
+    }
+
+    #[inline]
+    fn duration(&self) -> f32 {
+        return (*self).duration();
+    }
+
+    #[inline]
+    fn num_tracks(&self) -> usize {
+        return (*self).num_tracks();
+    }
+
+    #[inline]
+    fn translations(&self) -> &[Float3Key] {
+        return (*self).translations();
+    }
+
+    #[inline]
+    fn rotations(&self) -> &[QuaternionKey] {
+        return (*self).rotations();
+    }
+
+    #[inline]
+    fn scales(&self) -> &[Float3Key] {
+        return (*self).scales();
+    }
+}
+
+#[cfg(test)]
+mod lazy_animation_tests {
+    use std::io::Cursor;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::archive::ArchiveWriter;
+    use crate::joint_mask::JointMask;
+    use crate::math::SoaTransform;
+    use crate::sampling_job::{SamplingContext, SamplingJob, SamplingJobRef};
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_lazy_sampling() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let mut writer = ArchiveWriter::to_vec(Animation::tag(), Animation::version()).unwrap();
+        animation.to_archive(&mut writer).unwrap();
+        let mut lazy = LazyAnimation::from_reader(Cursor::new(writer.into_inner())).unwrap();
+        assert_eq!(lazy.duration(), animation.duration());
+        assert_eq!(lazy.num_tracks(), animation.num_tracks());
+        assert_eq!(lazy.num_loaded_tracks(), 0);
+        assert_eq!(lazy.translations().len(), animation.num_aligned_tracks() * 2);
+
+        let tracks = [0, 5, 17, 66];
+        lazy.load_tracks(&tracks).unwrap();
+        assert!(lazy.is_track_loaded(17));
+        assert!(!lazy.is_track_loaded(18));
+        assert_eq!(lazy.num_loaded_tracks(), 4);
+        assert!(lazy.translations().len() < animation.translations().len());
+        assert!(matches!(lazy.load_tracks(&[67]), Err(OzzError::InvalidIndex)));
+
+        let mut mask = JointMask::new(animation.num_tracks());
+        tracks.iter().for_each(|track| mask.set(*track, true));
+        let mut expected_output = vec![SoaTransform::default(); animation.num_soa_tracks()];
+        let mut expected_job: SamplingJobRef = SamplingJob::default();
+        expected_job.set_animation(&animation);
+        expected_job.set_context(SamplingContext::from_animation(&animation));
+        expected_job.set_output(&mut expected_output);
+        let mut output = vec![SoaTransform::default(); animation.num_soa_tracks()];
+        let mut job: SamplingJob<&LazyAnimation<_>, &mut [SoaTransform]> = SamplingJob::default();
+        job.set_animation(&lazy);
+        job.set_context(SamplingContext::for_joints(animation.num_tracks(), &mask));
+        job.set_output(&mut output);
+        for ratio in [0.0, 0.1, 0.37, 0.5, 0.92, 1.0, 0.3] {
+            expected_job.set_ratio(ratio);
+            expected_job.run().unwrap();
+            job.set_ratio(ratio);
+            job.run().unwrap();
+            for track in tracks {
+                let expected = expected_job.output().unwrap()[track / 4].col(track % 4);
+                assert_eq!(job.output().unwrap()[track / 4].col(track % 4), expected);
+            }
+        }
+        drop(job);
+
+        lazy.unload_tracks().unwrap();
+        assert_eq!(lazy.num_loaded_tracks(), 0);
+        assert_eq!(lazy.translations().len(), animation.num_aligned_tracks() * 2);
+        let eager = lazy.to_animation().unwrap();
+        assert_eq!(eager.translations(), animation.translations());
+        assert_eq!(eager.rotations(), animation.rotations());
+        assert_eq!(eager.scales(), animation.scales());
+
+        // All tracks loaded, the keyframes are the ones of the eager animation.
+        let all_tracks: Vec<usize> = (0..animation.num_tracks()).collect();
+        lazy.load_tracks(&all_tracks).unwrap();
+        assert_eq!(lazy.translations(), animation.translations());
+        assert_eq!(lazy.rotations(), animation.rotations());
+        assert_eq!(lazy.scales(), animation.scales());
+    }
+}
@@ -45,43 +45,148 @@
 //! ```
 //!
 
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(not(feature = "std"), feature(hashmap_internals))]
+#![cfg_attr(not(feature = "std"), allow(internal_features))]
 #![feature(allocator_api)]
-#![feature(const_collections_with_hasher)]
+#![cfg_attr(feature = "std", feature(const_collections_with_hasher))]
 #![feature(portable_simd)]
 
+extern crate alloc;
+
+pub mod aim_driver;
 pub mod animation;
+pub mod animation_events;
+pub mod animation_player;
+pub mod animation_set;
+#[cfg(feature = "std")]
 pub mod archive;
 pub mod base;
+pub mod blend_space;
 pub mod blending_job;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod clamp_pose_job;
+pub mod dirty_set;
+#[cfg(feature = "std")]
 mod endian;
+#[cfg(feature = "gltf")]
+pub mod gltf;
 pub mod ik_aim_job;
+pub mod ik_chain_job;
 pub mod ik_two_bone_job;
+pub mod interpolator;
+pub mod joint_limits;
+pub mod joint_mask;
+#[cfg(feature = "std")]
+pub mod lazy_animation;
 pub mod local_to_model_job;
 pub mod math;
+pub mod mirror;
+pub mod motion_blending_job;
+pub mod motion_extraction_job;
 #[cfg(all(feature = "wasm", feature = "nodejs"))]
 pub mod nodejs;
+pub mod pose_cache;
+pub mod pose_history;
+pub mod pose_utils;
+pub mod prelude;
+pub mod retarget_job;
+pub mod sampled_pose;
 pub mod sampling_job;
 pub mod skeleton;
 pub mod skinning_job;
+pub mod spring_bone_job;
 pub mod track;
 pub mod track_sampling_job;
 pub mod track_triggering_job;
+pub mod twist_distribution_job;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "wide8")]
+pub mod wide8;
 
-pub use animation::Animation;
-pub use archive::{Archive, ArchiveRead};
+pub use aim_driver::AimDriver;
+pub use animation::{
+    compress_quat, compress_vec3, decompress_quat, decompress_vec3, Animation, AnimationBuilder, AnimationData,
+    CompressedQuat, CompressedVec3, JointTrack, OptimizeReport,
+};
+pub use animation_events::AnimationEvents;
+pub use animation_player::{phase_ratio, AnimationPlayer};
+pub use animation_set::AnimationSet;
+#[cfg(feature = "std")]
+pub use archive::{Archive, ArchiveRead, ArchiveWrite, ArchiveWriter, DECOMPRESSED_MAX_SIZE};
 pub use base::{OzzBuf, OzzError, OzzMutBuf, OzzObj, SKELETON_MAX_JOINTS, SKELETON_MAX_SOA_JOINTS, SKELETON_NO_PARENT};
-pub use blending_job::{BlendingContext, BlendingJob, BlendingJobArc, BlendingJobRc, BlendingJobRef, BlendingLayer};
+pub use blend_space::{BlendSpace1D, BlendSpace2D};
+pub use blending_job::{
+    normalize_weights, validate_weights, BlendingContext, BlendingJob, BlendingJobRc, BlendingJobRef, BlendingLayer,
+    OverrideLayer, WeightFallback,
+};
+#[cfg(feature = "std")]
+pub use blending_job::BlendingJobArc;
+pub use clamp_pose_job::{ClampPoseJob, ClampPoseJobRc, ClampPoseJobRef};
+#[cfg(feature = "std")]
+pub use clamp_pose_job::ClampPoseJobArc;
+pub use dirty_set::DirtySet;
+#[cfg(feature = "gltf")]
+pub use crate::gltf::{
+    GltfAnimation, GltfChannel, GltfChannelValues, GltfDocument, GltfInterpolation, GltfNode, GltfSkin,
+    GLTF_CUBIC_SAMPLE_RATE,
+};
 pub use ik_aim_job::IKAimJob;
+pub use ik_chain_job::IKChainJob;
 pub use ik_two_bone_job::IKTwoBoneJob;
-pub use local_to_model_job::{LocalToModelJob, LocalToModelJobArc, LocalToModelJobRc, LocalToModelJobRef};
-pub use math::{SoaMat4, SoaQuat, SoaTransform, SoaVec3};
-pub use sampling_job::{
-    InterpSoaFloat3, InterpSoaQuaternion, SamplingContext, SamplingJob, SamplingJobArc, SamplingJobRc, SamplingJobRef,
+pub use interpolator::{Interpolator, LinearInterpolator, SoaInterpolator};
+pub use joint_limits::{JointLimit, JointLimits};
+pub use joint_mask::JointMask;
+#[cfg(feature = "std")]
+pub use lazy_animation::LazyAnimation;
+pub use local_to_model_job::{LocalToModelJob, LocalToModelJobRc, LocalToModelJobRef};
+#[cfg(feature = "std")]
+pub use local_to_model_job::{batch_local_to_model, LocalToModelJobArc};
+pub use math::{
+    mat4_slice_as_f32, soa_transform_slice_as_f32, AosTransform, ApproxEq, SoaMat4, SoaQuat, SoaTransform, SoaVec3,
+};
+pub use mirror::{mirror_pose, BoneMirrorMap, MirrorAxis};
+pub use motion_blending_job::{MotionBlendingJob, MotionBlendingLayer};
+pub use motion_extraction_job::{MotionExtractionJob, MotionExtractionJobRc, MotionExtractionJobRef};
+#[cfg(feature = "std")]
+pub use motion_extraction_job::MotionExtractionJobArc;
+pub use pose_cache::PoseCache;
+pub use pose_history::PoseHistory;
+pub use pose_utils::{
+    apply_remap, attachment_matrix, clamp_scale, compute_center_of_mass, compute_pose_aabb, debug_validate_pose,
+    joint_position, joint_positions, local_pose_to_gltf_nodes, make_additive_pose, model_pose_to_gltf_nodes,
+    model_to_local, pose_difference, pose_velocities, poses_to_quat_pos, sanitize_padding, soa_to_aos_iter,
+    validate_aos_pose, validate_pose, Aabb, JointDiff, PoseDiff,
 };
-pub use skeleton::{JointHashMap, Skeleton};
-pub use skinning_job::{SkinningJob, SkinningJobArc, SkinningJobRc, SkinningJobRef};
-pub use track::Track;
-pub use track_sampling_job::{TrackSamplingJob, TrackSamplingJobArc, TrackSamplingJobRc, TrackSamplingJobRef};
-pub use track_triggering_job::{
-    Edge, TrackTriggeringJob, TrackTriggeringJobArc, TrackTriggeringJobRc, TrackTriggeringJobRef,
+pub use retarget_job::{retarget_pose, RetargetJob, RetargetJobRc, RetargetJobRef};
+#[cfg(feature = "std")]
+pub use retarget_job::RetargetJobArc;
+pub use sampled_pose::SampledPose;
+#[cfg(feature = "instrument")]
+pub use sampling_job::JobStats;
+pub use sampling_job::{
+    InterpSoaFloat3, InterpSoaQuaternion, RatioRemap, SamplingContext, SamplingJob, SamplingJobRc, SamplingJobRef,
 };
+#[cfg(feature = "std")]
+pub use sampling_job::SamplingJobArc;
+pub use skeleton::{JointHashMap, Skeleton, SkeletonBuilder};
+#[cfg(feature = "rkyv")]
+pub use skeleton::SkeletonV1;
+pub use skinning_job::{SkinningJob, SkinningJobRc, SkinningJobRef};
+#[cfg(feature = "std")]
+pub use skinning_job::SkinningJobArc;
+pub use spring_bone_job::{SpringBone, SpringBoneJob, SpringBoneJobRc, SpringBoneJobRef};
+#[cfg(feature = "std")]
+pub use spring_bone_job::SpringBoneJobArc;
+pub use track::{Track, TrackBuilder, TrackInterpolation};
+pub use track_sampling_job::{TrackSamplingJob, TrackSamplingJobRc, TrackSamplingJobRef};
+#[cfg(feature = "std")]
+pub use track_sampling_job::TrackSamplingJobArc;
+pub use track_triggering_job::{Edge, TrackTriggeringJob, TrackTriggeringJobRc, TrackTriggeringJobRef};
+#[cfg(feature = "std")]
+pub use track_triggering_job::TrackTriggeringJobArc;
+pub use twist_distribution_job::{TwistDistributionJob, TwistDistributionJobRc, TwistDistributionJobRef};
+#[cfg(feature = "std")]
+pub use twist_distribution_job::TwistDistributionJobArc;
@@ -2,15 +2,26 @@
 //! Local to Model Job.
 //!
 
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
 use glam::Mat4;
-use std::cell::RefCell;
-use std::rc::Rc;
+#[cfg(feature = "std")]
 use std::sync::{Arc, RwLock};
 
 use crate::base::{OzzBuf, OzzError, OzzIndex, OzzMutBuf, OzzObj, SKELETON_MAX_JOINTS, SKELETON_NO_PARENT};
-use crate::math::{AosMat4, SoaMat4, SoaTransform};
+use crate::dirty_set::DirtySet;
+use crate::math::{AosMat4, SoaMat4, SoaTransform, SoaVec3, ONE};
 use crate::skeleton::Skeleton;
 
+/// Minimum number of joints computed by each thread of `batch_local_to_model`.
+#[cfg(feature = "std")]
+const BATCH_JOINTS_PER_THREAD: usize = 2048;
+
+/// Minimum number of skeleton joints for `LocalToModelJob::run_parallel` to use threads.
+#[cfg(feature = "std")]
+const PARALLEL_MIN_JOINTS: usize = 1024;
+
 ///
 /// Computes model-space joint matrices from local-space `SoaTransform`.
 ///
@@ -37,11 +48,15 @@ where
     from: i32,
     to: i32,
     from_excluded: bool,
+    ignore_scale: bool,
+    inverse_bind: Vec<Mat4>,
+    models: Vec<Mat4>,
     output: Option<O>,
 }
 
 pub type LocalToModelJobRef<'t> = LocalToModelJob<&'t Skeleton, &'t [SoaTransform], &'t mut [Mat4]>;
 pub type LocalToModelJobRc = LocalToModelJob<Rc<Skeleton>, Rc<RefCell<Vec<SoaTransform>>>, Rc<RefCell<Vec<Mat4>>>>;
+#[cfg(feature = "std")]
 pub type LocalToModelJobArc = LocalToModelJob<Arc<Skeleton>, Arc<RwLock<Vec<SoaTransform>>>, Arc<RwLock<Vec<Mat4>>>>;
 
 impl<S, I, O> Default for LocalToModelJob<S, I, O>
@@ -58,6 +73,9 @@ where
             from: SKELETON_NO_PARENT,
             to: SKELETON_MAX_JOINTS,
             from_excluded: false,
+            ignore_scale: false,
+            inverse_bind: Vec::new(),
+            models: Vec::new(),
             output: None,
         };
     }
@@ -117,7 +135,7 @@ where
 
     /// Sets root of `LocalToModelJob`.
     ///
-    /// The root matrix will multiply to every model space matrices, None means an identity matrix.
+    /// The root matrix will multiply to every model space matrices, default is an identity matrix.
     /// This can be used to directly compute world-space transforms for example.
     #[inline]
     pub fn set_root(&mut self, root: &Mat4) {
@@ -157,6 +175,9 @@ where
     ///
     /// Default value is `SKELETON_MAX_JOINTS`, meaning the hierarchy (starting from "from") is updated to
     /// the last joint.
+    ///
+    /// Output entries outside of the updated `[from, to]` range are left untouched, so a single chain (e.g.
+    /// up to an IK effector) can be updated without recomputing the whole skeleton.
     #[inline]
     pub fn set_to(&mut self, to: impl OzzIndex) {
         self.to = to.i32();
@@ -182,6 +203,52 @@ where
         self.from_excluded = from_excluded;
     }
 
+    /// Gets ignore_scale of `LocalToModelJob`.
+    #[inline]
+    pub fn ignore_scale(&self) -> bool {
+        return self.ignore_scale;
+    }
+
+    /// Sets ignore_scale of `LocalToModelJob`.
+    ///
+    /// If `true`, local-space scales are ignored, so output matrices only contain rotations and translations,
+    /// with orthonormal basis vectors. This is usually what physics engines expect.
+    ///
+    /// Default value is `false`.
+    ///
+    /// Note that ignoring scale is not the same as normalizing a scaled matrix: a parent scale is neither applied
+    /// to its children translations, whereas normalizing each output matrix would keep scaled joint positions.
+    #[inline]
+    pub fn set_ignore_scale(&mut self, ignore_scale: bool) {
+        self.ignore_scale = ignore_scale;
+    }
+
+    /// Gets inverse bind matrices of `LocalToModelJob`.
+    #[inline]
+    pub fn inverse_bind(&self) -> &[Mat4] {
+        return &self.inverse_bind;
+    }
+
+    /// Sets inverse bind matrices of `LocalToModelJob`.
+    ///
+    /// The inverse bind matrices, ordered like skeleton's joints. See `Skeleton::inverse_bind_matrices`.
+    ///
+    /// If set, every updated output matrix is multiplied by its joint inverse bind matrix, so the job directly
+    /// outputs skinning matrices instead of model-space matrices. The multiplication is done in the same pass
+    /// as the hierarchy traversal, model-space matrices being kept by the job for children. Parents of a
+    /// partial update ("from") are then read from the model-space matrices of the previous run.
+    #[inline]
+    pub fn set_inverse_bind(&mut self, inverse_bind: &[Mat4]) {
+        self.inverse_bind.clear();
+        self.inverse_bind.extend_from_slice(inverse_bind);
+    }
+
+    /// Clears inverse bind matrices of `LocalToModelJob`.
+    #[inline]
+    pub fn clear_inverse_bind(&mut self) {
+        self.inverse_bind.clear();
+    }
+
     /// Gets output of `LocalToModelJob`.
     #[inline]
     pub fn output(&self) -> Option<&O> {
@@ -211,6 +278,7 @@ where
 
             let mut ok = input.len() >= skeleton.num_soa_joints();
             ok &= output.len() >= skeleton.num_joints();
+            ok &= self.inverse_bind.is_empty() || self.inverse_bind.len() >= skeleton.num_joints();
             return Some(ok);
         })()
         .unwrap_or(false);
@@ -225,6 +293,7 @@ where
 
         let mut ok = input.len() >= skeleton.num_soa_joints();
         ok &= output.len() >= skeleton.num_joints();
+        ok &= self.inverse_bind.is_empty() || self.inverse_bind.len() >= skeleton.num_joints();
         if !ok {
             return Err(OzzError::InvalidJob);
         }
@@ -232,21 +301,42 @@ where
         let begin = i32::max(0, self.from + (self.from_excluded as i32)) as usize;
         let end = i32::max(0, i32::min(self.to + 1, skeleton.num_joints() as i32)) as usize;
 
+        let unit_scale = SoaVec3 { x: ONE, y: ONE, z: ONE };
+        let with_bind = !self.inverse_bind.is_empty();
+        if with_bind {
+            self.models.resize(skeleton.num_joints(), Mat4::IDENTITY);
+        }
+
         let mut idx = begin;
         let mut process = idx < end && (!self.from_excluded || skeleton.joint_parent(idx) as i32 >= self.from);
 
         while process {
             let transform = &input[idx / 4];
-            let soa_matrices = SoaMat4::from_affine(&transform.translation, &transform.rotation, &transform.scale);
+            let scale = if self.ignore_scale {
+                &unit_scale
+            } else {
+                &transform.scale
+            };
+            let soa_matrices = SoaMat4::from_affine(&transform.translation, &transform.rotation, scale);
             let aos_matrices = soa_matrices.to_aos();
 
             let soa_end = (idx + 4) & !3;
             while idx < soa_end && process {
                 let parent = skeleton.joint_parent(idx);
-                if parent as i32 == SKELETON_NO_PARENT {
-                    output[idx] = AosMat4::mul(&self.root, &aos_matrices[idx & 3]).into();
+                let model = if parent as i32 == SKELETON_NO_PARENT {
+                    AosMat4::mul(&self.root, &aos_matrices[idx & 3])
+                } else if with_bind {
+                    AosMat4::mul(&self.models[parent as usize].into(), &aos_matrices[idx & 3])
                 } else {
-                    output[idx] = AosMat4::mul(&output[parent as usize].into(), &aos_matrices[idx & 3]).into();
+                    AosMat4::mul(&output[parent as usize].into(), &aos_matrices[idx & 3])
+                };
+
+                // Children need their parent model-space matrix, kept by the job with inverse bind matrices.
+                if with_bind {
+                    self.models[idx] = model.into();
+                    output[idx] = AosMat4::mul(&model, &self.inverse_bind[idx].into()).into();
+                } else {
+                    output[idx] = model.into();
                 }
 
                 idx += 1;
@@ -256,16 +346,279 @@ where
 
         return Ok(());
     }
+
+    /// Runs local to model job's task incrementally, updating the subtrees of `dirty` joints only.
+    /// The validate job before any operation is performed.
+    ///
+    /// Output matrices of the joints neither marked nor descendant of a marked joint are left untouched, and
+    /// read as parents of the updated subtrees. So they must hold the model-space matrices of a previous
+    /// update. `from`/`to` range is ignored, see `DirtySet::dirty_ranges`.
+    ///
+    /// With inverse bind matrices, output matrices can't be used as parents anymore, the whole hierarchy is
+    /// updated like `run`. Returns `OzzError::InvalidJob` if `dirty` isn't sized for the skeleton.
+    pub fn run_dirty(&mut self, dirty: &DirtySet) -> Result<(), OzzError> {
+        if !self.inverse_bind.is_empty() {
+            return self.run();
+        }
+
+        let skeleton = self.skeleton.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        let input = self.input.as_ref().ok_or(OzzError::InvalidJob)?.buf()?;
+        let mut output = self.output.as_mut().ok_or(OzzError::InvalidJob)?.mut_buf()?;
+
+        let mut ok = input.len() >= skeleton.num_soa_joints();
+        ok &= output.len() >= skeleton.num_joints();
+        ok &= dirty.num_joints() == skeleton.num_joints();
+        if !ok {
+            return Err(OzzError::InvalidJob);
+        }
+
+        for (begin, end) in dirty.dirty_ranges(skeleton) {
+            let (prefix, rest) = output.split_at_mut(begin);
+            local_to_model_range(
+                skeleton,
+                &input,
+                &self.root,
+                self.ignore_scale,
+                prefix,
+                &mut rest[..end - begin],
+                begin,
+            );
+        }
+        return Ok(());
+    }
+
+    /// Runs local to model job's task, computing independent subtrees concurrently.
+    /// The validate job before any operation is performed.
+    ///
+    /// The skeleton is walked down from the root to the first joint with several children (the common
+    /// ancestor). Joints up to this ancestor are computed serially, then the child subtrees (or the roots of
+    /// a multi-root skeleton) are split into contiguous groups, one per available thread. Inverse bind
+    /// matrices are applied by each thread to its own joints.
+    ///
+    /// Partial updates (`from`/`to` set), skeletons of less than 1024 joints, skeletons without independent
+    /// subtrees and wasm32 targets (no threads) fall back to `run`. Requires the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn run_parallel(&mut self) -> Result<(), OzzError> {
+        let num_threads = if cfg!(target_arch = "wasm32") {
+            1
+        } else {
+            std::thread::available_parallelism().map_or(1, |x| x.get())
+        };
+        return self.run_threads(num_threads, PARALLEL_MIN_JOINTS);
+    }
+
+    // `run_parallel` with an explicit number of threads and joints threshold.
+    #[cfg(feature = "std")]
+    fn run_threads(&mut self, num_threads: usize, min_joints: usize) -> Result<(), OzzError> {
+        if self.from != SKELETON_NO_PARENT || self.to < SKELETON_MAX_JOINTS || self.from_excluded {
+            return self.run();
+        }
+        let groups = match self.skeleton.as_ref() {
+            Some(skeleton) if num_threads > 1 && skeleton.obj().num_joints() >= min_joints => {
+                subtree_groups(skeleton.obj(), num_threads)
+            }
+            _ => Vec::new(),
+        };
+        if groups.len() <= 1 {
+            return self.run();
+        }
+
+        let skeleton = self.skeleton.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        let input = self.input.as_ref().ok_or(OzzError::InvalidJob)?.buf()?;
+        let mut output = self.output.as_mut().ok_or(OzzError::InvalidJob)?.mut_buf()?;
+
+        let num_joints = skeleton.num_joints();
+        let mut ok = input.len() >= skeleton.num_soa_joints();
+        ok &= output.len() >= num_joints;
+        ok &= self.inverse_bind.is_empty() || self.inverse_bind.len() >= num_joints;
+        if !ok {
+            return Err(OzzError::InvalidJob);
+        }
+
+        // With inverse bind matrices, model-space matrices are kept by the job (see `run`).
+        let inverse_bind = &self.inverse_bind[..];
+        let (models, mut binds) = if inverse_bind.is_empty() {
+            (&mut output[..num_joints], None)
+        } else {
+            self.models.resize(num_joints, Mat4::IDENTITY);
+            (&mut self.models[..num_joints], Some(&mut output[..num_joints]))
+        };
+
+        let prefix_end = groups[0].0;
+        let (prefix, mut rest) = models.split_at_mut(prefix_end);
+        local_to_model_range(skeleton, &input, &self.root, self.ignore_scale, &[], prefix, 0);
+        if let Some(binds) = binds.as_mut() {
+            apply_inverse_bind(prefix, &inverse_bind[..prefix_end], &mut binds[..prefix_end]);
+        }
+        let mut binds = binds.map(|binds| &mut binds[prefix_end..]);
+
+        let prefix: &[Mat4] = prefix;
+        let (input, root, ignore_scale) = (&*input, &self.root, self.ignore_scale);
+        std::thread::scope(|scope| {
+            for (begin, end) in groups.iter().copied() {
+                let (range, next) = rest.split_at_mut(end - begin);
+                rest = next;
+                let bind_range = match binds.take() {
+                    Some(all) => {
+                        let (range, next) = all.split_at_mut(end - begin);
+                        binds = Some(next);
+                        Some(range)
+                    }
+                    None => None,
+                };
+                scope.spawn(move || {
+                    local_to_model_range(skeleton, input, root, ignore_scale, prefix, range, begin);
+                    if let Some(bind_range) = bind_range {
+                        apply_inverse_bind(range, &inverse_bind[begin..end], bind_range);
+                    }
+                });
+            }
+        });
+        return Ok(());
+    }
+}
+
+/// Computes model-space matrices of many instances of `skeleton`, like running a `LocalToModelJob` (with
+/// default root and no inverse bind matrices) per instance.
+///
+/// * `locals` - The local-space poses of the instances, ordered like skeleton's joints.
+/// * `out` - The model-space matrices of the instances, resized to the number of joints.
+///
+/// Instances are split among the available threads for large batches (except on wasm32). Returns
+/// `OzzError::InvalidJob` if `locals` and `out` lengths differ, or a pose is smaller than the skeleton.
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+pub fn batch_local_to_model(
+    skeleton: &Skeleton,
+    locals: &[&[SoaTransform]],
+    out: &mut [Vec<Mat4>],
+) -> Result<(), OzzError> {
+    if locals.len() != out.len() || locals.iter().any(|local| local.len() < skeleton.num_soa_joints()) {
+        return Err(OzzError::InvalidJob);
+    }
+
+    let root = AosMat4::identity();
+    let run = |locals: &[&[SoaTransform]], out: &mut [Vec<Mat4>]| {
+        for (local, output) in locals.iter().zip(out.iter_mut()) {
+            output.resize(skeleton.num_joints(), Mat4::IDENTITY);
+            local_to_model_range(skeleton, local, &root, false, &[], output, 0);
+        }
+    };
+
+    let num_threads = if cfg!(target_arch = "wasm32") {
+        1
+    } else {
+        std::thread::available_parallelism().map_or(1, |x| x.get())
+    };
+    // Spawning threads only pays for batches of several thousand joints.
+    let num_threads = num_threads.min(locals.len() * skeleton.num_joints() / BATCH_JOINTS_PER_THREAD);
+    if num_threads <= 1 {
+        run(locals, out);
+        return Ok(());
+    }
+
+    let chunk = locals.len().div_ceil(num_threads);
+    std::thread::scope(|scope| {
+        for (locals, out) in locals.chunks(chunk).zip(out.chunks_mut(chunk)) {
+            scope.spawn(move || run(locals, out));
+        }
+    });
+    return Ok(());
+}
+
+// Splits the joints following the common ancestor of `skeleton` (see `LocalToModelJob::run_parallel`) into
+// at most `num_groups` contiguous groups of whole subtrees, of about the same number of joints. Subtree ends
+// are computed once, in a single reverse pass.
+#[cfg(feature = "std")]
+fn subtree_groups(skeleton: &Skeleton, num_groups: usize) -> Vec<(usize, usize)> {
+    let num_joints = skeleton.num_joints();
+    let parents = skeleton.joint_parents();
+    let mut ends: Vec<usize> = (1..=num_joints).collect();
+    for idx in (0..num_joints).rev() {
+        if parents[idx] >= 0 {
+            let parent = parents[idx] as usize;
+            ends[parent] = ends[parent].max(ends[idx]);
+        }
+    }
+
+    // The joints before the common ancestor are a chain.
+    let mut prefix_end = 0;
+    if num_joints > 0 && ends[0] == num_joints {
+        let mut ancestor = 0;
+        while ancestor + 1 < num_joints
+            && parents[ancestor + 1] as usize == ancestor
+            && ends[ancestor + 1] == num_joints
+        {
+            ancestor += 1;
+        }
+        prefix_end = ancestor + 1;
+    }
+
+    let mut groups = Vec::new();
+    let target = (num_joints - prefix_end).div_ceil(num_groups.max(1));
+    let (mut begin, mut end) = (prefix_end, prefix_end);
+    while end < num_joints {
+        end = ends[end];
+        if end - begin >= target || end == num_joints {
+            groups.push((begin, end));
+            begin = end;
+        }
+    }
+    return groups;
+}
+
+// Writes `output[i] = models[i] * inverse_bind[i]`.
+#[cfg(feature = "std")]
+fn apply_inverse_bind(models: &[Mat4], inverse_bind: &[Mat4], output: &mut [Mat4]) {
+    for ((out, model), bind) in output.iter_mut().zip(models.iter()).zip(inverse_bind.iter()) {
+        *out = AosMat4::mul(&(*model).into(), &(*bind).into()).into();
+    }
+}
+
+// Computes model-space matrices of joints [begin, begin + output.len()). Parents before `begin` are read from
+// `prefix`, which holds model-space matrices of joints [0, begin).
+fn local_to_model_range(
+    skeleton: &Skeleton,
+    input: &[SoaTransform],
+    root: &AosMat4,
+    ignore_scale: bool,
+    prefix: &[Mat4],
+    output: &mut [Mat4],
+    begin: usize,
+) {
+    let unit_scale = SoaVec3 { x: ONE, y: ONE, z: ONE };
+
+    let mut aos_matrices = [AosMat4::identity(); 4];
+    for idx in begin..begin + output.len() {
+        if idx == begin || idx & 3 == 0 {
+            let transform = &input[idx / 4];
+            let scale = if ignore_scale { &unit_scale } else { &transform.scale };
+            let soa_matrices = SoaMat4::from_affine(&transform.translation, &transform.rotation, scale);
+            aos_matrices = soa_matrices.to_aos();
+        }
+
+        let parent = skeleton.joint_parent(idx);
+        let parent_matrix = if parent as i32 == SKELETON_NO_PARENT {
+            *root
+        } else if (parent as usize) < begin {
+            prefix[parent as usize].into()
+        } else {
+            output[parent as usize - begin].into()
+        };
+        output[idx - begin] = AosMat4::mul(&parent_matrix, &aos_matrices[idx & 3]).into();
+    }
 }
 
 #[cfg(test)]
 mod local_to_model_tests {
-    use glam::Vec3;
+    use glam::{Quat, Vec3};
     use wasm_bindgen_test::*;
 
     use super::*;
+    use crate::animation::Animation;
     use crate::base::DeterministicState;
     use crate::math::{SoaQuat, SoaVec3};
+    use crate::sampling_job::{SamplingContext, SamplingJob, SamplingJobRc};
     use crate::skeleton::JointHashMap;
 
     #[test]
@@ -662,4 +1015,293 @@ mod local_to_model_tests {
             Mat4::from_cols_array(&[1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0]),
         ], "from_to_exclude from=6 to=*");
     }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_to_untouched() {
+        let skeleton = new_skeleton2();
+        let input = new_input2();
+        let sentinel = Mat4::from_scale(Vec3::splat(3.0));
+
+        let output = Rc::new(RefCell::new(vec![sentinel; 8]));
+        let mut job = LocalToModelJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.set_input(input.clone());
+        job.set_output(output.clone());
+        job.set_from(3);
+        job.set_to(5);
+        job.run().unwrap();
+
+        let output = output.borrow();
+        for idx in [0, 1, 2, 6, 7] {
+            assert_eq!(output[idx], sentinel, "joint={}", idx);
+        }
+        for idx in [3, 4, 5] {
+            assert_ne!(output[idx], sentinel, "joint={}", idx);
+        }
+        let expected = sentinel * Mat4::from_translation(Vec3::new(1.0, 2.0, 4.0));
+        assert!(output[3].abs_diff_eq(expected, 2e-6));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_root_offset() {
+        let skeleton = new_skeleton2();
+        let input = new_input2();
+
+        let mut job = LocalToModelJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.set_input(input.clone());
+        assert_eq!(job.root(), Mat4::IDENTITY);
+
+        let model = Rc::new(RefCell::new(vec![Mat4::IDENTITY; 8]));
+        job.set_output(model.clone());
+        job.run().unwrap();
+
+        let root =
+            Mat4::from_rotation_translation(glam::Quat::from_axis_angle(Vec3::Y, 1.0), Vec3::new(-5.0, 10.0, 3.0));
+        let world = Rc::new(RefCell::new(vec![Mat4::IDENTITY; 8]));
+        job.set_root(&root);
+        job.set_output(world.clone());
+        job.run().unwrap();
+
+        for idx in 0..skeleton.num_joints() {
+            let expected = root * model.borrow()[idx];
+            let actual = world.borrow()[idx];
+            assert!(
+                actual.abs_diff_eq(expected, 2e-5),
+                "joint={} {} {}",
+                idx,
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_inverse_bind() {
+        let skeleton = Rc::new(Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap());
+        let input = Rc::new(RefCell::new(skeleton.joint_rest_poses().to_vec()));
+        input.borrow_mut()[1].translation = SoaVec3::splat_col([0.5, -1.0, 2.0]);
+        let inverse_bind = skeleton.inverse_bind_matrices().unwrap();
+        assert_eq!(inverse_bind.len(), skeleton.num_joints());
+
+        let mut job = LocalToModelJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.set_input(input.clone());
+
+        let model = Rc::new(RefCell::new(vec![Mat4::IDENTITY; skeleton.num_joints()]));
+        job.set_output(model.clone());
+        job.run().unwrap();
+
+        job.set_inverse_bind(&inverse_bind[..10]);
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        let skinning = Rc::new(RefCell::new(vec![Mat4::IDENTITY; skeleton.num_joints()]));
+        job.set_inverse_bind(&inverse_bind);
+        job.set_output(skinning.clone());
+        assert!(job.validate());
+        job.run().unwrap();
+
+        for idx in 0..skeleton.num_joints() {
+            let expected = model.borrow()[idx] * inverse_bind[idx];
+            let actual = skinning.borrow()[idx];
+            assert!(actual.abs_diff_eq(expected, 1e-4), "joint={}", idx);
+        }
+
+        // Partial updates read parents from the model-space matrices of the previous run.
+        skinning.borrow_mut()[10..skeleton.subtree_end(10)].fill(Mat4::ZERO);
+        job.set_from(10);
+        job.run().unwrap();
+        for idx in 0..skeleton.num_joints() {
+            let expected = model.borrow()[idx] * inverse_bind[idx];
+            let actual = skinning.borrow()[idx];
+            assert!(actual.abs_diff_eq(expected, 1e-4), "joint={}", idx);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_ignore_scale() {
+        let skeleton = new_skeleton1();
+        let input = new_input1();
+        let output = Rc::new(RefCell::new(vec![Mat4::IDENTITY; 6]));
+
+        let mut job = LocalToModelJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.set_input(input.clone());
+        job.set_output(output.clone());
+        job.set_ignore_scale(true);
+        job.run().unwrap();
+
+        for (idx, m) in output.borrow().iter().enumerate() {
+            for axis in [m.x_axis, m.y_axis, m.z_axis] {
+                assert!((axis.truncate().length() - 1.0).abs() < 1e-6, "joint={} {}", idx, m);
+            }
+        }
+        let expected = Mat4::from_translation(Vec3::new(12.0, 46.0, -12.0));
+        assert!(output.borrow()[4].abs_diff_eq(expected, 2e-6));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_run_dirty() {
+        let skeleton = Rc::new(Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap());
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+        let locals = Rc::new(RefCell::new(vec![SoaTransform::default(); skeleton.num_soa_joints()]));
+        let mut sampling_job: SamplingJobRc = SamplingJob::default();
+        sampling_job.set_context(SamplingContext::new(animation.num_tracks()));
+        sampling_job.set_animation(animation);
+        sampling_job.set_output(locals.clone());
+        sampling_job.set_ratio(0.4);
+        sampling_job.run().unwrap();
+
+        let output = Rc::new(RefCell::new(vec![Mat4::IDENTITY; skeleton.num_joints()]));
+        let mut job = LocalToModelJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.set_input(locals.clone());
+        job.set_output(output.clone());
+        job.run().unwrap();
+        let previous = output.borrow().clone();
+
+        // Bends the left arm, then updates its subtree only.
+        let left_arm = skeleton.joint_by_name("LeftArm").unwrap() as usize;
+        let end = skeleton.subtree_end(left_arm);
+        let mut transform = locals.borrow()[left_arm / 4].col(left_arm % 4);
+        transform.rotation = Quat::from_rotation_z(0.7) * transform.rotation;
+        locals.borrow_mut()[left_arm / 4].set_col(left_arm % 4, &transform);
+
+        let mut dirty = DirtySet::from_skeleton(&skeleton);
+        dirty.mark(left_arm);
+        job.run_dirty(&dirty).unwrap();
+        let incremental = output.borrow().clone();
+        job.run().unwrap();
+        for idx in 0..skeleton.num_joints() {
+            if (left_arm..end).contains(&idx) {
+                assert_eq!(incremental[idx], output.borrow()[idx], "joint={}", idx);
+                assert_ne!(incremental[idx], previous[idx], "joint={}", idx);
+            } else {
+                assert_eq!(incremental[idx], previous[idx], "joint={}", idx);
+            }
+        }
+
+        // Nothing marked, nothing updated.
+        output.borrow_mut().fill(Mat4::ZERO);
+        job.run_dirty(&DirtySet::from_skeleton(&skeleton)).unwrap();
+        assert!(output.borrow().iter().all(|m| *m == Mat4::ZERO));
+        assert!(matches!(job.run_dirty(&DirtySet::new(3)), Err(OzzError::InvalidJob)));
+    }
+
+    // Threads aren't available on wasm32 without atomics.
+    #[test]
+    fn test_run_parallel() {
+        let skeleton = Rc::new(Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap());
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+        let locals = Rc::new(RefCell::new(vec![SoaTransform::default(); skeleton.num_soa_joints()]));
+        let mut sampling_job: SamplingJobRc = SamplingJob::default();
+        sampling_job.set_context(SamplingContext::new(animation.num_tracks()));
+        sampling_job.set_animation(animation);
+        sampling_job.set_output(locals.clone());
+        sampling_job.set_ratio(0.4);
+        sampling_job.run().unwrap();
+
+        let cases = [
+            (skeleton.clone(), locals),
+            (new_skeleton1(), new_input1()),
+            // Multi-root skeleton.
+            (new_skeleton2(), new_input2()),
+        ];
+        for (skeleton, input) in cases {
+            let serial = Rc::new(RefCell::new(vec![Mat4::IDENTITY; skeleton.num_joints()]));
+            let parallel = Rc::new(RefCell::new(vec![Mat4::ZERO; skeleton.num_joints()]));
+            let mut job = LocalToModelJob::default();
+            job.set_skeleton(skeleton.clone());
+            job.set_input(input);
+            job.set_root(&Mat4::from_translation(Vec3::new(1.0, 2.0, 3.0)));
+            job.set_inverse_bind(&skeleton.inverse_bind_matrices().unwrap());
+            job.set_output(serial.clone());
+            job.run().unwrap();
+            for num_threads in [2, 3, 8] {
+                parallel.borrow_mut().fill(Mat4::ZERO);
+                job.set_output(parallel.clone());
+                job.run_threads(num_threads, 0).unwrap();
+                assert_eq!(*serial.borrow(), *parallel.borrow());
+            }
+            job.run_parallel().unwrap();
+            assert_eq!(*serial.borrow(), *parallel.borrow());
+
+            // Partial updates after a parallel run read the kept model-space matrices.
+            let end = skeleton.subtree_end(1);
+            parallel.borrow_mut()[1..end].fill(Mat4::ZERO);
+            job.set_from(1);
+            job.run().unwrap();
+            job.set_from(SKELETON_NO_PARENT);
+            assert_eq!(*serial.borrow(), *parallel.borrow());
+        }
+
+        let groups = subtree_groups(&skeleton, 4);
+        assert!(groups.len() <= 4 && groups.len() > 1);
+        assert_eq!(groups.last().unwrap().1, skeleton.num_joints());
+        for pair in groups.windows(2) {
+            assert_eq!(pair[0].1, pair[1].0);
+        }
+
+        // Partial updates fall back to the serial path.
+        let output = Rc::new(RefCell::new(vec![Mat4::ZERO; 6]));
+        let mut job = LocalToModelJob::default();
+        job.set_skeleton(new_skeleton1());
+        job.set_input(new_input1());
+        job.set_output(output.clone());
+        job.set_to(1);
+        job.run_parallel().unwrap();
+        assert_ne!(output.borrow()[1], Mat4::ZERO);
+        assert_eq!(output.borrow()[2], Mat4::ZERO);
+
+        job.set_input(Rc::new(RefCell::new(vec![])));
+        assert!(job.run_parallel().unwrap_err().is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_batch_local_to_model() {
+        let skeleton = Rc::new(Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap());
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+        let mut sampling_job: SamplingJob = SamplingJob::default();
+        sampling_job.set_context(SamplingContext::new(animation.num_tracks()));
+        sampling_job.set_animation(animation);
+
+        // Enough instances to be split among threads.
+        let poses: Vec<Vec<SoaTransform>> = (0..64)
+            .map(|idx| {
+                let locals = Rc::new(RefCell::new(vec![SoaTransform::default(); skeleton.num_soa_joints()]));
+                sampling_job.set_output(locals.clone());
+                sampling_job.set_ratio(idx as f32 / 64.0);
+                sampling_job.run().unwrap();
+                return locals.take();
+            })
+            .collect();
+        let locals: Vec<&[SoaTransform]> = poses.iter().map(|pose| pose.as_slice()).collect();
+        let mut batch = vec![Vec::new(); locals.len()];
+        batch_local_to_model(&skeleton, &locals, &mut batch).unwrap();
+
+        for (local, batch) in locals.iter().zip(batch.iter()) {
+            let mut expected = vec![Mat4::ZERO; skeleton.num_joints()];
+            let mut job: LocalToModelJobRef = LocalToModelJob::default();
+            job.set_skeleton(&skeleton);
+            job.set_input(local);
+            job.set_output(&mut expected);
+            job.run().unwrap();
+            assert_eq!(batch, &expected);
+        }
+
+        batch_local_to_model(&skeleton, &locals[..1], &mut batch[..1]).unwrap();
+        assert!(batch_local_to_model(&skeleton, &locals, &mut batch[1..])
+            .unwrap_err()
+            .is_invalid_job());
+        assert!(batch_local_to_model(&skeleton, &[&poses[0][1..]], &mut batch[..1])
+            .unwrap_err()
+            .is_invalid_job());
+    }
 }
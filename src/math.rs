@@ -6,15 +6,17 @@
 
 #![allow(dead_code)]
 
-use glam::{Mat4, Quat, Vec3, Vec3A, Vec4};
+use core::fmt::Debug;
+use core::mem;
+use core::simd::prelude::*;
+use glam::{Affine3A, Mat4, Quat, Vec3, Vec3A, Vec4};
 use static_assertions::const_assert_eq;
-use std::fmt::Debug;
-use std::io::Read;
-use std::mem;
-use std::simd::prelude::*;
-use std::simd::*;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
 
-use crate::archive::{Archive, ArchiveRead};
+#[cfg(feature = "std")]
+use crate::archive::{Archive, ArchiveRead, ArchiveWrite, ArchiveWriter};
+#[cfg(feature = "std")]
 use crate::base::OzzError;
 
 pub(crate) const ZERO: f32x4 = f32x4::from_array([0.0; 4]);
@@ -36,6 +38,128 @@ pub(crate) const QUAT_UNIT: f32x4 = f32x4::from_array([0.0, 0.0, 0.0, 1.0]);
 const SIGN: i32x4 = i32x4::from_array([core::i32::MIN; 4]);
 const SIGN_W: i32x4 = i32x4::from_array([0, 0, 0, core::i32::MIN]);
 
+//
+// Float math without std
+//
+
+/// SIMD float methods of `std`, implemented lane by lane with `libm` without the `std` feature.
+#[cfg(feature = "std")]
+pub(crate) use std::simd::StdFloat;
+
+/// SIMD float methods of `std`, implemented lane by lane with `libm` without the `std` feature.
+#[cfg(not(feature = "std"))]
+pub(crate) trait StdFloat {
+    fn sqrt(self) -> Self;
+    fn floor(self) -> Self;
+    fn ceil(self) -> Self;
+    fn round(self) -> Self;
+    fn trunc(self) -> Self;
+    fn mul_add(self, a: Self, b: Self) -> Self;
+}
+
+#[cfg(not(feature = "std"))]
+macro_rules! std_float {
+    ($type:ty) => {
+        impl StdFloat for $type {
+            #[inline]
+            fn sqrt(self) -> Self {
+                return <$type>::from_array(self.to_array().map(libm::sqrtf));
+            }
+
+            #[inline]
+            fn floor(self) -> Self {
+                return <$type>::from_array(self.to_array().map(libm::floorf));
+            }
+
+            #[inline]
+            fn ceil(self) -> Self {
+                return <$type>::from_array(self.to_array().map(libm::ceilf));
+            }
+
+            #[inline]
+            fn round(self) -> Self {
+                return <$type>::from_array(self.to_array().map(libm::roundf));
+            }
+
+            #[inline]
+            fn trunc(self) -> Self {
+                return <$type>::from_array(self.to_array().map(libm::truncf));
+            }
+
+            #[inline]
+            fn mul_add(self, a: Self, b: Self) -> Self {
+                let (a, b) = (a.to_array(), b.to_array());
+                let mut out = self.to_array();
+                for idx in 0..out.len() {
+                    out[idx] = libm::fmaf(out[idx], a[idx], b[idx]);
+                }
+                return <$type>::from_array(out);
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "std"))]
+std_float!(f32x4);
+#[cfg(not(feature = "std"))]
+std_float!(f32x8);
+
+/// `f32` methods of `std`, implemented with `libm` without the `std` feature.
+#[cfg(not(feature = "std"))]
+pub(crate) trait F32Ext {
+    fn sqrt(self) -> f32;
+    fn floor(self) -> f32;
+    fn ceil(self) -> f32;
+    fn round(self) -> f32;
+    fn trunc(self) -> f32;
+    fn acos(self) -> f32;
+    fn atan2(self, other: f32) -> f32;
+    fn exp(self) -> f32;
+}
+
+#[cfg(not(feature = "std"))]
+impl F32Ext for f32 {
+    #[inline]
+    fn sqrt(self) -> f32 {
+        return libm::sqrtf(self);
+    }
+
+    #[inline]
+    fn floor(self) -> f32 {
+        return libm::floorf(self);
+    }
+
+    #[inline]
+    fn ceil(self) -> f32 {
+        return libm::ceilf(self);
+    }
+
+    #[inline]
+    fn round(self) -> f32 {
+        return libm::roundf(self);
+    }
+
+    #[inline]
+    fn trunc(self) -> f32 {
+        return libm::truncf(self);
+    }
+
+    #[inline]
+    fn acos(self) -> f32 {
+        return libm::acosf(self);
+    }
+
+    #[inline]
+    fn atan2(self, other: f32) -> f32 {
+        return libm::atan2f(self, other);
+    }
+
+    #[inline]
+    fn exp(self) -> f32 {
+        return libm::expf(self);
+    }
+}
+
 //
 // SoaVec3
 //
@@ -481,6 +605,7 @@ pub struct SoaTransform {
     pub scale: SoaVec3,
 }
 
+#[cfg(feature = "std")]
 impl ArchiveRead<SoaTransform> for SoaTransform {
     fn read<R: Read>(archive: &mut Archive<R>) -> Result<SoaTransform, OzzError> {
         const COUNT: usize = mem::size_of::<SoaTransform>() / mem::size_of::<f32>();
@@ -492,6 +617,15 @@ impl ArchiveRead<SoaTransform> for SoaTransform {
     }
 }
 
+#[cfg(feature = "std")]
+impl ArchiveWrite<SoaTransform> for SoaTransform {
+    fn write<W: Write>(archive: &mut ArchiveWriter<W>, val: &SoaTransform) -> Result<(), OzzError> {
+        const COUNT: usize = mem::size_of::<SoaTransform>() / mem::size_of::<f32>();
+        let buffer: [f32; COUNT] = unsafe { mem::transmute(*val) };
+        return f32::write_slice(archive, &buffer);
+    }
+}
+
 impl SoaTransform {
     pub fn new(translation: SoaVec3, rotation: SoaQuat, scale: SoaVec3) -> SoaTransform {
         return SoaTransform {
@@ -500,6 +634,18 @@ impl SoaTransform {
             scale,
         };
     }
+
+    #[inline]
+    pub fn col(&self, idx: usize) -> AosTransform {
+        return AosTransform::new(self.translation.col(idx), self.rotation.col(idx), self.scale.col(idx));
+    }
+
+    #[inline]
+    pub fn set_col(&mut self, idx: usize, v: &AosTransform) {
+        self.translation.set_col(idx, v.translation);
+        self.rotation.set_col(idx, v.rotation);
+        self.scale.set_col(idx, v.scale);
+    }
 }
 
 #[cfg(feature = "rkyv")]
@@ -545,6 +691,142 @@ const _: () = {
     }
 };
 
+//
+// AosTransform
+//
+
+/// Array of Structures (AoS) for Transformations, a single translation, rotation and scale.
+///
+/// Math types of this crate are `glam` types, so ozz and `glam` share the same conventions: matrices are
+/// column-major (`Mat4::w_axis` holds the translation), vectors are column vectors transformed as `M * v`,
+/// and the coordinate system is right-handed, like ozz-animation runtime data.
+/// `AosTransform` converts from/into `glam::Mat4`, `glam::Affine3A` and `(scale, rotation, translation)`
+/// tuples, matching glam `from_scale_rotation_translation()` and `to_scale_rotation_translation()`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AosTransform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Default for AosTransform {
+    #[inline]
+    fn default() -> AosTransform {
+        return AosTransform::IDENTITY;
+    }
+}
+
+impl AosTransform {
+    pub const IDENTITY: AosTransform = AosTransform {
+        translation: Vec3::ZERO,
+        rotation: Quat::IDENTITY,
+        scale: Vec3::ONE,
+    };
+
+    #[inline]
+    pub fn new(translation: Vec3, rotation: Quat, scale: Vec3) -> AosTransform {
+        return AosTransform {
+            translation,
+            rotation,
+            scale,
+        };
+    }
+
+    #[inline]
+    pub fn from_translation_rotation(translation: Vec3, rotation: Quat) -> AosTransform {
+        return AosTransform::new(translation, rotation, Vec3::ONE);
+    }
+
+    /// Composes `self` with `other`, `other` being expressed in `self` space.
+    /// Shear introduced by non-uniform scales is dropped.
+    #[inline]
+    pub fn mul(&self, other: &AosTransform) -> AosTransform {
+        return AosTransform {
+            translation: self.transform_point(other.translation),
+            rotation: self.rotation * other.rotation,
+            scale: self.scale * other.scale,
+        };
+    }
+
+    /// Inverse transformation, exact for uniform scales.
+    #[inline]
+    pub fn inverse(&self) -> AosTransform {
+        let rotation = self.rotation.conjugate();
+        let scale = self.scale.recip();
+        return AosTransform {
+            translation: -(scale * (rotation * self.translation)),
+            rotation,
+            scale,
+        };
+    }
+
+    #[inline]
+    pub fn transform_point(&self, p: Vec3) -> Vec3 {
+        return self.rotation * (self.scale * p) + self.translation;
+    }
+
+    #[inline]
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        return self.rotation * (self.scale * v);
+    }
+
+    #[inline]
+    pub fn to_mat4(&self) -> Mat4 {
+        return Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation);
+    }
+}
+
+impl From<AosTransform> for Mat4 {
+    #[inline]
+    fn from(t: AosTransform) -> Mat4 {
+        return t.to_mat4();
+    }
+}
+
+impl From<Mat4> for AosTransform {
+    /// Decomposes an affine matrix, shear and projection are lost.
+    #[inline]
+    fn from(mat: Mat4) -> AosTransform {
+        let (scale, rotation, translation) = mat.to_scale_rotation_translation();
+        return AosTransform::new(translation, rotation, scale);
+    }
+}
+
+impl From<AosTransform> for Affine3A {
+    #[inline]
+    fn from(t: AosTransform) -> Affine3A {
+        return Affine3A::from_scale_rotation_translation(t.scale, t.rotation, t.translation);
+    }
+}
+
+impl From<Affine3A> for AosTransform {
+    /// Decomposes an affine transformation, shear is lost.
+    #[inline]
+    fn from(affine: Affine3A) -> AosTransform {
+        let (scale, rotation, translation) = affine.to_scale_rotation_translation();
+        return AosTransform::new(translation, rotation, scale);
+    }
+}
+
+impl From<AosTransform> for (Vec3, Quat, Vec3) {
+    /// Converts into a `(scale, rotation, translation)` tuple, glam order.
+    #[inline]
+    fn from(t: AosTransform) -> (Vec3, Quat, Vec3) {
+        return (t.scale, t.rotation, t.translation);
+    }
+}
+
+impl From<(Vec3, Quat, Vec3)> for AosTransform {
+    /// Converts from a `(scale, rotation, translation)` tuple, glam order.
+    #[inline]
+    fn from((scale, rotation, translation): (Vec3, Quat, Vec3)) -> AosTransform {
+        return AosTransform::new(translation, rotation, scale);
+    }
+}
+
 //
 // AosMat4
 //
@@ -729,7 +1011,7 @@ impl AosMat4 {
         let mut det = c0 * minor0;
         det = simd_swizzle!(det, I4E) + det;
         det = simd_swizzle!(det, IB1) + det; // first
-        let invertible: i32x4 = det.simd_ne(ZERO).to_int(); // first
+        let invertible: i32x4 = det.simd_ne(ZERO).to_simd(); // first
 
         let det_recip = det.recip(); // first
                                      // det_recip = (det_recip + det_recip) - det_recip * det_recip * det; // first
@@ -845,10 +1127,137 @@ impl SoaMat4 {
     }
 }
 
+//
+// ApproxEq
+//
+
+///
+/// Approximate equality of math types, component-wise within an absolute tolerance.
+///
+/// Two values are equal if the absolute difference of each component is less than or equal to `eps`.
+/// Soa types compare all lanes, slices compare elements pairwise and must have the same length.
+///
+pub trait ApproxEq<Rhs: ?Sized = Self> {
+    /// Tests if `self` and `other` are equal within tolerance `eps`.
+    fn approx_eq(&self, other: &Rhs, eps: f32) -> bool;
+}
+
+impl ApproxEq for Vec3 {
+    #[inline]
+    fn approx_eq(&self, other: &Vec3, eps: f32) -> bool {
+        return self.abs_diff_eq(*other, eps);
+    }
+}
+
+impl ApproxEq for Quat {
+    #[inline]
+    fn approx_eq(&self, other: &Quat, eps: f32) -> bool {
+        return self.abs_diff_eq(*other, eps);
+    }
+}
+
+impl ApproxEq for Mat4 {
+    #[inline]
+    fn approx_eq(&self, other: &Mat4, eps: f32) -> bool {
+        return self.abs_diff_eq(*other, eps);
+    }
+}
+
+impl ApproxEq for AosTransform {
+    #[inline]
+    fn approx_eq(&self, other: &AosTransform, eps: f32) -> bool {
+        return self.translation.approx_eq(&other.translation, eps)
+            && self.rotation.approx_eq(&other.rotation, eps)
+            && self.scale.approx_eq(&other.scale, eps);
+    }
+}
+
+#[inline]
+fn fx4_approx_eq(a: f32x4, b: f32x4, eps: f32) -> bool {
+    return (a - b).abs().simd_le(f32x4::splat(eps)).all();
+}
+
+impl ApproxEq for SoaVec3 {
+    #[inline]
+    fn approx_eq(&self, other: &SoaVec3, eps: f32) -> bool {
+        return fx4_approx_eq(self.x, other.x, eps)
+            && fx4_approx_eq(self.y, other.y, eps)
+            && fx4_approx_eq(self.z, other.z, eps);
+    }
+}
+
+impl ApproxEq for SoaQuat {
+    #[inline]
+    fn approx_eq(&self, other: &SoaQuat, eps: f32) -> bool {
+        return fx4_approx_eq(self.x, other.x, eps)
+            && fx4_approx_eq(self.y, other.y, eps)
+            && fx4_approx_eq(self.z, other.z, eps)
+            && fx4_approx_eq(self.w, other.w, eps);
+    }
+}
+
+impl ApproxEq for SoaTransform {
+    #[inline]
+    fn approx_eq(&self, other: &SoaTransform, eps: f32) -> bool {
+        return self.translation.approx_eq(&other.translation, eps)
+            && self.rotation.approx_eq(&other.rotation, eps)
+            && self.scale.approx_eq(&other.scale, eps);
+    }
+}
+
+impl<T: ApproxEq> ApproxEq for [T] {
+    #[inline]
+    fn approx_eq(&self, other: &[T], eps: f32) -> bool {
+        return self.len() == other.len() && self.iter().zip(other).all(|(a, b)| a.approx_eq(b, eps));
+    }
+}
+
 //
 // functions
 //
 
+const_assert_eq!(mem::size_of::<Mat4>(), 16 * mem::size_of::<f32>());
+const_assert_eq!(mem::size_of::<SoaVec3>(), 12 * mem::size_of::<f32>());
+const_assert_eq!(mem::size_of::<SoaQuat>(), 16 * mem::size_of::<f32>());
+const_assert_eq!(mem::size_of::<SoaTransform>(), 40 * mem::size_of::<f32>());
+const_assert_eq!(mem::size_of::<SoaMat4>(), 64 * mem::size_of::<f32>());
+
+// SoA types are `#[repr(C)]` and made of `f32x4` only, without padding (sizes are checked above). Any bit
+// pattern is a valid `f32x4`, so they are `Pod`. `AosTransform` has padding between its fields, it isn't.
+#[cfg(feature = "bytemuck")]
+mod bytemuck_impls {
+    use super::*;
+
+    unsafe impl bytemuck::Zeroable for SoaVec3 {}
+    unsafe impl bytemuck::Pod for SoaVec3 {}
+    unsafe impl bytemuck::Zeroable for SoaQuat {}
+    unsafe impl bytemuck::Pod for SoaQuat {}
+    unsafe impl bytemuck::Zeroable for SoaTransform {}
+    unsafe impl bytemuck::Pod for SoaTransform {}
+    unsafe impl bytemuck::Zeroable for SoaMat4 {}
+    unsafe impl bytemuck::Pod for SoaMat4 {}
+}
+
+/// Views matrices as a flat slice of 16 floats per matrix, in column-major order, without copying.
+///
+/// Useful to upload joint matrices to the GPU. With `bytemuck` feature (which enables glam `bytemuck`
+/// feature), `bytemuck::cast_slice(&matrices)` works too.
+/// Note that `AosTransform` has padding between its fields, so it can't be viewed as floats.
+#[inline]
+pub fn mat4_slice_as_f32(matrices: &[Mat4]) -> &[f32] {
+    // Mat4 is 16 contiguous f32 without padding, with an alignment greater or equal to f32.
+    return unsafe { core::slice::from_raw_parts(matrices.as_ptr() as *const f32, matrices.len() * 16) };
+}
+
+/// Views SoA transforms as a flat slice of 40 floats per `SoaTransform`, without copying.
+///
+/// Floats are ordered by component then by lane: translation x (4 lanes), y, z, rotation x, y, z, w,
+/// scale x, y, z. `SoaTransform` is `#[repr(C)]` and made of `f32x4` only, so it has no padding.
+#[inline]
+pub fn soa_transform_slice_as_f32(transforms: &[SoaTransform]) -> &[f32] {
+    return unsafe { core::slice::from_raw_parts(transforms.as_ptr() as *const f32, transforms.len() * 40) };
+}
+
 pub(crate) fn f16_to_f32(n: u16) -> f32 {
     let sign = (n & 0x8000) as u32;
     let expo = (n & 0x7C00) as u32;
@@ -872,6 +1281,31 @@ pub(crate) fn f16_to_f32(n: u16) -> f32 {
     };
 }
 
+pub(crate) fn f32_to_f16(f: f32) -> u16 {
+    const F32_INFTY: u32 = 255 << 23;
+    const F16_INFTY: u32 = 31 << 23;
+    const MAGIC: u32 = 15 << 23;
+    const SIGN_MASK: u32 = 0x80000000;
+    const ROUND_MASK: u32 = !0xFFF;
+
+    let bits = f.to_bits();
+    let sign = bits & SIGN_MASK;
+    let abs = bits ^ sign;
+    let half = if abs >= F32_INFTY {
+        // Inf or NaN (all exponent bits set).
+        if abs > F32_INFTY {
+            0x7E00
+        } else {
+            0x7C00
+        }
+    } else {
+        let scaled = f32::from_bits(abs & ROUND_MASK) * f32::from_bits(MAGIC);
+        let rounded = scaled.to_bits().wrapping_sub(ROUND_MASK).min(F16_INFTY);
+        rounded >> 13
+    };
+    return (half | (sign >> 16)) as u16;
+}
+
 pub(crate) fn simd_f16_to_f32(half4: [u16; 4]) -> f32x4 {
     const MASK_NO_SIGN: i32x4 = i32x4::from_array([0x7FFF; 4]);
     const MAGIC: f32x4 = fx4(i32x4::from_array([(254 - 15) << 23; 4]));
@@ -882,7 +1316,7 @@ pub(crate) fn simd_f16_to_f32(half4: [u16; 4]) -> f32x4 {
     let expmant = MASK_NO_SIGN & int4;
     let shifted = expmant << 13;
     let scaled = fx4(shifted) * MAGIC;
-    let was_infnan = i32x4::simd_ge(expmant, WAS_INFNAN).to_int();
+    let was_infnan = i32x4::simd_ge(expmant, WAS_INFNAN).to_simd();
     let sign = (int4 ^ expmant) << 16;
     let infnanexp = was_infnan & EXP_INFNAN;
     let sign_inf = sign | infnanexp;
@@ -996,7 +1430,7 @@ pub(crate) fn fx4_sign(v: f32x4) -> i32x4 {
     // In some case, x86_64 and aarch64 may produce different sign NaN (+/-NaN) in same command.
     // So the result of `NaN & SIGN` may be different.
     // For cross-platform deterministic, we want to make sure the sign of NaN is always 0(+).
-    return v.simd_lt(ZERO).to_int() & SIGN;
+    return v.simd_lt(ZERO).to_simd() & SIGN;
 }
 
 #[inline(always)]
@@ -1038,6 +1472,33 @@ pub(crate) fn f32_clamp_or_min(v: f32, min: f32, max: f32) -> f32 {
     return v.max(min).min(max);
 }
 
+// Canonical float bits, -0.0 becomes 0.0 and all NaN become `f32::NAN`.
+#[inline(always)]
+pub(crate) fn f32_canonicalize(v: f32) -> f32 {
+    if v.is_nan() {
+        return f32::NAN;
+    }
+    return if v == 0.0 { 0.0 } else { v };
+}
+
+#[inline(always)]
+pub(crate) fn fx4_canonicalize(v: f32x4) -> f32x4 {
+    let v = v.simd_eq(ZERO).select(ZERO, v);
+    return v.is_nan().select(f32x4::splat(f32::NAN), v);
+}
+
+// Canonical half float bits, like `f32_canonicalize`.
+#[inline(always)]
+pub(crate) fn f16_canonicalize(n: u16) -> u16 {
+    if n == 0x8000 {
+        return 0;
+    }
+    if n & 0x7C00 == 0x7C00 && n & 0x03FF != 0 {
+        return 0x7E00;
+    }
+    return n;
+}
+
 pub(crate) fn fx4_sin_cos(v: f32x4) -> (f32x4, f32x4) {
     // Implementation based on Vec4.inl from the JoltPhysics
     // https://github.com/jrouwe/JoltPhysics/blob/master/Jolt/Math/Vec4.inl
@@ -1289,7 +1750,7 @@ pub(crate) fn quat_mul(a: f32x4, b: f32x4) -> f32x4 {
 
 #[inline]
 pub(crate) fn quat_positive_w(q: f32x4) -> f32x4 {
-    let s = fx4_splat_w(q).simd_lt(ZERO).to_int() & SIGN;
+    let s = fx4_splat_w(q).simd_lt(ZERO).to_simd() & SIGN;
     return fx4_xor(q, s);
 }
 
@@ -1313,6 +1774,71 @@ mod tests {
         assert!(f16_to_f32(0xFFFF).is_nan());
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_slice_as_f32() {
+        let matrices = vec![
+            Mat4::from_translation(Vec3::new(1.0, 2.0, 3.0)),
+            Mat4::from_rotation_y(1.0),
+            Mat4::from_scale(Vec3::splat(2.0)),
+        ];
+        let floats = mat4_slice_as_f32(&matrices);
+        assert_eq!(floats.len(), 48);
+        assert_eq!(&floats[0..16], &matrices[0].to_cols_array());
+        assert_eq!(&floats[12..16], &[1.0, 2.0, 3.0, 1.0]);
+        assert_eq!(&floats[32..48], &matrices[2].to_cols_array());
+        assert!(mat4_slice_as_f32(&[]).is_empty());
+
+        let mut transforms = vec![SoaTransform::default(); 2];
+        let transform = AosTransform::new(Vec3::new(1.0, 2.0, 3.0), Quat::IDENTITY, Vec3::ONE);
+        transforms[1].set_col(1, &transform);
+        let floats = soa_transform_slice_as_f32(&transforms);
+        assert_eq!(floats.len(), 80);
+        assert_eq!([floats[41], floats[45], floats[49]], [1.0, 2.0, 3.0]);
+        assert_eq!([floats[65], floats[69], floats[73], floats[77]], [1.0; 4]);
+        assert_eq!([floats[64], floats[68]], [0.0; 2]);
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_bytemuck() {
+        let matrices = vec![
+            Mat4::from_translation(Vec3::new(1.0, 2.0, 3.0)),
+            Mat4::from_rotation_y(1.0),
+        ];
+        let floats = bytemuck::cast_slice::<Mat4, f32>(&matrices);
+        assert_eq!(floats.len(), 32);
+        assert_eq!(&floats[0..4], &[1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(&floats[12..16], &[1.0, 2.0, 3.0, 1.0]);
+        assert_eq!(floats, mat4_slice_as_f32(&matrices));
+
+        let mut transforms = vec![SoaTransform::default(); 2];
+        let transform = AosTransform::new(Vec3::new(1.0, 2.0, 3.0), Quat::IDENTITY, Vec3::ONE);
+        transforms[1].set_col(1, &transform);
+        let floats = bytemuck::cast_slice::<SoaTransform, f32>(&transforms);
+        assert_eq!(floats, soa_transform_slice_as_f32(&transforms));
+        let zeroed: SoaMat4 = bytemuck::Zeroable::zeroed();
+        assert_eq!(zeroed, SoaMat4::default());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_f32_to_f16() {
+        assert_eq!(f32_to_f16(1.0), 0b00111100_00000000);
+        assert_eq!(f32_to_f16(-1.0), 0b10111100_00000000);
+        assert_eq!(f32_to_f16(3.5), 0b01000011_00000000);
+        assert_eq!(f32_to_f16(f32::INFINITY), 0b01111100_00000000);
+        assert_eq!(f32_to_f16(f32::NEG_INFINITY), 0b11111100_00000000);
+        assert_eq!(f32_to_f16(1e10), 0b01111100_00000000);
+        assert_eq!(f32_to_f16(0.0), 0);
+        assert_eq!(f32_to_f16(-0.0), 0x8000);
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+        for v in [0.1f32, -0.25, 1.0471976, 123.456, -4096.5, 1e-4] {
+            assert!((f16_to_f32(f32_to_f16(v)) - v).abs() <= v.abs() * 1e-3);
+        }
+    }
+
     #[test]
     #[wasm_bindgen_test]
     fn test_simd_f16_to_f32() {
@@ -1384,6 +1910,63 @@ mod tests {
         );
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_aos_transform() {
+        let a = AosTransform::new(Vec3::new(1.0, 2.0, 3.0), Quat::from_rotation_y(0.7), Vec3::splat(2.0));
+        let b = AosTransform::new(Vec3::new(-1.0, 0.5, 4.0), Quat::from_rotation_x(-0.3), Vec3::splat(0.5));
+        let ab = a.mul(&b);
+        assert!(Mat4::from(ab).abs_diff_eq(a.to_mat4() * b.to_mat4(), 1e-5));
+
+        let p = Vec3::new(0.3, -0.2, 5.0);
+        assert!(ab
+            .transform_point(p)
+            .abs_diff_eq(a.transform_point(b.transform_point(p)), 1e-5));
+
+        let identity = a.mul(&a.inverse());
+        assert!(identity.translation.abs_diff_eq(Vec3::ZERO, 1e-5));
+        assert!(identity.rotation.abs_diff_eq(Quat::IDENTITY, 1e-6));
+        assert!(identity.scale.abs_diff_eq(Vec3::ONE, 1e-6));
+
+        let mut soa = SoaTransform::default();
+        soa.set_col(2, &a);
+        assert_eq!(soa.col(2), a);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_aos_transform_glam() {
+        let t = AosTransform::new(
+            Vec3::new(1.0, -2.0, 3.0),
+            Quat::from_euler(glam::EulerRot::YXZ, 0.7, -0.3, 1.2),
+            Vec3::new(2.0, 0.5, 1.5),
+        );
+
+        // Column-major, translation in w_axis, transforms column vectors.
+        let mat = Mat4::from(t);
+        assert!(mat.w_axis.abs_diff_eq(Vec4::new(1.0, -2.0, 3.0, 1.0), 1e-6));
+        let p = Vec3::new(0.3, -0.2, 5.0);
+        assert!(mat.transform_point3(p).abs_diff_eq(t.transform_point(p), 1e-5));
+        assert!(mat.transform_vector3(p).abs_diff_eq(t.transform_vector(p), 1e-5));
+
+        let from_mat = AosTransform::from(mat);
+        assert!(from_mat.translation.abs_diff_eq(t.translation, 1e-5));
+        assert!(from_mat.rotation.abs_diff_eq(t.rotation, 1e-5));
+        assert!(from_mat.scale.abs_diff_eq(t.scale, 1e-5));
+
+        let affine = Affine3A::from(t);
+        assert!(Mat4::from(affine).abs_diff_eq(mat, 1e-6));
+        assert!(affine.transform_point3(p).abs_diff_eq(t.transform_point(p), 1e-5));
+        let from_affine = AosTransform::from(affine);
+        assert!(from_affine.translation.abs_diff_eq(t.translation, 1e-5));
+        assert!(from_affine.rotation.abs_diff_eq(t.rotation, 1e-5));
+        assert!(from_affine.scale.abs_diff_eq(t.scale, 1e-5));
+
+        let srt: (Vec3, Quat, Vec3) = t.into();
+        assert!(Mat4::from_scale_rotation_translation(srt.0, srt.1, srt.2).abs_diff_eq(mat, 1e-6));
+        assert_eq!(AosTransform::from(srt), t);
+    }
+
     #[test]
     #[wasm_bindgen_test]
     fn test_sin_cos() {
@@ -1520,6 +2103,62 @@ mod tests {
         assert_eq!(transform_de, transform);
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_approx_eq() {
+        const EPS: f32 = 1e-3;
+        let (near, far) = (EPS * 0.5, EPS * 2.0);
+
+        let vec3 = Vec3::new(1.0, 2.0, 3.0);
+        assert!(vec3.approx_eq(&(vec3 + Vec3::splat(near)), EPS));
+        assert!(!vec3.approx_eq(&(vec3 + Vec3::new(0.0, far, 0.0)), EPS));
+
+        let quat = Quat::from_rotation_y(0.5);
+        let quat_near = Quat::from_xyzw(quat.x, quat.y + near, quat.z, quat.w);
+        let quat_far = Quat::from_xyzw(quat.x, quat.y, quat.z, quat.w - far);
+        assert!(quat.approx_eq(&quat_near, EPS));
+        assert!(!quat.approx_eq(&quat_far, EPS));
+
+        let mat = Mat4::from_rotation_translation(quat, vec3);
+        assert!(mat.approx_eq(&(mat + Mat4::from_cols_slice(&[near; 16])), EPS));
+        let mut mat_far = mat;
+        mat_far.w_axis.z += far;
+        assert!(!mat.approx_eq(&mat_far, EPS));
+
+        let transform = AosTransform::new(vec3, quat, Vec3::ONE);
+        let transform_near = AosTransform::new(vec3, quat_near, Vec3::splat(1.0 + near));
+        assert!(transform.approx_eq(&transform_near, EPS));
+        assert!(!transform.approx_eq(&AosTransform::new(vec3, quat, Vec3::splat(1.0 + far)), EPS));
+
+        let soa_vec3 = SoaVec3::splat_col([1.0, 2.0, 3.0]);
+        let mut soa_vec3_far = soa_vec3;
+        soa_vec3_far.y[2] += far;
+        assert!(soa_vec3.approx_eq(&soa_vec3.add(&SoaVec3::splat_col([near; 3])), EPS));
+        assert!(!soa_vec3.approx_eq(&soa_vec3_far, EPS));
+
+        let soa_quat = SoaQuat::splat_col([0.0, 0.0, 0.0, 1.0]);
+        let mut soa_quat_near = soa_quat;
+        soa_quat_near.w -= f32x4::splat(near);
+        let mut soa_quat_far = soa_quat;
+        soa_quat_far.x[3] = far;
+        assert!(soa_quat.approx_eq(&soa_quat_near, EPS));
+        assert!(!soa_quat.approx_eq(&soa_quat_far, EPS));
+
+        let soa_transform = SoaTransform::new(soa_vec3, soa_quat, SoaVec3::splat_col([1.0; 3]));
+        let mut soa_transform_near = soa_transform;
+        soa_transform_near.rotation = soa_quat_near;
+        let mut soa_transform_far = soa_transform;
+        soa_transform_far.translation = soa_vec3_far;
+        assert!(soa_transform.approx_eq(&soa_transform_near, EPS));
+        assert!(!soa_transform.approx_eq(&soa_transform_far, EPS));
+
+        assert!([soa_transform; 3].approx_eq(&[soa_transform_near; 3], EPS));
+        assert!(![soa_transform, soa_transform_far][..].approx_eq(&[soa_transform; 2], EPS));
+        assert!(![soa_transform; 2][..].approx_eq(&[soa_transform; 3], EPS));
+        assert!(vec![vec3, vec3].approx_eq(&vec![vec3, vec3 + Vec3::splat(near)], EPS));
+        assert!(![mat][..].approx_eq(&[mat_far], EPS));
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     #[wasm_bindgen_test]
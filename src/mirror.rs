@@ -0,0 +1,329 @@
+//!
+//! Animation mirroring.
+//!
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use glam::{Quat, Vec3};
+
+use crate::animation::{Animation, AnimationBuilder, JointTrack};
+use crate::base::OzzError;
+use crate::math::SoaTransform;
+use crate::skeleton::Skeleton;
+
+/// Axis normal to the mirror plane of `Animation::mirror` and `mirror_pose`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MirrorAxis {
+    /// Mirrors across the YZ plane.
+    #[default]
+    X,
+    /// Mirrors across the XZ plane.
+    Y,
+    /// Mirrors across the XY plane.
+    Z,
+}
+
+impl MirrorAxis {
+    /// Mirrors a translation across the plane.
+    #[inline]
+    pub fn mirror_translation(&self, value: Vec3) -> Vec3 {
+        return match self {
+            MirrorAxis::X => Vec3::new(-value.x, value.y, value.z),
+            MirrorAxis::Y => Vec3::new(value.x, -value.y, value.z),
+            MirrorAxis::Z => Vec3::new(value.x, value.y, -value.z),
+        };
+    }
+
+    /// Mirrors a rotation across the plane. The rotation axis component along the plane normal is kept,
+    /// the 2 others are negated.
+    #[inline]
+    pub fn mirror_rotation(&self, value: Quat) -> Quat {
+        return match self {
+            MirrorAxis::X => Quat::from_xyzw(value.x, -value.y, -value.z, value.w),
+            MirrorAxis::Y => Quat::from_xyzw(-value.x, value.y, -value.z, value.w),
+            MirrorAxis::Z => Quat::from_xyzw(-value.x, -value.y, value.z, value.w),
+        };
+    }
+}
+
+///
+/// Pairs of left/right joint names, for `Animation::mirror` and `mirror_pose`.
+///
+/// Joints not paired (like the spine) are mirrored onto themselves.
+///
+#[derive(Debug, Default, Clone)]
+pub struct BoneMirrorMap {
+    pairs: Vec<(String, String)>,
+}
+
+impl BoneMirrorMap {
+    /// Creates an empty `BoneMirrorMap`.
+    #[inline]
+    pub fn new() -> BoneMirrorMap {
+        return BoneMirrorMap::default();
+    }
+
+    /// Creates a `BoneMirrorMap` pairing every joint of `skeleton` whose name contains `left`, with the
+    /// joint named the same with `left` replaced by `right` (like "LeftArm" and "RightArm").
+    pub fn from_skeleton(skeleton: &Skeleton, left: &str, right: &str) -> BoneMirrorMap {
+        let mut map = BoneMirrorMap::new();
+        for idx in 0..skeleton.num_joints() {
+            let Some(name) = skeleton.name_by_joint(idx as i16) else {
+                continue;
+            };
+            if left.is_empty() || !name.contains(left) {
+                continue;
+            }
+            let other = name.replacen(left, right, 1);
+            if skeleton.joint_by_name(&other).is_some() {
+                map.add_pair(name, &other);
+            }
+        }
+        return map;
+    }
+
+    /// Adds a pair of left/right joint names.
+    #[inline]
+    pub fn add_pair(&mut self, left: &str, right: &str) -> &mut Self {
+        self.pairs.push((left.to_string(), right.to_string()));
+        return self;
+    }
+
+    /// Gets left/right joint name pairs of `BoneMirrorMap`.
+    #[inline]
+    pub fn pairs(&self) -> &[(String, String)] {
+        return &self.pairs;
+    }
+
+    /// Computes the mirrored joint of every joint of `skeleton`. Names missing from `skeleton` are
+    /// ignored.
+    pub fn joint_mapping(&self, skeleton: &Skeleton) -> Vec<usize> {
+        let mut mapping: Vec<usize> = (0..skeleton.num_joints()).collect();
+        for (left, right) in self.pairs.iter() {
+            if let (Some(l), Some(r)) = (skeleton.joint_by_name(left), skeleton.joint_by_name(right)) {
+                mapping[l as usize] = r as usize;
+                mapping[r as usize] = l as usize;
+            }
+        }
+        return mapping;
+    }
+}
+
+impl Animation {
+    /// Mirrors `Animation` across the plane normal to `axis`, generating a left-handed variant of a
+    /// right-handed clip for example.
+    ///
+    /// Keyframes of the joints paired in `mapping` are swapped, and every local translation and rotation
+    /// is mirrored across the plane. The mirror assumes a symmetric skeleton, whose paired joints have
+    /// mirrored rest poses. Tracks are ordered like `skeleton` joints.
+    ///
+    /// Returns `OzzError::InvalidKeyframe` if the mirrored keyframes can't be built, see `AnimationBuilder::build`.
+    pub fn mirror(
+        &self,
+        skeleton: &Skeleton,
+        mapping: &BoneMirrorMap,
+        axis: MirrorAxis,
+    ) -> Result<Animation, OzzError> {
+        let mut tracks = vec![JointTrack::default(); self.num_tracks()];
+        let duration = self.duration();
+        for key in self.translations().iter() {
+            if let Some(track) = tracks.get_mut(key.track as usize) {
+                let value = axis.mirror_translation(key.decompress());
+                track.translations.push((key.ratio * duration, value));
+            }
+        }
+        for key in self.rotations().iter() {
+            if let Some(track) = tracks.get_mut(key.track() as usize) {
+                let value = axis.mirror_rotation(key.decompress());
+                track.rotations.push((key.ratio * duration, value));
+            }
+        }
+        for key in self.scales().iter() {
+            if let Some(track) = tracks.get_mut(key.track as usize) {
+                track.scales.push((key.ratio * duration, key.decompress()));
+            }
+        }
+
+        let mut builder = AnimationBuilder::new(duration, self.num_tracks());
+        builder.set_name(self.name());
+        let joint_mapping = mapping.joint_mapping(skeleton);
+        for (idx, track) in tracks.into_iter().enumerate() {
+            let target = joint_mapping.get(idx).copied().filter(|t| *t < self.num_tracks());
+            builder.tracks_mut()[target.unwrap_or(idx)] = track;
+        }
+        return builder.build();
+    }
+}
+
+/// Mirrors a local-space pose across the plane normal to `axis`, like reusing a right hand grab for the left
+/// hand at runtime.
+///
+/// Transforms of the joints paired in `joint_mapping` are swapped, and every local translation and rotation is
+/// mirrored across the plane, like `Animation::mirror` does for a whole clip. Scales are swapped unchanged.
+///
+/// * `pose` - Local-space pose to mirror, ordered like skeleton's joints.
+/// * `joint_mapping` - Mirrored joint of every skeleton joint, computed once by `BoneMirrorMap::joint_mapping`.
+/// * `axis` - Normal of the mirror plane.
+/// * `out` - Output mirrored pose. Padding lanes of the last soa transform are left unchanged.
+///
+/// Returns `OzzError::BufferSize` if `pose` or `out` is smaller than the soa joints of `joint_mapping`, and
+/// `OzzError::InvalidIndex` if `joint_mapping` contains an out of range joint.
+pub fn mirror_pose(
+    pose: &[SoaTransform],
+    joint_mapping: &[usize],
+    axis: MirrorAxis,
+    out: &mut [SoaTransform],
+) -> Result<(), OzzError> {
+    let num_soa_joints = joint_mapping.len().div_ceil(4);
+    if pose.len() < num_soa_joints {
+        return Err(OzzError::BufferSize {
+            expected: num_soa_joints,
+            actual: pose.len(),
+        });
+    }
+    if out.len() < num_soa_joints {
+        return Err(OzzError::BufferSize {
+            expected: num_soa_joints,
+            actual: out.len(),
+        });
+    }
+    if joint_mapping.iter().any(|mirrored| *mirrored >= joint_mapping.len()) {
+        return Err(OzzError::InvalidIndex);
+    }
+    for (idx, mirrored) in joint_mapping.iter().copied().enumerate() {
+        let mut transform = pose[mirrored / 4].col(mirrored % 4);
+        transform.translation = axis.mirror_translation(transform.translation);
+        transform.rotation = axis.mirror_rotation(transform.rotation);
+        out[idx / 4].set_col(idx % 4, &transform);
+    }
+    return Ok(());
+}
+
+#[cfg(test)]
+mod mirror_tests {
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::math::{AosTransform, SoaTransform};
+    use crate::sampling_job::{SamplingContext, SamplingJob};
+
+    fn sample(animation: &Animation, ratio: f32) -> Vec<AosTransform> {
+        let mut job: SamplingJob<&Animation, Vec<SoaTransform>> = SamplingJob::default();
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_output(vec![SoaTransform::default(); animation.num_soa_tracks()]);
+        job.set_animation(animation);
+        job.set_ratio(ratio);
+        job.run().unwrap();
+        let output = job.output().unwrap();
+        return (0..animation.num_tracks())
+            .map(|idx| output[idx / 4].col(idx % 4))
+            .collect();
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_mirror_axis() {
+        let rotation = Quat::from_rotation_y(0.7) * Quat::from_rotation_x(0.3);
+        let point = Vec3::new(1.0, 2.0, 3.0);
+        assert_eq!(MirrorAxis::X.mirror_translation(point), Vec3::new(-1.0, 2.0, 3.0));
+        for axis in [MirrorAxis::X, MirrorAxis::Y, MirrorAxis::Z] {
+            let mirrored = axis.mirror_translation(point);
+            assert_eq!(axis.mirror_translation(mirrored), point);
+            assert_eq!(axis.mirror_rotation(axis.mirror_rotation(rotation)), rotation);
+
+            // Mirroring a rotated point is rotating the mirrored point by the mirrored rotation.
+            let expected = axis.mirror_translation(rotation * point);
+            let actual = axis.mirror_rotation(rotation) * mirrored;
+            assert!(actual.abs_diff_eq(expected, 1e-5), "{:?}", axis);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_mirror_animation() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let mapping = BoneMirrorMap::from_skeleton(&skeleton, "Left", "Right");
+        let left_arm = skeleton.joint_by_name("LeftArm").unwrap() as usize;
+        let right_arm = skeleton.joint_by_name("RightArm").unwrap() as usize;
+        let joint_mapping = mapping.joint_mapping(&skeleton);
+        assert_eq!(joint_mapping[left_arm], right_arm);
+        assert_eq!(joint_mapping[right_arm], left_arm);
+        assert_eq!(joint_mapping[0], 0);
+
+        let mirrored = animation.mirror(&skeleton, &mapping, MirrorAxis::X).unwrap();
+        assert_eq!(mirrored.duration(), animation.duration());
+        assert_eq!(mirrored.num_tracks(), animation.num_tracks());
+        assert_eq!(mirrored.name(), animation.name());
+        let twice = mirrored.mirror(&skeleton, &mapping, MirrorAxis::X).unwrap();
+
+        for ratio in [0.0, 0.3, 0.77, 1.0] {
+            let original = sample(&animation, ratio);
+            let once = sample(&mirrored, ratio);
+            let left = &once[left_arm];
+            let right = &original[right_arm];
+            assert!(left
+                .translation
+                .abs_diff_eq(MirrorAxis::X.mirror_translation(right.translation), 1e-6));
+            assert!(left
+                .rotation
+                .abs_diff_eq(MirrorAxis::X.mirror_rotation(right.rotation), 2e-4));
+
+            let restored = sample(&twice, ratio);
+            for (idx, (a, b)) in original.iter().zip(restored.iter()).enumerate() {
+                assert!(a.translation.abs_diff_eq(b.translation, 1e-6), "joint={}", idx);
+                assert!(a.rotation.abs_diff_eq(b.rotation, 2e-4), "joint={}", idx);
+                assert!(a.scale.abs_diff_eq(b.scale, 1e-6), "joint={}", idx);
+            }
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_mirror_pose() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let mapping = BoneMirrorMap::from_skeleton(&skeleton, "Left", "Right").joint_mapping(&skeleton);
+        let left_arm = skeleton.joint_by_name("LeftArm").unwrap() as usize;
+        let right_arm = skeleton.joint_by_name("RightArm").unwrap() as usize;
+
+        let mut pose = vec![SoaTransform::default(); skeleton.num_soa_joints()];
+        let mut job: SamplingJob<&Animation, &mut [SoaTransform]> = SamplingJob::default();
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_animation(&animation);
+        job.set_output(&mut pose);
+        job.set_ratio(0.4);
+        job.run().unwrap();
+
+        let mut once = vec![SoaTransform::default(); skeleton.num_soa_joints()];
+        mirror_pose(&pose, &mapping, MirrorAxis::X, &mut once).unwrap();
+        let left = once[left_arm / 4].col(left_arm % 4);
+        let right = pose[right_arm / 4].col(right_arm % 4);
+        assert_eq!(left.translation, MirrorAxis::X.mirror_translation(right.translation));
+        assert_eq!(left.rotation, MirrorAxis::X.mirror_rotation(right.rotation));
+        assert_eq!(left.scale, right.scale);
+
+        let mut twice = vec![SoaTransform::default(); skeleton.num_soa_joints()];
+        mirror_pose(&once, &mapping, MirrorAxis::X, &mut twice).unwrap();
+        for idx in 0..skeleton.num_joints() {
+            let (a, b) = (pose[idx / 4].col(idx % 4), twice[idx / 4].col(idx % 4));
+            assert!(a.translation.abs_diff_eq(b.translation, 1e-6), "joint={}", idx);
+            assert!(a.rotation.abs_diff_eq(b.rotation, 1e-6), "joint={}", idx);
+            assert!(a.scale.abs_diff_eq(b.scale, 1e-6), "joint={}", idx);
+        }
+
+        let err = mirror_pose(&pose[1..], &mapping, MirrorAxis::X, &mut twice).unwrap_err();
+        assert!(
+            matches!(err, OzzError::BufferSize { expected, actual } if expected == pose.len() && actual == pose.len() - 1)
+        );
+        let err = mirror_pose(&pose, &mapping, MirrorAxis::X, &mut twice[1..]).unwrap_err();
+        assert!(
+            matches!(err, OzzError::BufferSize { expected, actual } if expected == pose.len() && actual == pose.len() - 1)
+        );
+        let mut invalid = mapping.clone();
+        invalid[0] = mapping.len();
+        let err = mirror_pose(&pose, &invalid, MirrorAxis::X, &mut twice).unwrap_err();
+        assert!(matches!(err, OzzError::InvalidIndex));
+    }
+}
@@ -0,0 +1,209 @@
+//!
+//! Motion Blending Job.
+//!
+
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use glam::{Quat, Vec3};
+
+use crate::base::OzzError;
+use crate::math::AosTransform;
+
+/// Defines a layer of blending input data (motion delta and weight).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MotionBlendingLayer {
+    /// Motion delta of this layer, usually outputted from a `MotionExtractionJob`.
+    pub delta: AosTransform,
+
+    /// Blending weight of this layer. Negative values are considered as 0.
+    /// Normalization is performed during the blending stage so weight can be in
+    /// any range, even though range 0.0-1.0 is optimal.
+    /// It should be the same weight as the matching layer of the pose `BlendingJob`.
+    pub weight: f32,
+}
+
+impl MotionBlendingLayer {
+    pub fn new(delta: AosTransform, weight: f32) -> MotionBlendingLayer {
+        return MotionBlendingLayer { delta, weight };
+    }
+}
+
+///
+/// Motion blending job implementation.
+///
+/// Blends root motion deltas of several animations (clips of a blended locomotion), using the same
+/// weights as the pose `BlendingJob`, so that the character motion stays in sync with the blended pose.
+///
+/// Translations are the weighted average of the layer translations, rotations are blended with a
+/// normalized lerp. Scales are ignored, the output delta has a unit scale.
+///
+#[derive(Debug, Default)]
+pub struct MotionBlendingJob {
+    layers: Vec<MotionBlendingLayer>,
+    delta: AosTransform,
+}
+
+impl MotionBlendingJob {
+    /// Gets layers of `MotionBlendingJob`.
+    #[inline]
+    pub fn layers(&self) -> &[MotionBlendingLayer] {
+        return &self.layers;
+    }
+
+    /// Gets mutable layers of `MotionBlendingJob`.
+    ///
+    /// Job input layers, can be empty. The range of layers that must be blended.
+    #[inline]
+    pub fn layers_mut(&mut self) -> &mut Vec<MotionBlendingLayer> {
+        return &mut self.layers;
+    }
+
+    /// Gets **output** delta of `MotionBlendingJob`.
+    ///
+    /// Blended motion delta. It's an identity transform if no layer has a positive weight.
+    #[inline]
+    pub fn delta(&self) -> AosTransform {
+        return self.delta;
+    }
+
+    /// Clears delta of `MotionBlendingJob`.
+    #[inline]
+    pub fn clear_delta(&mut self) {
+        self.delta = AosTransform::IDENTITY;
+    }
+
+    /// Clears all outputs of `MotionBlendingJob`.
+    #[inline]
+    pub fn clear_outs(&mut self) {
+        self.clear_delta();
+    }
+
+    /// Validates `MotionBlendingJob` parameters.
+    #[inline]
+    pub fn validate(&self) -> bool {
+        return self.layers.iter().all(|layer| layer.weight.is_finite());
+    }
+
+    /// Runs motion blending job's task.
+    /// The validate job before any operation is performed.
+    pub fn run(&mut self) -> Result<(), OzzError> {
+        if !self.validate() {
+            return Err(OzzError::InvalidJob);
+        }
+
+        let mut acc_weight = 0.0;
+        let mut acc_translation = Vec3::ZERO;
+        let mut acc_rotation = Quat::from_xyzw(0.0, 0.0, 0.0, 0.0);
+        for layer in self.layers.iter() {
+            let weight = layer.weight.max(0.0);
+            if weight <= 0.0 {
+                continue;
+            }
+            acc_weight += weight;
+
+            acc_translation += layer.delta.translation * weight;
+
+            // Rotations are accumulated in the same hemisphere.
+            let rotation = layer.delta.rotation;
+            if acc_rotation.dot(rotation) < 0.0 {
+                acc_rotation = acc_rotation - rotation * weight;
+            } else {
+                acc_rotation = acc_rotation + rotation * weight;
+            }
+        }
+
+        if acc_weight <= 0.0 {
+            self.delta = AosTransform::IDENTITY;
+            return Ok(());
+        }
+
+        let translation = acc_translation / acc_weight;
+        let rotation = if acc_rotation.length_squared() > 0.0 {
+            acc_rotation.normalize()
+        } else {
+            Quat::IDENTITY
+        };
+        self.delta = AosTransform::from_translation_rotation(translation, rotation);
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod motion_blending_tests {
+    use std::rc::Rc;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::animation::Animation;
+    use crate::motion_extraction_job::{MotionExtractionJob, MotionExtractionJobRc};
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_validity() {
+        let mut job = MotionBlendingJob::default();
+        assert!(job.validate());
+        assert!(job.run().is_ok());
+        assert_eq!(job.delta(), AosTransform::IDENTITY);
+
+        job.layers_mut()
+            .push(MotionBlendingLayer::new(AosTransform::IDENTITY, f32::NAN));
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_weights() {
+        let forward = AosTransform::from_translation_rotation(Vec3::new(0.0, 0.0, 2.0), Quat::IDENTITY);
+        let left = AosTransform::from_translation_rotation(Vec3::new(2.0, 0.0, 0.0), Quat::from_rotation_y(1.0));
+
+        let mut job = MotionBlendingJob::default();
+        job.layers_mut().push(MotionBlendingLayer::new(forward, 1.0));
+        job.layers_mut().push(MotionBlendingLayer::new(left, 0.0));
+        job.run().unwrap();
+        assert!(job.delta().translation.abs_diff_eq(forward.translation, 1e-6));
+        assert!(job.delta().rotation.abs_diff_eq(Quat::IDENTITY, 1e-6));
+
+        // Weights are normalized.
+        job.layers_mut()[0].weight = 2.0;
+        job.layers_mut()[1].weight = 2.0;
+        job.run().unwrap();
+        assert!(job.delta().translation.abs_diff_eq(Vec3::new(1.0, 0.0, 1.0), 1e-5));
+        assert!(job.delta().rotation.abs_diff_eq(Quat::from_rotation_y(0.5), 1e-5));
+
+        // Negative weights are considered as 0.
+        job.layers_mut()[1].weight = -1.0;
+        job.run().unwrap();
+        assert!(job.delta().translation.abs_diff_eq(forward.translation, 1e-6));
+
+        job.layers_mut()[0].weight = 0.0;
+        job.run().unwrap();
+        assert_eq!(job.delta(), AosTransform::IDENTITY);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_blend_clips() {
+        let mut deltas = Vec::new();
+        for path in ["./resource/blend/animation1.ozz", "./resource/blend/animation3.ozz"] {
+            let animation = Rc::new(Animation::from_path(path).unwrap());
+            let mut job: MotionExtractionJobRc = MotionExtractionJob::default();
+            job.set_animation(animation);
+            job.set_from(0.2);
+            job.set_to(0.3);
+            job.run().unwrap();
+            deltas.push(job.delta());
+        }
+
+        let mut job = MotionBlendingJob::default();
+        job.layers_mut().push(MotionBlendingLayer::new(deltas[0], 0.5));
+        job.layers_mut().push(MotionBlendingLayer::new(deltas[1], 0.5));
+        job.run().unwrap();
+
+        // The blended delta is the weighted combination of clip deltas.
+        let expected = (deltas[0].translation + deltas[1].translation) * 0.5;
+        assert!(job.delta().translation.abs_diff_eq(expected, 1e-6));
+        let expected = deltas[0].rotation.lerp(deltas[1].rotation, 0.5);
+        assert!(job.delta().rotation.abs_diff_eq(expected, 1e-5));
+    }
+}
@@ -0,0 +1,514 @@
+//!
+//! Motion Extraction Job.
+//!
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt::Debug;
+use glam::{Quat, Vec3};
+#[cfg(feature = "std")]
+use std::sync::{Arc, RwLock};
+
+use crate::animation::Animation;
+use crate::base::{OzzError, OzzMutBuf, OzzObj};
+use crate::joint_mask::JointMask;
+#[cfg(not(feature = "std"))]
+use crate::math::F32Ext;
+use crate::math::{AosTransform, SoaTransform};
+use crate::sampling_job::{SamplingContext, SamplingJob, SamplingJobRef};
+
+///
+/// Motion extraction job implementation.
+///
+/// Extracts the motion (aka root motion) of a joint, as the delta transform accumulated between two
+/// ratios of the animation. The motion is the joint horizontal translation (x and z) and its yaw
+/// (rotation around y axis), the remaining of the joint transform (height, pitch and roll) stays in
+/// the pose. This allows locomotion systems to drive the character with game code, applying the delta
+/// to the character transform on each update.
+///
+/// The job can optionally remove the motion from a pose sampled at `to` ratio, so the motion joint
+/// stays in place while the character transform moves.
+///
+#[derive(Debug)]
+pub struct MotionExtractionJob<A = Rc<Animation>, O = Rc<RefCell<Vec<SoaTransform>>>>
+where
+    A: OzzObj<Animation>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    animation: Option<A>,
+    joint: usize,
+    from: f32,
+    to: f32,
+    output: Option<O>,
+
+    sampler: JointSampler,
+    delta: AosTransform,
+}
+
+pub type MotionExtractionJobRef<'t> = MotionExtractionJob<&'t Animation, &'t mut [SoaTransform]>;
+pub type MotionExtractionJobRc = MotionExtractionJob<Rc<Animation>, Rc<RefCell<Vec<SoaTransform>>>>;
+#[cfg(feature = "std")]
+pub type MotionExtractionJobArc = MotionExtractionJob<Arc<Animation>, Arc<RwLock<Vec<SoaTransform>>>>;
+
+impl<A, O> Default for MotionExtractionJob<A, O>
+where
+    A: OzzObj<Animation>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    fn default() -> MotionExtractionJob<A, O> {
+        return MotionExtractionJob {
+            animation: None,
+            joint: 0,
+            from: 0.0,
+            to: 0.0,
+            output: None,
+            sampler: JointSampler::default(),
+            delta: AosTransform::IDENTITY,
+        };
+    }
+}
+
+impl<A, O> MotionExtractionJob<A, O>
+where
+    A: OzzObj<Animation>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    /// Gets animation of `MotionExtractionJob`.
+    #[inline]
+    pub fn animation(&self) -> Option<&A> {
+        return self.animation.as_ref();
+    }
+
+    /// Sets animation of `MotionExtractionJob`.
+    ///
+    /// Animation to extract the motion from.
+    #[inline]
+    pub fn set_animation(&mut self, animation: A) {
+        self.animation = Some(animation);
+    }
+
+    /// Clears animation of `MotionExtractionJob`.
+    #[inline]
+    pub fn clear_animation(&mut self) {
+        self.animation = None;
+    }
+
+    /// Gets joint of `MotionExtractionJob`.
+    #[inline]
+    pub fn joint(&self) -> usize {
+        return self.joint;
+    }
+
+    /// Sets joint of `MotionExtractionJob`.
+    ///
+    /// Index of the motion joint, usually the root. It's expected to have no parent, so that its local
+    /// transform is also its model-space transform.
+    #[inline]
+    pub fn set_joint(&mut self, joint: usize) {
+        self.joint = joint;
+    }
+
+    /// Gets from of `MotionExtractionJob`.
+    #[inline]
+    pub fn from(&self) -> f32 {
+        return self.from;
+    }
+
+    /// Sets from of `MotionExtractionJob`.
+    ///
+    /// Previous ratio. 0 is the beginning of the animation, 1 is the end.
+    ///
+    /// `from` and `to` can be of any sign, any order, and any finite range. Crossing the loop boundary
+    /// accumulates the motion of the whole loop, once per loop. Non-finite ratios make the job invalid.
+    #[inline]
+    pub fn set_from(&mut self, from: f32) {
+        self.from = from;
+    }
+
+    /// Gets to of `MotionExtractionJob`.
+    #[inline]
+    pub fn to(&self) -> f32 {
+        return self.to;
+    }
+
+    /// Sets to of `MotionExtractionJob`.
+    ///
+    /// Current ratio. 0 is the beginning of the animation, 1 is the end.
+    ///
+    /// `from` and `to` can be of any sign, any order, and any finite range. Crossing the loop boundary
+    /// accumulates the motion of the whole loop, once per loop. Non-finite ratios make the job invalid.
+    #[inline]
+    pub fn set_to(&mut self, to: f32) {
+        self.to = to;
+    }
+
+    /// Gets output of `MotionExtractionJob`.
+    #[inline]
+    pub fn output(&self) -> Option<&O> {
+        return self.output.as_ref();
+    }
+
+    /// Sets output of `MotionExtractionJob`.
+    ///
+    /// Optional local-space pose, sampled at `to` ratio. If set, the motion is removed from the joint
+    /// transform during job execution.
+    #[inline]
+    pub fn set_output(&mut self, output: O) {
+        self.output = Some(output);
+    }
+
+    /// Clears output of `MotionExtractionJob`.
+    #[inline]
+    pub fn clear_output(&mut self) {
+        self.output = None;
+    }
+
+    /// Gets **output** delta of `MotionExtractionJob`.
+    ///
+    /// Motion accumulated from `from` to `to`, relative to the motion joint transform at `from`. Its axes
+    /// are the model space ones, considering the motion joint heading at ratio 0 as the reference.
+    #[inline]
+    pub fn delta(&self) -> AosTransform {
+        return self.delta;
+    }
+
+    /// Clears delta of `MotionExtractionJob`.
+    #[inline]
+    pub fn clear_delta(&mut self) {
+        self.delta = AosTransform::IDENTITY;
+    }
+
+    /// Clears all outputs of `MotionExtractionJob`.
+    #[inline]
+    pub fn clear_outs(&mut self) {
+        self.clear_delta();
+    }
+
+    /// Validates `MotionExtractionJob` parameters.
+    pub fn validate(&self) -> bool {
+        return (|| {
+            let animation = self.animation.as_ref()?.obj();
+            let mut ok = self.joint < animation.num_tracks();
+            ok &= self.from.is_finite() && self.to.is_finite();
+            if let Some(output) = self.output.as_ref() {
+                ok &= output.buf().ok()?.len() > self.joint / 4;
+            }
+            return Some(ok);
+        })()
+        .unwrap_or(false);
+    }
+
+    /// Runs motion extraction job's task.
+    /// The validate job before any operation is performed.
+    pub fn run(&mut self) -> Result<(), OzzError> {
+        if !self.validate() {
+            return Err(OzzError::InvalidJob);
+        }
+        let animation = self.animation.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        let sampler = &mut self.sampler;
+        sampler.prepare(animation, self.joint);
+
+        let motion_begin = sampler.motion(animation, self.joint, 0.0)?;
+        let motion_end = sampler.motion(animation, self.joint, 1.0)?;
+        let motion_loop = motion_begin.inverse().mul(&motion_end);
+
+        let motion_from = sampler.accumulated_motion(animation, self.joint, self.from, &motion_begin, &motion_loop)?;
+        let motion_to = sampler.accumulated_motion(animation, self.joint, self.to, &motion_begin, &motion_loop)?;
+        let delta = motion_from.inverse().mul(&motion_to);
+
+        // Expresses delta in model space, where the motion joint heading at ratio 0 is the reference.
+        let heading = AosTransform::from_translation_rotation(Vec3::ZERO, motion_begin.rotation);
+        self.delta = heading.mul(&delta).mul(&heading.inverse());
+
+        if let Some(output) = self.output.as_mut() {
+            let mut output = output.mut_buf()?;
+            let soa = &mut output[self.joint / 4];
+            let transform = soa.col(self.joint & 3);
+            let motion = extract(&transform);
+            soa.set_col(self.joint & 3, &motion.inverse().mul(&transform));
+        }
+        return Ok(());
+    }
+}
+
+// Transform composed `n` times with itself, by squaring so that the cost grows with the log of `n`.
+fn pow(transform: &AosTransform, mut n: u64) -> AosTransform {
+    let mut result = AosTransform::IDENTITY;
+    let mut base = *transform;
+    while n > 0 {
+        if n & 1 == 1 {
+            result = result.mul(&base);
+        }
+        base = base.mul(&base);
+        n >>= 1;
+    }
+    return result;
+}
+
+// Horizontal translation and yaw of a transform.
+fn extract(transform: &AosTransform) -> AosTransform {
+    let translation = Vec3::new(transform.translation.x, 0.0, transform.translation.z);
+    let twist = Quat::from_xyzw(0.0, transform.rotation.y, 0.0, transform.rotation.w);
+    let rotation = if twist.length_squared() > 1e-12 {
+        twist.normalize()
+    } else {
+        Quat::IDENTITY
+    };
+    return AosTransform::from_translation_rotation(translation, rotation);
+}
+
+// Samples the motion joint with a `SamplingJob`, through a partial context restricted to the motion joint.
+// The context and the scratch pose are kept between runs, so keyframes cursors are reused.
+#[derive(Debug, Default)]
+struct JointSampler {
+    context: Option<SamplingContext>,
+    pose: Vec<SoaTransform>,
+}
+
+impl JointSampler {
+    // Rebuilds the context if it can't sample `joint` of `animation`.
+    fn prepare(&mut self, animation: &Animation, joint: usize) {
+        let num_tracks = animation.num_tracks();
+        let reusable = self
+            .context
+            .as_ref()
+            .and_then(|ctx| ctx.joint_mask())
+            .is_some_and(|mask| {
+                return mask.num_joints() == num_tracks && mask.count() == 1 && mask.contains(joint);
+            });
+        if !reusable {
+            let mut mask = JointMask::new(num_tracks);
+            mask.set(joint, true);
+            self.context = Some(SamplingContext::for_joints(num_tracks, &mask));
+        }
+        self.pose.resize(animation.num_soa_tracks(), SoaTransform::default());
+    }
+
+    // Motion at any ratio, accounting for the loops from ratio 0, relative to motion at ratio 0.
+    fn accumulated_motion(
+        &mut self,
+        animation: &Animation,
+        joint: usize,
+        ratio: f32,
+        motion_begin: &AosTransform,
+        motion_loop: &AosTransform,
+    ) -> Result<AosTransform, OzzError> {
+        let loops = ratio.floor();
+        let mut frac = ratio - loops;
+        let mut loops = loops as i64;
+        if frac >= 1.0 {
+            frac = 0.0;
+            loops = loops.saturating_add(1);
+        }
+
+        let step = if loops >= 0 {
+            *motion_loop
+        } else {
+            motion_loop.inverse()
+        };
+        let motion = pow(&step, loops.unsigned_abs());
+        let partial = motion_begin.inverse().mul(&self.motion(animation, joint, frac)?);
+        return Ok(motion.mul(&partial));
+    }
+
+    // Motion of the joint sampled at a ratio in range 0.0-1.0.
+    fn motion(&mut self, animation: &Animation, joint: usize, ratio: f32) -> Result<AosTransform, OzzError> {
+        let mut job: SamplingJobRef = SamplingJob::default();
+        job.set_animation(animation);
+        job.set_context(self.context.take().ok_or(OzzError::InvalidJob)?);
+        job.set_output(&mut self.pose);
+        job.set_ratio(ratio);
+        let result = job.run();
+        self.context = job.take_context();
+        result?;
+        return Ok(extract(&self.pose[joint / 4].col(joint & 3)));
+    }
+}
+
+#[cfg(test)]
+mod motion_extraction_tests {
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::sampling_job::{SamplingContext, SamplingJob};
+
+    fn approx_eq(a: &AosTransform, b: &AosTransform) -> bool {
+        return a.translation.abs_diff_eq(b.translation, 1e-3)
+            && (a.rotation.abs_diff_eq(b.rotation, 1e-4) || a.rotation.abs_diff_eq(-b.rotation, 1e-4));
+    }
+
+    fn extract(job: &mut MotionExtractionJobRc, from: f32, to: f32) -> AosTransform {
+        job.set_from(from);
+        job.set_to(to);
+        job.run().unwrap();
+        return job.delta();
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_validity() {
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+
+        let mut job: MotionExtractionJobRc = MotionExtractionJob::default();
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        let mut job: MotionExtractionJobRc = MotionExtractionJob::default();
+        job.set_animation(animation.clone());
+        job.set_joint(animation.num_tracks());
+        assert!(!job.validate());
+
+        let mut job: MotionExtractionJobRc = MotionExtractionJob::default();
+        job.set_animation(animation.clone());
+        job.set_joint(5);
+        job.set_output(Rc::new(RefCell::new(vec![SoaTransform::default(); 1])));
+        assert!(!job.validate());
+
+        let mut job: MotionExtractionJobRc = MotionExtractionJob::default();
+        job.set_animation(animation.clone());
+        job.set_to(f32::INFINITY);
+        assert!(!job.validate());
+        job.set_to(0.5);
+        job.set_from(f32::NAN);
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        let mut job: MotionExtractionJobRc = MotionExtractionJob::default();
+        job.set_animation(animation.clone());
+        assert!(job.validate());
+        assert!(job.run().is_ok());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_full_loop() {
+        let animation = Rc::new(Animation::from_path("./resource/blend/animation1.ozz").unwrap());
+        let mut job: MotionExtractionJobRc = MotionExtractionJob::default();
+        job.set_animation(animation.clone());
+
+        // The clip moves forward, along z axis.
+        let total = extract(&mut job, 0.0, 1.0);
+        assert!(total.translation.abs_diff_eq(Vec3::new(0.0, 0.0, 1.4255), 1e-3));
+        assert!(approx_eq(
+            &total,
+            &AosTransform::from_translation_rotation(total.translation, Quat::IDENTITY)
+        ));
+
+        // Per frame deltas accumulate to the whole clip motion.
+        let mut accumulated = AosTransform::IDENTITY;
+        for i in 0..60 {
+            let delta = extract(&mut job, i as f32 / 60.0, (i + 1) as f32 / 60.0);
+            accumulated = accumulated.mul(&delta);
+        }
+        assert!(approx_eq(&accumulated, &total), "{:?} != {:?}", accumulated, total);
+
+        // Looping twice.
+        let twice = extract(&mut job, 0.0, 2.0);
+        assert!(approx_eq(&twice, &total.mul(&total)));
+
+        // Crossing the loop boundary.
+        let seam = extract(&mut job, 0.9, 1.1);
+        let expected = extract(&mut job, 0.9, 1.0).mul(&extract(&mut job, 0.0, 0.1));
+        assert!(approx_eq(&seam, &expected), "{:?} != {:?}", seam, expected);
+
+        // Backward is the inverse of forward.
+        let backward = extract(&mut job, 1.1, 0.9);
+        assert!(approx_eq(&backward, &seam.inverse()));
+
+        assert!(approx_eq(&extract(&mut job, 0.3, 0.3), &AosTransform::IDENTITY));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_huge_ratio() {
+        let animation = Rc::new(Animation::from_path("./resource/blend/animation1.ozz").unwrap());
+        let mut job: MotionExtractionJobRc = MotionExtractionJob::default();
+        job.set_animation(animation.clone());
+        let total = extract(&mut job, 0.0, 1.0);
+
+        // Composing the loops one by one agrees with the composition by squaring.
+        let mut expected = AosTransform::IDENTITY;
+        for _ in 0..100 {
+            expected = expected.mul(&total);
+        }
+        let hundred = extract(&mut job, 0.0, 100.0);
+        assert!(hundred
+            .translation
+            .abs_diff_eq(expected.translation, 1e-5 * expected.translation.length()));
+        assert!(approx_eq(
+            &AosTransform::from_translation_rotation(Vec3::ZERO, hundred.rotation),
+            &AosTransform::from_translation_rotation(Vec3::ZERO, expected.rotation)
+        ));
+        let backward = extract(&mut job, 100.0, 0.0);
+        assert!(backward
+            .translation
+            .abs_diff_eq(-expected.translation, 1e-5 * expected.translation.length()));
+
+        // Huge ratios return right away, a billion loops forward along z axis.
+        let huge = extract(&mut job, 0.0, 1e9);
+        assert!(huge.translation.is_finite() && huge.rotation.is_finite());
+        let loops = huge.translation.z / total.translation.z;
+        assert!((loops - 1e9).abs() < 1e9 * 1e-3, "{}", loops);
+        job.set_from(-1e9);
+        job.set_to(f32::MAX);
+        assert!(job.run().is_ok());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_matches_sampling() {
+        let animation = Rc::new(Animation::from_path("./resource/blend/animation1.ozz").unwrap());
+        let output = Rc::new(RefCell::new(vec![SoaTransform::default(); animation.num_soa_tracks()]));
+        let mut sampling: SamplingJob = SamplingJob::default();
+        sampling.set_animation(animation.clone());
+        sampling.set_context(SamplingContext::from_animation(&animation));
+        sampling.set_output(output.clone());
+        let mut sample = |ratio: f32| {
+            sampling.set_ratio(ratio);
+            sampling.run().unwrap();
+            return super::extract(&output.borrow()[0].col(0));
+        };
+        let begin = sample(0.0);
+        let heading = AosTransform::from_translation_rotation(Vec3::ZERO, begin.rotation);
+
+        let mut job: MotionExtractionJobRc = MotionExtractionJob::default();
+        job.set_animation(animation.clone());
+        for ratio in [0.1, 0.35, 0.5, 0.8, 1.0] {
+            let at = sample(ratio);
+            let expected = heading.mul(&begin.inverse().mul(&at)).mul(&heading.inverse());
+            let delta = extract(&mut job, 0.0, ratio);
+            assert!(approx_eq(&delta, &expected), "{:?} != {:?}", delta, expected);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_remove_motion() {
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+        let output = Rc::new(RefCell::new(vec![SoaTransform::default(); animation.num_soa_tracks()]));
+
+        let mut sampling: SamplingJob = SamplingJob::default();
+        sampling.set_animation(animation.clone());
+        sampling.set_context(SamplingContext::from_animation(&animation));
+        sampling.set_output(output.clone());
+        sampling.set_ratio(0.4);
+        sampling.run().unwrap();
+        let before = output.borrow()[0];
+
+        let mut job: MotionExtractionJobRc = MotionExtractionJob::default();
+        job.set_animation(animation.clone());
+        job.set_from(0.0);
+        job.set_to(0.4);
+        job.set_output(output.clone());
+        job.run().unwrap();
+
+        let after = output.borrow()[0];
+        assert!(after.col(0).translation.x.abs() < 1e-5);
+        assert!(after.col(0).translation.z.abs() < 1e-5);
+        assert!((after.col(0).translation.y - before.col(0).translation.y).abs() < 1e-5);
+        assert!(after.col(0).rotation.y.abs() < 1e-5);
+        for idx in 1..4 {
+            assert_eq!(after.col(idx), before.col(idx));
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//!
+//! Pose Cache.
+//!
+
+use alloc::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use crate::math::F32Ext;
+use crate::math::SoaTransform;
+
+///
+/// LRU cache of sampled poses, keyed by animation id and quantized time ratio.
+///
+/// Set to a `SamplingJob` (see `SamplingJob::set_pose_cache`), repeated samples of the same animation at
+/// the same time ratio (like a paused frame, or editor scrubbing) copy the cached pose instead of
+/// decompressing keyframes again.
+///
+/// Time ratios are quantized to `quantization` steps in the unit interval, ratios falling in the same
+/// step share the same cached pose.
+///
+/// Cached poses also depend on the interpolator and ratio remap of the job that sampled them, which aren't
+/// part of the key. `SamplingJob` clears its cache when they change, a cache moved to a job with other
+/// settings must be cleared by hand.
+///
+#[derive(Debug, Clone)]
+pub struct PoseCache {
+    capacity: usize,
+    quantization: u32,
+    // Least recently used first.
+    entries: Vec<(u64, u32, Vec<SoaTransform>)>,
+}
+
+impl PoseCache {
+    /// Default number of quantization steps of the time ratio.
+    pub const DEFAULT_QUANTIZATION: u32 = 1 << 16;
+
+    /// Creates a `PoseCache` storing up to `capacity` poses.
+    pub fn new(capacity: usize) -> PoseCache {
+        return PoseCache {
+            capacity,
+            quantization: Self::DEFAULT_QUANTIZATION,
+            entries: Vec::with_capacity(capacity),
+        };
+    }
+
+    /// Gets capacity of `PoseCache`.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        return self.capacity;
+    }
+
+    /// Sets capacity of `PoseCache`.
+    ///
+    /// Maximum number of cached poses. Least recently used poses are evicted. 0 disables the cache.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        if self.entries.len() > capacity {
+            self.entries.drain(..self.entries.len() - capacity);
+        }
+    }
+
+    /// Gets quantization of `PoseCache`.
+    #[inline]
+    pub fn quantization(&self) -> u32 {
+        return self.quantization;
+    }
+
+    /// Sets quantization of `PoseCache`.
+    ///
+    /// Number of steps the time ratio unit interval is quantized to, `DEFAULT_QUANTIZATION` by default.
+    /// Changing it clears the cache.
+    pub fn set_quantization(&mut self, quantization: u32) {
+        self.quantization = quantization.max(1);
+        self.entries.clear();
+    }
+
+    /// Gets the number of cached poses.
+    #[inline]
+    pub fn len(&self) -> usize {
+        return self.entries.len();
+    }
+
+    /// Tests if `PoseCache` is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        return self.entries.is_empty();
+    }
+
+    /// Clears all cached poses.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Gets the cached pose of `animation_id` at `ratio`, marking it as most recently used.
+    pub fn get(&mut self, animation_id: u64, ratio: f32) -> Option<&[SoaTransform]> {
+        let key = self.quantize(ratio);
+        let idx = self
+            .entries
+            .iter()
+            .position(|(id, ratio, _)| *id == animation_id && *ratio == key)?;
+        let entry = self.entries.remove(idx);
+        self.entries.push(entry);
+        return self.entries.last().map(|(_, _, pose)| pose.as_slice());
+    }
+
+    /// Caches `pose` of `animation_id` at `ratio`, evicting the least recently used pose if full.
+    pub fn insert(&mut self, animation_id: u64, ratio: f32, pose: &[SoaTransform]) {
+        if self.capacity == 0 {
+            return;
+        }
+        let key = self.quantize(ratio);
+        if let Some(idx) = self
+            .entries
+            .iter()
+            .position(|(id, ratio, _)| *id == animation_id && *ratio == key)
+        {
+            self.entries.remove(idx);
+        } else if self.entries.len() >= self.capacity {
+            self.entries.remove(0);
+        }
+        self.entries.push((animation_id, key, pose.to_vec()));
+    }
+
+    fn quantize(&self, ratio: f32) -> u32 {
+        return (ratio.clamp(0.0, 1.0) * self.quantization as f32).round() as u32;
+    }
+}
+
+#[cfg(test)]
+mod pose_cache_tests {
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::math::SoaVec3;
+
+    fn pose(x: f32) -> Vec<SoaTransform> {
+        let mut transform = SoaTransform::default();
+        transform.translation = SoaVec3::splat_col([x, 0.0, 0.0]);
+        return vec![transform];
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_lru() {
+        let mut cache = PoseCache::new(2);
+        assert!(cache.is_empty());
+        cache.insert(1, 0.5, &pose(1.0));
+        cache.insert(2, 0.5, &pose(2.0));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(1, 0.5).unwrap(), &pose(1.0)[..]);
+        assert!(cache.get(1, 0.6).is_none());
+
+        // Ratios are quantized.
+        assert!(cache.get(2, 0.5 + 1e-6).is_some());
+
+        // 2 is the most recently used, 1 is evicted.
+        cache.insert(3, 0.0, &pose(3.0));
+        assert!(cache.get(1, 0.5).is_none());
+        assert_eq!(cache.get(3, 0.0).unwrap(), &pose(3.0)[..]);
+
+        cache.insert(3, 0.0, &pose(4.0));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(3, 0.0).unwrap(), &pose(4.0)[..]);
+
+        cache.set_capacity(1);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get(3, 0.0).is_some());
+        cache.clear();
+        assert!(cache.is_empty());
+
+        cache.set_capacity(0);
+        cache.insert(1, 0.5, &pose(1.0));
+        assert!(cache.is_empty());
+    }
+}
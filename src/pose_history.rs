@@ -0,0 +1,237 @@
+//!
+//! Pose History.
+//!
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::simd::prelude::*;
+
+use crate::base::OzzError;
+use crate::math::{fx4_sign, SoaTransform, SoaVec3};
+
+///
+/// Ring buffer of the last sampled poses, with their timestamps.
+///
+/// For fixed-timestep simulation with interpolated rendering: the simulation pushes a pose per tick, and
+/// the rendering samples the pose at an arbitrary time between ticks. Translations and scales are lerped,
+/// rotations are nlerped along the shortest path, between the 2 poses bracketing the render time.
+///
+#[derive(Debug, Clone, Default)]
+pub struct PoseHistory {
+    capacity: usize,
+    // Oldest first.
+    entries: VecDeque<(f32, Vec<SoaTransform>)>,
+}
+
+impl PoseHistory {
+    /// Creates a `PoseHistory` storing up to `capacity` poses.
+    pub fn new(capacity: usize) -> PoseHistory {
+        return PoseHistory {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        };
+    }
+
+    /// Gets capacity of `PoseHistory`.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        return self.capacity;
+    }
+
+    /// Sets capacity of `PoseHistory`.
+    ///
+    /// Maximum number of stored poses. Oldest poses are dropped.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Gets the number of stored poses.
+    #[inline]
+    pub fn len(&self) -> usize {
+        return self.entries.len();
+    }
+
+    /// Tests if `PoseHistory` is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        return self.entries.is_empty();
+    }
+
+    /// Clears all stored poses.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Gets the `(oldest, newest)` timestamps of the stored poses.
+    #[inline]
+    pub fn time_range(&self) -> Option<(f32, f32)> {
+        return Some((self.entries.front()?.0, self.entries.back()?.0));
+    }
+
+    /// Stores `pose` at `time`, dropping the oldest pose if full.
+    ///
+    /// Times are expected to be ascending. Storing a pose not newer than the newest one clears the history
+    /// first (like after a time jump).
+    pub fn push(&mut self, time: f32, pose: &[SoaTransform]) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.back().is_some_and(|(newest, _)| time <= *newest) {
+            self.entries.clear();
+        }
+
+        // Reuses the oldest pose buffer.
+        let mut buf = if self.entries.len() >= self.capacity {
+            self.entries.pop_front().map(|(_, buf)| buf).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        buf.clear();
+        buf.extend_from_slice(pose);
+        self.entries.push_back((time, buf));
+    }
+
+    /// Gets the stored pose at `time`, if any.
+    pub fn get(&self, time: f32) -> Option<&[SoaTransform]> {
+        return (self.entries.iter())
+            .find(|(t, _)| *t == time)
+            .map(|(_, pose)| pose.as_slice());
+    }
+
+    /// Samples the pose at `time` into `output`, interpolating the 2 stored poses bracketing `time`.
+    ///
+    /// Times out of the stored range are clamped to the oldest or newest pose.
+    ///
+    /// Returns `OzzError::InvalidIndex` if the history is empty, or `output` is smaller than the stored
+    /// poses.
+    pub fn sample(&self, time: f32, output: &mut [SoaTransform]) -> Result<(), OzzError> {
+        let (first, last) = match (self.entries.front(), self.entries.back()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Err(OzzError::InvalidIndex),
+        };
+        if output.len() < last.1.len() {
+            return Err(OzzError::InvalidIndex);
+        }
+
+        let copy = |output: &mut [SoaTransform], pose: &[SoaTransform]| {
+            output[..pose.len()].copy_from_slice(pose);
+        };
+        if time <= first.0 {
+            copy(output, &first.1);
+            return Ok(());
+        }
+        if time >= last.0 {
+            copy(output, &last.1);
+            return Ok(());
+        }
+
+        let next = self.entries.partition_point(|(t, _)| *t <= time);
+        let (t0, from) = &self.entries[next - 1];
+        let (t1, to) = &self.entries[next];
+        if time == *t0 {
+            copy(output, from);
+            return Ok(());
+        }
+
+        let alpha = f32x4::splat((time - t0) / (t1 - t0));
+        for (out, (from, to)) in output.iter_mut().zip(from.iter().zip(to.iter())) {
+            // Shortest path.
+            let to_rotation = to.rotation.xor_num(fx4_sign(from.rotation.dot(&to.rotation)));
+            *out = SoaTransform {
+                translation: SoaVec3::lerp(&from.translation, &to.translation, alpha),
+                rotation: from.rotation.nlerp(&to_rotation, alpha),
+                scale: SoaVec3::lerp(&from.scale, &to.scale, alpha),
+            };
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod pose_history_tests {
+    use glam::{Quat, Vec3};
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::math::AosTransform;
+
+    fn pose(x: f32, angle: f32) -> Vec<SoaTransform> {
+        let mut pose = vec![SoaTransform::default(); 2];
+        for idx in 0..8 {
+            let transform = AosTransform::new(Vec3::new(x, idx as f32, 0.0), Quat::from_rotation_y(angle), Vec3::ONE);
+            pose[idx / 4].set_col(idx % 4, &transform);
+        }
+        return pose;
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_ring_buffer() {
+        let mut history = PoseHistory::new(3);
+        assert!(history.is_empty());
+        assert!(history.time_range().is_none());
+        let mut output = pose(0.0, 0.0);
+        assert!(matches!(history.sample(0.0, &mut output), Err(OzzError::InvalidIndex)));
+
+        for tick in 0..5 {
+            history.push(tick as f32 * 0.1, &pose(tick as f32, 0.0));
+        }
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.time_range(), Some((0.2, 0.4)));
+        assert!(history.get(0.1).is_none());
+        assert_eq!(history.get(0.3).unwrap(), &pose(3.0, 0.0)[..]);
+
+        // Time jump.
+        history.push(0.0, &pose(9.0, 0.0));
+        assert_eq!(history.len(), 1);
+
+        history.set_capacity(0);
+        assert!(history.is_empty());
+        history.push(1.0, &pose(1.0, 0.0));
+        assert!(history.is_empty());
+
+        let mut history = PoseHistory::new(2);
+        history.push(0.0, &pose(1.0, 0.0));
+        assert!(matches!(
+            history.sample(0.0, &mut output[..1]),
+            Err(OzzError::InvalidIndex)
+        ));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_sample() {
+        let mut history = PoseHistory::new(4);
+        let ticks = [pose(0.0, 0.0), pose(1.0, 0.5), pose(3.0, 1.5), pose(4.0, -3.0)];
+        for (idx, tick) in ticks.iter().enumerate() {
+            history.push(idx as f32 / 60.0, tick);
+        }
+
+        let mut output = pose(0.0, 0.0);
+        for (idx, tick) in ticks.iter().enumerate() {
+            history.sample(idx as f32 / 60.0, &mut output).unwrap();
+            assert_eq!(&output, tick);
+        }
+
+        history.sample(-1.0, &mut output).unwrap();
+        assert_eq!(&output, &ticks[0]);
+        history.sample(1.0, &mut output).unwrap();
+        assert_eq!(&output, &ticks[3]);
+
+        history.sample(1.5 / 60.0, &mut output).unwrap();
+        let transform = output[1].col(2);
+        assert!(transform.translation.abs_diff_eq(Vec3::new(2.0, 6.0, 0.0), 1e-5));
+        assert!(transform.rotation.abs_diff_eq(Quat::from_rotation_y(1.0), 1e-5));
+        assert!(transform.scale.abs_diff_eq(Vec3::ONE, 1e-6));
+
+        // Shortest path, from 1.5 to -3.0 through PI.
+        history.sample(2.5 / 60.0, &mut output).unwrap();
+        let rotation = output[0].col(0).rotation;
+        let expected = Quat::from_rotation_y(1.5).slerp(Quat::from_rotation_y(-3.0), 0.5);
+        assert!(rotation.dot(expected).abs() > 1.0 - 1e-5);
+    }
+}
@@ -0,0 +1,1038 @@
+//!
+//! Pose utils, helpers working on model-space joint matrices.
+//!
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::simd::prelude::*;
+use glam::{Mat4, Vec3};
+
+use crate::base::OzzError;
+#[cfg(not(feature = "std"))]
+use crate::math::F32Ext;
+use crate::math::{AosTransform, SoaTransform};
+use crate::skeleton::Skeleton;
+
+///
+/// Axis aligned bounding box.
+///
+/// A box is valid if `min <= max` on every axis, an empty box is invalid.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Default for Aabb {
+    fn default() -> Aabb {
+        return Aabb::empty();
+    }
+}
+
+impl Aabb {
+    /// Creates a box from its min and max corners.
+    #[inline]
+    pub fn new(min: Vec3, max: Vec3) -> Aabb {
+        return Aabb { min, max };
+    }
+
+    /// Creates an empty (invalid) box, that can be extended with points.
+    #[inline]
+    pub fn empty() -> Aabb {
+        return Aabb {
+            min: Vec3::splat(f32::MAX),
+            max: Vec3::splat(-f32::MAX),
+        };
+    }
+
+    /// Tests whether the box is valid (`min <= max` on every axis).
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        return self.min.cmple(self.max).all();
+    }
+
+    /// Tests whether `point` is inside the box (bounds included).
+    #[inline]
+    pub fn contains(&self, point: Vec3) -> bool {
+        return self.min.cmple(point).all() && point.cmple(self.max).all();
+    }
+
+    /// Extends the box to include a sphere of center `point` and `radius`.
+    #[inline]
+    pub fn extend(&mut self, point: Vec3, radius: f32) {
+        self.min = self.min.min(point - Vec3::splat(radius));
+        self.max = self.max.max(point + Vec3::splat(radius));
+    }
+}
+
+/// Computes the bounding box of a pose, from its model-space matrices (the output of `LocalToModelJob`).
+///
+/// * `model_matrices` - Model-space matrices of the joints.
+/// * `joint_radii` - Optional per-joint radius, used to inflate the box around each joint origin.
+///   Joints without a radius (out of `joint_radii` range) are considered as points.
+///
+/// Returns an empty (invalid) box if `model_matrices` is empty.
+pub fn compute_pose_aabb(model_matrices: &[Mat4], joint_radii: Option<&[f32]>) -> Aabb {
+    let mut aabb = Aabb::empty();
+    for (idx, matrix) in model_matrices.iter().enumerate() {
+        let radius = joint_radii.and_then(|radii| radii.get(idx)).copied().unwrap_or(0.0);
+        aabb.extend(matrix.w_axis.truncate(), radius);
+    }
+    return aabb;
+}
+
+/// Converts a local-space pose to glTF node transforms.
+///
+/// Returns one `(name, local transform, parent index)` entry per joint, ordered like skeleton's joints, so
+/// that parents always come before their children. The parent index of root joints is -1.
+///
+/// * `skeleton` - Skeleton giving joint names and hierarchy.
+/// * `local_pose` - Local-space SoA transforms of the joints, like `SamplingJob` or `BlendingJob` outputs.
+///
+/// Returns `OzzError::InvalidIndex` if `local_pose` is smaller than skeleton's soa joints.
+pub fn local_pose_to_gltf_nodes(
+    skeleton: &Skeleton,
+    local_pose: &[SoaTransform],
+) -> Result<Vec<(String, AosTransform, i16)>, OzzError> {
+    if local_pose.len() < skeleton.num_soa_joints() {
+        return Err(OzzError::InvalidIndex);
+    }
+
+    let mut nodes = Vec::with_capacity(skeleton.num_joints());
+    for idx in 0..skeleton.num_joints() {
+        let name = skeleton.name_by_joint(idx as i16).unwrap_or_default().to_string();
+        let transform = local_pose[idx / 4].col(idx % 4);
+        nodes.push((name, transform, skeleton.joint_parent(idx)));
+    }
+    return Ok(nodes);
+}
+
+/// Converts a model-space pose to glTF node transforms.
+///
+/// Local transforms are recovered from model-space matrices and the joint hierarchy. Shear introduced by
+/// non-uniform scales is lost, and mirrored (negative) scales may be decomposed to a different but
+/// equivalent scale and rotation.
+///
+/// * `skeleton` - Skeleton giving joint names and hierarchy.
+/// * `model_matrices` - Model-space matrices of the joints, like the output of `LocalToModelJob`.
+///
+/// Returns `OzzError::InvalidIndex` if `model_matrices` is smaller than skeleton's joints.
+pub fn model_pose_to_gltf_nodes(
+    skeleton: &Skeleton,
+    model_matrices: &[Mat4],
+) -> Result<Vec<(String, AosTransform, i16)>, OzzError> {
+    if model_matrices.len() < skeleton.num_joints() {
+        return Err(OzzError::InvalidIndex);
+    }
+
+    let mut nodes = Vec::with_capacity(skeleton.num_joints());
+    for (idx, matrix) in model_matrices.iter().take(skeleton.num_joints()).enumerate() {
+        let name = skeleton.name_by_joint(idx as i16).unwrap_or_default().to_string();
+        let parent = skeleton.joint_parent(idx);
+        let local = if parent < 0 {
+            *matrix
+        } else {
+            model_matrices[parent as usize].inverse() * *matrix
+        };
+        nodes.push((name, AosTransform::from(local), parent));
+    }
+    return Ok(nodes);
+}
+
+/// Converts model-space matrices back to a local-space pose, the inverse of `LocalToModelJob`.
+///
+/// Each joint local transform is `parent_model^-1 * joint_model`, decomposed to translation, rotation and
+/// scale. Root joints are relative to the identity. Like `model_pose_to_gltf_nodes`, shear introduced by
+/// non-uniform scales is lost. Used to import baked model-space (world-space) animations.
+///
+/// * `skeleton` - Skeleton giving the joint hierarchy.
+/// * `model_matrices` - Model-space matrices of the joints, like the output of `LocalToModelJob`.
+/// * `out` - Output local-space pose. Padding lanes of the last soa transform are left unchanged.
+///
+/// Returns `OzzError::BufferSize` if `model_matrices` is smaller than skeleton's joints, or `out` than
+/// skeleton's soa joints.
+pub fn model_to_local(skeleton: &Skeleton, model_matrices: &[Mat4], out: &mut [SoaTransform]) -> Result<(), OzzError> {
+    if model_matrices.len() < skeleton.num_joints() {
+        return Err(OzzError::BufferSize {
+            expected: skeleton.num_joints(),
+            actual: model_matrices.len(),
+        });
+    }
+    if out.len() < skeleton.num_soa_joints() {
+        return Err(OzzError::BufferSize {
+            expected: skeleton.num_soa_joints(),
+            actual: out.len(),
+        });
+    }
+
+    for (idx, matrix) in model_matrices.iter().take(skeleton.num_joints()).enumerate() {
+        let parent = skeleton.joint_parent(idx);
+        let local = if parent < 0 {
+            *matrix
+        } else {
+            model_matrices[parent as usize].inverse() * *matrix
+        };
+        out[idx / 4].set_col(idx % 4, &AosTransform::from(local));
+    }
+    return Ok(());
+}
+
+/// Computes per-joint linear and angular velocities between 2 poses.
+///
+/// Returns one `(linear velocity, angular velocity)` entry per joint. The linear velocity is the
+/// translation delta over `dt`, the angular velocity is the rotation delta (`curr * prev^-1`) as an
+/// axis-angle vector over `dt`, taking the shortest path. Poses are usually both local-space or both
+/// model-space transforms, velocities are expressed in the same space.
+///
+/// * `prev` - Transforms of the previous pose.
+/// * `curr` - Transforms of the current pose, ordered like `prev`. Extra joints of the longest pose are
+///   ignored.
+/// * `dt` - Elapsed time between the 2 poses in seconds. Velocities are zero if `dt` is 0.
+pub fn pose_velocities(prev: &[AosTransform], curr: &[AosTransform], dt: f32) -> Vec<(Vec3, Vec3)> {
+    if dt == 0.0 {
+        return vec![(Vec3::ZERO, Vec3::ZERO); prev.len().min(curr.len())];
+    }
+
+    let inv_dt = 1.0 / dt;
+    return (prev.iter().zip(curr.iter()))
+        .map(|(prev, curr)| {
+            let linear = (curr.translation - prev.translation) * inv_dt;
+            let mut delta = curr.rotation * prev.rotation.conjugate();
+            if delta.w < 0.0 {
+                delta = -delta;
+            }
+            let (axis, angle) = delta.normalize().to_axis_angle();
+            return (linear, axis * angle * inv_dt);
+        })
+        .collect();
+}
+
+/// Computes the additive delta from `reference` to `current` poses, per joint.
+///
+/// The delta is usable as an additive blending layer input (see `BlendingJob::additive_layers_mut`):
+/// additively applied onto `reference` with a weight of 1, it gives back `current`. Translations are
+/// subtracted, rotations are `current * reference^-1`, and scales are divided (components of a zero
+/// reference scale give a unit delta scale).
+///
+/// * `reference` - Transforms of the reference pose.
+/// * `current` - Transforms of the current pose, ordered like `reference`.
+/// * `out` - Output delta transforms. Only joints present in the 3 slices are written.
+pub fn make_additive_pose(reference: &[AosTransform], current: &[AosTransform], out: &mut [AosTransform]) {
+    for ((reference, current), out) in reference.iter().zip(current.iter()).zip(out.iter_mut()) {
+        let scale = Vec3::select(
+            reference.scale.cmpeq(Vec3::ZERO),
+            Vec3::ONE,
+            current.scale / reference.scale,
+        );
+        *out = AosTransform {
+            translation: current.translation - reference.translation,
+            rotation: (current.rotation * reference.rotation.conjugate()).normalize(),
+            scale,
+        };
+    }
+}
+
+/// Computes the model-space transform of an attachment (socket), for a weapon or accessory attached to a
+/// joint with an offset.
+///
+/// * `model_matrices` - Model-space matrices of the joints, like the output of `LocalToModelJob`.
+/// * `joint` - Index of the joint the attachment is attached to.
+/// * `local_offset` - Offset of the attachment, in joint local-space.
+///
+/// Panics if `joint` is out of `model_matrices` range.
+#[inline]
+pub fn attachment_matrix(model_matrices: &[Mat4], joint: i16, local_offset: &AosTransform) -> Mat4 {
+    return model_matrices[joint as usize] * Mat4::from(*local_offset);
+}
+
+/// Extracts the model-space positions of the joints, the translation of their model matrices.
+///
+/// * `model_matrices` - Model-space matrices of the joints, like the output of `LocalToModelJob`.
+pub fn joint_positions(model_matrices: &[Mat4]) -> Vec<Vec3> {
+    return model_matrices.iter().map(|m| m.w_axis.truncate()).collect();
+}
+
+/// Extracts the model-space position of `joint`, the translation of its model matrix.
+///
+/// Returns `None` if `joint` is out of `model_matrices` range.
+#[inline]
+pub fn joint_position(model_matrices: &[Mat4], joint: i16) -> Option<Vec3> {
+    if joint < 0 {
+        return None;
+    }
+    return model_matrices.get(joint as usize).map(|m| m.w_axis.truncate());
+}
+
+/// Computes the approximate center of mass of a pose, the mass-weighted average of the joint positions.
+///
+/// * `model_matrices` - Model-space matrices of the joints, like the output of `LocalToModelJob`.
+/// * `joint_masses` - Mass of each joint, ordered like `model_matrices`. Masses are relative, any unit works.
+///
+/// Returns `Vec3::ZERO` if the total mass is 0, and `OzzError::BufferSize` if `joint_masses` and
+/// `model_matrices` lengths differ.
+pub fn compute_center_of_mass(model_matrices: &[Mat4], joint_masses: &[f32]) -> Result<Vec3, OzzError> {
+    if joint_masses.len() != model_matrices.len() {
+        return Err(OzzError::BufferSize {
+            expected: model_matrices.len(),
+            actual: joint_masses.len(),
+        });
+    }
+    let mut total = 0.0;
+    let mut weighted = Vec3::ZERO;
+    for (matrix, mass) in model_matrices.iter().zip(joint_masses.iter()) {
+        weighted += matrix.w_axis.truncate() * *mass;
+        total += *mass;
+    }
+    if total == 0.0 {
+        return Ok(Vec3::ZERO);
+    }
+    return Ok(weighted / total);
+}
+
+/// Packs model-space matrices to a quaternion + translation layout, for GPU skinning without matrices.
+///
+/// Each entry is 8 floats: the rotation quaternion (x, y, z, w), the translation (x, y, z) and a zero
+/// padding float, half the size of a 16 floats matrix. Scale and shear are lost, and rotations are
+/// normalized.
+///
+/// * `model` - Model-space matrices of the joints, like the output of `LocalToModelJob`.
+pub fn poses_to_quat_pos(model: &[Mat4]) -> Vec<[f32; 8]> {
+    return model
+        .iter()
+        .map(|m| {
+            let (_, rotation, translation) = m.to_scale_rotation_translation();
+            let (q, t) = (rotation.normalize(), translation);
+            return [q.x, q.y, q.z, q.w, t.x, t.y, t.z, 0.0];
+        })
+        .collect();
+}
+
+/// Difference of a joint between 2 poses, see `pose_difference`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct JointDiff {
+    /// Distance between the local-space translations.
+    pub translation: f32,
+    /// Angle between the local-space rotations in radians, in [0, PI].
+    pub rotation: f32,
+    /// Largest absolute difference of the local-space scale components.
+    pub scale: f32,
+    /// Distance between the model-space positions of the joint, accumulating the error of its parents.
+    pub model_translation: f32,
+}
+
+///
+/// Difference between 2 poses, see `pose_difference`.
+///
+/// `joints` are ordered like skeleton's joints, `max` holds the largest value of each field over all
+/// joints (not necessarily the same joint).
+///
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PoseDiff {
+    pub joints: Vec<JointDiff>,
+    pub max: JointDiff,
+}
+
+/// Computes how much 2 local-space poses of a skeleton differ, per joint. Useful to quantify animation
+/// compression or retargeting errors.
+///
+/// * `skeleton` - The skeleton of the poses, used to compute model-space differences.
+/// * `a` - Local-space transforms of the first pose, ordered like skeleton's joints.
+/// * `b` - Local-space transforms of the second pose, ordered like skeleton's joints.
+///
+/// Only joints present in the skeleton and both poses are compared.
+pub fn pose_difference(skeleton: &Skeleton, a: &[AosTransform], b: &[AosTransform]) -> PoseDiff {
+    let num_joints = skeleton.num_joints().min(a.len()).min(b.len());
+    let mut models_a: Vec<Mat4> = Vec::with_capacity(num_joints);
+    let mut models_b: Vec<Mat4> = Vec::with_capacity(num_joints);
+    let mut diff = PoseDiff {
+        joints: Vec::with_capacity(num_joints),
+        max: JointDiff::default(),
+    };
+
+    for (idx, (a, b)) in a.iter().zip(b.iter()).take(num_joints).enumerate() {
+        let parent = skeleton.joint_parent(idx as i16);
+        let (parent_a, parent_b) = if parent < 0 {
+            (Mat4::IDENTITY, Mat4::IDENTITY)
+        } else {
+            (models_a[parent as usize], models_b[parent as usize])
+        };
+        models_a.push(parent_a * Mat4::from(*a));
+        models_b.push(parent_b * Mat4::from(*b));
+
+        // atan2 is accurate for small angles, unlike acos of the dot product.
+        let delta = a.rotation.conjugate() * b.rotation;
+        let joint = JointDiff {
+            translation: a.translation.distance(b.translation),
+            rotation: 2.0 * delta.xyz().length().atan2(delta.w.abs()),
+            scale: (a.scale - b.scale).abs().max_element(),
+            model_translation: (models_a[idx].w_axis.truncate()).distance(models_b[idx].w_axis.truncate()),
+        };
+        diff.max = JointDiff {
+            translation: diff.max.translation.max(joint.translation),
+            rotation: diff.max.rotation.max(joint.rotation),
+            scale: diff.max.scale.max(joint.scale),
+            model_translation: diff.max.model_translation.max(joint.model_translation),
+        };
+        diff.joints.push(joint);
+    }
+    return diff;
+}
+
+const COMPONENTS: [&str; 10] = [
+    "translation.x",
+    "translation.y",
+    "translation.z",
+    "rotation.x",
+    "rotation.y",
+    "rotation.z",
+    "rotation.w",
+    "scale.x",
+    "scale.y",
+    "scale.z",
+];
+
+/// Checks that a SoA pose has no NaN or infinite value, in any lane (padding lanes included).
+///
+/// Returns `OzzError::NonFinite` naming the first bad joint (`soa index * 4 + lane`) and component.
+pub fn validate_pose(poses: &[SoaTransform]) -> Result<(), OzzError> {
+    for (idx, soa) in poses.iter().enumerate() {
+        // Fast path, skips soa joints whose lanes are all finite.
+        let fields = [
+            soa.translation.x,
+            soa.translation.y,
+            soa.translation.z,
+            soa.rotation.x,
+            soa.rotation.y,
+            soa.rotation.z,
+            soa.rotation.w,
+            soa.scale.x,
+            soa.scale.y,
+            soa.scale.z,
+        ];
+        if fields.iter().all(|field| field.is_finite().all()) {
+            continue;
+        }
+        for lane in 0..4 {
+            validate_aos_pose_at(idx * 4 + lane, &soa.col(lane))?;
+        }
+    }
+    return Ok(());
+}
+
+/// Checks that an AoS pose has no NaN or infinite value.
+///
+/// Returns `OzzError::NonFinite` naming the first bad joint and component.
+pub fn validate_aos_pose(poses: &[AosTransform]) -> Result<(), OzzError> {
+    for (joint, transform) in poses.iter().enumerate() {
+        validate_aos_pose_at(joint, transform)?;
+    }
+    return Ok(());
+}
+
+fn validate_aos_pose_at(joint: usize, transform: &AosTransform) -> Result<(), OzzError> {
+    let (t, r, s) = (transform.translation, transform.rotation, transform.scale);
+    let values = [t.x, t.y, t.z, r.x, r.y, r.z, r.w, s.x, s.y, s.z];
+    return match values.iter().position(|value| !value.is_finite()) {
+        Some(idx) => Err(OzzError::NonFinite {
+            joint,
+            component: COMPONENTS[idx],
+        }),
+        None => Ok(()),
+    };
+}
+
+/// Development helper, checks a SoA pose like `validate_pose` in debug builds only. Always succeeds
+/// without any cost in release builds.
+#[inline]
+pub fn debug_validate_pose(poses: &[SoaTransform]) -> Result<(), OzzError> {
+    if cfg!(debug_assertions) {
+        return validate_pose(poses);
+    }
+    return Ok(());
+}
+
+/// Sets the padding lanes of a SoA pose to identity.
+///
+/// When `num_joints` isn't a multiple of 4, the last soa transform of a pose has `4 - num_joints % 4`
+/// padding lanes. Jobs compute them like the other lanes, but they never affect the joints: they can hold
+/// garbage or NaN (from uninitialized buffers, or a NaN pose written by user code) without any
+/// visible effect. They do matter to operations working on whole soa transforms: `validate_pose` checks
+/// them, pose comparisons (`PartialEq`) and `soa_transform_slice_as_f32` include them.
+///
+/// * `poses` - SoA pose to sanitize. Soa transforms after the last joint are left unchanged.
+/// * `num_joints` - Number of joints of the pose.
+///
+/// See `SamplingJob::set_sanitize_padding`.
+pub fn sanitize_padding(poses: &mut [SoaTransform], num_joints: usize) {
+    if num_joints % 4 == 0 {
+        return;
+    }
+    if let Some(soa) = poses.get_mut(num_joints / 4) {
+        for lane in (num_joints % 4)..4 {
+            soa.set_col(lane, &AosTransform::IDENTITY);
+        }
+    }
+}
+
+/// Clamps each scale axis of a local-space pose into [min, max], to avoid skinning explosions.
+///
+/// Compressed or authored clips can produce tiny or zero scales, that collapse (or blow up, once inverted)
+/// skinned vertices for a frame. Clamping is lossy: negative (mirroring) scales also become `min` or more,
+/// and NaN scales are left unchanged.
+///
+/// * `poses` - SoA pose to clamp, like the output of `SamplingJob` or `BlendingJob`.
+/// * `min` - Minimum scale of each axis.
+/// * `max` - Maximum scale of each axis, expected to be greater than or equal to `min`. Otherwise all scales
+///   become `max`. A NaN bound is ignored.
+pub fn clamp_scale(poses: &mut [SoaTransform], min: f32, max: f32) {
+    let (min, max) = (f32x4::splat(min), f32x4::splat(max));
+    let clamp = |scale: f32x4| {
+        let scale = scale.simd_lt(min).select(min, scale);
+        return scale.simd_gt(max).select(max, scale);
+    };
+    for pose in poses.iter_mut() {
+        pose.scale.x = clamp(pose.scale.x);
+        pose.scale.y = clamp(pose.scale.y);
+        pose.scale.z = clamp(pose.scale.z);
+    }
+}
+
+/// Iterates through the joints of a SoA pose as AoS transforms, without allocating.
+///
+/// * `poses` - SoA pose to unpack.
+/// * `num_joints` - Number of joints of the pose. The iteration stops after `num_joints` transforms, skipping
+///   padding lanes, or at the end of `poses` if it is smaller.
+pub fn soa_to_aos_iter(poses: &[SoaTransform], num_joints: usize) -> impl Iterator<Item = AosTransform> + '_ {
+    return (0..num_joints.min(poses.len() * 4)).map(move |idx| poses[idx / 4].col(idx % 4));
+}
+
+/// Reorders a pose in an external joint order, `out[i] = pose[remap[i]]`.
+///
+/// * `pose` - Pose in `Skeleton` joints order, like model-space matrices or AoS local transforms.
+/// * `remap` - Joint index of each output element, see `Skeleton::build_remap`.
+/// * `out` - Output pose in the external order.
+///
+/// Returns `OzzError::BufferSize` if `out` is smaller than `remap`, and `OzzError::InvalidIndex` if a remap
+/// index is out of `pose` range.
+pub fn apply_remap<T: Copy>(pose: &[T], remap: &[i16], out: &mut [T]) -> Result<(), OzzError> {
+    if out.len() < remap.len() {
+        return Err(OzzError::BufferSize {
+            expected: remap.len(),
+            actual: out.len(),
+        });
+    }
+    for (out, idx) in out.iter_mut().zip(remap.iter()) {
+        if *idx < 0 || *idx as usize >= pose.len() {
+            return Err(OzzError::InvalidIndex);
+        }
+        *out = pose[*idx as usize];
+    }
+    return Ok(());
+}
+
+#[cfg(test)]
+mod pose_utils_tests {
+    use glam::Quat;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::animation::{Animation, AnimationBuilder};
+    use crate::blending_job::{BlendingJob, BlendingLayer};
+    use crate::local_to_model_job::LocalToModelJob;
+    use crate::sampling_job::{SamplingContext, SamplingJob};
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_compute_pose_aabb() {
+        assert!(!compute_pose_aabb(&[], None).is_valid());
+
+        let matrices = [
+            Mat4::from_translation(Vec3::new(1.0, 2.0, 3.0)),
+            Mat4::from_rotation_translation(Quat::from_rotation_y(1.0), Vec3::new(-1.0, 0.5, 4.0)),
+            Mat4::from_scale_rotation_translation(Vec3::splat(2.0), Quat::IDENTITY, Vec3::new(0.0, -3.0, 0.0)),
+        ];
+        let aabb = compute_pose_aabb(&matrices, None);
+        assert_eq!(aabb, Aabb::new(Vec3::new(-1.0, -3.0, 0.0), Vec3::new(1.0, 2.0, 4.0)));
+
+        let aabb = compute_pose_aabb(&matrices, Some(&[0.5, 1.0]));
+        assert_eq!(aabb, Aabb::new(Vec3::new(-2.0, -3.0, 0.0), Vec3::new(1.5, 2.5, 5.0)));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_compute_pose_aabb_rest_pose() {
+        let skeleton = Rc::new(Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap());
+        let output = Rc::new(RefCell::new(vec![Mat4::IDENTITY; skeleton.num_joints()]));
+        let mut job: LocalToModelJob = LocalToModelJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.set_input(Rc::new(RefCell::new(skeleton.joint_rest_poses().to_vec())));
+        job.set_output(output.clone());
+        job.run().unwrap();
+
+        let aabb = compute_pose_aabb(&output.borrow(), None);
+        assert!(aabb.is_valid());
+        for matrix in output.borrow().iter() {
+            assert!(aabb.contains(matrix.w_axis.truncate()));
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_gltf_nodes_rest_pose() {
+        let skeleton = Rc::new(Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap());
+        let rest_poses = skeleton.joint_rest_poses();
+        assert!(matches!(
+            local_pose_to_gltf_nodes(&skeleton, &rest_poses[1..]),
+            Err(OzzError::InvalidIndex)
+        ));
+
+        let nodes = local_pose_to_gltf_nodes(&skeleton, rest_poses).unwrap();
+        assert_eq!(nodes.len(), skeleton.num_joints());
+        for (idx, (name, transform, parent)) in nodes.iter().enumerate() {
+            assert_eq!(Some(name.as_str()), skeleton.name_by_joint(idx as i16));
+            assert_eq!(*parent, skeleton.joint_parent(idx));
+            assert!(*parent < idx as i16);
+            assert_eq!(*transform, rest_poses[idx / 4].col(idx % 4));
+        }
+
+        let output = Rc::new(RefCell::new(vec![Mat4::IDENTITY; skeleton.num_joints()]));
+        let mut job: LocalToModelJob = LocalToModelJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.set_input(Rc::new(RefCell::new(rest_poses.to_vec())));
+        job.set_output(output.clone());
+        job.run().unwrap();
+
+        let model_nodes = model_pose_to_gltf_nodes(&skeleton, &output.borrow()).unwrap();
+        assert_eq!(model_nodes.len(), nodes.len());
+        for ((name, transform, parent), (name2, transform2, parent2)) in nodes.iter().zip(model_nodes.iter()) {
+            assert_eq!(name, name2);
+            assert_eq!(parent, parent2);
+            assert!(transform.translation.abs_diff_eq(transform2.translation, 1e-4));
+            // Negative scales can be decomposed differently, but describe the same transform.
+            assert!(transform.to_mat4().abs_diff_eq(transform2.to_mat4(), 1e-4));
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_model_to_local() {
+        let skeleton = Rc::new(Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap());
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+        let sampled = Rc::new(RefCell::new(vec![SoaTransform::default(); skeleton.num_soa_joints()]));
+        let mut sampling_job = SamplingJob::default();
+        sampling_job.set_context(SamplingContext::from_animation(animation.as_ref()));
+        sampling_job.set_animation(animation);
+        sampling_job.set_output(sampled.clone());
+        sampling_job.set_ratio(0.7);
+        sampling_job.run().unwrap();
+
+        let models = Rc::new(RefCell::new(vec![Mat4::IDENTITY; skeleton.num_joints()]));
+        let mut job: LocalToModelJob = LocalToModelJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.set_input(sampled.clone());
+        job.set_output(models.clone());
+        job.run().unwrap();
+
+        let mut locals = vec![SoaTransform::default(); skeleton.num_soa_joints()];
+        assert!(matches!(
+            model_to_local(&skeleton, &models.borrow()[1..], &mut locals),
+            Err(OzzError::BufferSize { expected, actual }) if expected == skeleton.num_joints() && actual == expected - 1
+        ));
+        assert!(matches!(
+            model_to_local(&skeleton, &models.borrow(), &mut locals[1..]),
+            Err(OzzError::BufferSize { expected, actual }) if expected == skeleton.num_soa_joints() && actual == expected - 1
+        ));
+
+        model_to_local(&skeleton, &models.borrow(), &mut locals).unwrap();
+        for idx in 0..skeleton.num_joints() {
+            let expected = sampled.borrow()[idx / 4].col(idx % 4);
+            let actual = locals[idx / 4].col(idx % 4);
+            assert!(actual.translation.abs_diff_eq(expected.translation, 1e-4));
+            // Negative scales can be decomposed differently, but describe the same transform.
+            assert!(actual.to_mat4().abs_diff_eq(expected.to_mat4(), 1e-4), "joint {}", idx);
+        }
+
+        // Round trips again to the same model-space matrices.
+        let models2 = Rc::new(RefCell::new(vec![Mat4::IDENTITY; skeleton.num_joints()]));
+        job.set_input(Rc::new(RefCell::new(locals)));
+        job.set_output(models2.clone());
+        job.run().unwrap();
+        for (model, model2) in models.borrow().iter().zip(models2.borrow().iter()) {
+            assert!(model.abs_diff_eq(*model2, 1e-4));
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_pose_velocities() {
+        let prev = [
+            AosTransform::from_translation_rotation(Vec3::new(1.0, 0.0, 0.0), Quat::from_rotation_y(0.2)),
+            AosTransform::from_translation_rotation(Vec3::ZERO, Quat::from_rotation_z(3.0)),
+        ];
+        let curr = [
+            AosTransform::from_translation_rotation(Vec3::new(1.0, 2.0, 0.0), Quat::from_rotation_y(0.7)),
+            AosTransform::from_translation_rotation(Vec3::ZERO, Quat::from_rotation_z(-3.0)),
+        ];
+
+        let velocities = pose_velocities(&prev, &curr, 0.5);
+        assert_eq!(velocities.len(), 2);
+        assert!(velocities[0].0.abs_diff_eq(Vec3::new(0.0, 4.0, 0.0), 1e-6));
+        assert!(velocities[0].1.abs_diff_eq(Vec3::new(0.0, 1.0, 0.0), 1e-5));
+        // Shortest path, 3.0 to -3.0 is a 2PI - 6 rotation around z.
+        let expected = (2.0 * std::f32::consts::PI - 6.0) / 0.5;
+        assert!(velocities[1].1.abs_diff_eq(Vec3::new(0.0, 0.0, expected), 1e-4));
+
+        let velocities = pose_velocities(&prev, &curr[..1], 0.0);
+        assert_eq!(velocities, vec![(Vec3::ZERO, Vec3::ZERO)]);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_make_additive_pose() {
+        let skeleton = Rc::new(Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap());
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+        let sampled = Rc::new(RefCell::new(vec![SoaTransform::default(); skeleton.num_soa_joints()]));
+        let mut sampling_job = SamplingJob::default();
+        sampling_job.set_context(SamplingContext::from_animation(animation.as_ref()));
+        sampling_job.set_animation(animation);
+        sampling_job.set_output(sampled.clone());
+        sampling_job.set_ratio(0.4);
+        sampling_job.run().unwrap();
+
+        let num_joints = skeleton.num_joints();
+        let rest_poses = skeleton.joint_rest_poses();
+        let reference: Vec<_> = (0..num_joints).map(|idx| rest_poses[idx / 4].col(idx % 4)).collect();
+        let current: Vec<_> = (0..num_joints)
+            .map(|idx| sampled.borrow()[idx / 4].col(idx % 4))
+            .collect();
+        let mut delta = vec![AosTransform::IDENTITY; num_joints];
+        make_additive_pose(&reference, &current, &mut delta);
+
+        let mut soa_delta = vec![SoaTransform::default(); skeleton.num_soa_joints()];
+        for (idx, transform) in delta.iter().enumerate() {
+            soa_delta[idx / 4].set_col(idx % 4, transform);
+        }
+        let output = Rc::new(RefCell::new(vec![SoaTransform::default(); skeleton.num_soa_joints()]));
+        let mut blending_job = BlendingJob::default();
+        blending_job.set_skeleton(skeleton.clone());
+        blending_job.set_output(output.clone());
+        blending_job.layers_mut().push(BlendingLayer::with_weight(
+            Rc::new(RefCell::new(rest_poses.to_vec())),
+            1.0,
+        ));
+        blending_job
+            .additive_layers_mut()
+            .push(BlendingLayer::with_weight(Rc::new(RefCell::new(soa_delta)), 1.0));
+        blending_job.run().unwrap();
+
+        for (idx, expected) in current.iter().enumerate() {
+            let actual = output.borrow()[idx / 4].col(idx % 4);
+            assert!(
+                actual.translation.abs_diff_eq(expected.translation, 1e-5),
+                "joint={}",
+                idx
+            );
+            assert!(
+                actual.rotation.dot(expected.rotation).abs() > 1.0 - 1e-5,
+                "joint={}",
+                idx
+            );
+            assert!(actual.scale.abs_diff_eq(expected.scale, 1e-5), "joint={}", idx);
+        }
+
+        let zero_scale = AosTransform::new(Vec3::ZERO, Quat::IDENTITY, Vec3::new(0.0, 1.0, 2.0));
+        let mut out = [AosTransform::IDENTITY; 2];
+        make_additive_pose(&[zero_scale], &[AosTransform::IDENTITY], &mut out);
+        assert_eq!(out[0].scale, Vec3::new(1.0, 1.0, 0.5));
+        assert_eq!(out[1], AosTransform::IDENTITY);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_attachment_matrix() {
+        let matrices = [
+            Mat4::from_translation(Vec3::new(1.0, 2.0, 3.0)),
+            Mat4::from_scale_rotation_translation(Vec3::splat(2.0), Quat::from_rotation_z(1.0), Vec3::X),
+        ];
+        assert_eq!(attachment_matrix(&matrices, 0, &AosTransform::IDENTITY), matrices[0]);
+        assert_eq!(attachment_matrix(&matrices, 1, &AosTransform::IDENTITY), matrices[1]);
+
+        let offset = AosTransform::from_translation_rotation(Vec3::new(0.5, 0.0, 0.0), Quat::from_rotation_x(0.3));
+        let matrix = attachment_matrix(&matrices, 1, &offset);
+        let expected = matrices[1].transform_point3(Vec3::new(0.5, 0.0, 0.0));
+        assert!(matrix.w_axis.truncate().abs_diff_eq(expected, 1e-6));
+        assert!(matrix.abs_diff_eq(matrices[1] * Mat4::from(offset), 1e-6));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_joint_positions() {
+        let skeleton = Rc::new(Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap());
+        let matrices = Rc::new(RefCell::new(vec![Mat4::IDENTITY; skeleton.num_joints()]));
+        let mut job = LocalToModelJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.set_input(Rc::new(RefCell::new(skeleton.joint_rest_poses().to_vec())));
+        job.set_output(matrices.clone());
+        job.run().unwrap();
+
+        let matrices = matrices.borrow();
+        let positions = joint_positions(&matrices);
+        assert_eq!(positions.len(), skeleton.num_joints());
+        for (idx, (position, matrix)) in positions.iter().zip(matrices.iter()).enumerate() {
+            let (_, _, translation) = matrix.to_scale_rotation_translation();
+            assert_eq!(*position, translation);
+            assert_eq!(joint_position(&matrices, idx as i16), Some(*position));
+        }
+        assert_ne!(positions[1], Vec3::ZERO);
+        assert_eq!(joint_position(&matrices, -1), None);
+        assert_eq!(joint_position(&matrices, skeleton.num_joints() as i16), None);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_compute_center_of_mass() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let mut models = vec![Mat4::IDENTITY; skeleton.num_joints()];
+        let mut job: LocalToModelJob<&Skeleton, &[SoaTransform], &mut [Mat4]> = LocalToModelJob::default();
+        job.set_skeleton(&skeleton);
+        job.set_input(skeleton.joint_rest_poses());
+        job.set_output(&mut models);
+        job.run().unwrap();
+
+        // Uniform masses, the center of mass is the mean joint position.
+        let positions = joint_positions(&models);
+        let mean = positions.iter().sum::<Vec3>() / positions.len() as f32;
+        let com = compute_center_of_mass(&models, &vec![2.5; models.len()]).unwrap();
+        assert!(com.abs_diff_eq(mean, 1e-5), "{} {}", com, mean);
+
+        let matrices = [Mat4::IDENTITY, Mat4::from_translation(Vec3::new(4.0, 0.0, -2.0))];
+        let com = compute_center_of_mass(&matrices, &[1.0, 3.0]).unwrap();
+        assert!(com.abs_diff_eq(Vec3::new(3.0, 0.0, -1.5), 1e-6));
+        assert_eq!(compute_center_of_mass(&matrices, &[0.0, 0.0]).unwrap(), Vec3::ZERO);
+        assert_eq!(compute_center_of_mass(&[], &[]).unwrap(), Vec3::ZERO);
+
+        // One mass per joint is expected.
+        assert!(matches!(
+            compute_center_of_mass(&matrices, &[1.0]),
+            Err(OzzError::BufferSize { expected: 2, actual: 1 })
+        ));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_poses_to_quat_pos() {
+        let rotation = Quat::from_euler(glam::EulerRot::XYZ, 0.3, -1.2, 2.5);
+        let matrices = [
+            Mat4::IDENTITY,
+            Mat4::from_rotation_translation(rotation, Vec3::new(1.0, -2.0, 3.0)),
+            Mat4::from_scale_rotation_translation(Vec3::new(2.0, 0.5, 3.0), rotation, Vec3::new(-4.0, 5.0, 0.5)),
+        ];
+        let packed = poses_to_quat_pos(&matrices);
+        assert_eq!(packed.len(), matrices.len());
+        assert_eq!(packed[0], [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0]);
+        for (entry, matrix) in packed.iter().zip(matrices.iter()) {
+            let (_, expected_rotation, expected_translation) = matrix.to_scale_rotation_translation();
+            let rotation = Quat::from_slice(&entry[0..4]);
+            let translation = Vec3::from_slice(&entry[4..7]);
+            assert!(rotation.is_normalized());
+            assert!(rotation.abs_diff_eq(expected_rotation, 1e-5));
+            assert_eq!(translation, expected_translation);
+            assert_eq!(entry[7], 0.0);
+        }
+        assert!(poses_to_quat_pos(&[]).is_empty());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_pose_difference() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let rest_pose: Vec<AosTransform> = (0..skeleton.num_joints())
+            .map(|idx| skeleton.joint_rest_poses()[idx / 4].col(idx % 4))
+            .collect();
+
+        let diff = pose_difference(&skeleton, &rest_pose, &rest_pose);
+        assert_eq!(diff.joints.len(), skeleton.num_joints());
+        assert!(diff.joints.iter().all(|joint| *joint == JointDiff::default()));
+        assert_eq!(diff.max, JointDiff::default());
+
+        // A joint with an offset child.
+        let child = (1..skeleton.num_joints())
+            .find(|idx| rest_pose[*idx].translation.length() > 1e-3)
+            .unwrap();
+        let parent = skeleton.joint_parent(child as i16) as usize;
+        let last = skeleton.num_joints() - 1;
+
+        let mut rotated = rest_pose.clone();
+        rotated[parent].rotation = Quat::from_rotation_y(0.3) * rotated[parent].rotation;
+        rotated[last].rotation = Quat::from_rotation_x(-0.1) * rotated[last].rotation;
+        let diff = pose_difference(&skeleton, &rest_pose, &rotated);
+        assert!((diff.joints[parent].rotation - 0.3).abs() < 1e-5);
+        assert!((diff.joints[last].rotation - 0.1).abs() < 1e-5);
+        assert!((diff.max.rotation - 0.3).abs() < 1e-5);
+        assert_eq!(diff.joints[child].rotation, 0.0);
+        assert_eq!(diff.max.translation, 0.0);
+        assert_eq!(diff.max.scale, 0.0);
+        // The rotation moves the children in model-space, not the joint itself.
+        assert_eq!(diff.joints[parent].model_translation, 0.0);
+        assert!(diff.joints[child].model_translation > 0.0);
+
+        let diff = pose_difference(&skeleton, &rest_pose, &rotated[..3]);
+        assert_eq!(diff.joints.len(), 3);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_validate_pose() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let mut pose = skeleton.joint_rest_poses().to_vec();
+        assert!(validate_pose(&pose).is_ok());
+        assert!(debug_validate_pose(&pose).is_ok());
+
+        pose[2].rotation.set_col(1, Quat::from_xyzw(0.0, f32::NAN, 0.0, 1.0));
+        pose[3].scale.set_col(0, Vec3::splat(f32::INFINITY));
+        let err = validate_pose(&pose).unwrap_err();
+        assert!(err.is_non_finite());
+        assert!(matches!(
+            err,
+            OzzError::NonFinite {
+                joint: 9,
+                component: "rotation.y"
+            }
+        ));
+        assert_eq!(err.to_string(), "Non-finite rotation.y of joint 9");
+        if cfg!(debug_assertions) {
+            assert!(debug_validate_pose(&pose).is_err());
+        }
+
+        let mut aos = vec![AosTransform::IDENTITY; 3];
+        assert!(validate_aos_pose(&aos).is_ok());
+        aos[1].translation.z = f32::NEG_INFINITY;
+        assert!(matches!(
+            validate_aos_pose(&aos),
+            Err(OzzError::NonFinite {
+                joint: 1,
+                component: "translation.z"
+            })
+        ));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_sanitize_padding() {
+        let nan = AosTransform::new(Vec3::splat(f32::NAN), Quat::IDENTITY, Vec3::ONE);
+        let joint = AosTransform::new(Vec3::X, Quat::from_rotation_y(0.5), Vec3::splat(2.0));
+        let mut pose = vec![SoaTransform::default(); 6];
+        for soa in pose.iter_mut() {
+            (0..4).for_each(|lane| soa.set_col(lane, &nan));
+        }
+        pose[4].set_col(0, &joint);
+
+        // 17 joints, the 5th soa transform has 3 padding lanes.
+        sanitize_padding(&mut pose, 17);
+        assert_eq!(pose[4].col(0), joint);
+        for lane in 1..4 {
+            assert_eq!(pose[4].col(lane), AosTransform::IDENTITY);
+        }
+        assert!(pose[3].col(3).translation.is_nan());
+        assert!(pose[5].col(1).translation.is_nan());
+        assert!(validate_pose(&pose[4..5]).is_ok());
+
+        // No padding lane, or out of range.
+        let mut pose2 = pose.clone();
+        sanitize_padding(&mut pose2, 16);
+        sanitize_padding(&mut pose2, 100);
+        assert!(pose2[3].col(3).translation.is_nan());
+        assert!(pose2[5].col(1).translation.is_nan());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_clamp_scale() {
+        let mut builder = AnimationBuilder::new(1.0, 3);
+        builder.tracks_mut()[1].scales = vec![(0.0, Vec3::new(1e-9, 1.0, 0.0)), (1.0, Vec3::new(-2.0, 50.0, 1.0))];
+        let animation = builder.build().unwrap();
+
+        let mut poses = vec![SoaTransform::default(); animation.num_soa_tracks()];
+        let mut job = SamplingJob::default();
+        job.set_animation(&animation);
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_output(poses.as_mut_slice());
+        job.set_ratio(0.0);
+        job.run().unwrap();
+        clamp_scale(&mut poses, 1e-3, 10.0);
+        assert_eq!(poses[0].col(1).scale, Vec3::new(1e-3, 1.0, 1e-3));
+        assert_eq!(poses[0].col(0).scale, Vec3::ONE);
+
+        let transform = AosTransform::new(Vec3::ZERO, Quat::IDENTITY, Vec3::new(-2.0, 50.0, 1.0));
+        poses[0].set_col(1, &transform);
+        clamp_scale(&mut poses, 1e-3, 10.0);
+        assert_eq!(poses[0].col(1).scale, Vec3::new(1e-3, 10.0, 1.0));
+
+        // Inverted or NaN bounds don't panic.
+        clamp_scale(&mut poses, 10.0, 2.0);
+        assert_eq!(poses[0].col(1).scale, Vec3::splat(2.0));
+        clamp_scale(&mut poses, f32::NAN, 1.0);
+        assert_eq!(poses[0].col(1).scale, Vec3::ONE);
+        clamp_scale(&mut poses, 3.0, f32::NAN);
+        assert_eq!(poses[0].col(1).scale, Vec3::splat(3.0));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_soa_to_aos_iter() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let poses = skeleton.joint_rest_poses();
+        let mut count = 0;
+        for (idx, transform) in soa_to_aos_iter(poses, skeleton.num_joints()).enumerate() {
+            assert_eq!(transform, poses[idx / 4].col(idx % 4));
+            count += 1;
+        }
+        assert_eq!(count, skeleton.num_joints());
+        assert_eq!(soa_to_aos_iter(poses, 5).count(), 5);
+        assert_eq!(soa_to_aos_iter(&poses[..2], 100).count(), 8);
+        assert_eq!(soa_to_aos_iter(&[], 10).count(), 0);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_remap() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let pose: Vec<_> = soa_to_aos_iter(skeleton.joint_rest_poses(), skeleton.num_joints()).collect();
+
+        // Shuffled order, odd joints reversed then even joints.
+        let (_, names) = skeleton.export_hierarchy();
+        let mut external: Vec<&str> = names.iter().skip(1).step_by(2).rev().map(String::as_str).collect();
+        external.extend(names.iter().step_by(2).map(String::as_str));
+        let remap = skeleton.build_remap(&external).unwrap();
+        assert_eq!(remap.len(), skeleton.num_joints());
+        assert_eq!(remap[0], 65);
+
+        let mut external_pose = vec![AosTransform::IDENTITY; remap.len()];
+        apply_remap(&pose, &remap, &mut external_pose).unwrap();
+        for (name, transform) in external.iter().zip(external_pose.iter()) {
+            assert_eq!(*transform, pose[skeleton.joint_by_name(name).unwrap() as usize]);
+        }
+
+        // And back to skeleton order.
+        let mut inverse = vec![0i16; remap.len()];
+        for (idx, joint) in remap.iter().enumerate() {
+            inverse[*joint as usize] = idx as i16;
+        }
+        let mut restored = vec![AosTransform::IDENTITY; pose.len()];
+        apply_remap(&external_pose, &inverse, &mut restored).unwrap();
+        assert_eq!(restored, pose);
+
+        let err = skeleton.build_remap(&["Hips", "Tail"]);
+        assert!(matches!(err, Err(OzzError::InvalidIndex)));
+        let mut out = vec![AosTransform::IDENTITY; 1];
+        let err = apply_remap(&pose, &remap, &mut out);
+        assert!(matches!(err, Err(OzzError::BufferSize { expected, actual: 1 }) if expected == remap.len()));
+        let err = apply_remap(&pose, &[100], &mut out);
+        assert!(matches!(err, Err(OzzError::InvalidIndex)));
+    }
+}
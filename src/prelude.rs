@@ -0,0 +1,34 @@
+//!
+//! Prelude.
+//!
+//! Re-exports the commonly used types and traits, `use ozz_animation_rs::prelude::*` is enough to load
+//! skeletons and animations, and to run the sampling, blending, local to model, skinning and IK jobs.
+//!
+
+pub use crate::animation::{Animation, AnimationData};
+#[cfg(feature = "std")]
+pub use crate::archive::{Archive, ArchiveWriter};
+pub use crate::base::{OzzBuf, OzzError, OzzIndex, OzzMutBuf, OzzObj, SKELETON_NO_PARENT};
+#[cfg(feature = "std")]
+pub use crate::blending_job::BlendingJobArc;
+pub use crate::blending_job::{
+    BlendingContext, BlendingJob, BlendingJobRc, BlendingJobRef, BlendingLayer, OverrideLayer,
+};
+pub use crate::ik_aim_job::IKAimJob;
+pub use crate::ik_two_bone_job::IKTwoBoneJob;
+pub use crate::joint_mask::JointMask;
+#[cfg(feature = "std")]
+pub use crate::local_to_model_job::LocalToModelJobArc;
+pub use crate::local_to_model_job::{LocalToModelJob, LocalToModelJobRc, LocalToModelJobRef};
+pub use crate::math::{AosTransform, ApproxEq, SoaMat4, SoaQuat, SoaTransform, SoaVec3};
+#[cfg(feature = "std")]
+pub use crate::sampling_job::SamplingJobArc;
+pub use crate::sampling_job::{SamplingContext, SamplingJob, SamplingJobRc, SamplingJobRef};
+pub use crate::skeleton::Skeleton;
+#[cfg(feature = "std")]
+pub use crate::skinning_job::SkinningJobArc;
+pub use crate::skinning_job::{SkinningJob, SkinningJobRc, SkinningJobRef};
+pub use crate::track::Track;
+#[cfg(feature = "std")]
+pub use crate::track_sampling_job::TrackSamplingJobArc;
+pub use crate::track_sampling_job::{TrackSamplingJob, TrackSamplingJobRc, TrackSamplingJobRef};
@@ -0,0 +1,330 @@
+//!
+//! Retarget Job.
+//!
+
+use alloc::rc::Rc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use glam::Vec3;
+#[cfg(feature = "std")]
+use std::sync::{Arc, RwLock};
+
+use crate::base::{OzzBuf, OzzError, OzzMutBuf, OzzObj};
+use crate::math::{AosTransform, SoaTransform};
+use crate::skeleton::Skeleton;
+
+/// Bone length under which the translation can't be scaled, and the target rest translation is used.
+const BONE_LENGTH_EPSILON: f32 = 1e-6;
+
+///
+/// Retargets a local-space pose from a source skeleton onto a target skeleton.
+///
+/// Joints are matched by name. For each matched joint, the rotation delta from the source rest pose is
+/// applied to the target rest pose rotation, and the source translation is scaled by the ratio of the
+/// target and source bone lengths (rest pose translation lengths). Scales are copied from the source.
+///
+/// Target joints missing from the source skeleton keep their rest pose, source joints missing from the
+/// target skeleton are ignored.
+///
+/// Job input is the source local-space pose, ordered like source skeleton's joints. Job output is the
+/// target local-space pose, ordered like target skeleton's joints.
+///
+#[derive(Debug)]
+pub struct RetargetJob<S = Rc<Skeleton>, I = Rc<RefCell<Vec<SoaTransform>>>, O = Rc<RefCell<Vec<SoaTransform>>>>
+where
+    S: OzzObj<Skeleton>,
+    I: OzzBuf<SoaTransform>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    source_skeleton: Option<S>,
+    target_skeleton: Option<S>,
+    input: Option<I>,
+    output: Option<O>,
+
+    // Per target joint, the matching source joint and bone length ratio. Built on first run.
+    bindings: Vec<Option<(usize, f32)>>,
+}
+
+pub type RetargetJobRef<'t> = RetargetJob<&'t Skeleton, &'t [SoaTransform], &'t mut [SoaTransform]>;
+pub type RetargetJobRc = RetargetJob<Rc<Skeleton>, Rc<RefCell<Vec<SoaTransform>>>, Rc<RefCell<Vec<SoaTransform>>>>;
+#[cfg(feature = "std")]
+pub type RetargetJobArc = RetargetJob<Arc<Skeleton>, Arc<RwLock<Vec<SoaTransform>>>, Arc<RwLock<Vec<SoaTransform>>>>;
+
+impl<S, I, O> Default for RetargetJob<S, I, O>
+where
+    S: OzzObj<Skeleton>,
+    I: OzzBuf<SoaTransform>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    fn default() -> RetargetJob<S, I, O> {
+        return RetargetJob {
+            source_skeleton: None,
+            target_skeleton: None,
+            input: None,
+            output: None,
+            bindings: Vec::new(),
+        };
+    }
+}
+
+impl<S, I, O> RetargetJob<S, I, O>
+where
+    S: OzzObj<Skeleton>,
+    I: OzzBuf<SoaTransform>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    /// Gets source skeleton of `RetargetJob`.
+    #[inline]
+    pub fn source_skeleton(&self) -> Option<&S> {
+        return self.source_skeleton.as_ref();
+    }
+
+    /// Sets source skeleton of `RetargetJob`.
+    ///
+    /// The skeleton the input pose is ordered like.
+    #[inline]
+    pub fn set_source_skeleton(&mut self, skeleton: S) {
+        self.source_skeleton = Some(skeleton);
+        self.bindings.clear();
+    }
+
+    /// Clears source skeleton of `RetargetJob`.
+    #[inline]
+    pub fn clear_source_skeleton(&mut self) {
+        self.source_skeleton = None;
+        self.bindings.clear();
+    }
+
+    /// Gets target skeleton of `RetargetJob`.
+    #[inline]
+    pub fn target_skeleton(&self) -> Option<&S> {
+        return self.target_skeleton.as_ref();
+    }
+
+    /// Sets target skeleton of `RetargetJob`.
+    ///
+    /// The skeleton the output pose is ordered like.
+    #[inline]
+    pub fn set_target_skeleton(&mut self, skeleton: S) {
+        self.target_skeleton = Some(skeleton);
+        self.bindings.clear();
+    }
+
+    /// Clears target skeleton of `RetargetJob`.
+    #[inline]
+    pub fn clear_target_skeleton(&mut self) {
+        self.target_skeleton = None;
+        self.bindings.clear();
+    }
+
+    /// Gets input of `RetargetJob`.
+    #[inline]
+    pub fn input(&self) -> Option<&I> {
+        return self.input.as_ref();
+    }
+
+    /// Sets input of `RetargetJob`.
+    ///
+    /// The source local-space pose.
+    #[inline]
+    pub fn set_input(&mut self, input: I) {
+        self.input = Some(input);
+    }
+
+    /// Clears input of `RetargetJob`.
+    #[inline]
+    pub fn clear_input(&mut self) {
+        self.input = None;
+    }
+
+    /// Gets output of `RetargetJob`.
+    #[inline]
+    pub fn output(&self) -> Option<&O> {
+        return self.output.as_ref();
+    }
+
+    /// Sets output of `RetargetJob`.
+    ///
+    /// The target local-space pose.
+    #[inline]
+    pub fn set_output(&mut self, output: O) {
+        self.output = Some(output);
+    }
+
+    /// Clears output of `RetargetJob`.
+    #[inline]
+    pub fn clear_output(&mut self) {
+        self.output = None;
+    }
+
+    /// Validates `RetargetJob` parameters.
+    pub fn validate(&self) -> bool {
+        return (|| {
+            let source = self.source_skeleton.as_ref()?.obj();
+            let target = self.target_skeleton.as_ref()?.obj();
+            let input = self.input.as_ref()?.buf().ok()?;
+            let output = self.output.as_ref()?.buf().ok()?;
+            return Some(input.len() >= source.num_soa_joints() && output.len() >= target.num_soa_joints());
+        })()
+        .unwrap_or(false);
+    }
+
+    /// Runs retarget job's task.
+    /// The validate job before any operation is performed.
+    pub fn run(&mut self) -> Result<(), OzzError> {
+        let source = self.source_skeleton.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        let target = self.target_skeleton.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        let input = self.input.as_ref().ok_or(OzzError::InvalidJob)?.buf()?;
+        let mut output = self.output.as_mut().ok_or(OzzError::InvalidJob)?.mut_buf()?;
+        if input.len() < source.num_soa_joints() || output.len() < target.num_soa_joints() {
+            return Err(OzzError::InvalidJob);
+        }
+
+        if self.bindings.len() != target.num_joints() {
+            self.bindings = Self::bind(source, target);
+        }
+
+        for (idx, binding) in self.bindings.iter().enumerate() {
+            let rest = target.joint_rest_poses()[idx / 4].col(idx % 4);
+            let transform = match binding {
+                Some((src, ratio)) => {
+                    let src_rest = source.joint_rest_poses()[src / 4].col(src % 4);
+                    let src_pose = input[src / 4].col(src % 4);
+                    let translation = if *ratio > 0.0 {
+                        src_pose.translation * *ratio
+                    } else {
+                        rest.translation
+                    };
+                    let delta = src_rest.rotation.conjugate() * src_pose.rotation;
+                    AosTransform::new(translation, (rest.rotation * delta).normalize(), src_pose.scale)
+                }
+                None => rest,
+            };
+            output[idx / 4].set_col(idx % 4, &transform);
+        }
+        return Ok(());
+    }
+
+    fn bind(source: &Skeleton, target: &Skeleton) -> Vec<Option<(usize, f32)>> {
+        let length = |skeleton: &Skeleton, idx: usize| -> f32 {
+            let translation: Vec3 = skeleton.joint_rest_poses()[idx / 4].translation.col(idx % 4);
+            return translation.length();
+        };
+
+        return (0..target.num_joints())
+            .map(|idx| {
+                let name = target.name_by_joint(idx as i16)?;
+                let src = source.joint_by_name(name)? as usize;
+                let src_length = length(source, src);
+                let ratio = if src_length > BONE_LENGTH_EPSILON {
+                    length(target, idx) / src_length
+                } else {
+                    0.0
+                };
+                return Some((src, ratio));
+            })
+            .collect();
+    }
+}
+
+/// Retargets `input`, a local-space pose of `source` skeleton, onto `target` skeleton. See `RetargetJob`.
+pub fn retarget_pose(
+    source: &Skeleton,
+    target: &Skeleton,
+    input: &[SoaTransform],
+) -> Result<Vec<SoaTransform>, OzzError> {
+    let mut output = vec![SoaTransform::default(); target.num_soa_joints()];
+    let mut job: RetargetJobRef = RetargetJob::default();
+    job.set_source_skeleton(source);
+    job.set_target_skeleton(target);
+    job.set_input(input);
+    job.set_output(&mut output);
+    job.run()?;
+    return Ok(output);
+}
+
+#[cfg(test)]
+mod retarget_tests {
+    use glam::Quat;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::skeleton::SkeletonBuilder;
+
+    fn new_skeleton(scale: f32, extra: Option<&str>) -> Skeleton {
+        let mut builder = SkeletonBuilder::new();
+        let rest =
+            |x: f32, y: f32| AosTransform::from_translation_rotation(Vec3::new(x, y, 0.0) * scale, Quat::IDENTITY);
+        let hips = builder.add_joint("hips", -1, rest(0.0, 1.0));
+        let spine = builder.add_joint("spine", hips, rest(0.0, 0.5));
+        builder.add_joint("head", spine, rest(0.0, 0.3));
+        let arm = builder.add_joint("arm", spine, rest(0.2, 0.2));
+        builder.add_joint("hand", arm, rest(0.4, 0.0));
+        if let Some(name) = extra {
+            builder.add_joint(name, hips, rest(-0.1, 0.0));
+        }
+        return builder.build().unwrap();
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_validity() {
+        let skeleton = new_skeleton(1.0, None);
+        let input = vec![SoaTransform::default(); skeleton.num_soa_joints()];
+        let mut output = vec![SoaTransform::default(); skeleton.num_soa_joints()];
+
+        let mut job: RetargetJobRef = RetargetJob::default();
+        job.set_source_skeleton(&skeleton);
+        job.set_input(&input);
+        job.set_output(&mut output);
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        job.set_target_skeleton(&skeleton);
+        assert!(job.validate());
+        job.set_input(&[]);
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_retarget() {
+        let source = new_skeleton(1.0, Some("tail"));
+        let target = new_skeleton(2.0, Some("sword"));
+
+        let mut input = source.joint_rest_poses().to_vec();
+        for idx in 0..source.num_joints() {
+            let mut transform = input[idx / 4].col(idx % 4);
+            transform.rotation = Quat::from_rotation_z(0.1 * (idx + 1) as f32);
+            transform.translation *= 1.5;
+            input[idx / 4].set_col(idx % 4, &transform);
+        }
+
+        let output = retarget_pose(&source, &target, &input).unwrap();
+        for idx in 0..target.num_joints() {
+            let name = target.name_by_joint(idx as i16).unwrap();
+            let actual = output[idx / 4].col(idx % 4);
+            let rest = target.joint_rest_poses()[idx / 4].col(idx % 4);
+            match source.joint_by_name(name) {
+                Some(src) => {
+                    let src = src as usize;
+                    let expected = input[src / 4].col(src % 4);
+                    assert!(actual.rotation.abs_diff_eq(expected.rotation, 1e-6), "{}", name);
+                    assert!(
+                        actual.translation.abs_diff_eq(expected.translation * 2.0, 1e-5),
+                        "{}",
+                        name
+                    );
+                    assert!((actual.translation.length() - rest.translation.length() * 1.5).abs() < 1e-5);
+                    assert_eq!(actual.scale, Vec3::ONE);
+                }
+                None => {
+                    assert_eq!(name, "sword");
+                    assert_eq!(actual, rest);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//!
+//! Sampled Pose.
+//!
+
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+
+use crate::base::{ObSliceRef, ObSliceRefMut, OzzBuf, OzzError, OzzMutBuf};
+use crate::math::{AosTransform, SoaTransform};
+use crate::skeleton::Skeleton;
+
+///
+/// A local-space pose, owning its SoA buffer sized for a skeleton.
+///
+/// `SampledPose` implements `OzzBuf<SoaTransform>` and `OzzMutBuf<SoaTransform>`, so it can be the output
+/// of `SamplingJob` or `BlendingJob`, and the input of `LocalToModelJob`, either owned by the job or
+/// borrowed (`&SampledPose`, `&mut SampledPose`). Joints are read and written one by one in AoS form, or
+/// through the SoA slice.
+///
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SampledPose {
+    num_joints: usize,
+    poses: Vec<SoaTransform>,
+}
+
+impl SampledPose {
+    /// Creates a `SampledPose` sized for `skeleton`, initialized to its rest pose.
+    pub fn new(skeleton: &Skeleton) -> SampledPose {
+        return SampledPose {
+            num_joints: skeleton.num_joints(),
+            poses: skeleton.joint_rest_poses().to_vec(),
+        };
+    }
+
+    /// Gets the number of joints of `SampledPose`.
+    #[inline]
+    pub fn num_joints(&self) -> usize {
+        return self.num_joints;
+    }
+
+    /// Gets the number of soa joints of `SampledPose`.
+    #[inline]
+    pub fn num_soa_joints(&self) -> usize {
+        return self.poses.len();
+    }
+
+    /// Gets the local transform of joint `idx`.
+    ///
+    /// Panics if `idx` is out of range.
+    #[inline]
+    pub fn get_joint(&self, idx: usize) -> AosTransform {
+        assert!(idx < self.num_joints, "joint {} out of range", idx);
+        return self.poses[idx / 4].col(idx % 4);
+    }
+
+    /// Sets the local transform of joint `idx`.
+    ///
+    /// Panics if `idx` is out of range.
+    #[inline]
+    pub fn set_joint(&mut self, idx: usize, transform: &AosTransform) {
+        assert!(idx < self.num_joints, "joint {} out of range", idx);
+        self.poses[idx / 4].set_col(idx % 4, transform);
+    }
+
+    /// Gets the SoA local transforms of `SampledPose`.
+    #[inline]
+    pub fn as_slice(&self) -> &[SoaTransform] {
+        return &self.poses;
+    }
+
+    /// Gets the mutable SoA local transforms of `SampledPose`.
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [SoaTransform] {
+        return &mut self.poses;
+    }
+}
+
+impl Deref for SampledPose {
+    type Target = [SoaTransform];
+
+    #[inline]
+    fn deref(&self) -> &[SoaTransform] {
+        return &self.poses;
+    }
+}
+
+impl DerefMut for SampledPose {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [SoaTransform] {
+        return &mut self.poses;
+    }
+}
+
+impl OzzBuf<SoaTransform> for SampledPose {
+    type Buf<'t> = ObSliceRef<'t, SoaTransform>;
+
+    #[inline(always)]
+    fn buf(&self) -> Result<ObSliceRef<'_, SoaTransform>, OzzError> {
+        return self.poses.buf();
+    }
+}
+
+impl OzzMutBuf<SoaTransform> for SampledPose {
+    type MutBuf<'t> = ObSliceRefMut<'t, SoaTransform>;
+
+    #[inline(always)]
+    fn mut_buf(&mut self) -> Result<ObSliceRefMut<'_, SoaTransform>, OzzError> {
+        return self.poses.mut_buf();
+    }
+}
+
+impl<'a> OzzBuf<SoaTransform> for &'a SampledPose {
+    type Buf<'b>
+        = ObSliceRef<'b, SoaTransform>
+    where
+        'a: 'b;
+
+    #[inline(always)]
+    fn buf(&self) -> Result<ObSliceRef<'_, SoaTransform>, OzzError> {
+        return self.poses.buf();
+    }
+}
+
+impl<'a> OzzBuf<SoaTransform> for &'a mut SampledPose {
+    type Buf<'b>
+        = ObSliceRef<'b, SoaTransform>
+    where
+        'a: 'b;
+
+    #[inline(always)]
+    fn buf(&self) -> Result<ObSliceRef<'_, SoaTransform>, OzzError> {
+        return self.poses.buf();
+    }
+}
+
+impl<'a> OzzMutBuf<SoaTransform> for &'a mut SampledPose {
+    type MutBuf<'b>
+        = ObSliceRefMut<'b, SoaTransform>
+    where
+        'a: 'b;
+
+    #[inline(always)]
+    fn mut_buf(&mut self) -> Result<ObSliceRefMut<'_, SoaTransform>, OzzError> {
+        return self.poses.mut_buf();
+    }
+}
+
+#[cfg(test)]
+mod sampled_pose_tests {
+    use glam::{Mat4, Quat, Vec3};
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::animation::Animation;
+    use crate::local_to_model_job::LocalToModelJob;
+    use crate::sampling_job::{SamplingContext, SamplingJob, SamplingJobRef};
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_joints() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let mut pose = SampledPose::new(&skeleton);
+        assert_eq!(pose.num_joints(), skeleton.num_joints());
+        assert_eq!(pose.num_soa_joints(), skeleton.num_soa_joints());
+        assert_eq!(pose.as_slice(), skeleton.joint_rest_poses());
+
+        let transform = AosTransform::new(Vec3::new(1.0, 2.0, 3.0), Quat::from_rotation_x(0.5), Vec3::splat(2.0));
+        pose.set_joint(5, &transform);
+        assert_eq!(pose.get_joint(5), transform);
+        assert_eq!(pose[1].col(1), transform);
+        assert_eq!(pose.get_joint(4), skeleton.joint_rest_poses()[1].col(0));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_sampling() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let mut expected = vec![SoaTransform::default(); skeleton.num_soa_joints()];
+        let mut job: SamplingJobRef = SamplingJob::default();
+        job.set_animation(&animation);
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_output(&mut expected);
+        job.set_ratio(0.3);
+        job.run().unwrap();
+
+        // Owned by the job.
+        let mut job: SamplingJob<&Animation, SampledPose> = SamplingJob::default();
+        job.set_animation(&animation);
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_output(SampledPose::new(&skeleton));
+        job.set_ratio(0.3);
+        job.run().unwrap();
+        let pose = job.output().unwrap();
+        assert_eq!(pose.as_slice(), &expected[..]);
+        assert_eq!(pose.get_joint(9), expected[2].col(1));
+
+        // Borrowed by the jobs.
+        let mut pose = SampledPose::new(&skeleton);
+        let mut job: SamplingJob<&Animation, &mut SampledPose> = SamplingJob::default();
+        job.set_animation(&animation);
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_output(&mut pose);
+        job.set_ratio(0.3);
+        job.run().unwrap();
+        assert_eq!(pose.get_joint(9), expected[2].col(1));
+
+        let mut models = vec![Mat4::IDENTITY; skeleton.num_joints()];
+        let mut job: LocalToModelJob<&Skeleton, &SampledPose, &mut [Mat4]> = LocalToModelJob::default();
+        job.set_skeleton(&skeleton);
+        job.set_input(&pose);
+        job.set_output(&mut models);
+        job.run().unwrap();
+    }
+}
@@ -2,17 +2,33 @@
 //! Sampling Job.
 //!
 
-use std::alloc::{self, Layout};
-use std::cell::RefCell;
-use std::fmt::{Debug, Formatter};
-use std::rc::Rc;
-use std::simd::prelude::*;
-use std::sync::{Arc, RwLock};
-use std::{mem, slice};
-
-use crate::animation::{Animation, Float3Key, QuaternionKey};
+use alloc::alloc::Layout;
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt::{Debug, Formatter};
+use core::mem;
+use core::simd::prelude::*;
+#[cfg(feature = "rkyv")]
+use core::slice;
+use glam::Mat4;
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use crate::animation::{Animation, AnimationData, Float3Key, QuaternionKey};
 use crate::base::{OzzError, OzzMutBuf, OzzObj};
-use crate::math::{f32_clamp_or_max, SoaQuat, SoaTransform, SoaVec3};
+use crate::interpolator::{Interpolator, SoaInterpolator};
+use crate::joint_mask::JointMask;
+use crate::local_to_model_job::{LocalToModelJob, LocalToModelJobRef};
+use crate::math::{f32_clamp_or_max, AosTransform, SoaQuat, SoaTransform, SoaVec3};
+use crate::motion_extraction_job::{MotionExtractionJob, MotionExtractionJobRef};
+use crate::pose_cache::PoseCache;
+use crate::pose_utils::sanitize_padding;
+use crate::skeleton::Skeleton;
+use crate::track::Track;
+use crate::track_sampling_job::TrackSamplingJobRef;
 
 /// Soa hot `SoaVec3` data to interpolate.
 #[repr(C)]
@@ -122,9 +138,9 @@ const _: () = {
 
 #[cfg(feature = "serde")]
 mod serde_interp {
+    use core::simd::prelude::*;
     use serde::ser::SerializeSeq;
     use serde::{Deserialize, Deserializer, Serializer};
-    use std::simd::prelude::*;
 
     pub(crate) fn serialize<S: Serializer>(value: &[f32x4; 2], serializer: S) -> Result<S::Ok, S::Error> {
         let mut seq = serializer.serialize_seq(Some(2))?;
@@ -168,17 +184,48 @@ impl Default for SamplingContextInner {
             max_soa_tracks: 0,
             num_outdated: 0,
 
-            translations_ptr: std::ptr::null_mut(),
-            rotations_ptr: std::ptr::null_mut(),
-            scales_ptr: std::ptr::null_mut(),
+            translations_ptr: core::ptr::null_mut(),
+            rotations_ptr: core::ptr::null_mut(),
+            scales_ptr: core::ptr::null_mut(),
 
-            translation_keys_ptr: std::ptr::null_mut(),
-            rotation_keys_ptr: std::ptr::null_mut(),
-            scale_keys_ptr: std::ptr::null_mut(),
+            translation_keys_ptr: core::ptr::null_mut(),
+            rotation_keys_ptr: core::ptr::null_mut(),
+            scale_keys_ptr: core::ptr::null_mut(),
 
-            outdated_translations_ptr: std::ptr::null_mut(),
-            outdated_rotations_ptr: std::ptr::null_mut(),
-            outdated_scales_ptr: std::ptr::null_mut(),
+            outdated_translations_ptr: core::ptr::null_mut(),
+            outdated_rotations_ptr: core::ptr::null_mut(),
+            outdated_scales_ptr: core::ptr::null_mut(),
+        };
+    }
+}
+
+// Soa tracks sampled by a partial `SamplingContext`, see `SamplingContext::for_joints`.
+#[derive(Debug, Clone, PartialEq)]
+struct TrackSubset {
+    num_tracks: usize,
+    mask: JointMask,
+    // Animation soa track of each context soa entry.
+    soa_tracks: Vec<u16>,
+    // Context soa entry of each animation soa track, if sampled.
+    slots: Vec<Option<u16>>,
+}
+
+impl TrackSubset {
+    fn new(num_tracks: usize, mask: &JointMask) -> TrackSubset {
+        let num_soa_tracks = num_tracks.div_ceil(4);
+        let mut soa_tracks = Vec::new();
+        let mut slots = vec![None; num_soa_tracks];
+        for (soa, slot) in slots.iter_mut().enumerate() {
+            if mask.soa_lanes(soa) != 0 {
+                *slot = Some(soa_tracks.len() as u16);
+                soa_tracks.push(soa as u16);
+            }
+        }
+        return TrackSubset {
+            num_tracks,
+            mask: mask.clone(),
+            soa_tracks,
+            slots,
         };
     }
 }
@@ -193,18 +240,21 @@ pub struct SamplingContext {
     translation_cursor: usize,
     rotation_cursor: usize,
     scale_cursor: usize,
+
+    subset: Option<TrackSubset>,
 }
 
 unsafe impl Send for SamplingContext {}
 unsafe impl Sync for SamplingContext {}
 
 impl Debug for SamplingContext {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         return f
             .debug_struct("SamplingContext")
             .field("mem", &self.inner)
             .field("animation_id", &self.animation_id)
             .field("ratio", &self.ratio)
+            .field("joint_mask", &self.joint_mask())
             .finish();
     }
 }
@@ -232,6 +282,7 @@ impl Clone for SamplingContext {
         ctx.outdated_rotations_mut().copy_from_slice(self.outdated_rotations());
         ctx.outdated_scales_mut().copy_from_slice(self.outdated_scales());
 
+        ctx.subset = self.subset.clone();
         return ctx;
     }
 }
@@ -254,7 +305,8 @@ impl PartialEq for SamplingContext {
             && self.scale_cursor == other.scale_cursor
             && self.outdated_translations() == other.outdated_translations()
             && self.outdated_rotations() == other.outdated_rotations()
-            && self.outdated_scales() == other.outdated_scales();
+            && self.outdated_scales() == other.outdated_scales()
+            && self.subset == other.subset;
     }
 }
 
@@ -263,9 +315,9 @@ impl Drop for SamplingContext {
         if !self.inner.is_null() {
             unsafe {
                 let layout = Layout::from_size_align_unchecked(self.size(), mem::size_of::<f32x4>());
-                alloc::dealloc(self.inner as *mut u8, layout);
+                alloc::alloc::dealloc(self.inner as *mut u8, layout);
             }
-            self.inner = std::ptr::null_mut();
+            self.inner = core::ptr::null_mut();
         }
     }
 }
@@ -292,7 +344,7 @@ impl SamplingContext {
 
         unsafe {
             let layout = Layout::from_size_align_unchecked(size, mem::size_of::<f32x4>());
-            let mut ptr = alloc::alloc(layout);
+            let mut ptr = alloc::alloc::alloc(layout);
             let ctx = SamplingContext {
                 inner: ptr as *const SamplingContextInner,
                 animation_id: 0,
@@ -300,6 +352,7 @@ impl SamplingContext {
                 translation_cursor: 0,
                 rotation_cursor: 0,
                 scale_cursor: 0,
+                subset: None,
             };
 
             let inner = &mut *(ptr as *mut SamplingContextInner);
@@ -338,9 +391,25 @@ impl SamplingContext {
     /// Create a new `SamplingContext` from an `Animation`.
     ///
     /// * `animation` - The animation to sample. Use `animation.num_tracks()` as max_tracks.
-    pub fn from_animation(animation: &Animation) -> SamplingContext {
+    pub fn from_animation(animation: &impl AnimationData) -> SamplingContext {
         let mut ctx = SamplingContext::new(animation.num_tracks());
-        ctx.animation_id = animation as *const _ as u64;
+        ctx.animation_id = animation.id();
+        return ctx;
+    }
+
+    /// Create a new partial `SamplingContext`, sampling only the joints of `mask`.
+    ///
+    /// * `num_tracks` - The number of tracks of the animations to sample.
+    /// * `mask` - The joints to sample, ordered like the animation tracks.
+    ///
+    /// The context memory is only allocated for the soa tracks (groups of 4 joints) containing masked joints.
+    /// Sampling with a partial context only writes masked joints of the output, others are left untouched.
+    /// It can only sample animations of `num_tracks` tracks.
+    /// The joint subset isn't serialized (serde or rkyv), a deserialized context samples all its tracks.
+    pub fn for_joints(num_tracks: usize, mask: &JointMask) -> SamplingContext {
+        let subset = TrackSubset::new(num_tracks, mask);
+        let mut ctx = SamplingContext::new(subset.soa_tracks.len() * 4);
+        ctx.subset = Some(subset);
         return ctx;
     }
 
@@ -353,6 +422,14 @@ impl SamplingContext {
         self.scale_cursor = 0;
     }
 
+    /// Resets the `SamplingContext` cached cursors and keyframes, so that the next sample starts cold
+    /// (like a fresh context). Useful when reusing a context for another animation, or after a large seek.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.clear();
+        self.ratio = 0.0;
+    }
+
     /// Clone the `SamplingContext` without the animation id. Usually used for serialization.
     #[inline]
     pub fn clone_without_animation_id(&self) -> SamplingContext {
@@ -379,6 +456,70 @@ impl SamplingContext {
         return self.inner().max_tracks;
     }
 
+    /// The number of tracks the context is sized for (aligned to 4 * SoA), same as `max_tracks`. A partial
+    /// context (see `SamplingContext::for_joints`) is sized for its sampled soa tracks only.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        return self.max_tracks();
+    }
+
+    /// The joints sampled by a partial context, `None` if the context samples all joints.
+    /// See `SamplingContext::for_joints`.
+    #[inline]
+    pub fn joint_mask(&self) -> Option<&JointMask> {
+        return self.subset.as_ref().map(|subset| &subset.mask);
+    }
+
+    // Tests if the context can sample an animation of `num_soa_tracks`.
+    #[inline]
+    fn can_sample(&self, num_soa_tracks: usize) -> bool {
+        return match &self.subset {
+            Some(subset) => subset.slots.len() == num_soa_tracks,
+            None => self.max_soa_tracks() >= num_soa_tracks,
+        };
+    }
+
+    // The number of soa entries sampled for an animation of `num_soa_tracks`.
+    #[inline]
+    fn num_slots(&self, num_soa_tracks: usize) -> usize {
+        return match &self.subset {
+            Some(subset) => subset.soa_tracks.len(),
+            None => num_soa_tracks,
+        };
+    }
+
+    // The animation soa track of soa entry `slot`.
+    #[inline]
+    fn slot_soa_track(&self, slot: usize) -> usize {
+        return match &self.subset {
+            Some(subset) => subset.soa_tracks[slot] as usize,
+            None => slot,
+        };
+    }
+
+    // The context track of animation track `track`, `None` if it isn't sampled.
+    #[inline]
+    fn track_slot(&self, track: usize) -> Option<usize> {
+        return match &self.subset {
+            Some(subset) => subset.slots[track / 4].map(|slot| slot as usize * 4 + track % 4),
+            None => Some(track),
+        };
+    }
+
+    // Tests if the cached keyframes of the `num_soa_tracks` animation soa tracks all start before `ratio`,
+    // so that they still bracket `ratio` after a backward step from the context ratio.
+    fn brackets(&self, num_soa_tracks: usize, ratio: f32) -> bool {
+        if self.translation_cursor == 0 || self.rotation_cursor == 0 || self.scale_cursor == 0 {
+            return false;
+        }
+        let ratio4 = f32x4::splat(ratio);
+        return (0..self.num_slots(num_soa_tracks)).all(|idx| {
+            return self.translations()[idx].ratio[0].simd_le(ratio4).all()
+                && self.rotations()[idx].ratio[0].simd_le(ratio4).all()
+                && self.scales()[idx].ratio[0].simd_le(ratio4).all();
+        });
+    }
+
     /// The number of tracks that are outdated.
     #[inline]
     pub fn num_outdated(&self) -> usize {
@@ -389,117 +530,117 @@ impl SamplingContext {
     #[inline]
     pub fn translations(&self) -> &[InterpSoaFloat3] {
         let inner = self.inner();
-        return unsafe { std::slice::from_raw_parts(inner.translations_ptr, inner.max_soa_tracks) };
+        return unsafe { core::slice::from_raw_parts(inner.translations_ptr, inner.max_soa_tracks) };
     }
 
     #[inline]
     fn translations_mut(&mut self) -> &mut [InterpSoaFloat3] {
         let inner = self.inner();
-        return unsafe { std::slice::from_raw_parts_mut(inner.translations_ptr, inner.max_soa_tracks) };
+        return unsafe { core::slice::from_raw_parts_mut(inner.translations_ptr, inner.max_soa_tracks) };
     }
 
     /// Soa hot data to interpolate.
     #[inline]
     pub fn rotations(&self) -> &[InterpSoaQuaternion] {
         let inner = self.inner();
-        return unsafe { std::slice::from_raw_parts(inner.rotations_ptr, inner.max_soa_tracks) };
+        return unsafe { core::slice::from_raw_parts(inner.rotations_ptr, inner.max_soa_tracks) };
     }
 
     #[inline]
     fn rotations_mut(&mut self) -> &mut [InterpSoaQuaternion] {
         let inner = self.inner();
-        return unsafe { std::slice::from_raw_parts_mut(inner.rotations_ptr, inner.max_soa_tracks) };
+        return unsafe { core::slice::from_raw_parts_mut(inner.rotations_ptr, inner.max_soa_tracks) };
     }
 
     /// Soa hot data to interpolate.
     #[inline]
     pub fn scales(&self) -> &[InterpSoaFloat3] {
         let inner = self.inner();
-        return unsafe { std::slice::from_raw_parts(inner.scales_ptr, inner.max_soa_tracks) };
+        return unsafe { core::slice::from_raw_parts(inner.scales_ptr, inner.max_soa_tracks) };
     }
 
     #[inline]
     fn scales_mut(&mut self) -> &mut [InterpSoaFloat3] {
         let inner = self.inner();
-        return unsafe { std::slice::from_raw_parts_mut(inner.scales_ptr, inner.max_soa_tracks) };
+        return unsafe { core::slice::from_raw_parts_mut(inner.scales_ptr, inner.max_soa_tracks) };
     }
 
     /// The keys in the animation that are valid for the current time ratio.
     #[inline]
     pub fn translation_keys(&self) -> &[i32] {
         let inner = self.inner();
-        return unsafe { std::slice::from_raw_parts(inner.translation_keys_ptr, inner.max_tracks * 2) };
+        return unsafe { core::slice::from_raw_parts(inner.translation_keys_ptr, inner.max_tracks * 2) };
     }
 
     #[inline]
     fn translation_keys_mut(&mut self) -> &mut [i32] {
         let inner = self.inner();
-        return unsafe { std::slice::from_raw_parts_mut(inner.translation_keys_ptr, inner.max_tracks * 2) };
+        return unsafe { core::slice::from_raw_parts_mut(inner.translation_keys_ptr, inner.max_tracks * 2) };
     }
 
     /// The keys in the animation that are valid for the current time ratio.
     #[inline]
     pub fn rotation_keys(&self) -> &[i32] {
         let inner = self.inner();
-        return unsafe { std::slice::from_raw_parts(inner.rotation_keys_ptr, inner.max_tracks * 2) };
+        return unsafe { core::slice::from_raw_parts(inner.rotation_keys_ptr, inner.max_tracks * 2) };
     }
 
     #[inline]
     fn rotation_keys_mut(&mut self) -> &mut [i32] {
         let inner = self.inner();
-        return unsafe { std::slice::from_raw_parts_mut(inner.rotation_keys_ptr, inner.max_tracks * 2) };
+        return unsafe { core::slice::from_raw_parts_mut(inner.rotation_keys_ptr, inner.max_tracks * 2) };
     }
 
     /// The keys in the animation that are valid for the current time ratio.
     #[inline]
     pub fn scale_keys(&self) -> &[i32] {
         let inner = self.inner();
-        return unsafe { std::slice::from_raw_parts(inner.scale_keys_ptr, inner.max_tracks * 2) };
+        return unsafe { core::slice::from_raw_parts(inner.scale_keys_ptr, inner.max_tracks * 2) };
     }
 
     #[inline]
     fn scale_keys_mut(&mut self) -> &mut [i32] {
         let inner = self.inner();
-        return unsafe { std::slice::from_raw_parts_mut(inner.scale_keys_ptr, inner.max_tracks * 2) };
+        return unsafe { core::slice::from_raw_parts_mut(inner.scale_keys_ptr, inner.max_tracks * 2) };
     }
 
     /// Outdated soa entries. One bit per soa entry (32 joints per byte).
     #[inline]
     pub fn outdated_translations(&self) -> &[u8] {
         let inner = self.inner();
-        return unsafe { std::slice::from_raw_parts(inner.outdated_translations_ptr, inner.num_outdated) };
+        return unsafe { core::slice::from_raw_parts(inner.outdated_translations_ptr, inner.num_outdated) };
     }
 
     #[inline]
     fn outdated_translations_mut(&mut self) -> &mut [u8] {
         let inner = self.inner();
-        return unsafe { std::slice::from_raw_parts_mut(inner.outdated_translations_ptr, inner.num_outdated) };
+        return unsafe { core::slice::from_raw_parts_mut(inner.outdated_translations_ptr, inner.num_outdated) };
     }
 
     /// Outdated soa entries. One bit per soa entry (32 joints per byte).
     #[inline]
     pub fn outdated_rotations(&self) -> &[u8] {
         let inner = self.inner();
-        return unsafe { std::slice::from_raw_parts(inner.outdated_rotations_ptr, inner.num_outdated) };
+        return unsafe { core::slice::from_raw_parts(inner.outdated_rotations_ptr, inner.num_outdated) };
     }
 
     #[inline]
     fn outdated_rotations_mut(&mut self) -> &mut [u8] {
         let inner = self.inner();
-        return unsafe { std::slice::from_raw_parts_mut(inner.outdated_rotations_ptr, inner.num_outdated) };
+        return unsafe { core::slice::from_raw_parts_mut(inner.outdated_rotations_ptr, inner.num_outdated) };
     }
 
     /// Outdated soa entries. One bit per soa entry (32 joints per byte).
     #[inline]
     pub fn outdated_scales(&self) -> &[u8] {
         let inner = self.inner();
-        return unsafe { std::slice::from_raw_parts(inner.outdated_scales_ptr, inner.num_outdated) };
+        return unsafe { core::slice::from_raw_parts(inner.outdated_scales_ptr, inner.num_outdated) };
     }
 
     #[inline]
     fn outdated_scales_mut(&mut self) -> &mut [u8] {
         let inner = self.inner();
-        return unsafe { std::slice::from_raw_parts_mut(inner.outdated_scales_ptr, inner.num_outdated) };
+        return unsafe { core::slice::from_raw_parts_mut(inner.outdated_scales_ptr, inner.num_outdated) };
     }
 
     /// The unique identifier of the animation that the context is sampling.
@@ -724,6 +865,8 @@ const _: () = {
             resolver.outdated_scales_pos = serializer.align_for::<u8>()?;
             serializer.write(self.outdated_scales())?;
 
+            // The archived context must be aligned to 16 bytes, see `CheckBytes`.
+            serializer.align(mem::align_of::<f32x4>())?;
             return Ok(resolver);
         }
     }
@@ -816,7 +959,7 @@ const _: () = {
     impl<'de> Visitor<'de> for SamplingContextVisitor {
         type Value = SamplingContext;
 
-        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
             return formatter.write_str("struct SamplingContext");
         }
 
@@ -888,6 +1031,71 @@ const _: () = {
     }
 };
 
+///
+/// Sampling statistics, returned by `SamplingJob::run_instrumented` (`instrument` feature).
+///
+/// Useful to profile clips that thrash the `SamplingContext` keyframes cache.
+///
+#[cfg(feature = "instrument")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct JobStats {
+    /// Number of keyframes decompressed into the context.
+    pub keyframes_decoded: usize,
+
+    /// Number of keyframes fetched from the animation because they weren't cached in the context.
+    /// A context reset (first sample, animation change or backward sampling) fetches all start keyframes.
+    pub cache_misses: usize,
+
+    /// true if the pose is copied from the job `PoseCache`, nothing is decoded.
+    pub pose_cache_hit: bool,
+}
+
+///
+/// Time ratio remapping curve of `SamplingJob`, see `SamplingJob::set_ratio_remap`.
+///
+/// Remapped ratios are clamped to the unit interval 0.0-1.0.
+///
+#[derive(Clone)]
+pub enum RatioRemap {
+    /// Remaps ratios through a float track, sampled like `TrackSamplingJob` does.
+    Track(Arc<Track<f32>>),
+    /// Remaps ratios through a function.
+    Fn(Arc<dyn Fn(f32) -> f32 + Send + Sync>),
+}
+
+impl Debug for RatioRemap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        return match self {
+            RatioRemap::Track(track) => f.debug_tuple("Track").field(track).finish(),
+            RatioRemap::Fn(_) => f.write_str("Fn(..)"),
+        };
+    }
+}
+
+impl RatioRemap {
+    /// Creates a `RatioRemap` from a function.
+    #[inline]
+    pub fn from_fn(remap: impl Fn(f32) -> f32 + Send + Sync + 'static) -> RatioRemap {
+        return RatioRemap::Fn(Arc::new(remap));
+    }
+
+    /// Remaps `ratio`, the result is clamped to the unit interval 0.0-1.0.
+    pub fn remap(&self, ratio: f32) -> f32 {
+        let remapped = match self {
+            RatioRemap::Track(track) => {
+                let mut job: TrackSamplingJobRef<f32> = TrackSamplingJobRef::default();
+                job.set_track(track);
+                job.set_ratio(ratio);
+                // A track is always valid.
+                let _ = job.run();
+                job.result()
+            }
+            RatioRemap::Fn(remap) => remap(ratio),
+        };
+        return f32_clamp_or_max(remapped, 0.0f32, 1.0f32);
+    }
+}
+
 ///
 /// Samples an animation at a given time ratio in the unit interval 0.0-1.0 (where 0.0 is the beginning of
 /// the animation, 1.0 is the end), to output the corresponding posture in local-space.
@@ -902,37 +1110,58 @@ const _: () = {
 #[derive(Debug)]
 pub struct SamplingJob<A = Rc<Animation>, O = Rc<RefCell<Vec<SoaTransform>>>>
 where
-    A: OzzObj<Animation>,
+    A: AnimationData,
     O: OzzMutBuf<SoaTransform>,
 {
     animation: Option<A>,
     context: Option<SamplingContext>,
+    pose_cache: Option<PoseCache>,
     ratio: f32,
+    previous_ratio: Option<f32>,
+    ratio_remap: Option<RatioRemap>,
+    interpolator: Option<Arc<dyn SoaInterpolator>>,
+    fill_rest: Option<Vec<SoaTransform>>,
+    sanitize_padding: bool,
+    num_skeleton_joints: Option<usize>,
     output: Option<O>,
+
+    // Partial context and scratch pose of `run_sparse`.
+    sparse_context: Option<SamplingContext>,
+    sparse_pose: Vec<SoaTransform>,
 }
 
 pub type SamplingJobRef<'t> = SamplingJob<&'t Animation, &'t mut [SoaTransform]>;
 pub type SamplingJobRc = SamplingJob<Rc<Animation>, Rc<RefCell<Vec<SoaTransform>>>>;
+#[cfg(feature = "std")]
 pub type SamplingJobArc = SamplingJob<Arc<Animation>, Arc<RwLock<Vec<SoaTransform>>>>;
 
 impl<A, O> Default for SamplingJob<A, O>
 where
-    A: OzzObj<Animation>,
+    A: AnimationData,
     O: OzzMutBuf<SoaTransform>,
 {
     fn default() -> SamplingJob<A, O> {
         return SamplingJob {
             animation: None,
             context: None,
+            pose_cache: None,
             ratio: 0.0,
+            previous_ratio: None,
+            ratio_remap: None,
+            interpolator: None,
+            fill_rest: None,
+            sanitize_padding: false,
+            num_skeleton_joints: None,
             output: None,
+            sparse_context: None,
+            sparse_pose: Vec::new(),
         };
     }
 }
 
 impl<A, O> SamplingJob<A, O>
 where
-    A: OzzObj<Animation>,
+    A: AnimationData,
     O: OzzMutBuf<SoaTransform>,
 {
     /// Gets animation to sample of `SamplingJob`.
@@ -977,6 +1206,39 @@ where
         return self.context.take();
     }
 
+    /// Gets pose cache of `SamplingJob`. See [PoseCache].
+    #[inline]
+    pub fn pose_cache(&self) -> Option<&PoseCache> {
+        return self.pose_cache.as_ref();
+    }
+
+    /// Gets mutable pose cache of `SamplingJob`. See [PoseCache].
+    #[inline]
+    pub fn pose_cache_mut(&mut self) -> Option<&mut PoseCache> {
+        return self.pose_cache.as_mut();
+    }
+
+    /// Sets pose cache of `SamplingJob`. See [PoseCache].
+    ///
+    /// Optional. When set, sampled poses are cached, and samples of a cached (animation, ratio) pair
+    /// copy the cached pose to the output without using the context.
+    #[inline]
+    pub fn set_pose_cache(&mut self, cache: PoseCache) {
+        self.pose_cache = Some(cache);
+    }
+
+    /// Clears pose cache of `SamplingJob`. See [PoseCache].
+    #[inline]
+    pub fn clear_pose_cache(&mut self) {
+        self.pose_cache = None;
+    }
+
+    /// Takes pose cache of `SamplingJob`. See [PoseCache].
+    #[inline]
+    pub fn take_pose_cache(&mut self) -> Option<PoseCache> {
+        return self.pose_cache.take();
+    }
+
     /// Gets the time ratio of `SamplingJob`.
     #[inline]
     pub fn ratio(&self) -> f32 {
@@ -996,6 +1258,158 @@ where
         self.ratio = f32_clamp_or_max(ratio, 0.0f32, 1.0f32);
     }
 
+    /// Gets previous ratio of `SamplingJob`.
+    #[inline]
+    pub fn previous_ratio(&self) -> Option<f32> {
+        return self.previous_ratio;
+    }
+
+    /// Sets previous ratio of `SamplingJob`.
+    ///
+    /// Optional hint, the ratio of the previous sampling, clamped in range 0.0-1.0. The context keyframes
+    /// cache is scanned forward, so a backward step resets it by default. With a previous ratio greater
+    /// than the ratio (backward playback, ping-pong or scrubbing), the context is kept when its cached
+    /// keyframes still bracket the ratio, and only reset otherwise.
+    #[inline]
+    pub fn set_previous_ratio(&mut self, ratio: f32) {
+        self.previous_ratio = Some(f32_clamp_or_max(ratio, 0.0f32, 1.0f32));
+    }
+
+    /// Clears previous ratio of `SamplingJob`.
+    #[inline]
+    pub fn clear_previous_ratio(&mut self) {
+        self.previous_ratio = None;
+    }
+
+    /// Gets ratio remap of `SamplingJob`. See [RatioRemap].
+    #[inline]
+    pub fn ratio_remap(&self) -> Option<&RatioRemap> {
+        return self.ratio_remap.as_ref();
+    }
+
+    /// Sets ratio remap of `SamplingJob`. See [RatioRemap].
+    ///
+    /// Optional. When set, the job ratio is remapped through this curve before sampling, so that
+    /// sampling ratio `r` gives the pose at `remap(r)`. Allows to ease in/out or time-warp a clip without
+    /// re-authoring it. Clears the pose cache, if any.
+    #[inline]
+    pub fn set_ratio_remap(&mut self, remap: RatioRemap) {
+        self.ratio_remap = Some(remap);
+        self.invalidate_pose_cache();
+    }
+
+    /// Clears ratio remap of `SamplingJob`. See [RatioRemap]. Clears the pose cache, if any.
+    #[inline]
+    pub fn clear_ratio_remap(&mut self) {
+        self.ratio_remap = None;
+        self.invalidate_pose_cache();
+    }
+
+    /// Gets the time ratio actually sampled by `SamplingJob`, the job ratio passed through the ratio
+    /// remap if any.
+    #[inline]
+    pub fn sampled_ratio(&self) -> f32 {
+        return self.remap_ratio(self.ratio);
+    }
+
+    #[inline]
+    fn remap_ratio(&self, ratio: f32) -> f32 {
+        return match &self.ratio_remap {
+            Some(remap) => remap.remap(ratio),
+            None => ratio,
+        };
+    }
+
+    /// Gets interpolator of `SamplingJob`. See [Interpolator].
+    #[inline]
+    pub fn interpolator(&self) -> Option<&Arc<dyn SoaInterpolator>> {
+        return self.interpolator.as_ref();
+    }
+
+    /// Sets interpolator of `SamplingJob`. See [Interpolator].
+    ///
+    /// Optional. Replaces the built-in interpolation between keyframes (lerp for translations and scales,
+    /// nlerp for rotations). Clears the pose cache, if any.
+    #[inline]
+    pub fn set_interpolator(&mut self, interpolator: Arc<dyn SoaInterpolator>) {
+        self.interpolator = Some(interpolator);
+        self.invalidate_pose_cache();
+    }
+
+    /// Clears interpolator of `SamplingJob`, restoring the built-in interpolation. Clears the pose cache,
+    /// if any.
+    #[inline]
+    pub fn clear_interpolator(&mut self) {
+        self.interpolator = None;
+        self.invalidate_pose_cache();
+    }
+
+    // Cached poses are keyed by animation and ratio only, they are stale once the sampling settings change.
+    #[inline]
+    fn invalidate_pose_cache(&mut self) {
+        if let Some(cache) = self.pose_cache.as_mut() {
+            cache.clear();
+        }
+    }
+
+    /// Gets the rest pose filling untracked joints of `SamplingJob`.
+    #[inline]
+    pub fn fill_rest(&self) -> Option<&[SoaTransform]> {
+        return self.fill_rest.as_deref();
+    }
+
+    /// Sets the rest pose filling untracked joints of `SamplingJob`, from `skeleton` rest poses.
+    ///
+    /// Optional. When the output is larger than the animation (the animation drives fewer joints than
+    /// the skeleton), output joints without a track are filled with the skeleton rest pose instead of
+    /// being left unchanged.
+    #[inline]
+    pub fn set_fill_rest(&mut self, skeleton: &Skeleton) {
+        self.fill_rest = Some(skeleton.joint_rest_poses().to_vec());
+    }
+
+    /// Clears the rest pose filling untracked joints of `SamplingJob`.
+    #[inline]
+    pub fn clear_fill_rest(&mut self) {
+        self.fill_rest = None;
+    }
+
+    /// Gets sanitize padding of `SamplingJob`.
+    #[inline]
+    pub fn sanitize_padding(&self) -> bool {
+        return self.sanitize_padding;
+    }
+
+    /// Sets sanitize padding of `SamplingJob`.
+    ///
+    /// False by default. When true, the padding lanes of the last animated soa joint are set to identity
+    /// after sampling, see `sanitize_padding`. Ignored with a rest pose fill, that writes these lanes.
+    #[inline]
+    pub fn set_sanitize_padding(&mut self, sanitize: bool) {
+        self.sanitize_padding = sanitize;
+    }
+
+    /// Gets the number of joints of the skeleton set to `SamplingJob`.
+    #[inline]
+    pub fn num_skeleton_joints(&self) -> Option<usize> {
+        return self.num_skeleton_joints;
+    }
+
+    /// Sets the skeleton the animation of `SamplingJob` is meant for.
+    ///
+    /// Optional. When set, the job fails with `OzzError::TrackCountMismatch` if the animation has more
+    /// tracks than the skeleton has joints, a common mismatch when pairing the wrong clip with a skeleton.
+    #[inline]
+    pub fn set_skeleton(&mut self, skeleton: &Skeleton) {
+        self.num_skeleton_joints = Some(skeleton.num_joints());
+    }
+
+    /// Clears the skeleton set to `SamplingJob`.
+    #[inline]
+    pub fn clear_skeleton(&mut self) {
+        self.num_skeleton_joints = None;
+    }
+
     /// Gets output of `SamplingJob`.
     #[inline]
     pub fn output(&self) -> Option<&O> {
@@ -1023,54 +1437,224 @@ where
     /// Validates `SamplingJob` parameters.
     pub fn validate(&self) -> bool {
         return (|| {
-            let animation = self.animation.as_ref()?.obj();
+            let animation = self.animation.as_ref()?;
             let context = self.context.as_ref()?;
             let output = self.output.as_ref()?.buf().ok()?;
 
-            let mut ok = context.max_soa_tracks() >= animation.num_soa_tracks();
+            let mut ok = context.can_sample(animation.num_soa_tracks());
             ok &= output.len() >= animation.num_soa_tracks();
+            ok &= self.check_skeleton(animation).is_ok();
+            ok &= animation.check_keys().is_ok();
             return Some(ok);
         })()
         .unwrap_or(false);
     }
 
+    fn check_skeleton(&self, animation: &A) -> Result<(), OzzError> {
+        return match self.num_skeleton_joints {
+            Some(num_joints) if animation.num_tracks() > num_joints => Err(OzzError::TrackCountMismatch {
+                num_tracks: animation.num_tracks(),
+                num_joints,
+            }),
+            _ => Ok(()),
+        };
+    }
+
     /// Runs job's sampling task.
     /// The validate job before any operation is performed.
     pub fn run(&mut self) -> Result<(), OzzError> {
-        let animation = self.animation.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        self.run_impl()?;
+        return Ok(());
+    }
+
+    // Returns true if the pose is served from the pose cache.
+    fn run_impl(&mut self) -> Result<bool, OzzError> {
+        let pose_cache_hit = self.sample_impl()?;
+        self.fill_rest_impl()?;
+        self.sanitize_padding_impl()?;
+        return Ok(pose_cache_hit);
+    }
+
+    fn sanitize_padding_impl(&mut self) -> Result<(), OzzError> {
+        if !self.sanitize_padding || self.fill_rest.is_some() {
+            return Ok(());
+        }
+        let Some(animation) = self.animation.as_ref() else {
+            return Ok(());
+        };
+        let mut output = self.output.as_mut().ok_or(OzzError::InvalidJob)?.mut_buf()?;
+        sanitize_padding(&mut output, animation.num_tracks());
+        return Ok(());
+    }
+
+    // Fills output joints after the animation tracks with the rest pose.
+    fn fill_rest_impl(&mut self) -> Result<(), OzzError> {
+        let (Some(animation), Some(rest_poses)) = (self.animation.as_ref(), self.fill_rest.as_ref()) else {
+            return Ok(());
+        };
+        let mut output = self.output.as_mut().ok_or(OzzError::InvalidJob)?.mut_buf()?;
+        let num_soa_joints = output.len().min(rest_poses.len());
+        let num_tracks = animation.num_tracks();
+
+        // Padding lanes of the last animated soa joint.
+        if num_tracks % 4 != 0 && num_tracks / 4 < num_soa_joints {
+            let soa = num_tracks / 4;
+            for lane in (num_tracks % 4)..4 {
+                output[soa].set_col(lane, &rest_poses[soa].col(lane));
+            }
+        }
+        let begin = num_tracks.div_ceil(4).min(num_soa_joints);
+        output[begin..num_soa_joints].copy_from_slice(&rest_poses[begin..num_soa_joints]);
+        return Ok(());
+    }
+
+    fn sample_impl(&mut self) -> Result<bool, OzzError> {
+        let ratio = self.sampled_ratio();
+        let previous_ratio = self.previous_ratio.map(|previous| self.remap_ratio(previous));
+        let animation = self.animation.as_ref().ok_or(OzzError::InvalidJob)?;
+        self.check_skeleton(animation)?;
+        animation.check_keys()?;
         let ctx = self.context.as_mut().ok_or(OzzError::InvalidJob)?;
         let mut output = self.output.as_mut().ok_or(OzzError::InvalidJob)?.mut_buf()?;
 
-        let mut ok = ctx.max_soa_tracks() >= animation.num_soa_tracks();
+        let mut ok = ctx.can_sample(animation.num_soa_tracks());
         ok &= output.len() >= animation.num_soa_tracks();
         if !ok {
             return Err(OzzError::InvalidJob);
         }
 
         if animation.num_soa_tracks() == 0 {
-            return Ok(());
+            return Ok(false);
+        }
+
+        // A partial pose can't be cached.
+        let pose_cache = self.pose_cache.as_mut().filter(|_| ctx.subset.is_none());
+        if let Some(cache) = pose_cache {
+            if let Some(pose) = cache.get(animation.id(), ratio) {
+                output[..pose.len()].copy_from_slice(pose);
+                return Ok(true);
+            }
+        }
+
+        let interpolator = self.interpolator.as_deref();
+        Self::sample_keyframes(animation, ctx, interpolator, ratio, previous_ratio, false, &mut output)?;
+
+        if let Some(cache) = self.pose_cache.as_mut().filter(|_| ctx.subset.is_none()) {
+            cache.insert(animation.id(), ratio, &output[..animation.num_soa_tracks()]);
         }
+        return Ok(false);
+    }
 
-        Self::step_context(animation, ctx, self.ratio);
+    // Updates the context keyframes to `ratio`, and interpolates them into `output`. A `compact` output is
+    // ordered like the context soa entries (see `SamplingContext::track_slot`) instead of the animation tracks.
+    fn sample_keyframes(
+        animation: &A,
+        ctx: &mut SamplingContext,
+        interpolator: Option<&dyn SoaInterpolator>,
+        ratio: f32,
+        previous_ratio: Option<f32>,
+        compact: bool,
+        output: &mut [SoaTransform],
+    ) -> Result<(), OzzError> {
+        Self::step_context(animation, ctx, ratio, previous_ratio);
 
-        Self::update_translation_cursor(animation, ctx, self.ratio);
+        Self::update_translation_cursor(animation, ctx, ratio);
         Self::update_translation_key_frames(animation, ctx);
 
-        Self::update_rotation_cursor(animation, ctx, self.ratio);
+        Self::update_rotation_cursor(animation, ctx, ratio);
         Self::update_rotation_key_frames(animation, ctx);
 
-        Self::update_scale_cursor(animation, ctx, self.ratio);
+        Self::update_scale_cursor(animation, ctx, ratio);
         Self::update_scale_key_frames(animation, ctx);
 
-        Self::interpolates(animation, ctx, self.ratio, &mut output)?;
+        return Self::interpolates(animation, ctx, interpolator, ratio, compact, output);
+    }
 
-        return Ok(());
+    // Samples `animation` with a caller owned context and output, see `Animation::sample_into`.
+    pub(crate) fn sample_into(
+        animation: &A,
+        ctx: &mut SamplingContext,
+        ratio: f32,
+        output: &mut [SoaTransform],
+    ) -> Result<(), OzzError> {
+        let num_soa_tracks = animation.num_soa_tracks();
+        if !ctx.can_sample(num_soa_tracks) {
+            let actual = ctx.max_soa_tracks();
+            return Err(OzzError::BufferSize {
+                expected: num_soa_tracks,
+                actual,
+            });
+        }
+        if output.len() < num_soa_tracks {
+            let actual = output.len();
+            return Err(OzzError::BufferSize {
+                expected: num_soa_tracks,
+                actual,
+            });
+        }
+        if num_soa_tracks == 0 {
+            return Ok(());
+        }
+        let ratio = f32_clamp_or_max(ratio, 0.0f32, 1.0f32);
+        return Self::sample_keyframes(animation, ctx, None, ratio, None, false, output);
     }
 
-    fn step_context(animation: &Animation, ctx: &mut SamplingContext, ratio: f32) {
-        let animation_id = animation as *const _ as u64;
-        if (ctx.animation_id != animation_id) || ratio < ctx.ratio {
-            ctx.animation_id = animation_id;
+    /// Runs job's sampling task like `run`, and returns keyframes decoding statistics of this run.
+    /// The validate job before any operation is performed.
+    #[cfg(feature = "instrument")]
+    pub fn run_instrumented(&mut self) -> Result<JobStats, OzzError> {
+        let cursors = |ctx: &SamplingContext| ctx.translation_cursor + ctx.rotation_cursor + ctx.scale_cursor;
+        let animation = self.animation.as_ref().ok_or(OzzError::InvalidJob)?;
+        let ctx = self.context.as_ref().ok_or(OzzError::InvalidJob)?;
+        let previous_ratio = self.previous_ratio.map(|previous| self.remap_ratio(previous));
+        let reset = Self::resets_context(animation, ctx, self.sampled_ratio(), previous_ratio);
+        let before = if reset { 0 } else { cursors(ctx) };
+
+        let pose_cache_hit = self.run_impl()?;
+
+        let animation = self.animation.as_ref().ok_or(OzzError::InvalidJob)?;
+        let ctx = self.context.as_ref().ok_or(OzzError::InvalidJob)?;
+        if animation.num_soa_tracks() == 0 || pose_cache_hit {
+            return Ok(JobStats {
+                pose_cache_hit,
+                ..JobStats::default()
+            });
+        }
+
+        // Each outdated flag stands for a soa track, whose 2 * 4 keyframes are decompressed.
+        let num_flags = ctx.num_slots(animation.num_soa_tracks()).div_ceil(8);
+        let outdated = [
+            &ctx.outdated_translations()[..num_flags],
+            &ctx.outdated_rotations()[..num_flags],
+            &ctx.outdated_scales()[..num_flags],
+        ];
+        let num_outdated: u32 = outdated
+            .iter()
+            .flat_map(|flags| flags.iter())
+            .map(|x| x.count_ones())
+            .sum();
+        return Ok(JobStats {
+            keyframes_decoded: num_outdated as usize * 8,
+            cache_misses: cursors(ctx) - before,
+            pose_cache_hit,
+        });
+    }
+
+    fn resets_context(animation: &A, ctx: &SamplingContext, ratio: f32, previous_ratio: Option<f32>) -> bool {
+        if ctx.animation_id != animation.id() {
+            return true;
+        }
+        if ratio >= ctx.ratio {
+            return false;
+        }
+        // Backward step, the cached keyframes may still bracket the ratio.
+        let backward = previous_ratio.is_some_and(|previous| ratio <= previous);
+        return !(backward && ctx.brackets(animation.num_soa_tracks(), ratio));
+    }
+
+    fn step_context(animation: &A, ctx: &mut SamplingContext, ratio: f32, previous_ratio: Option<f32>) {
+        if Self::resets_context(animation, ctx, ratio, previous_ratio) {
+            ctx.animation_id = animation.id();
             ctx.translation_cursor = 0;
             ctx.rotation_cursor = 0;
             ctx.scale_cursor = 0;
@@ -1078,10 +1662,11 @@ where
         ctx.ratio = ratio;
     }
 
-    fn update_translation_cursor(animation: &Animation, ctx: &mut SamplingContext, ratio: f32) {
+    fn update_translation_cursor(animation: &A, ctx: &mut SamplingContext, ratio: f32) {
+        let num_slots = ctx.num_slots(animation.num_soa_tracks());
         if ctx.translation_cursor == 0 {
-            for i in 0..animation.num_soa_tracks() {
-                let in_idx0 = i * 4;
+            for i in 0..num_slots {
+                let in_idx0 = ctx.slot_soa_track(i) * 4;
                 let in_idx1 = in_idx0 + animation.num_aligned_tracks();
                 let out_idx = i * 4 * 2;
                 ctx.translation_keys_mut()[out_idx + 0] = (in_idx0 + 0) as i32;
@@ -1095,7 +1680,7 @@ where
             }
 
             ctx.outdated_translations_mut().iter_mut().for_each(|x| *x = 0xFF);
-            let last_offset = ((animation.num_soa_tracks() + 7) / 8 * 8) - animation.num_soa_tracks();
+            let last_offset = ((num_slots + 7) / 8 * 8) - num_slots;
             ctx.outdated_translations_mut()
                 .last_mut()
                 .map(|x| *x = 0xFF >> last_offset);
@@ -1104,7 +1689,11 @@ where
         }
 
         while ctx.translation_cursor < animation.translations().len() {
-            let track = animation.translations()[ctx.translation_cursor].track as usize;
+            let Some(track) = ctx.track_slot(animation.translations()[ctx.translation_cursor].track as usize) else {
+                // Keys of tracks that aren't sampled are skipped.
+                ctx.translation_cursor += 1;
+                continue;
+            };
             let key_idx = ctx.translation_keys()[track * 2 + 1] as usize;
             let ani_ratio = animation.translations()[key_idx].ratio;
             if ani_ratio > ratio {
@@ -1112,15 +1701,15 @@ where
             }
 
             ctx.outdated_translations_mut()[track / 32] |= 1 << ((track & 0x1F) / 4);
-            let base = (animation.translations()[ctx.translation_cursor].track as usize) * 2;
+            let base = track * 2;
             ctx.translation_keys_mut()[base] = ctx.translation_keys()[base + 1];
             ctx.translation_keys_mut()[base + 1] = ctx.translation_cursor as i32;
             ctx.translation_cursor = ctx.translation_cursor + 1;
         }
     }
 
-    fn update_translation_key_frames(animation: &Animation, ctx: &mut SamplingContext) {
-        let num_outdated_flags = (animation.num_soa_tracks() + 7) / 8;
+    fn update_translation_key_frames(animation: &A, ctx: &mut SamplingContext) {
+        let num_outdated_flags = (ctx.num_slots(animation.num_soa_tracks()) + 7) / 8;
         for j in 0..num_outdated_flags {
             let mut outdated = ctx.outdated_translations()[j];
             for i in (8 * j)..(8 * j + 8) {
@@ -1148,10 +1737,11 @@ where
         }
     }
 
-    fn update_rotation_cursor(animation: &Animation, ctx: &mut SamplingContext, ratio: f32) {
+    fn update_rotation_cursor(animation: &A, ctx: &mut SamplingContext, ratio: f32) {
+        let num_slots = ctx.num_slots(animation.num_soa_tracks());
         if ctx.rotation_cursor == 0 {
-            for i in 0..animation.num_soa_tracks() {
-                let in_idx0 = i * 4;
+            for i in 0..num_slots {
+                let in_idx0 = ctx.slot_soa_track(i) * 4;
                 let in_idx1 = in_idx0 + animation.num_aligned_tracks();
                 let out_idx = i * 4 * 2;
                 ctx.rotation_keys_mut()[out_idx + 0] = (in_idx0 + 0) as i32;
@@ -1165,7 +1755,7 @@ where
             }
 
             ctx.outdated_rotations_mut().iter_mut().for_each(|x| *x = 0xFF);
-            let last_offset = ((animation.num_soa_tracks() + 7) / 8 * 8) - animation.num_soa_tracks();
+            let last_offset = ((num_slots + 7) / 8 * 8) - num_slots;
             ctx.outdated_rotations_mut()
                 .last_mut()
                 .map(|x| *x = 0xFF >> last_offset);
@@ -1174,7 +1764,11 @@ where
         }
 
         while ctx.rotation_cursor < animation.rotations().len() {
-            let track = animation.rotations()[ctx.rotation_cursor].track() as usize;
+            let Some(track) = ctx.track_slot(animation.rotations()[ctx.rotation_cursor].track() as usize) else {
+                // Keys of tracks that aren't sampled are skipped.
+                ctx.rotation_cursor += 1;
+                continue;
+            };
             let key_idx = ctx.rotation_keys()[track * 2 + 1] as usize;
             let ani_ratio = animation.rotations()[key_idx].ratio;
             if ani_ratio > ratio {
@@ -1182,15 +1776,15 @@ where
             }
 
             ctx.outdated_rotations_mut()[track / 32] |= 1 << ((track & 0x1F) / 4);
-            let base = (animation.rotations()[ctx.rotation_cursor].track() as usize) * 2;
+            let base = track * 2;
             ctx.rotation_keys_mut()[base] = ctx.rotation_keys()[base + 1];
             ctx.rotation_keys_mut()[base + 1] = ctx.rotation_cursor as i32;
             ctx.rotation_cursor = ctx.rotation_cursor + 1;
         }
     }
 
-    fn update_rotation_key_frames(animation: &Animation, ctx: &mut SamplingContext) {
-        let num_outdated_flags = (animation.num_soa_tracks() + 7) / 8;
+    fn update_rotation_key_frames(animation: &A, ctx: &mut SamplingContext) {
+        let num_outdated_flags = (ctx.num_slots(animation.num_soa_tracks()) + 7) / 8;
         for j in 0..num_outdated_flags {
             let mut outdated = ctx.outdated_rotations()[j];
             for i in (8 * j)..(8 * j + 8) {
@@ -1218,10 +1812,11 @@ where
         }
     }
 
-    fn update_scale_cursor(animation: &Animation, ctx: &mut SamplingContext, ratio: f32) {
+    fn update_scale_cursor(animation: &A, ctx: &mut SamplingContext, ratio: f32) {
+        let num_slots = ctx.num_slots(animation.num_soa_tracks());
         if ctx.scale_cursor == 0 {
-            for i in 0..animation.num_soa_tracks() {
-                let in_idx0 = i * 4;
+            for i in 0..num_slots {
+                let in_idx0 = ctx.slot_soa_track(i) * 4;
                 let in_idx1 = in_idx0 + animation.num_aligned_tracks();
                 let out_idx = i * 4 * 2;
                 ctx.scale_keys_mut()[out_idx + 0] = (in_idx0 + 0) as i32;
@@ -1235,14 +1830,18 @@ where
             }
 
             ctx.outdated_scales_mut().iter_mut().for_each(|x| *x = 0xFF);
-            let last_offset = ((animation.num_soa_tracks() + 7) / 8 * 8) - animation.num_soa_tracks();
+            let last_offset = ((num_slots + 7) / 8 * 8) - num_slots;
             ctx.outdated_scales_mut().last_mut().map(|x| *x = 0xFF >> last_offset);
 
             ctx.scale_cursor = animation.num_aligned_tracks() * 2;
         }
 
         while ctx.scale_cursor < animation.scales().len() {
-            let track = animation.scales()[ctx.scale_cursor].track as usize;
+            let Some(track) = ctx.track_slot(animation.scales()[ctx.scale_cursor].track as usize) else {
+                // Keys of tracks that aren't sampled are skipped.
+                ctx.scale_cursor += 1;
+                continue;
+            };
             let key_idx = ctx.scale_keys()[track * 2 + 1] as usize;
             let ani_ratio = animation.scales()[key_idx].ratio;
             if ani_ratio > ratio {
@@ -1250,15 +1849,15 @@ where
             }
 
             ctx.outdated_scales_mut()[track / 32] |= 1 << ((track & 0x1F) / 4);
-            let base = (animation.scales()[ctx.scale_cursor].track as usize) * 2;
+            let base = track * 2;
             ctx.scale_keys_mut()[base] = ctx.scale_keys()[base + 1];
             ctx.scale_keys_mut()[base + 1] = ctx.scale_cursor as i32;
             ctx.scale_cursor = ctx.scale_cursor + 1;
         }
     }
 
-    fn update_scale_key_frames(animation: &Animation, ctx: &mut SamplingContext) {
-        let num_outdated_flags = (animation.num_soa_tracks() + 7) / 8;
+    fn update_scale_key_frames(animation: &A, ctx: &mut SamplingContext) {
+        let num_outdated_flags = (ctx.num_slots(animation.num_soa_tracks()) + 7) / 8;
         for j in 0..num_outdated_flags {
             let mut outdated = ctx.outdated_scales()[j];
             for i in (8 * j)..(8 * j + 8) {
@@ -1287,37 +1886,175 @@ where
     }
 
     fn interpolates(
-        animation: &Animation,
+        animation: &A,
         ctx: &mut SamplingContext,
+        interpolator: Option<&dyn SoaInterpolator>,
         ratio: f32,
+        compact: bool,
         output: &mut [SoaTransform],
     ) -> Result<(), OzzError> {
         let ratio4 = f32x4::splat(ratio);
-        for idx in 0..animation.num_soa_tracks() {
+        for idx in 0..ctx.num_slots(animation.num_soa_tracks()) {
             let translation = &ctx.translations()[idx];
             let translation_ratio = (ratio4 - translation.ratio[0]) / (translation.ratio[1] - translation.ratio[0]);
-            output[idx].translation = SoaVec3::lerp(&translation.value[0], &translation.value[1], translation_ratio);
-
             let rotation = &ctx.rotations()[idx];
             let rotation_ratio = (ratio4 - rotation.ratio[0]) / (rotation.ratio[1] - rotation.ratio[0]);
-            output[idx].rotation = SoaQuat::nlerp(&rotation.value[0], &rotation.value[1], rotation_ratio);
-
             let scale = &ctx.scales()[idx];
             let scale_ratio = (ratio4 - scale.ratio[0]) / (scale.ratio[1] - scale.ratio[0]);
-            output[idx].scale = SoaVec3::lerp(&scale.value[0], &scale.value[1], scale_ratio);
+            let transform = match interpolator {
+                None => SoaTransform {
+                    translation: SoaVec3::lerp(&translation.value[0], &translation.value[1], translation_ratio),
+                    rotation: SoaQuat::nlerp(&rotation.value[0], &rotation.value[1], rotation_ratio),
+                    scale: SoaVec3::lerp(&scale.value[0], &scale.value[1], scale_ratio),
+                },
+                Some(interpolator) => SoaTransform {
+                    translation: Interpolator::<SoaVec3, f32x4>::interpolate(
+                        interpolator,
+                        &translation.value[0],
+                        &translation.value[1],
+                        translation_ratio,
+                    ),
+                    rotation: Interpolator::<SoaQuat, f32x4>::interpolate(
+                        interpolator,
+                        &rotation.value[0],
+                        &rotation.value[1],
+                        rotation_ratio,
+                    ),
+                    scale: Interpolator::<SoaVec3, f32x4>::interpolate(
+                        interpolator,
+                        &scale.value[0],
+                        &scale.value[1],
+                        scale_ratio,
+                    ),
+                },
+            };
+
+            let soa = ctx.slot_soa_track(idx);
+            let lanes = match &ctx.subset {
+                Some(subset) => subset.mask.soa_lanes(soa),
+                None => 0xF,
+            };
+            let out = if compact { idx } else { soa };
+            if lanes == 0xF {
+                output[out] = transform;
+            } else {
+                // Only masked joints are written.
+                for lane in (0..4).filter(|lane| lanes & (1 << lane) != 0) {
+                    output[out].set_col(lane, &transform.col(lane));
+                }
+            }
         }
 
         return Ok(());
     }
 }
 
+impl<A, O> SamplingJob<A, O>
+where
+    A: OzzObj<Animation> + Debug,
+    O: OzzMutBuf<SoaTransform>,
+{
+    /// Runs job's sampling task, removes the motion of `joint` from the sampled pose, then computes the
+    /// model-space matrices of the pose. It chains `SamplingJob`, `MotionExtractionJob` and
+    /// `LocalToModelJob` for the common locomotion case.
+    ///
+    /// * `skeleton` - The skeleton the animation is meant for.
+    /// * `joint` - The motion joint, see `MotionExtractionJob::set_joint`.
+    /// * `from` - The previous ratio, the motion is extracted from `from` to the job ratio. Both are
+    ///   remapped by the ratio remap if any.
+    /// * `models` - The model-space matrices of the pose without motion, ordered like skeleton's joints.
+    ///
+    /// Returns the motion delta from `from` to the job ratio, see `MotionExtractionJob::delta`.
+    pub fn run_without_motion(
+        &mut self,
+        skeleton: &Skeleton,
+        joint: usize,
+        from: f32,
+        models: &mut [Mat4],
+    ) -> Result<AosTransform, OzzError> {
+        self.run()?;
+        let (from, to) = (self.remap_ratio(from), self.sampled_ratio());
+        let animation = self.animation.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        let mut output = self.output.as_mut().ok_or(OzzError::InvalidJob)?.mut_buf()?;
+
+        let mut motion_job: MotionExtractionJobRef = MotionExtractionJob::default();
+        motion_job.set_animation(animation);
+        motion_job.set_joint(joint);
+        motion_job.set_from(from);
+        motion_job.set_to(to);
+        motion_job.set_output(&mut output);
+        motion_job.run()?;
+        let delta = motion_job.delta();
+
+        let mut ltm_job: LocalToModelJobRef = LocalToModelJob::default();
+        ltm_job.set_skeleton(skeleton);
+        ltm_job.set_input(&output);
+        ltm_job.set_output(models);
+        ltm_job.run()?;
+        return Ok(delta);
+    }
+
+    /// Samples the transforms of `joints` only, as a compact `(joint, local transform)` list. For overlay
+    /// clips (like face rigs) animating a few joints of a large skeleton, only the soa tracks of `joints` are
+    /// sampled, and downstream code iterates the animated joints only.
+    ///
+    /// The job ratio, ratio remap and interpolator are used like `run`, but the job context and output are
+    /// not: the job keeps a partial context restricted to `joints` (see `SamplingContext::for_joints`) and
+    /// its scratch pose between runs, rebuilt when `joints` change.
+    ///
+    /// * `joints` - Joints to output, usually `Animation::animated_joints` computed once.
+    /// * `sparse` - Output list, cleared then filled in `joints` order.
+    ///
+    /// Returns `OzzError::InvalidIndex` if a joint is out of the animation tracks range.
+    pub fn run_sparse(&mut self, joints: &[i16], sparse: &mut Vec<(i16, AosTransform)>) -> Result<(), OzzError> {
+        let ratio = self.sampled_ratio();
+        let previous_ratio = self.previous_ratio.map(|previous| self.remap_ratio(previous));
+        let animation = self.animation.as_ref().ok_or(OzzError::InvalidJob)?;
+        self.check_skeleton(animation)?;
+
+        let num_tracks = animation.obj().num_tracks();
+        let mut mask = JointMask::new(num_tracks);
+        for joint in joints.iter() {
+            if *joint < 0 || *joint as usize >= num_tracks {
+                return Err(OzzError::InvalidIndex);
+            }
+            mask.set(*joint as usize, true);
+        }
+        sparse.clear();
+        if joints.is_empty() {
+            return Ok(());
+        }
+
+        let reusable = (self.sparse_context.as_ref())
+            .and_then(|ctx| ctx.joint_mask())
+            .is_some_and(|joint_mask| *joint_mask == mask);
+        if !reusable {
+            self.sparse_context = Some(SamplingContext::for_joints(num_tracks, &mask));
+        }
+        let ctx = self.sparse_context.as_mut().ok_or(OzzError::InvalidJob)?;
+        self.sparse_pose.resize(ctx.max_soa_tracks(), SoaTransform::default());
+
+        let interpolator = self.interpolator.as_deref();
+        let pose = &mut self.sparse_pose;
+        Self::sample_keyframes(animation, ctx, interpolator, ratio, previous_ratio, true, pose)?;
+        for joint in joints.iter() {
+            let slot = ctx.track_slot(*joint as usize).ok_or(OzzError::InvalidIndex)?;
+            sparse.push((*joint, pose[slot / 4].col(slot % 4)));
+        }
+        return Ok(());
+    }
+}
+
 #[cfg(test)]
 mod sampling_tests {
     use glam::{Quat, Vec3};
     use wasm_bindgen_test::*;
 
     use super::*;
+    use crate::animation::AnimationBuilder;
     use crate::base::OzzBuf;
+    use crate::skeleton::Skeleton;
+    use crate::track::{TrackBuilder, TrackInterpolation};
 
     fn make_buf<T>(v: Vec<T>) -> Rc<RefCell<Vec<T>>> {
         return Rc::new(RefCell::new(v));
@@ -1740,6 +2477,636 @@ mod sampling_tests {
         run_test(&mut job).unwrap();
     }
 
+    #[cfg(feature = "instrument")]
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_run_instrumented() {
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+        let mut job = SamplingJob::default();
+        job.set_animation(animation.clone());
+        job.set_context(SamplingContext::new(animation.num_tracks()));
+        job.set_output(Rc::new(RefCell::new(vec![
+            SoaTransform::default();
+            animation.num_soa_tracks()
+        ])));
+
+        job.set_ratio(0.5);
+        let first = job.run_instrumented().unwrap();
+        assert!(first.keyframes_decoded > 0);
+        assert!(first.cache_misses > 0);
+
+        // Keyframes around a nearby time are mostly cached.
+        job.set_ratio(0.51);
+        let second = job.run_instrumented().unwrap();
+        assert!(second.keyframes_decoded > 0);
+        assert!(second.cache_misses < first.cache_misses);
+
+        // Backward sampling resets the context.
+        job.set_ratio(0.5);
+        assert_eq!(job.run_instrumented().unwrap(), first);
+    }
+
+    #[cfg(feature = "instrument")]
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_context_reset() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let ctx = SamplingContext::from_animation(&animation);
+        assert_eq!(ctx.capacity(), animation.num_aligned_tracks());
+        assert_eq!(SamplingContext::new(7).capacity(), 8);
+
+        let mut cold_output = vec![SoaTransform::default(); animation.num_soa_tracks()];
+        let mut cold_job: SamplingJobRef = SamplingJob::default();
+        cold_job.set_animation(&animation);
+        cold_job.set_context(SamplingContext::from_animation(&animation));
+        cold_job.set_output(&mut cold_output);
+        cold_job.set_ratio(0.51);
+        let cold = cold_job.run_instrumented().unwrap();
+
+        let mut output = vec![SoaTransform::default(); animation.num_soa_tracks()];
+        let mut job: SamplingJobRef = SamplingJob::default();
+        job.set_animation(&animation);
+        job.set_context(ctx);
+        job.set_output(&mut output);
+        job.set_ratio(0.5);
+        job.run().unwrap();
+        job.set_ratio(0.51);
+        let warm = job.run_instrumented().unwrap();
+        assert!(warm.cache_misses < cold.cache_misses);
+
+        job.set_ratio(0.5);
+        job.run().unwrap();
+        let mut ctx = job.take_context().unwrap();
+        ctx.reset();
+        assert_eq!(ctx.ratio(), 0.0);
+        assert_eq!(ctx.translation_cursor(), 0);
+        assert_eq!(ctx.capacity(), animation.num_aligned_tracks());
+        job.set_context(ctx);
+        job.set_ratio(0.51);
+        assert_eq!(job.run_instrumented().unwrap(), cold);
+        assert_eq!(job.output().unwrap(), cold_job.output().unwrap());
+    }
+
+    #[cfg(feature = "instrument")]
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_pose_cache() {
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+        let output = Rc::new(RefCell::new(vec![SoaTransform::default(); animation.num_soa_tracks()]));
+        let mut job = SamplingJob::default();
+        job.set_animation(animation.clone());
+        job.set_context(SamplingContext::new(animation.num_tracks()));
+        job.set_pose_cache(PoseCache::new(4));
+        job.set_output(output.clone());
+
+        job.set_ratio(0.5);
+        let stats = job.run_instrumented().unwrap();
+        assert!(!stats.pose_cache_hit);
+        assert!(stats.keyframes_decoded > 0);
+        let expected = output.borrow().clone();
+
+        output.borrow_mut().fill(SoaTransform::default());
+        let stats = job.run_instrumented().unwrap();
+        assert!(stats.pose_cache_hit);
+        assert_eq!(stats.keyframes_decoded, 0);
+        assert_eq!(stats.cache_misses, 0);
+        assert_eq!(*output.borrow(), expected);
+
+        job.pose_cache_mut().unwrap().clear();
+        assert!(!job.run_instrumented().unwrap().pose_cache_hit);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_pose_cache_invalidation() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let mut output = vec![SoaTransform::default(); animation.num_soa_tracks()];
+        let mut job: SamplingJobRef = SamplingJob::default();
+        job.set_animation(&animation);
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_pose_cache(PoseCache::new(4));
+        job.set_output(&mut output);
+        job.set_ratio(0.5);
+
+        let settings: [fn(&mut SamplingJobRef); 4] = [
+            |job| job.set_interpolator(Arc::new(crate::interpolator::LinearInterpolator)),
+            |job| job.clear_interpolator(),
+            |job| job.set_ratio_remap(RatioRemap::from_fn(|ratio| ratio)),
+            |job| job.clear_ratio_remap(),
+        ];
+        for setting in settings {
+            job.run().unwrap();
+            assert_eq!(job.pose_cache().unwrap().len(), 1);
+            setting(&mut job);
+            assert!(job.pose_cache().unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_sampling_joints() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+        let mut mask = JointMask::from_hierarchy(&skeleton, skeleton.joint_by_name("LeftArm").unwrap());
+        mask.set(0, true);
+
+        let full_ctx = SamplingContext::from_animation(animation.as_ref());
+        let partial_ctx = SamplingContext::for_joints(animation.num_tracks(), &mask);
+        assert!(partial_ctx.size() < full_ctx.size());
+        assert_eq!(partial_ctx.joint_mask(), Some(&mask));
+
+        let mut full_output = vec![SoaTransform::default(); animation.num_soa_tracks()];
+        let mut full_job: SamplingJobRef = SamplingJob::default();
+        full_job.set_animation(&animation);
+        full_job.set_context(full_ctx);
+        full_job.set_output(&mut full_output);
+
+        let sentinel = SoaTransform {
+            translation: SoaVec3::splat_col([9.0; 3]),
+            rotation: SoaQuat::splat_col([0.0, 0.0, 0.0, 1.0]),
+            scale: SoaVec3::splat_col([9.0; 3]),
+        };
+        let mut partial_output = vec![sentinel; animation.num_soa_tracks()];
+        let mut partial_job: SamplingJobRef = SamplingJob::default();
+        partial_job.set_animation(&animation);
+        partial_job.set_context(partial_ctx);
+        partial_job.set_output(&mut partial_output);
+        partial_job.set_pose_cache(PoseCache::new(4));
+
+        // Forward, then backward.
+        for ratio in [0.0, 0.1, 0.35, 0.36, 0.8, 1.0, 0.2, 0.5, 0.5] {
+            full_job.set_ratio(ratio);
+            full_job.run().unwrap();
+            partial_job.set_ratio(ratio);
+            partial_job.run().unwrap();
+
+            let full_output = full_job.output().unwrap();
+            let partial_output = partial_job.output().unwrap();
+            for joint in 0..animation.num_tracks() {
+                let actual = partial_output[joint / 4].col(joint % 4);
+                if mask.contains(joint) {
+                    assert_eq!(
+                        actual,
+                        full_output[joint / 4].col(joint % 4),
+                        "ratio {} joint {}",
+                        ratio,
+                        joint
+                    );
+                } else {
+                    assert_eq!(actual, sentinel.col(joint % 4), "ratio {} joint {}", ratio, joint);
+                }
+            }
+        }
+
+        // The joint subset isn't persisted, a partial context is restored as a full one.
+        #[cfg(feature = "rkyv")]
+        {
+            use rkyv::Deserialize;
+            let ctx = partial_job.context().unwrap();
+            let bytes = rkyv::to_bytes::<_, 256>(ctx).unwrap();
+            let archived = rkyv::check_archived_root::<SamplingContext>(&bytes[..]).unwrap();
+            let ctx_de: SamplingContext = archived.deserialize(&mut rkyv::Infallible).unwrap();
+            assert_eq!(ctx_de.joint_mask(), None);
+            assert_eq!(ctx_de.max_soa_tracks(), ctx.max_soa_tracks());
+            assert_eq!(ctx_de.translations(), ctx.translations());
+        }
+        #[cfg(feature = "serde")]
+        {
+            let ctx = partial_job.context().unwrap();
+            let ctx_de: SamplingContext = serde_json::from_str(&serde_json::to_string(ctx).unwrap()).unwrap();
+            assert_eq!(ctx_de.joint_mask(), None);
+            assert_eq!(ctx_de.max_soa_tracks(), ctx.max_soa_tracks());
+            assert_eq!(ctx_de.translations(), ctx.translations());
+        }
+
+        let mut job: SamplingJobRef = SamplingJob::default();
+        job.set_animation(&animation);
+        job.set_context(SamplingContext::for_joints(animation.num_tracks() + 4, &mask));
+        job.set_output(&mut full_output);
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_ratio_remap() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let sample = |remap: Option<RatioRemap>, ratio: f32| {
+            let mut output = vec![SoaTransform::default(); animation.num_soa_tracks()];
+            let mut job: SamplingJobRef = SamplingJob::default();
+            job.set_animation(&animation);
+            job.set_context(SamplingContext::from_animation(&animation));
+            job.set_output(&mut output);
+            if let Some(remap) = remap {
+                job.set_ratio_remap(remap);
+            }
+            job.set_ratio(ratio);
+            job.run().unwrap();
+            return output;
+        };
+
+        let mut builder = TrackBuilder::new();
+        builder.add_keyframe(0.0, 0.0, TrackInterpolation::Linear);
+        builder.add_keyframe(1.0, 1.0, TrackInterpolation::Linear);
+        let identity_track = RatioRemap::Track(Arc::new(builder.build().unwrap()));
+        let identity_fn = RatioRemap::from_fn(|ratio| ratio);
+        let ease = RatioRemap::from_fn(|ratio| ratio * ratio * (3.0 - 2.0 * ratio));
+        assert_eq!(ease.remap(0.25), 0.15625);
+        assert_eq!(RatioRemap::from_fn(|_| 2.0).remap(0.5), 1.0);
+
+        for ratio in [0.0, 0.25, 0.5, 0.9, 1.0] {
+            let expected = sample(None, ratio);
+            assert_eq!(sample(Some(identity_track.clone()), ratio), expected);
+            assert_eq!(sample(Some(identity_fn.clone()), ratio), expected);
+            // Sampling the eased ratio is sampling the remapped ratio.
+            assert_eq!(sample(Some(ease.clone()), ratio), sample(None, ease.remap(ratio)));
+        }
+        assert_ne!(sample(Some(ease.clone()), 0.25), sample(None, 0.25));
+
+        let mut job: SamplingJobRef = SamplingJob::default();
+        job.set_ratio(0.25);
+        assert_eq!(job.sampled_ratio(), 0.25);
+        job.set_ratio_remap(ease);
+        assert_eq!(job.sampled_ratio(), 0.15625);
+        assert!(job.ratio_remap().is_some());
+        job.clear_ratio_remap();
+        assert_eq!(job.sampled_ratio(), 0.25);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_fill_rest() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let mut builder = AnimationBuilder::new(1.0, 6);
+        builder.tracks_mut()[1].translations = vec![(0.0, Vec3::ZERO), (1.0, Vec3::new(2.0, 0.0, 0.0))];
+        let animation = builder.build().unwrap();
+        assert!(skeleton.num_joints() > 8);
+
+        let sentinel = SoaTransform {
+            translation: SoaVec3::splat_col([9.0; 3]),
+            rotation: SoaQuat::splat_col([0.0, 0.0, 0.0, 1.0]),
+            scale: SoaVec3::splat_col([9.0; 3]),
+        };
+        let mut output = vec![sentinel; skeleton.num_soa_joints()];
+        let mut job: SamplingJobRef = SamplingJob::default();
+        job.set_animation(&animation);
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_output(&mut output);
+        job.set_ratio(0.5);
+        job.run().unwrap();
+        // Without fill rest, extra joints are left unchanged.
+        assert_eq!(job.output().unwrap()[2], sentinel);
+
+        job.set_fill_rest(&skeleton);
+        assert_eq!(job.fill_rest().unwrap(), skeleton.joint_rest_poses());
+        job.run().unwrap();
+        let output = job.output().unwrap();
+        assert_eq!(output[0].translation.col(1), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(output[1].col(0), AosTransform::IDENTITY);
+        assert_eq!(output[1].col(1), AosTransform::IDENTITY);
+        for joint in 6..skeleton.num_joints() {
+            let rest = skeleton.joint_rest_poses()[joint / 4].col(joint % 4);
+            assert_eq!(output[joint / 4].col(joint % 4), rest, "joint {}", joint);
+        }
+
+        // Output smaller than the skeleton.
+        let mut output = vec![sentinel; 2];
+        let mut job: SamplingJobRef = SamplingJob::default();
+        job.set_animation(&animation);
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_output(&mut output);
+        job.set_fill_rest(&skeleton);
+        job.run().unwrap();
+        assert_eq!(job.output().unwrap()[1].col(3), skeleton.joint_rest_poses()[1].col(3));
+        job.clear_fill_rest();
+        assert!(job.fill_rest().is_none());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_previous_ratio() {
+        let mut builder = AnimationBuilder::new(1.0, 2);
+        builder.tracks_mut()[0].translations = vec![(0.0, Vec3::ZERO), (0.5, Vec3::X), (1.0, Vec3::Y)];
+        builder.tracks_mut()[1].rotations = vec![
+            (0.0, Quat::IDENTITY),
+            (0.4, Quat::from_rotation_z(1.0)),
+            (1.0, Quat::from_rotation_z(2.0)),
+        ];
+        let animation = builder.build().unwrap();
+        let expected = |ratio: f32| {
+            let mut output = vec![SoaTransform::default(); 1];
+            let mut job: SamplingJobRef = SamplingJob::default();
+            job.set_animation(&animation);
+            job.set_context(SamplingContext::from_animation(&animation));
+            job.set_output(&mut output);
+            job.set_ratio(ratio);
+            job.run().unwrap();
+            return output[0];
+        };
+
+        let mut output = vec![SoaTransform::default(); 1];
+        let mut job: SamplingJobRef = SamplingJob::default();
+        job.set_animation(&animation);
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_output(&mut output);
+        job.set_ratio(0.8);
+        job.run().unwrap();
+        let resets = |job: &SamplingJobRef, ratio: f32| {
+            let (animation, ctx) = (job.animation().unwrap(), job.context().unwrap());
+            return SamplingJobRef::resets_context(animation, ctx, ratio, job.previous_ratio());
+        };
+
+        // Backward step in the cached keyframes, the context is kept.
+        assert!(resets(&job, 0.6));
+        job.set_previous_ratio(0.8);
+        assert_eq!(job.previous_ratio(), Some(0.8));
+        assert!(!resets(&job, 0.6));
+        job.set_ratio(0.6);
+        job.run().unwrap();
+        assert_eq!(job.output().unwrap()[0], expected(0.6));
+        assert_eq!(job.context().unwrap().ratio(), 0.6);
+
+        // Backward step before a cached keyframe, the context is reset.
+        job.set_previous_ratio(0.6);
+        assert!(resets(&job, 0.45));
+        job.set_ratio(0.45);
+        job.run().unwrap();
+        assert_eq!(job.output().unwrap()[0], expected(0.45));
+
+        // Forward steps aren't affected.
+        job.set_previous_ratio(0.45);
+        job.set_ratio(0.9);
+        job.run().unwrap();
+        assert_eq!(job.output().unwrap()[0], expected(0.9));
+
+        job.clear_previous_ratio();
+        assert!(resets(&job, 0.85));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_sanitize_padding() {
+        // Interpolates NaN translations, in padding lanes too.
+        #[derive(Debug)]
+        struct NanInterpolator;
+        impl Interpolator<SoaVec3, f32x4> for NanInterpolator {
+            fn interpolate(&self, _: &SoaVec3, _: &SoaVec3, _: f32x4) -> SoaVec3 {
+                return SoaVec3::splat_col([f32::NAN; 3]);
+            }
+        }
+        impl Interpolator<SoaQuat, f32x4> for NanInterpolator {
+            fn interpolate(&self, prev: &SoaQuat, _: &SoaQuat, _: f32x4) -> SoaQuat {
+                return *prev;
+            }
+        }
+
+        let animation = AnimationBuilder::new(1.0, 17).build().unwrap();
+        let mut output = vec![SoaTransform::default(); animation.num_soa_tracks()];
+        let mut job: SamplingJobRef = SamplingJob::default();
+        job.set_animation(&animation);
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_output(&mut output);
+        job.set_interpolator(Arc::new(NanInterpolator));
+        job.run().unwrap();
+        assert!(job.output().unwrap()[4].col(3).translation.is_nan());
+
+        assert!(!job.sanitize_padding());
+        job.set_sanitize_padding(true);
+        job.run().unwrap();
+        let output = job.output().unwrap();
+        assert!(output[4].col(0).translation.is_nan());
+        for lane in 1..4 {
+            assert_eq!(output[4].col(lane), AosTransform::IDENTITY);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_skeleton_track_count() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let mut builder = AnimationBuilder::new(1.0, skeleton.num_joints() + 3);
+        builder.tracks_mut()[0].translations = vec![(0.0, Vec3::ZERO), (1.0, Vec3::X)];
+        let too_large = builder.build().unwrap();
+
+        let mut output = vec![SoaTransform::default(); too_large.num_soa_tracks()];
+        let mut job: SamplingJobRef = SamplingJob::default();
+        job.set_animation(&too_large);
+        job.set_context(SamplingContext::from_animation(&too_large));
+        job.set_output(&mut output);
+        assert!(job.validate());
+
+        job.set_skeleton(&skeleton);
+        assert_eq!(job.num_skeleton_joints(), Some(skeleton.num_joints()));
+        assert!(!job.validate());
+        let err = job.run().unwrap_err();
+        assert!(err.is_track_count_mismatch());
+        assert!(matches!(
+            err,
+            OzzError::TrackCountMismatch { num_tracks, num_joints }
+                if num_tracks == skeleton.num_joints() + 3 && num_joints == skeleton.num_joints()
+        ));
+
+        job.set_animation(&animation);
+        job.set_context(SamplingContext::from_animation(&animation));
+        assert!(job.validate());
+        job.run().unwrap();
+
+        job.set_animation(&too_large);
+        job.set_context(SamplingContext::from_animation(&too_large));
+        job.clear_skeleton();
+        assert!(job.num_skeleton_joints().is_none());
+        job.run().unwrap();
+    }
+
+    // Keys that can't be sampled in place, like an `ArchivedAnimation` archived in a foreign byte order.
+    #[derive(Debug)]
+    struct ForeignKeys<'t>(&'t Animation);
+
+    impl AnimationData for ForeignKeys<'_> {
+        fn id(&self) -> u64 {
+            return self.0.id();
+        }
+
+        fn duration(&self) -> f32 {
+            return self.0.duration();
+        }
+
+        fn num_tracks(&self) -> usize {
+            return self.0.num_tracks();
+        }
+
+        fn translations(&self) -> &[Float3Key] {
+            return &[];
+        }
+
+        fn rotations(&self) -> &[QuaternionKey] {
+            return &[];
+        }
+
+        fn scales(&self) -> &[Float3Key] {
+            return &[];
+        }
+
+        fn check_keys(&self) -> Result<(), OzzError> {
+            return Err(OzzError::Unsupported("foreign byte order".into()));
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_check_keys() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let mut output = vec![SoaTransform::default(); animation.num_soa_tracks()];
+        let mut job: SamplingJob<ForeignKeys, &mut [SoaTransform]> = SamplingJob::default();
+        job.set_animation(ForeignKeys(&animation));
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_output(&mut output);
+        job.set_ratio(0.5);
+        assert!(!job.validate());
+        assert!(matches!(job.run(), Err(OzzError::Unsupported(_))));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_run_without_motion() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+
+        let mut output = vec![SoaTransform::default(); animation.num_soa_tracks()];
+        let mut models = vec![Mat4::default(); skeleton.num_joints()];
+        let mut job: SamplingJobRef = SamplingJob::default();
+        job.set_animation(&animation);
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_output(&mut output);
+        job.set_ratio(0.4);
+        let delta = job.run_without_motion(&skeleton, 0, 0.0, &mut models).unwrap();
+
+        // Raw root transforms, sampled without motion extraction.
+        let mut raw_output = vec![SoaTransform::default(); animation.num_soa_tracks()];
+        let mut raw_job: SamplingJobRef = SamplingJob::default();
+        raw_job.set_animation(&animation);
+        raw_job.set_context(SamplingContext::from_animation(&animation));
+        raw_job.set_output(&mut raw_output);
+        raw_job.set_ratio(0.0);
+        raw_job.run().unwrap();
+        let raw_begin = raw_job.output().unwrap()[0].col(0);
+        raw_job.set_ratio(0.4);
+        raw_job.run().unwrap();
+        let raw = raw_job.output().unwrap()[0].col(0);
+
+        // The delta is relative to the motion (horizontal translation and yaw) at ratio 0, in model space.
+        // Applying it to the in-place root gives back the raw root.
+        let heading = Quat::from_xyzw(0.0, raw_begin.rotation.y, 0.0, raw_begin.rotation.w).normalize();
+        let begin = AosTransform::from_translation_rotation(
+            Vec3::new(raw_begin.translation.x, 0.0, raw_begin.translation.z),
+            heading,
+        );
+        let heading = AosTransform::from_translation_rotation(Vec3::ZERO, heading);
+        let in_place = job.output().unwrap()[0].col(0);
+        let restored = begin.mul(&heading.inverse()).mul(&delta).mul(&heading).mul(&in_place);
+        assert!(delta.translation.length() > 1e-3);
+        assert!(restored.translation.abs_diff_eq(raw.translation, 1e-4));
+        assert!(
+            restored.rotation.abs_diff_eq(raw.rotation, 1e-4) || restored.rotation.abs_diff_eq(-raw.rotation, 1e-4)
+        );
+        assert!(restored.scale.abs_diff_eq(raw.scale, 1e-5));
+
+        // Same as chaining the jobs.
+        let mut expected_output = vec![SoaTransform::default(); animation.num_soa_tracks()];
+        let mut sampling_job: SamplingJobRef = SamplingJob::default();
+        sampling_job.set_animation(&animation);
+        sampling_job.set_context(SamplingContext::from_animation(&animation));
+        sampling_job.set_output(&mut expected_output);
+        sampling_job.set_ratio(0.4);
+        sampling_job.run().unwrap();
+
+        let mut motion_job: MotionExtractionJobRef = MotionExtractionJob::default();
+        motion_job.set_animation(&animation);
+        motion_job.set_joint(0);
+        motion_job.set_from(0.0);
+        motion_job.set_to(0.4);
+        motion_job.set_output(&mut expected_output);
+        motion_job.run().unwrap();
+        assert_eq!(delta, motion_job.delta());
+
+        let mut expected_models = vec![Mat4::default(); skeleton.num_joints()];
+        let mut ltm_job: LocalToModelJobRef = LocalToModelJob::default();
+        ltm_job.set_skeleton(&skeleton);
+        ltm_job.set_input(&expected_output);
+        ltm_job.set_output(&mut expected_models);
+        ltm_job.run().unwrap();
+        assert_eq!(models, expected_models);
+        assert_eq!(&job.output().unwrap()[..], &expected_output[..]);
+
+        let mut job: SamplingJobRef = SamplingJob::default();
+        job.set_animation(&animation);
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_output(&mut output);
+        let result = job.run_without_motion(&skeleton, animation.num_tracks(), 0.0, &mut models);
+        assert!(result.unwrap_err().is_invalid_job());
+        let result = job.run_without_motion(&skeleton, 0, 0.0, &mut models[..1]);
+        assert!(result.unwrap_err().is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_run_sparse() {
+        let mut builder = AnimationBuilder::new(1.0, 10);
+        let tracks = builder.tracks_mut();
+        tracks[1].translations = vec![(0.0, Vec3::ZERO), (1.0, Vec3::X)];
+        tracks[5].rotations = vec![(0.0, Quat::IDENTITY), (1.0, Quat::from_rotation_y(1.0))];
+        tracks[8].scales = vec![(0.0, Vec3::ONE), (1.0, Vec3::splat(2.0))];
+        tracks[9].translations = vec![(0.0, Vec3::Y), (1.0, Vec3::Y)];
+        let animation = builder.build().unwrap();
+        let joints = animation.animated_joints();
+        assert_eq!(joints, [1, 5, 8]);
+
+        let mut full_job: SamplingJob<&Animation, Vec<SoaTransform>> = SamplingJob::default();
+        full_job.set_animation(&animation);
+        full_job.set_context(SamplingContext::from_animation(&animation));
+        full_job.set_output(vec![SoaTransform::default(); animation.num_soa_tracks()]);
+
+        // Neither a context nor an output is needed.
+        let mut job: SamplingJob<&Animation, Vec<SoaTransform>> = SamplingJob::default();
+        job.set_animation(&animation);
+        let mut sparse = vec![(0, AosTransform::IDENTITY); 5];
+        for (ratio, joints) in [
+            (0.5, &joints[..]),
+            (0.7, &joints[..]),
+            (0.2, &joints[..]),
+            (0.6, &[8, 5][..]),
+        ] {
+            full_job.set_ratio(ratio);
+            full_job.run().unwrap();
+            job.set_ratio(ratio);
+            job.run_sparse(joints, &mut sparse).unwrap();
+            assert_eq!(sparse.len(), joints.len());
+            let output = full_job.output().unwrap();
+            for ((joint, transform), expected) in sparse.iter().zip(joints.iter()) {
+                assert_eq!(joint, expected);
+                assert_eq!(*transform, output[*joint as usize / 4].col(*joint as usize % 4));
+            }
+        }
+        assert!(job.output().is_none());
+
+        job.set_ratio(0.5);
+        job.run_sparse(&joints, &mut sparse).unwrap();
+        assert!(sparse[0].1.translation.abs_diff_eq(Vec3::new(0.5, 0.0, 0.0), 1e-6));
+        assert!(sparse[2].1.scale.abs_diff_eq(Vec3::splat(1.5), 1e-3));
+
+        // Only the soa tracks of the joints are sampled.
+        job.run_sparse(&[8], &mut sparse).unwrap();
+        assert!(sparse[0].1.scale.abs_diff_eq(Vec3::splat(1.5), 1e-3));
+        assert_eq!(job.sparse_context.as_ref().unwrap().max_soa_tracks(), 1);
+        assert_eq!(job.sparse_pose.len(), 1);
+
+        job.run_sparse(&[], &mut sparse).unwrap();
+        assert!(sparse.is_empty());
+        let err = job.run_sparse(&[10], &mut sparse).unwrap_err();
+        assert!(matches!(err, OzzError::InvalidIndex));
+    }
+
     #[cfg(feature = "rkyv")]
     #[test]
     #[wasm_bindgen_test]
@@ -2,16 +2,48 @@
 //! Skeleton data structure definition.
 //!
 
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(not(feature = "std"))]
+use bimap::BiBTreeMap;
+#[cfg(feature = "std")]
 use bimap::BiHashMap;
-use std::io::Read;
+use core::hash::{BuildHasher, Hash, Hasher};
+use core::simd::prelude::*;
+use glam::Mat4;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
 
-use crate::archive::Archive;
-use crate::base::{DeterministicState, OzzError, OzzIndex};
-use crate::math::SoaTransform;
+#[cfg(feature = "std")]
+use crate::archive::{Archive, ArchiveWriter};
+use crate::base::{DeterministicState, OzzError, OzzIndex, SKELETON_MAX_JOINTS};
+use crate::local_to_model_job::LocalToModelJobRef;
+#[cfg(not(feature = "std"))]
+use crate::math::F32Ext;
+use crate::math::{fx4_canonicalize, AosMat4, AosTransform, SoaTransform};
 
 /// Rexported `BiHashMap` in bimap crate.
+#[cfg(feature = "std")]
 pub type JointHashMap = BiHashMap<String, i16, DeterministicState, DeterministicState>;
 
+/// Rexported `BiBTreeMap` in bimap crate, bimap hash maps require the `std` feature.
+#[cfg(not(feature = "std"))]
+pub type JointHashMap = BiBTreeMap<String, i16>;
+
+// Creates an empty `JointHashMap`, with room for `capacity` joints if it's a hash map.
+#[cfg(feature = "std")]
+fn new_joint_names(capacity: usize) -> JointHashMap {
+    return JointHashMap::with_capacity_and_hashers(capacity, DeterministicState::new(), DeterministicState::new());
+}
+
+// Creates an empty `JointHashMap`, with room for `capacity` joints if it's a hash map.
+#[cfg(not(feature = "std"))]
+fn new_joint_names(_capacity: usize) -> JointHashMap {
+    return JointHashMap::new();
+}
+
+#[cfg(feature = "rkyv")]
 struct JointHashMapWrapper;
 
 #[cfg(feature = "rkyv")]
@@ -49,11 +81,7 @@ const _: () = {
             field: &ArchivedVec<Entry<ArchivedString, i16>>,
             deserializer: &mut D,
         ) -> Result<JointHashMap, D::Error> {
-            let mut result = JointHashMap::with_capacity_and_hashers(
-                field.len() as usize,
-                DeterministicState::new(),
-                DeterministicState::new(),
-            );
+            let mut result = new_joint_names(field.len() as usize);
             for entry in field.iter() {
                 result.insert(
                     entry.key.deserialize(deserializer)?,
@@ -76,7 +104,7 @@ const _: () = {
 /// order. This is enough to traverse the whole joint hierarchy. Use
 /// iter_depth_first() to implement a depth-first traversal utility.
 ///
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 #[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Skeleton {
@@ -84,6 +112,37 @@ pub struct Skeleton {
     pub joint_parents: Vec<i16>,
     #[cfg_attr(feature = "rkyv", with(JointHashMapWrapper))]
     pub joint_names: JointHashMap,
+    /// Optional user group of each joint (like "left arm" or "spine"), empty if joints have no group.
+    /// Tools metadata, not part of ozz archives. See `Skeleton::set_joint_groups`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    joint_groups: Vec<u16>,
+}
+
+///
+/// The rkyv layout of `Skeleton` before joint groups were added.
+///
+/// Used to load rkyv data written by previous versions: read it as `SkeletonV1`, then convert it with
+/// `Skeleton::from`. The converted skeleton has no joint groups.
+///
+#[cfg(feature = "rkyv")]
+#[derive(Debug, Default, Clone, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+pub struct SkeletonV1 {
+    pub joint_rest_poses: Vec<SoaTransform>,
+    pub joint_parents: Vec<i16>,
+    #[with(JointHashMapWrapper)]
+    pub joint_names: JointHashMap,
+}
+
+#[cfg(feature = "rkyv")]
+impl From<SkeletonV1> for Skeleton {
+    fn from(skeleton: SkeletonV1) -> Skeleton {
+        return Skeleton {
+            joint_rest_poses: skeleton.joint_rest_poses,
+            joint_parents: skeleton.joint_parents,
+            joint_names: skeleton.joint_names,
+            joint_groups: Vec::new(),
+        };
+    }
 }
 
 /// Skeleton meta in `Archive`.
@@ -117,10 +176,12 @@ impl Skeleton {
             joint_rest_poses,
             joint_parents,
             joint_names,
+            joint_groups: Vec::new(),
         };
     }
 
     /// Reads a `SkeletonMeta` from a reader.
+    #[cfg(feature = "std")]
     pub fn read_meta(archive: &mut Archive<impl Read>, with_joints: bool) -> Result<SkeletonMeta, OzzError> {
         if archive.tag() != Self::tag() {
             return Err(OzzError::InvalidTag);
@@ -134,17 +195,13 @@ impl Skeleton {
             return Ok(SkeletonMeta {
                 version: Self::version(),
                 num_joints,
-                joint_names: BiHashMap::with_hashers(DeterministicState::new(), DeterministicState::new()),
+                joint_names: new_joint_names(0),
                 joint_parents: Vec::new(),
             });
         }
 
         let _char_count: i32 = archive.read()?;
-        let mut joint_names = BiHashMap::with_capacity_and_hashers(
-            num_joints as usize,
-            DeterministicState::new(),
-            DeterministicState::new(),
-        );
+        let mut joint_names = new_joint_names(num_joints as usize);
         for idx in 0..num_joints {
             joint_names.insert(archive.read::<String>()?, idx as i16);
         }
@@ -160,6 +217,7 @@ impl Skeleton {
     }
 
     /// Reads a `Skeleton` from a reader.
+    #[cfg(feature = "std")]
     pub fn from_archive(archive: &mut Archive<impl Read>) -> Result<Skeleton, OzzError> {
         let meta = Skeleton::read_meta(archive, true)?;
 
@@ -173,22 +231,87 @@ impl Skeleton {
             joint_rest_poses,
             joint_parents: meta.joint_parents,
             joint_names: meta.joint_names,
+            joint_groups: Vec::new(),
         });
     }
 
+    /// Writes a `Skeleton` to an `ArchiveWriter`, readable by `Skeleton::from_archive`.
+    ///
+    /// Joint groups aren't part of ozz archive format, they are not written.
+    #[cfg(feature = "std")]
+    pub fn to_archive(&self, archive: &mut ArchiveWriter<impl Write>) -> Result<(), OzzError> {
+        if archive.tag() != Self::tag() {
+            return Err(OzzError::InvalidTag);
+        }
+        if archive.version() != Self::version() {
+            return Err(OzzError::InvalidVersion);
+        }
+
+        archive.write(&(self.num_joints() as i32))?;
+        if self.num_joints() == 0 {
+            return Ok(());
+        }
+
+        let names = self.joint_names_sorted();
+        let char_count: usize = names.iter().map(|(_, name)| name.len() + 1).sum();
+        archive.write(&(char_count as i32))?;
+        for (_, name) in names {
+            archive.write(&name.to_string())?;
+        }
+        archive.write_slice(&self.joint_parents)?;
+        archive.write_slice(&self.joint_rest_poses)?;
+        return Ok(());
+    }
+
     /// Reads a `Skeleton` from a file.
-    #[cfg(not(feature = "wasm"))]
+    #[cfg(all(feature = "std", not(feature = "wasm")))]
     pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> Result<Skeleton, OzzError> {
         let mut archive = Archive::from_path(path)?;
         return Skeleton::from_archive(&mut archive);
     }
 
+    /// Reads a `Skeleton` from a gzip or zlib compressed file, or an uncompressed one.
+    /// See `Archive::from_compressed_reader`.
+    #[cfg(all(feature = "std", not(feature = "wasm")))]
+    pub fn from_compressed_path<P: AsRef<std::path::Path>>(path: P) -> Result<Skeleton, OzzError> {
+        let file = std::fs::File::open(path)?;
+        let mut archive = Archive::from_compressed_reader(file)?;
+        return Skeleton::from_archive(&mut archive);
+    }
+
+    /// Writes a `Skeleton` to a file path.
+    #[cfg(all(feature = "std", not(feature = "wasm")))]
+    pub fn to_path<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), OzzError> {
+        let mut archive = ArchiveWriter::from_path(path, Self::tag(), Self::version())?;
+        return self.to_archive(&mut archive);
+    }
+
     // Only for wasm test in NodeJS environment.
     #[cfg(all(feature = "wasm", feature = "nodejs"))]
     pub fn from_path(path: &str) -> Result<Skeleton, OzzError> {
         let mut archive = Archive::from_path(path)?;
         return Skeleton::from_archive(&mut archive);
     }
+
+    /// Canonicalizes rest poses floats, `-0.0` becomes `0.0` and all NaN share the same bits.
+    ///
+    /// Opt-in, archives store floats as is. Call it before `to_archive` so that skeletons with the same
+    /// values give the same bytes on all platforms, as expected by pipelines hashing serialized assets.
+    pub fn canonicalize_floats(&mut self) {
+        for rest_pose in self.joint_rest_poses.iter_mut() {
+            let vec3s = [&mut rest_pose.translation, &mut rest_pose.scale];
+            for vec3 in vec3s {
+                vec3.x = fx4_canonicalize(vec3.x);
+                vec3.y = fx4_canonicalize(vec3.y);
+                vec3.z = fx4_canonicalize(vec3.z);
+            }
+            let rotation = &mut rest_pose.rotation;
+            rotation.x = fx4_canonicalize(rotation.x);
+            rotation.y = fx4_canonicalize(rotation.y);
+            rotation.z = fx4_canonicalize(rotation.z);
+            rotation.w = fx4_canonicalize(rotation.w);
+        }
+    }
 }
 
 impl Skeleton {
@@ -247,6 +370,66 @@ impl Skeleton {
         return self.joint_names.get_by_right(&index).map(|s| s.as_str());
     }
 
+    /// Gets joint names sorted by joint index, as `(index, name)`.
+    ///
+    /// `joint_names` iteration order is deterministic but not sorted, this gives a canonical order for
+    /// serialization or diffing.
+    pub fn joint_names_sorted(&self) -> Vec<(i16, &str)> {
+        let mut names: Vec<(i16, &str)> = (self.joint_names.iter())
+            .map(|(name, idx)| (*idx, name.as_str()))
+            .collect();
+        names.sort_unstable_by_key(|(idx, _)| *idx);
+        return names;
+    }
+
+    /// Exports the joint hierarchy as `(parents, names)`, both ordered by joint index.
+    ///
+    /// A plain parent array and name list without rest poses, for external tools like debug views or graph
+    /// visualizers. Joints without name get an empty name.
+    pub fn export_hierarchy(&self) -> (Vec<i16>, Vec<String>) {
+        let mut names = vec![String::new(); self.num_joints()];
+        for (name, idx) in self.joint_names.iter() {
+            if let Some(slot) = names.get_mut(*idx as usize) {
+                *slot = name.clone();
+            }
+        }
+        return (self.joint_parents.clone(), names);
+    }
+
+    /// Builds the remap from an external joint order (like an engine skinning order) to `Skeleton` joints.
+    ///
+    /// Returns, for each name of `external_names`, the index of the joint with this name, to reorder poses
+    /// with `apply_remap`. Returns `OzzError::InvalidIndex` if a name isn't a joint of `Skeleton`.
+    pub fn build_remap(&self, external_names: &[&str]) -> Result<Vec<i16>, OzzError> {
+        return (external_names.iter())
+            .map(|name| self.joint_by_name(name).ok_or(OzzError::InvalidIndex))
+            .collect();
+    }
+
+    /// Computes a 64-bit hash of `Skeleton` content, to key asset caches.
+    ///
+    /// The hash covers joint parents, names in joint order, and rest poses quantized to 1e-5 to ignore float
+    /// noise (and -0.0). It uses `DeterministicState`, so it is reproducible across runs: structurally
+    /// identical skeletons hash equal. Joint groups aren't hashed.
+    pub fn content_hash(&self) -> u64 {
+        let quantize = |x: f32| (x * 1e5).round() as i64;
+        let mut hasher = DeterministicState::new().build_hasher();
+        self.joint_parents.hash(&mut hasher);
+        for (_, name) in self.joint_names_sorted() {
+            name.hash(&mut hasher);
+        }
+        for idx in 0..self.num_joints() {
+            let rest_pose = self.joint_rest_poses[idx / 4].col(idx % 4);
+            let translation = rest_pose.translation.to_array();
+            let rotation = rest_pose.rotation.to_array();
+            let scale = rest_pose.scale.to_array();
+            for x in translation.iter().chain(rotation.iter()).chain(scale.iter()) {
+                quantize(*x).hash(&mut hasher);
+            }
+        }
+        return hasher.finish();
+    }
+
     /// Test if a joint is a leaf.
     ///
     /// * `joint` - `joint` must be in range [0, num joints].
@@ -257,6 +440,19 @@ impl Skeleton {
         return next == self.num_joints() || (self.joint_parents()[next] as i32 != joint.i32());
     }
 
+    /// Gets the end of a joint's subtree, the index following its last descendant.
+    ///
+    /// * `joint` - `joint` must be in range [0, num joints].
+    ///   Joints are ordered depth-first, so `joint` subtree is the joint range [joint, subtree_end).
+    #[inline]
+    pub fn subtree_end(&self, joint: impl OzzIndex) -> usize {
+        let mut end = joint.usize() + 1;
+        while end < self.num_joints() && self.joint_parent(end) as i32 >= joint.i32() {
+            end += 1;
+        }
+        return end;
+    }
+
     /// Iterates through the joint hierarchy in depth-first order.
     ///
     /// * `from` - The joint index to start from. If negative, the iteration starts from the root.
@@ -286,10 +482,302 @@ impl Skeleton {
             f(i as i16, parent);
         }
     }
+
+    /// Gets joint groups of `Skeleton`, ordered like joints. Empty if joints have no group.
+    #[inline]
+    pub fn joint_groups(&self) -> &[u16] {
+        return &self.joint_groups;
+    }
+
+    /// Sets joint groups of `Skeleton`.
+    ///
+    /// User defined group of each joint, ordered like joints. Returns `OzzError::InvalidIndex` if `groups`
+    /// isn't empty and its length differs from the number of joints.
+    pub fn set_joint_groups(&mut self, groups: Vec<u16>) -> Result<(), OzzError> {
+        if !groups.is_empty() && groups.len() != self.num_joints() {
+            return Err(OzzError::InvalidIndex);
+        }
+        self.joint_groups = groups;
+        return Ok(());
+    }
+
+    /// Gets the group of a joint, `None` if joints have no group or `joint` is out of range.
+    #[inline]
+    pub fn joint_group(&self, joint: impl OzzIndex) -> Option<u16> {
+        return self.joint_groups.get(joint.usize()).copied();
+    }
+
+    /// Gets the joints of `group`, in joints order.
+    pub fn joints_in_group(&self, group: u16) -> Vec<i16> {
+        return (self.joint_groups.iter().enumerate())
+            .filter(|(_, joint_group)| **joint_group == group)
+            .map(|(idx, _)| idx as i16)
+            .collect();
+    }
+
+    /// Computes the inverse bind matrices of the skeleton, ordered like skeleton's joints.
+    ///
+    /// Bind matrices are the model-space matrices of the rest pose. Multiplying a model-space matrix by its
+    /// joint inverse bind matrix gives the skinning matrix (see `LocalToModelJob::set_inverse_bind`).
+    ///
+    /// Returns the error of the `LocalToModelJob` computing the rest pose model-space matrices.
+    pub fn inverse_bind_matrices(&self) -> Result<Vec<Mat4>, OzzError> {
+        let mut matrices = vec![Mat4::IDENTITY; self.num_joints()];
+        let mut job: LocalToModelJobRef = LocalToModelJobRef::default();
+        job.set_skeleton(self);
+        job.set_input(self.joint_rest_poses());
+        job.set_output(&mut matrices);
+        job.run()?;
+        matrices.iter_mut().for_each(|m| *m = AosMat4::from(*m).invert().into());
+        return Ok(matrices);
+    }
+
+    /// Tests if a local-space pose is within `tolerance` of the rest pose.
+    ///
+    /// Translation, rotation and scale components are compared lane-wise, rotations `q` and `-q` are
+    /// equivalent. Padding lanes are ignored. Returns false if `local` is smaller than the number of soa
+    /// joints, or contains NaN.
+    pub fn is_rest_pose(&self, local: &[SoaTransform], tolerance: f32) -> bool {
+        if local.len() < self.num_soa_joints() {
+            return false;
+        }
+        let tolerance = f32x4::splat(tolerance);
+        let near = |a: f32x4, b: f32x4| (a - b).abs().simd_le(tolerance);
+        for (idx, (pose, rest)) in local.iter().zip(self.joint_rest_poses.iter()).enumerate() {
+            let (t, rt) = (&pose.translation, &rest.translation);
+            let (s, rs) = (&pose.scale, &rest.scale);
+            let (q, rq) = (&pose.rotation, &rest.rotation);
+            let translation = near(t.x, rt.x) & near(t.y, rt.y) & near(t.z, rt.z);
+            let scale = near(s.x, rs.x) & near(s.y, rs.y) & near(s.z, rs.z);
+            let rotation = (near(q.x, rq.x) & near(q.y, rq.y) & near(q.z, rq.z) & near(q.w, rq.w))
+                | (near(q.x, -rq.x) & near(q.y, -rq.y) & near(q.z, -rq.z) & near(q.w, -rq.w));
+            let lanes = (1u64 << (self.num_joints() - idx * 4).min(4)) - 1;
+            if (translation & rotation & scale).to_bitmask() & lanes != lanes {
+                return false;
+            }
+        }
+        return true;
+    }
+
+    /// Extracts the subtree of `root` into a standalone `Skeleton`, like a detachable limb.
+    ///
+    /// The new skeleton holds `root` and its descendants with their names, rest poses and groups. `root`
+    /// becomes the root (parent -1), indices are remapped from 0 in the same depth-first order.
+    ///
+    /// Returns `OzzError::InvalidIndex` if `root` is out of range, and `OzzError::DuplicateName` if joint
+    /// names aren't unique.
+    pub fn extract_subtree(&self, root: i16) -> Result<Skeleton, OzzError> {
+        if root < 0 || root as usize >= self.num_joints() {
+            return Err(OzzError::InvalidIndex);
+        }
+        let begin = root as usize;
+        let end = self.subtree_end(begin);
+        let mut builder = SkeletonBuilder::new();
+        for idx in begin..end {
+            let parent = if idx == begin {
+                -1
+            } else {
+                self.joint_parent(idx) - root
+            };
+            let name = self.name_by_joint(idx as i16).unwrap_or_default();
+            builder.add_joint(name, parent, self.joint_rest_poses[idx / 4].col(idx % 4));
+        }
+
+        // The subtree is contiguous and depth-first ordered, the builder keeps joints order.
+        let mut skeleton = builder.build()?;
+        if !self.joint_groups.is_empty() {
+            skeleton.joint_groups = self.joint_groups[begin..end].to_vec();
+        }
+        return Ok(skeleton);
+    }
+
+    /// Grafts `other` into `Skeleton`, the roots of `other` becoming children of joint `attach_to`.
+    ///
+    /// Joints of `other` are inserted after the existing children subtrees of `attach_to`, with their names
+    /// and rest poses. Indices are remapped to keep the depth-first order, joints following the grafted
+    /// joints are shifted. Joint groups are kept if both skeletons have groups, cleared otherwise.
+    ///
+    /// Returns `OzzError::InvalidIndex` if `attach_to` is out of range or there are too many joints, and
+    /// `OzzError::DuplicateName` if a name of `other` is already used. `Skeleton` is unchanged on error.
+    pub fn graft(&mut self, other: &Skeleton, attach_to: i16) -> Result<(), OzzError> {
+        if attach_to < 0 || attach_to as usize >= self.num_joints() {
+            return Err(OzzError::InvalidIndex);
+        }
+
+        let mut builder = SkeletonBuilder::new();
+        let offset = self.num_joints() as i16;
+        for (skeleton, offset, root_parent) in [(&*self, 0, -1), (other, offset, attach_to)] {
+            for idx in 0..skeleton.num_joints() {
+                let parent = skeleton.joint_parent(idx);
+                let parent = if parent < 0 { root_parent } else { parent + offset };
+                let name = skeleton.name_by_joint(idx as i16).unwrap_or_default();
+                builder.add_joint(name, parent, skeleton.joint_rest_poses[idx / 4].col(idx % 4));
+            }
+        }
+        let mut grafted = builder.build()?;
+
+        if !self.joint_groups.is_empty() && !other.joint_groups.is_empty() {
+            let mut groups = vec![0; grafted.num_joints()];
+            let sources = self.joint_groups.iter().chain(other.joint_groups.iter());
+            for ((name, _, _), group) in builder.joints().iter().zip(sources) {
+                if let Some(idx) = grafted.joint_by_name(name) {
+                    groups[idx as usize] = *group;
+                }
+            }
+            grafted.joint_groups = groups;
+        }
+        *self = grafted;
+        return Ok(());
+    }
+}
+
+///
+/// Builds a runtime `Skeleton` from joints added in any order.
+///
+/// Joints are added with their name, parent (index returned by a previous or later `add_joint()`, -1 for
+/// roots) and local-space rest pose, or model-space rest matrix with `add_joint_model()`. `build()` sorts joints in the depth-first order required by ozz
+/// runtime (parents before children, siblings in insertion order), so the skeleton can have several roots.
+///
+#[derive(Debug, Default, Clone)]
+pub struct SkeletonBuilder {
+    joints: Vec<(String, i16, AosTransform)>,
+    models: Vec<Option<Mat4>>,
+}
+
+impl SkeletonBuilder {
+    /// Creates an empty `SkeletonBuilder`.
+    #[inline]
+    pub fn new() -> SkeletonBuilder {
+        return SkeletonBuilder {
+            joints: Vec::new(),
+            models: Vec::new(),
+        };
+    }
+
+    /// Gets joints of `SkeletonBuilder`, as `(name, parent, rest pose)`, in insertion order.
+    ///
+    /// Rest poses of joints added with `add_joint_model()` are model-space until `build()`.
+    #[inline]
+    pub fn joints(&self) -> &[(String, i16, AosTransform)] {
+        return &self.joints;
+    }
+
+    /// Adds a joint to `SkeletonBuilder`.
+    ///
+    /// Returns the index of the joint in the builder, that can be used as `parent` of other joints.
+    /// Indices of the built skeleton can differ, find joints by name.
+    #[inline]
+    pub fn add_joint(&mut self, name: &str, parent: i16, rest_pose: AosTransform) -> i16 {
+        self.joints.push((name.to_string(), parent, rest_pose));
+        self.models.push(None);
+        return (self.joints.len() - 1) as i16;
+    }
+
+    /// Adds a joint to `SkeletonBuilder`, with a model-space rest matrix (like a glTF bind matrix).
+    ///
+    /// The matrix is converted to a local-space rest pose by `build()`, relative to the parent model-space
+    /// rest matrix. Model-space and local-space joints can be mixed. Like `model_to_local`, shear is lost.
+    ///
+    /// Returns the index of the joint in the builder, see `add_joint()`.
+    #[inline]
+    pub fn add_joint_model(&mut self, name: &str, parent: i16, model_matrix: Mat4) -> i16 {
+        let rest_pose = AosTransform::from(model_matrix);
+        self.joints.push((name.to_string(), parent, rest_pose));
+        self.models.push(Some(model_matrix));
+        return (self.joints.len() - 1) as i16;
+    }
+
+    /// Clears joints of `SkeletonBuilder`.
+    #[inline]
+    pub fn clear_joints(&mut self) {
+        self.joints.clear();
+        self.models.clear();
+    }
+
+    /// Builds a `Skeleton` from the joints.
+    ///
+    /// Returns `OzzError::InvalidIndex` if a parent is out of range or there are too many joints,
+    /// `OzzError::CyclicHierarchy` if the hierarchy has a cycle, and `OzzError::DuplicateName` if names aren't
+    /// unique.
+    pub fn build(&self) -> Result<Skeleton, OzzError> {
+        let num_joints = self.joints.len();
+        if num_joints > SKELETON_MAX_JOINTS as usize {
+            return Err(OzzError::InvalidIndex);
+        }
+
+        let mut roots = Vec::new();
+        let mut children = vec![Vec::new(); num_joints];
+        for (idx, (_, parent, _)) in self.joints.iter().enumerate() {
+            if *parent < 0 {
+                roots.push(idx);
+            } else if *parent as usize == idx {
+                return Err(OzzError::CyclicHierarchy);
+            } else if (*parent as usize) < num_joints {
+                children[*parent as usize].push(idx);
+            } else {
+                return Err(OzzError::InvalidIndex);
+            }
+        }
+
+        // Depth-first order, joints of a cycle are never reached.
+        let mut order = Vec::with_capacity(num_joints);
+        let mut stack: Vec<usize> = roots.into_iter().rev().collect();
+        while let Some(idx) = stack.pop() {
+            order.push(idx);
+            stack.extend(children[idx].iter().rev());
+        }
+        if order.len() != num_joints {
+            return Err(OzzError::CyclicHierarchy);
+        }
+
+        let mut remap = vec![-1i16; num_joints];
+        for (new_idx, old_idx) in order.iter().enumerate() {
+            remap[*old_idx] = new_idx as i16;
+        }
+
+        let mut joint_names = new_joint_names(num_joints);
+        let mut joint_parents = Vec::with_capacity(num_joints);
+        let mut joint_rest_poses = vec![SoaTransform::default(); num_joints.div_ceil(4)];
+        for pose in joint_rest_poses.iter_mut() {
+            for lane in 0..4 {
+                pose.set_col(lane, &AosTransform::IDENTITY);
+            }
+        }
+        // Parents come first in depth-first order, their model-space rest matrices are known. Roots (-1) are
+        // relative to the identity.
+        let mut models = vec![Mat4::IDENTITY; num_joints];
+        for (new_idx, old_idx) in order.iter().enumerate() {
+            let (name, parent, rest_pose) = &self.joints[*old_idx];
+            if joint_names.insert_no_overwrite(name.clone(), new_idx as i16).is_err() {
+                return Err(OzzError::DuplicateName(name.clone()));
+            }
+            joint_parents.push(if *parent < 0 { -1 } else { remap[*parent as usize] });
+            let parent_model = models.get(*parent as usize).copied().unwrap_or(Mat4::IDENTITY);
+            let rest_pose = match self.models[*old_idx] {
+                Some(model) => {
+                    models[*old_idx] = model;
+                    AosTransform::from(parent_model.inverse() * model)
+                }
+                None => {
+                    models[*old_idx] = parent_model * Mat4::from(*rest_pose);
+                    *rest_pose
+                }
+            };
+            joint_rest_poses[new_idx / 4].set_col(new_idx % 4, &rest_pose);
+        }
+
+        return Ok(Skeleton {
+            joint_rest_poses,
+            joint_parents,
+            joint_names,
+            joint_groups: Vec::new(),
+        });
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use glam::{Quat, Vec3};
     use std::simd::prelude::*;
     use wasm_bindgen_test::*;
 
@@ -364,6 +852,87 @@ mod tests {
         assert_eq!(skeleton.joint_by_name("Bip01 R Toe0Nub"), Some(66));
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_joint_names_sorted() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let names = skeleton.joint_names_sorted();
+        assert_eq!(names.len(), skeleton.num_joints());
+        for (pos, (idx, name)) in names.iter().enumerate() {
+            assert_eq!(*idx as usize, pos);
+            assert_eq!(skeleton.name_by_joint(*idx), Some(*name));
+        }
+        assert!(names.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(names[0], (0, "Hips"));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_export_hierarchy() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let (parents, names) = skeleton.export_hierarchy();
+        assert_eq!(parents, skeleton.joint_parents());
+        assert_eq!(names.len(), skeleton.num_joints());
+        for (idx, name) in names.iter().enumerate() {
+            assert_eq!(skeleton.joint_by_name(name), Some(idx as i16));
+        }
+        assert_eq!(names[0], "Hips");
+        assert_eq!(names[66], "Bip01 R Toe0Nub");
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_content_hash() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let hash = skeleton.content_hash();
+        assert_eq!(skeleton.clone().content_hash(), hash);
+        let loaded = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        assert_eq!(loaded.content_hash(), hash);
+
+        let rebuild = |rename: Option<&str>, offset: f32| {
+            let (parents, names) = skeleton.export_hierarchy();
+            let mut builder = SkeletonBuilder::new();
+            for (idx, (parent, name)) in parents.iter().zip(names.iter()).enumerate() {
+                let mut rest_pose = skeleton.joint_rest_poses()[idx / 4].col(idx % 4);
+                rest_pose.translation.x += offset;
+                let name = if idx == 10 { rename.unwrap_or(name) } else { name };
+                builder.add_joint(name, *parent, rest_pose);
+            }
+            return builder.build().unwrap().content_hash();
+        };
+        assert_eq!(rebuild(None, 0.0), hash);
+        assert_ne!(rebuild(Some("LeftArm2"), 0.0), hash);
+        assert_ne!(rebuild(None, 1e-3), hash);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_inverse_bind_matrices() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let inverse_bind = skeleton.inverse_bind_matrices().unwrap();
+        assert_eq!(inverse_bind.len(), skeleton.num_joints());
+
+        let mut rest = Vec::<Mat4>::with_capacity(skeleton.num_joints());
+        for idx in 0..skeleton.num_joints() {
+            let local = Mat4::from(skeleton.joint_rest_poses()[idx / 4].col(idx % 4));
+            let parent = skeleton.joint_parent(idx);
+            let model = if parent < 0 {
+                local
+            } else {
+                rest[parent as usize] * local
+            };
+            rest.push(model);
+            assert!((model * inverse_bind[idx]).abs_diff_eq(Mat4::IDENTITY, 1e-4), "{}", idx);
+        }
+
+        assert!(SkeletonBuilder::new()
+            .build()
+            .unwrap()
+            .inverse_bind_matrices()
+            .unwrap()
+            .is_empty());
+    }
+
     #[cfg(feature = "rkyv")]
     #[test]
     #[wasm_bindgen_test]
@@ -382,5 +951,313 @@ mod tests {
         assert_eq!(skeleton.joint_rest_poses(), skeleton2.joint_rest_poses());
         assert_eq!(skeleton.joint_parents(), skeleton2.joint_parents());
         assert_eq!(skeleton.joint_names(), skeleton2.joint_names());
+        assert_eq!(skeleton.joint_groups(), skeleton2.joint_groups());
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_rkyv_skeleton_v1() {
+        use rkyv::{AlignedVec, Deserialize};
+
+        // Written before joint groups were added.
+        let data = std::fs::read("./resource/playback/skeleton-legacy.rkyv").unwrap();
+        let mut buf = AlignedVec::new();
+        buf.extend_from_slice(&data);
+        let archived = unsafe { rkyv::archived_root::<SkeletonV1>(&buf) };
+        let mut deserializer = rkyv::Infallible::default();
+        let skeleton_v1: SkeletonV1 = archived.deserialize(&mut deserializer).unwrap();
+        let skeleton2 = Skeleton::from(skeleton_v1);
+
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        assert_eq!(skeleton.joint_rest_poses(), skeleton2.joint_rest_poses());
+        assert_eq!(skeleton.joint_parents(), skeleton2.joint_parents());
+        assert_eq!(skeleton.joint_names(), skeleton2.joint_names());
+        assert!(skeleton2.joint_groups().is_empty());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_joint_groups() {
+        let mut skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        assert!(skeleton.joint_groups().is_empty());
+        assert_eq!(skeleton.joint_group(0), None);
+        assert!(skeleton.joints_in_group(0).is_empty());
+
+        const SPINE: u16 = 1;
+        const LEFT_ARM: u16 = 2;
+        let left_arm = skeleton.joint_by_name("LeftArm").unwrap();
+        let groups: Vec<u16> = (0..skeleton.num_joints())
+            .map(|idx| match idx {
+                _ if (left_arm as usize..skeleton.subtree_end(left_arm)).contains(&idx) => LEFT_ARM,
+                _ if skeleton.name_by_joint(idx as i16).unwrap().starts_with("Spine") => SPINE,
+                _ => 0,
+            })
+            .collect();
+        skeleton.set_joint_groups(groups.clone()).unwrap();
+        assert_eq!(skeleton.joint_group(left_arm), Some(LEFT_ARM));
+        assert_eq!(skeleton.joint_group(skeleton.num_joints()), None);
+
+        let arm = skeleton.joints_in_group(LEFT_ARM);
+        assert_eq!(arm.len(), skeleton.subtree_end(left_arm) - left_arm as usize);
+        assert_eq!(arm[0], left_arm);
+        let spine = skeleton.joints_in_group(SPINE);
+        assert!(!spine.is_empty());
+        assert!(spine.iter().all(|idx| groups[*idx as usize] == SPINE));
+
+        assert!(matches!(
+            skeleton.set_joint_groups(vec![0; 3]),
+            Err(OzzError::InvalidIndex)
+        ));
+        assert_eq!(skeleton.joint_groups(), &groups);
+
+        #[cfg(feature = "serde")]
+        {
+            let mut json = serde_json::to_value(&skeleton).unwrap();
+            let skeleton2: Skeleton = serde_json::from_value(json.clone()).unwrap();
+            assert_eq!(skeleton2.joint_groups(), &groups);
+
+            // Legacy data without groups.
+            json.as_object_mut().unwrap().remove("joint_groups");
+            let skeleton2: Skeleton = serde_json::from_value(json).unwrap();
+            assert!(skeleton2.joint_groups().is_empty());
+        }
+        skeleton.set_joint_groups(Vec::new()).unwrap();
+        assert!(skeleton.joints_in_group(LEFT_ARM).is_empty());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_builder() {
+        let mut builder = SkeletonBuilder::new();
+        let t = |x: f32| AosTransform::from_translation_rotation(Vec3::new(x, 0.0, 0.0), Quat::IDENTITY);
+        let hand = builder.add_joint("hand", 2, t(3.0));
+        let root = builder.add_joint("root", -1, t(1.0));
+        let arm = builder.add_joint("arm", root, t(2.0));
+        builder.add_joint("other_root", -1, t(4.0));
+        builder.add_joint("leg", root, t(5.0));
+        assert_eq!(hand, 0);
+        assert_eq!(arm, 2);
+
+        let skeleton = builder.build().unwrap();
+        assert_eq!(skeleton.num_joints(), 5);
+        assert_eq!(skeleton.num_soa_joints(), 2);
+        let names: Vec<_> = (0..5).map(|idx| skeleton.name_by_joint(idx).unwrap()).collect();
+        assert_eq!(names, ["root", "arm", "hand", "leg", "other_root"]);
+        assert_eq!(skeleton.joint_parents(), &[-1, 0, 1, 0, -1]);
+        let ends: Vec<_> = (0..5).map(|idx| skeleton.subtree_end(idx)).collect();
+        assert_eq!(ends, [4, 3, 3, 4, 5]);
+        assert_eq!(skeleton.joint_rest_poses()[0].col(2), t(3.0));
+        assert_eq!(skeleton.joint_rest_poses()[1].col(0), t(4.0));
+        assert_eq!(skeleton.joint_rest_poses()[1].col(3), AosTransform::IDENTITY);
+
+        let mut builder = SkeletonBuilder::new();
+        builder.add_joint("a", 5, AosTransform::IDENTITY);
+        assert!(matches!(builder.build(), Err(OzzError::InvalidIndex)));
+
+        let mut builder = SkeletonBuilder::new();
+        builder.add_joint("a", 1, AosTransform::IDENTITY);
+        builder.add_joint("b", 0, AosTransform::IDENTITY);
+        assert!(builder.build().unwrap_err().is_cyclic_hierarchy());
+
+        let mut builder = SkeletonBuilder::new();
+        builder.add_joint("a", 0, AosTransform::IDENTITY);
+        assert!(builder.build().unwrap_err().is_cyclic_hierarchy());
+
+        let mut builder = SkeletonBuilder::new();
+        builder.add_joint("a", -1, AosTransform::IDENTITY);
+        builder.add_joint("a", 0, AosTransform::IDENTITY);
+        assert!(matches!(builder.build(), Err(OzzError::DuplicateName(name)) if name == "a"));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_builder_model() {
+        let models = [
+            Mat4::from_rotation_translation(Quat::from_rotation_z(0.5), Vec3::new(0.0, 1.0, 0.0)),
+            Mat4::from_scale_rotation_translation(Vec3::splat(2.0), Quat::from_rotation_x(-0.3), Vec3::X),
+            Mat4::from_rotation_translation(Quat::from_rotation_y(1.2), Vec3::new(1.0, 2.0, 3.0)),
+        ];
+        let mut builder = SkeletonBuilder::new();
+        let hand = builder.add_joint_model("hand", 2, models[2]);
+        let root = builder.add_joint_model("root", -1, models[0]);
+        builder.add_joint_model("arm", root, models[1]);
+        let offset = AosTransform::from_translation_rotation(Vec3::new(0.0, 0.5, 0.0), Quat::IDENTITY);
+        builder.add_joint("finger", hand, offset);
+        let skeleton = builder.build().unwrap();
+        assert_eq!(skeleton.joint_parents(), &[-1, 0, 1, 2]);
+
+        let mut output = vec![Mat4::IDENTITY; skeleton.num_joints()];
+        let mut job: LocalToModelJobRef = LocalToModelJobRef::default();
+        job.set_skeleton(&skeleton);
+        job.set_input(skeleton.joint_rest_poses());
+        job.set_output(&mut output);
+        job.run().unwrap();
+        for (model, expected) in output.iter().zip(models.iter()) {
+            assert!(model.abs_diff_eq(*expected, 1e-5), "{} {}", model, expected);
+        }
+        assert!(output[3].abs_diff_eq(models[2] * Mat4::from(offset), 1e-5));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_canonicalize_floats() {
+        let to_bytes = |zero: f32| {
+            let mut builder = SkeletonBuilder::new();
+            let root = builder.add_joint("root", -1, AosTransform::IDENTITY);
+            let rest_pose = AosTransform::from_translation_rotation(Vec3::new(zero, 1.0, 0.0), Quat::IDENTITY);
+            builder.add_joint("child", root, rest_pose);
+            let mut skeleton = builder.build().unwrap();
+            let mut writer = ArchiveWriter::to_vec(Skeleton::tag(), Skeleton::version()).unwrap();
+            skeleton.to_archive(&mut writer).unwrap();
+            let raw = writer.into_inner();
+
+            skeleton.canonicalize_floats();
+            let mut writer = ArchiveWriter::to_vec(Skeleton::tag(), Skeleton::version()).unwrap();
+            skeleton.to_archive(&mut writer).unwrap();
+            return (raw, writer.into_inner());
+        };
+        let (raw_positive, positive) = to_bytes(0.0);
+        let (raw_negative, negative) = to_bytes(-0.0);
+        assert_ne!(raw_positive, raw_negative);
+        assert_eq!(raw_positive, positive);
+        assert_eq!(positive, negative);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_is_rest_pose() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let mut pose = skeleton.joint_rest_poses().to_vec();
+        assert!(skeleton.is_rest_pose(&pose, 0.0));
+        assert!(!skeleton.is_rest_pose(&pose[1..], 1e-3));
+
+        // Padding lanes are ignored, -q is the same rotation.
+        let last = skeleton.num_soa_joints() - 1;
+        pose[last].translation.x[3] = 100.0;
+        pose[0].rotation = SoaQuat {
+            x: -pose[0].rotation.x,
+            y: -pose[0].rotation.y,
+            z: -pose[0].rotation.z,
+            w: -pose[0].rotation.w,
+        };
+        assert!(skeleton.is_rest_pose(&pose, 1e-6));
+
+        pose[5].scale.y[2] += 1e-2;
+        assert!(skeleton.is_rest_pose(&pose, 2e-2));
+        assert!(!skeleton.is_rest_pose(&pose, 1e-3));
+        pose[5].scale.y[2] = f32::NAN;
+        assert!(!skeleton.is_rest_pose(&pose, 1.0));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_extract_subtree() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let left_arm = skeleton.joint_by_name("LeftArm").unwrap();
+        let end = skeleton.subtree_end(left_arm);
+
+        let arm = skeleton.extract_subtree(left_arm).unwrap();
+        assert_eq!(arm.num_joints(), end - left_arm as usize);
+        assert_eq!(arm.joint_parent(0), -1);
+        assert_eq!(arm.joint_by_name("LeftArm"), Some(0));
+        assert!(arm.joint_by_name("RightArm").is_none());
+        for idx in 0..arm.num_joints() {
+            let src = left_arm as usize + idx;
+            assert_eq!(arm.name_by_joint(idx as i16), skeleton.name_by_joint(src as i16));
+            assert_eq!(
+                arm.joint_rest_poses()[idx / 4].col(idx % 4),
+                skeleton.joint_rest_poses()[src / 4].col(src % 4)
+            );
+            if idx > 0 {
+                assert_eq!(arm.joint_parent(idx), skeleton.joint_parent(src) - left_arm);
+            }
+        }
+        assert!(arm.joint_groups().is_empty());
+
+        let mut skeleton = skeleton;
+        skeleton
+            .set_joint_groups((0..skeleton.num_joints() as u16).collect())
+            .unwrap();
+        let arm = skeleton.extract_subtree(left_arm).unwrap();
+        assert_eq!(arm.joint_group(0), Some(left_arm as u16));
+        assert_eq!(arm.extract_subtree(0).unwrap().num_joints(), arm.num_joints());
+        assert_eq!(
+            skeleton.extract_subtree(0).unwrap().joint_parents(),
+            skeleton.joint_parents()
+        );
+
+        assert!(matches!(skeleton.extract_subtree(-1), Err(OzzError::InvalidIndex)));
+        let num_joints = skeleton.num_joints() as i16;
+        assert!(matches!(
+            skeleton.extract_subtree(num_joints),
+            Err(OzzError::InvalidIndex)
+        ));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_graft() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let left_arm = skeleton.joint_by_name("LeftArm").unwrap();
+        let (begin, end) = (left_arm as usize, skeleton.subtree_end(left_arm));
+        let arm = skeleton.extract_subtree(left_arm).unwrap();
+
+        // The skeleton without its left arm.
+        let mut builder = SkeletonBuilder::new();
+        for idx in (0..begin).chain(end..skeleton.num_joints()) {
+            let parent = skeleton.joint_parent(idx);
+            let parent = if parent as usize >= end {
+                parent - arm.num_joints() as i16
+            } else {
+                parent
+            };
+            let rest_pose = skeleton.joint_rest_poses()[idx / 4].col(idx % 4);
+            builder.add_joint(skeleton.name_by_joint(idx as i16).unwrap(), parent, rest_pose);
+        }
+        let mut grafted = builder.build().unwrap();
+        assert_eq!(grafted.num_joints(), skeleton.num_joints() - arm.num_joints());
+
+        let shoulder = grafted.joint_by_name("LeftShoulder").unwrap();
+        grafted.graft(&arm, shoulder).unwrap();
+        assert_eq!(grafted.joint_parents(), skeleton.joint_parents());
+        assert_eq!(grafted.joint_rest_poses(), skeleton.joint_rest_poses());
+        assert_eq!(grafted.joint_names_sorted(), skeleton.joint_names_sorted());
+
+        // Name collision and out of range attachment.
+        assert!(grafted.graft(&arm, shoulder).unwrap_err().is_duplicate_name());
+        assert!(matches!(grafted.graft(&arm, 100), Err(OzzError::InvalidIndex)));
+        assert_eq!(grafted.joint_parents(), skeleton.joint_parents());
+    }
+
+    #[cfg(all(feature = "gzip", not(feature = "wasm")))]
+    #[test]
+    fn test_from_compressed_path() {
+        use crate::archive::crc32;
+        use miniz_oxide::deflate::compress_to_vec;
+
+        let raw = std::fs::read("./resource/playback/skeleton.ozz").unwrap();
+        let mut gzip = vec![0x1F, 0x8B, 8, 0, 0, 0, 0, 0, 0, 0xFF];
+        gzip.extend(compress_to_vec(&raw, 6));
+        gzip.extend(crc32(&raw).to_le_bytes());
+        gzip.extend((raw.len() as u32).to_le_bytes());
+        let path = std::env::temp_dir().join("ozz-animation-rs-test-skeleton.ozz.gz");
+        std::fs::write(&path, &gzip).unwrap();
+        let compressed = Skeleton::from_compressed_path(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let expected = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let compressed = compressed.unwrap();
+        assert_eq!(compressed.joint_rest_poses(), expected.joint_rest_poses());
+        assert_eq!(compressed.joint_parents(), expected.joint_parents());
+        assert_eq!(compressed.joint_names(), expected.joint_names());
+
+        // Uncompressed archives are read as is.
+        let uncompressed = Skeleton::from_compressed_path("./resource/playback/skeleton.ozz").unwrap();
+        assert_eq!(uncompressed.joint_parents(), expected.joint_parents());
+
+        let last = gzip.len() - 5;
+        gzip[last] ^= 1;
+        assert!(Archive::from_compressed_reader(gzip.as_slice()).is_err());
     }
 }
@@ -2,10 +2,12 @@
 //! Skinning Job.
 //!
 
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt::Debug;
 use glam::{Mat4, Vec3, Vec4};
-use std::cell::RefCell;
-use std::fmt::Debug;
-use std::rc::Rc;
+#[cfg(feature = "std")]
 use std::sync::{Arc, RwLock};
 
 use crate::base::{OzzBuf, OzzError, OzzMutBuf};
@@ -52,6 +54,7 @@ pub type SkinningJobRc = SkinningJob<
     Rc<RefCell<Vec<Vec3>>>,
     Rc<RefCell<Vec<Vec3>>>,
 >;
+#[cfg(feature = "std")]
 pub type SkinningJobArc = SkinningJob<
     Arc<RwLock<Vec<Mat4>>>,
     Arc<RwLock<Vec<u16>>>,
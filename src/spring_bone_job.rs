@@ -0,0 +1,354 @@
+//!
+//! Spring Bone Job.
+//!
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use glam::{Mat4, Quat, Vec3};
+#[cfg(feature = "std")]
+use std::sync::{Arc, RwLock};
+
+use crate::base::{OzzBuf, OzzError, OzzMutBuf};
+use crate::math::SoaTransform;
+
+/// A spring (jiggle) bone of `SpringBoneJob`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpringBone {
+    /// Joint index of the bone.
+    pub joint: i16,
+    /// Tip of the bone in joint local-space, the point the spring drags. Usually the child joint offset.
+    pub tip: Vec3,
+    /// Spring stiffness, the acceleration pulling the tip back to the animated tip per unit of distance.
+    pub stiffness: f32,
+    /// Spring damping, the deceleration per unit of tip velocity.
+    pub damping: f32,
+}
+
+impl SpringBone {
+    /// Creates a `SpringBone`, with the tip along joint X axis (ozz bones axis).
+    #[inline]
+    pub fn new(joint: i16, length: f32, stiffness: f32, damping: f32) -> SpringBone {
+        return SpringBone {
+            joint,
+            tip: Vec3::new(length, 0.0, 0.0),
+            stiffness,
+            damping,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct SpringState {
+    position: Vec3,
+    velocity: Vec3,
+}
+
+///
+/// Offsets local rotations of spring bones, so they lag and oscillate behind their parent motion.
+///
+/// Each bone tip is a damped spring in model-space, pulled towards the animated tip position. The bone is
+/// then rotated to aim at the simulated tip. Spring state is kept between runs, the first run (or the
+/// first run after `reset()`) starts at rest.
+///
+/// Job inputs are the local-space pose and its model-space matrices (the output of `LocalToModelJob`).
+/// Job output is the local-space pose with spring bones rotations offset. Bones are simulated
+/// independently, from the input model-space matrices.
+///
+#[derive(Debug)]
+pub struct SpringBoneJob<
+    I = Rc<RefCell<Vec<SoaTransform>>>,
+    M = Rc<RefCell<Vec<Mat4>>>,
+    O = Rc<RefCell<Vec<SoaTransform>>>,
+> where
+    I: OzzBuf<SoaTransform>,
+    M: OzzBuf<Mat4>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    input: Option<I>,
+    models: Option<M>,
+    output: Option<O>,
+    bones: Vec<SpringBone>,
+    dt: f32,
+
+    states: Vec<SpringState>,
+}
+
+pub type SpringBoneJobRef<'t> = SpringBoneJob<&'t [SoaTransform], &'t [Mat4], &'t mut [SoaTransform]>;
+pub type SpringBoneJobRc =
+    SpringBoneJob<Rc<RefCell<Vec<SoaTransform>>>, Rc<RefCell<Vec<Mat4>>>, Rc<RefCell<Vec<SoaTransform>>>>;
+#[cfg(feature = "std")]
+pub type SpringBoneJobArc =
+    SpringBoneJob<Arc<RwLock<Vec<SoaTransform>>>, Arc<RwLock<Vec<Mat4>>>, Arc<RwLock<Vec<SoaTransform>>>>;
+
+impl<I, M, O> Default for SpringBoneJob<I, M, O>
+where
+    I: OzzBuf<SoaTransform>,
+    M: OzzBuf<Mat4>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    fn default() -> SpringBoneJob<I, M, O> {
+        return SpringBoneJob {
+            input: None,
+            models: None,
+            output: None,
+            bones: Vec::new(),
+            dt: 1.0 / 60.0,
+            states: Vec::new(),
+        };
+    }
+}
+
+impl<I, M, O> SpringBoneJob<I, M, O>
+where
+    I: OzzBuf<SoaTransform>,
+    M: OzzBuf<Mat4>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    /// Gets input of `SpringBoneJob`.
+    #[inline]
+    pub fn input(&self) -> Option<&I> {
+        return self.input.as_ref();
+    }
+
+    /// Sets input of `SpringBoneJob`.
+    ///
+    /// The animated local-space pose.
+    #[inline]
+    pub fn set_input(&mut self, input: I) {
+        self.input = Some(input);
+    }
+
+    /// Clears input of `SpringBoneJob`.
+    #[inline]
+    pub fn clear_input(&mut self) {
+        self.input = None;
+    }
+
+    /// Gets models of `SpringBoneJob`.
+    #[inline]
+    pub fn models(&self) -> Option<&M> {
+        return self.models.as_ref();
+    }
+
+    /// Sets models of `SpringBoneJob`.
+    ///
+    /// The model-space matrices of the animated pose, ordered like skeleton's joints.
+    #[inline]
+    pub fn set_models(&mut self, models: M) {
+        self.models = Some(models);
+    }
+
+    /// Clears models of `SpringBoneJob`.
+    #[inline]
+    pub fn clear_models(&mut self) {
+        self.models = None;
+    }
+
+    /// Gets output of `SpringBoneJob`.
+    #[inline]
+    pub fn output(&self) -> Option<&O> {
+        return self.output.as_ref();
+    }
+
+    /// Sets output of `SpringBoneJob`.
+    ///
+    /// The local-space pose with spring bones offset, at least as large as the input.
+    #[inline]
+    pub fn set_output(&mut self, output: O) {
+        self.output = Some(output);
+    }
+
+    /// Clears output of `SpringBoneJob`.
+    #[inline]
+    pub fn clear_output(&mut self) {
+        self.output = None;
+    }
+
+    /// Gets bones of `SpringBoneJob`.
+    #[inline]
+    pub fn bones(&self) -> &[SpringBone] {
+        return &self.bones;
+    }
+
+    /// Gets mutable bones of `SpringBoneJob`.
+    ///
+    /// Changing the bones resets the springs on the next run.
+    #[inline]
+    pub fn bones_mut(&mut self) -> &mut Vec<SpringBone> {
+        self.states.clear();
+        return &mut self.bones;
+    }
+
+    /// Gets dt of `SpringBoneJob`.
+    #[inline]
+    pub fn dt(&self) -> f32 {
+        return self.dt;
+    }
+
+    /// Sets dt of `SpringBoneJob`.
+    ///
+    /// Elapsed time since the previous run in seconds, 1/60 by default.
+    #[inline]
+    pub fn set_dt(&mut self, dt: f32) {
+        self.dt = dt;
+    }
+
+    /// Resets springs to rest, at the animated pose of the next run.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.states.clear();
+    }
+
+    /// Validates `SpringBoneJob` parameters.
+    pub fn validate(&self) -> bool {
+        return (|| {
+            let input = self.input.as_ref()?.buf().ok()?;
+            let models = self.models.as_ref()?.buf().ok()?;
+            let output = self.output.as_ref()?.buf().ok()?;
+            return Some(Self::validate_impl(&self.bones, self.dt, &input, &models, &output));
+        })()
+        .unwrap_or(false);
+    }
+
+    fn validate_impl(
+        bones: &[SpringBone],
+        dt: f32,
+        input: &[SoaTransform],
+        models: &[Mat4],
+        output: &[SoaTransform],
+    ) -> bool {
+        let valid_joint = |joint: i16| {
+            return joint >= 0 && (joint as usize) < models.len() && (joint as usize) < input.len() * 4;
+        };
+        return output.len() >= input.len() && dt >= 0.0 && bones.iter().all(|bone| valid_joint(bone.joint));
+    }
+
+    /// Runs spring bone job's task.
+    /// The validate job before any operation is performed.
+    pub fn run(&mut self) -> Result<(), OzzError> {
+        let input = self.input.as_ref().ok_or(OzzError::InvalidJob)?.buf()?;
+        let models = self.models.as_ref().ok_or(OzzError::InvalidJob)?.buf()?;
+        let mut output = self.output.as_mut().ok_or(OzzError::InvalidJob)?.mut_buf()?;
+        if !Self::validate_impl(&self.bones, self.dt, &input, &models, &output) {
+            return Err(OzzError::InvalidJob);
+        }
+
+        if self.states.len() != self.bones.len() {
+            self.states = (self.bones.iter())
+                .map(|bone| SpringState {
+                    position: models[bone.joint as usize].transform_point3(bone.tip),
+                    velocity: Vec3::ZERO,
+                })
+                .collect();
+        }
+
+        output[..input.len()].copy_from_slice(&input);
+        for (bone, state) in self.bones.iter().zip(self.states.iter_mut()) {
+            let idx = bone.joint as usize;
+            let model = &models[idx];
+            let origin = model.w_axis.truncate();
+            let target = model.transform_point3(bone.tip);
+
+            // Semi-implicit Euler integration of the damped spring.
+            let acceleration = (target - state.position) * bone.stiffness - state.velocity * bone.damping;
+            state.velocity += acceleration * self.dt;
+            state.position += state.velocity * self.dt;
+
+            // Model-space rotation from the animated to the simulated bone direction.
+            let from = (target - origin).normalize_or_zero();
+            let to = (state.position - origin).normalize_or_zero();
+            if from == Vec3::ZERO || to == Vec3::ZERO {
+                continue;
+            }
+            let correction = Quat::from_rotation_arc(from, to);
+
+            // Expressed in joint local-space, post-multiplied to the local rotation.
+            let (_, model_rotation, _) = model.to_scale_rotation_translation();
+            let local_correction = model_rotation.conjugate() * correction * model_rotation;
+            let rotation = output[idx / 4].rotation.col(idx % 4);
+            output[idx / 4]
+                .rotation
+                .set_col(idx % 4, (rotation * local_correction).normalize());
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod spring_bone_tests {
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::math::AosTransform;
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_validity() {
+        let input = vec![SoaTransform::default(); 1];
+        let models = vec![Mat4::IDENTITY; 2];
+        let mut output = vec![SoaTransform::default(); 1];
+
+        let mut job: SpringBoneJobRef = SpringBoneJob::default();
+        job.set_input(&input);
+        job.set_output(&mut output);
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        job.set_models(&models);
+        job.bones_mut().push(SpringBone::new(1, 1.0, 100.0, 10.0));
+        assert!(job.validate());
+
+        job.bones_mut().push(SpringBone::new(2, 1.0, 100.0, 10.0));
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+    }
+
+    type VecJob = SpringBoneJob<Vec<SoaTransform>, Vec<Mat4>, Vec<SoaTransform>>;
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_relax_to_rest() {
+        let mut input = vec![SoaTransform::default(); 1];
+        for lane in 0..4 {
+            input[0].set_col(lane, &AosTransform::IDENTITY);
+        }
+
+        let mut job: VecJob = SpringBoneJob::default();
+        job.set_input(input.clone());
+        job.set_output(input.clone());
+        job.bones_mut().push(SpringBone::new(1, 1.0, 200.0, 20.0));
+
+        // At rest, the bone follows the animation.
+        job.set_models(vec![Mat4::IDENTITY, Mat4::from_translation(Vec3::new(0.0, 1.0, 0.0))]);
+        job.run().unwrap();
+        let angle = |job: &VecJob| job.output().unwrap()[0].rotation.col(1).angle_between(Quat::IDENTITY);
+        assert!(angle(&job) < 1e-6);
+
+        // The bone moves up, its tip lags behind.
+        job.set_models(vec![Mat4::IDENTITY, Mat4::from_translation(Vec3::new(0.0, 1.5, 0.0))]);
+        job.run().unwrap();
+        let displaced = angle(&job);
+        assert!(displaced > 0.1);
+        // The bone tip points down.
+        let tip = job.output().unwrap()[0].rotation.col(1) * Vec3::X;
+        assert!(tip.y < 0.0);
+
+        let mut max_late = 0.0f32;
+        for step in 0..120 {
+            job.run().unwrap();
+            if step >= 60 {
+                max_late = max_late.max(angle(&job));
+            }
+        }
+        assert!(max_late < displaced * 0.05);
+        assert!(angle(&job) < 1e-3);
+        assert_eq!(job.output().unwrap()[0].rotation.col(0), Quat::IDENTITY);
+
+        // Reset snaps to the animated pose.
+        job.set_models(vec![Mat4::IDENTITY, Mat4::from_translation(Vec3::new(0.0, 5.0, 0.0))]);
+        job.reset();
+        job.run().unwrap();
+        assert!(angle(&job) < 1e-6);
+    }
+}
@@ -2,22 +2,46 @@
 //! Track data structure definition.
 //!
 
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Debug;
 use glam::{Quat, Vec2, Vec3, Vec4};
-use std::fmt::Debug;
-use std::io::Read;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
 
-use crate::archive::{Archive, ArchiveRead};
+#[cfg(feature = "std")]
+use crate::archive::{Archive, ArchiveRead, ArchiveWrite, ArchiveWriter};
 use crate::base::OzzError;
 
+/// Value type that can be read from and written to an `Archive`, implemented for all `ArchiveRead` and
+/// `ArchiveWrite` types. Without the `std` feature (no `Archive`), it's implemented for all types.
+#[cfg(feature = "std")]
+pub trait TrackArchiveValue: ArchiveRead<Self> + ArchiveWrite<Self> + Sized {}
+
+#[cfg(feature = "std")]
+impl<T: ArchiveRead<T> + ArchiveWrite<T>> TrackArchiveValue for T {}
+
+/// Value type that can be read from and written to an `Archive`, implemented for all `ArchiveRead` and
+/// `ArchiveWrite` types. Without the `std` feature (no `Archive`), it's implemented for all types.
+#[cfg(not(feature = "std"))]
+pub trait TrackArchiveValue {}
+
+#[cfg(not(feature = "std"))]
+impl<T> TrackArchiveValue for T {}
+
 /// Value type that can be stored in a `Track`.
 pub trait TrackValue
 where
-    Self: Debug + Default + Copy + Clone + PartialEq + ArchiveRead<Self>,
+    Self: Debug + Default + Copy + Clone + PartialEq + TrackArchiveValue,
 {
     /// Ozz file tag in '.ozz' file for `Archive`.
     fn tag() -> &'static str;
 
     /// Linear interpolation between two values.
+    ///
+    /// Quaternions use a normalized lerp along the shortest path, as ozz does. It's equal to slerp at
+    /// both ends and halfway, and much cheaper in between.
     fn lerp(a: Self, b: Self, t: f32) -> Self;
 
     // Compare two values with a maximum difference.
@@ -153,6 +177,7 @@ impl<V: TrackValue> Track<V> {
     }
 
     /// Reads an `Track` from an `Archive`.
+    #[cfg(feature = "std")]
     pub fn from_archive(archive: &mut Archive<impl Read>) -> Result<Track<V>, OzzError> {
         if archive.tag() != Self::tag() {
             return Err(OzzError::InvalidTag);
@@ -183,8 +208,28 @@ impl<V: TrackValue> Track<V> {
         });
     }
 
+    /// Writes a `Track` to an `ArchiveWriter`.
+    #[cfg(feature = "std")]
+    pub fn to_archive(&self, archive: &mut ArchiveWriter<impl Write>) -> Result<(), OzzError> {
+        if archive.tag() != Self::tag() {
+            return Err(OzzError::InvalidTag);
+        }
+        if archive.version() != Self::version() {
+            return Err(OzzError::InvalidVersion);
+        }
+
+        archive.write(&self.key_count)?;
+        archive.write(&(self.name.len() as u32))?;
+
+        archive.write_slice(&self.ratios)?;
+        archive.write_slice(&self.values)?;
+        archive.write_slice(&self.steps)?;
+        archive.write_slice(self.name.as_bytes())?;
+        return Ok(());
+    }
+
     /// Reads an `Track` from a file path.
-    #[cfg(not(feature = "wasm"))]
+    #[cfg(all(feature = "std", not(feature = "wasm")))]
     pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> Result<Track<V>, OzzError> {
         let mut archive = Archive::from_path(path)?;
         return Track::from_archive(&mut archive);
@@ -196,6 +241,13 @@ impl<V: TrackValue> Track<V> {
         let mut archive = Archive::from_path(path)?;
         return Track::from_archive(&mut archive);
     }
+
+    /// Writes a `Track` to a file path.
+    #[cfg(all(feature = "std", not(feature = "wasm")))]
+    pub fn to_path<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), OzzError> {
+        let mut archive = ArchiveWriter::from_path(path, Self::tag(), Self::version())?;
+        return self.to_archive(&mut archive);
+    }
 }
 
 impl<V: TrackValue> Track<V> {
@@ -229,3 +281,268 @@ impl<V: TrackValue> Track<V> {
         return &self.name;
     }
 }
+
+/// Keyframe interpolation mode of a `Track`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TrackInterpolation {
+    /// Linear interpolation to the next keyframe value.
+    #[default]
+    Linear,
+    /// Holds the keyframe value until the next keyframe.
+    Step,
+}
+
+///
+/// Runtime `Track` builder.
+///
+/// Builds a `Track` directly from keyframes, without going through an archive. This allows to
+/// author curves in code.
+///
+/// Keyframe ratios must be in range 0.0-1.0 and strictly ascending. If there's no keyframe at
+/// ratio 0 or 1, the first and last values are extended to the track bounds.
+///
+#[derive(Debug, Default, Clone)]
+pub struct TrackBuilder<V: TrackValue> {
+    keyframes: Vec<(f32, V, TrackInterpolation)>,
+    name: String,
+}
+
+impl<V: TrackValue> TrackBuilder<V> {
+    /// Creates an empty `TrackBuilder`.
+    #[inline]
+    pub fn new() -> TrackBuilder<V> {
+        return TrackBuilder {
+            keyframes: Vec::new(),
+            name: String::new(),
+        };
+    }
+
+    /// Gets name of `TrackBuilder`.
+    #[inline]
+    pub fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    /// Sets name of `TrackBuilder`.
+    ///
+    /// Name of the built track.
+    #[inline]
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+
+    /// Gets keyframes of `TrackBuilder`.
+    #[inline]
+    pub fn keyframes(&self) -> &[(f32, V, TrackInterpolation)] {
+        return &self.keyframes;
+    }
+
+    /// Adds a keyframe to `TrackBuilder`.
+    ///
+    /// Keyframes must be added in ascending ratio order, `interpolation` applies from this
+    /// keyframe to the next one.
+    #[inline]
+    pub fn add_keyframe(&mut self, ratio: f32, value: V, interpolation: TrackInterpolation) -> &mut TrackBuilder<V> {
+        self.keyframes.push((ratio, value, interpolation));
+        return self;
+    }
+
+    /// Clears keyframes of `TrackBuilder`.
+    #[inline]
+    pub fn clear_keyframes(&mut self) {
+        self.keyframes.clear();
+    }
+
+    /// Validates `TrackBuilder` keyframes.
+    pub fn validate(&self) -> bool {
+        let mut prev = -1.0;
+        for (ratio, _, _) in self.keyframes.iter() {
+            if !(0.0..=1.0).contains(ratio) || *ratio <= prev {
+                return false;
+            }
+            prev = *ratio;
+        }
+        return true;
+    }
+
+    /// Builds a `Track` from the keyframes.
+    ///
+    /// Returns `OzzError::InvalidKeyframe` if ratios are out of range or not strictly ascending.
+    pub fn build(&self) -> Result<Track<V>, OzzError> {
+        if !self.validate() {
+            return Err(OzzError::InvalidKeyframe);
+        }
+
+        let mut keyframes = Vec::with_capacity(self.keyframes.len() + 2);
+        if let Some(&(ratio, value, _)) = self.keyframes.first() {
+            if ratio != 0.0 {
+                keyframes.push((0.0, value, TrackInterpolation::Linear));
+            }
+        }
+        keyframes.extend_from_slice(&self.keyframes);
+        if let Some(&(ratio, value, _)) = self.keyframes.last() {
+            if ratio != 1.0 {
+                keyframes.push((1.0, value, TrackInterpolation::Linear));
+            }
+        }
+
+        let mut steps = vec![0u8; keyframes.len().div_ceil(8)];
+        for (idx, (_, _, interpolation)) in keyframes.iter().enumerate() {
+            if *interpolation == TrackInterpolation::Step {
+                steps[idx / 8] |= 1 << (idx & 7);
+            }
+        }
+
+        return Ok(Track {
+            key_count: keyframes.len() as u32,
+            ratios: keyframes.iter().map(|k| k.0).collect(),
+            values: keyframes.iter().map(|k| k.1).collect(),
+            steps,
+            name: self.name.clone(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod track_tests {
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::track_sampling_job::TrackSamplingJobRef;
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_builder_validity() {
+        let track = TrackBuilder::<f32>::new().build().unwrap();
+        assert_eq!(track.key_count(), 0);
+
+        let mut builder = TrackBuilder::<f32>::new();
+        builder.add_keyframe(-0.1, 0.0, TrackInterpolation::Linear);
+        assert!(builder.build().unwrap_err().is_invalid_keyframe());
+
+        let mut builder = TrackBuilder::<f32>::new();
+        builder.add_keyframe(1.1, 0.0, TrackInterpolation::Linear);
+        assert!(builder.build().unwrap_err().is_invalid_keyframe());
+
+        let mut builder = TrackBuilder::<f32>::new();
+        builder
+            .add_keyframe(0.5, 0.0, TrackInterpolation::Linear)
+            .add_keyframe(0.2, 1.0, TrackInterpolation::Linear);
+        assert!(builder.build().unwrap_err().is_invalid_keyframe());
+
+        let mut builder = TrackBuilder::<f32>::new();
+        builder
+            .add_keyframe(0.5, 0.0, TrackInterpolation::Linear)
+            .add_keyframe(0.5, 1.0, TrackInterpolation::Linear);
+        assert!(builder.build().unwrap_err().is_invalid_keyframe());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_builder() {
+        let mut builder = TrackBuilder::<f32>::new();
+        builder.set_name("curve");
+        builder
+            .add_keyframe(0.2, 1.0, TrackInterpolation::Linear)
+            .add_keyframe(0.6, 3.0, TrackInterpolation::Step)
+            .add_keyframe(0.8, 5.0, TrackInterpolation::Linear);
+        let track = builder.build().unwrap();
+        assert_eq!(track.name(), "curve");
+        assert_eq!(track.key_count(), 5);
+        assert_eq!(track.ratios(), &[0.0, 0.2, 0.6, 0.8, 1.0]);
+        assert_eq!(track.values(), &[1.0, 1.0, 3.0, 5.0, 5.0]);
+        assert_eq!(track.steps(), &[0x4]);
+
+        let mut job = TrackSamplingJobRef::default();
+        job.set_track(&track);
+        for (ratio, expected) in [
+            (0.0, 1.0),
+            (0.1, 1.0),
+            (0.4, 2.0),
+            (0.6, 3.0),
+            (0.7, 3.0),
+            (0.8, 5.0),
+            (1.0, 5.0),
+        ] {
+            job.set_ratio(ratio);
+            job.run().unwrap();
+            assert!((job.result() - expected).abs() < 1e-6, "{} {}", ratio, job.result());
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_archive_round_trip() {
+        let mut builder = TrackBuilder::<Vec3>::new();
+        builder.set_name("target");
+        builder
+            .add_keyframe(0.0, Vec3::new(1.0, 2.0, 3.0), TrackInterpolation::Linear)
+            .add_keyframe(0.5, Vec3::new(-1.0, 0.0, 2.0), TrackInterpolation::Step)
+            .add_keyframe(1.0, Vec3::new(0.0, 4.0, 0.0), TrackInterpolation::Linear);
+        let track = builder.build().unwrap();
+
+        let mut writer = ArchiveWriter::to_vec(Track::<Vec3>::tag(), Track::<Vec3>::version()).unwrap();
+        track.to_archive(&mut writer).unwrap();
+        let mut archive = Archive::from_vec(writer.into_inner()).unwrap();
+        let loaded = Track::<Vec3>::from_archive(&mut archive).unwrap();
+
+        assert_eq!(loaded.key_count(), track.key_count());
+        assert_eq!(loaded.ratios(), track.ratios());
+        assert_eq!(loaded.values(), track.values());
+        assert_eq!(loaded.steps(), track.steps());
+        assert_eq!(loaded.name(), track.name());
+
+        let mut job1 = TrackSamplingJobRef::default();
+        job1.set_track(&track);
+        let mut job2 = TrackSamplingJobRef::default();
+        job2.set_track(&loaded);
+        for i in 0..=20 {
+            job1.set_ratio(i as f32 / 20.0);
+            job1.run().unwrap();
+            job2.set_ratio(i as f32 / 20.0);
+            job2.run().unwrap();
+            assert_eq!(job1.result(), job2.result());
+        }
+
+        let mut writer = ArchiveWriter::to_vec(Track::<f32>::tag(), Track::<f32>::version()).unwrap();
+        assert!(track.to_archive(&mut writer).unwrap_err().is_invalid_tag());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_archive_rewrite_file() {
+        let file = std::fs::read("./resource/track/track.ozz").unwrap();
+        let track = Track::<f32>::from_archive(&mut Archive::from_slice(&file).unwrap()).unwrap();
+
+        let mut writer = ArchiveWriter::to_vec(Track::<f32>::tag(), Track::<f32>::version()).unwrap();
+        track.to_archive(&mut writer).unwrap();
+        assert_eq!(writer.into_inner(), file);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_serde_track() {
+        let track = Track::<f32>::from_path("./resource/track/track.ozz").unwrap();
+        let json = serde_json::to_string(&track).unwrap();
+        let track2: Track<f32> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(track2.key_count(), track.key_count());
+        assert_eq!(track2.ratios(), track.ratios());
+        assert_eq!(track2.values(), track.values());
+        assert_eq!(track2.steps(), track.steps());
+        assert_eq!(track2.name(), track.name());
+
+        let mut job1 = TrackSamplingJobRef::default();
+        job1.set_track(&track);
+        let mut job2 = TrackSamplingJobRef::default();
+        job2.set_track(&track2);
+        for i in 0..=20 {
+            job1.set_ratio(i as f32 / 20.0);
+            job1.run().unwrap();
+            job2.set_ratio(i as f32 / 20.0);
+            job2.run().unwrap();
+            assert_eq!(job1.result(), job2.result());
+        }
+    }
+}
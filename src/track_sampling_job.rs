@@ -2,11 +2,12 @@
 //! Track sampling job.
 //!
 
-use std::fmt::Debug;
-use std::rc::Rc;
-use std::sync::Arc;
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use core::fmt::Debug;
 
 use crate::base::{OzzError, OzzObj};
+use crate::interpolator::Interpolator;
 use crate::math::f32_clamp_or_max;
 use crate::track::{Track, TrackValue};
 
@@ -22,11 +23,13 @@ where
 {
     track: Option<T>,
     ratio: f32,
+    interpolator: Option<Arc<dyn Interpolator<V>>>,
     result: V,
 }
 
 pub type TrackSamplingJobRef<'t, V> = TrackSamplingJob<V, &'t Track<V>>;
 pub type TrackSamplingJobRc<V> = TrackSamplingJob<V, Rc<Track<V>>>;
+#[cfg(feature = "std")]
 pub type TrackSamplingJobArc<V> = TrackSamplingJob<V, Arc<Track<V>>>;
 
 impl<V, T> Default for TrackSamplingJob<V, T>
@@ -38,6 +41,7 @@ where
         return TrackSamplingJob {
             track: None,
             ratio: 0.0,
+            interpolator: None,
             result: V::default(),
         };
     }
@@ -84,6 +88,27 @@ where
         self.ratio = f32_clamp_or_max(ratio, 0.0f32, 1.0f32);
     }
 
+    /// Gets interpolator of `TrackSamplingJob`. See [Interpolator].
+    #[inline]
+    pub fn interpolator(&self) -> Option<&Arc<dyn Interpolator<V>>> {
+        return self.interpolator.as_ref();
+    }
+
+    /// Sets interpolator of `TrackSamplingJob`. See [Interpolator].
+    ///
+    /// Optional. Replaces the built-in linear interpolation between keyframes. Step keys are still
+    /// held, see `TrackInterpolation::Step`.
+    #[inline]
+    pub fn set_interpolator(&mut self, interpolator: Arc<dyn Interpolator<V>>) {
+        self.interpolator = Some(interpolator);
+    }
+
+    /// Clears interpolator of `TrackSamplingJob`, restoring the built-in interpolation.
+    #[inline]
+    pub fn clear_interpolator(&mut self) {
+        self.interpolator = None;
+    }
+
     /// Gets **output** result of `TrackSamplingJob`.
     #[inline]
     pub fn result(&self) -> V {
@@ -134,7 +159,10 @@ where
             let t = (self.ratio - tk0) / (tk1 - tk0);
             let v0 = track.values()[id0];
             let v1 = track.values()[id1];
-            self.result = V::lerp(v0, v1, t);
+            self.result = match &self.interpolator {
+                Some(interpolator) => interpolator.interpolate(&v0, &v1, t),
+                None => V::lerp(v0, v1, t),
+            };
         }
 
         return Ok(());
@@ -208,6 +236,32 @@ mod track_sampling_tests {
         execute_test(&mut job, 1.0, 0.0);
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_float_from_file() {
+        let track = Rc::new(Track::<f32>::from_path("./resource/track/track.ozz").unwrap());
+        let mut job = TrackSamplingJob::default();
+        job.set_track(track.clone());
+
+        // Keyframes are sampled exactly.
+        for (&ratio, &value) in track.ratios().iter().zip(track.values().iter()) {
+            execute_test(&mut job, ratio, value);
+        }
+
+        // Linear interpolation in between keyframes.
+        let mid = (track.ratios()[1] + track.ratios()[2]) * 0.5;
+        execute_test(&mut job, mid, 0.5);
+        execute_test(&mut job, 0.1, 0.0);
+        execute_test(&mut job, 0.3, 1.0);
+        execute_test(&mut job, 0.45, 0.0);
+        execute_test(&mut job, 0.6, 1.0);
+        execute_test(&mut job, 0.95, 0.0);
+
+        // Clamped out of [0, 1].
+        execute_test(&mut job, -0.5, 0.0);
+        execute_test(&mut job, 1.5, 0.0);
+    }
+
     #[test]
     #[wasm_bindgen_test]
     fn test_vec2() {
@@ -328,4 +382,52 @@ mod track_sampling_tests {
         execute_test(&mut job, 0.9, Quat::from_xyzw(0.0, 0.0, 0.0, 1.0));
         execute_test(&mut job, 1.0, Quat::from_xyzw(0.0, 0.0, 0.0, 1.0));
     }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_vec3_interior() {
+        let mut job = TrackSamplingJob::default();
+        let track = Rc::new(
+            Track::from_raw(
+                &[
+                    Vec3::new(0.0, 0.0, 0.0),
+                    Vec3::new(1.0, 2.0, 4.0),
+                    Vec3::new(-1.0, 2.0, 0.0),
+                ],
+                &[0.0, 0.4, 1.0],
+                &[0x0],
+            )
+            .unwrap(),
+        );
+        job.set_track(track.clone());
+
+        execute_test(&mut job, 0.1, Vec3::new(0.25, 0.5, 1.0));
+        execute_test(&mut job, 0.2, Vec3::new(0.5, 1.0, 2.0));
+        execute_test(&mut job, 0.55, Vec3::new(0.5, 2.0, 3.0));
+        execute_test(&mut job, 0.85, Vec3::new(-0.5, 2.0, 1.0));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_quat_interior() {
+        let mut job = TrackSamplingJob::default();
+        let q0 = Quat::IDENTITY;
+        let q1 = Quat::from_rotation_z(std::f32::consts::FRAC_PI_2);
+        let track = Rc::new(Track::from_raw(&[q0, q1, -q0], &[0.0, 0.5, 1.0], &[0x0]).unwrap());
+        job.set_track(track.clone());
+
+        // Halfway, normalized lerp matches slerp.
+        execute_test(&mut job, 0.25, Quat::from_rotation_z(std::f32::consts::FRAC_PI_4));
+
+        // Interpolation takes the shortest path, even if keys are in opposite hemispheres.
+        execute_test(&mut job, 0.75, Quat::from_rotation_z(std::f32::consts::FRAC_PI_4));
+
+        // Interior values are normalized and close to slerp.
+        for ratio in [0.05, 0.1, 0.2, 0.3, 0.4, 0.45] {
+            job.set_ratio(ratio);
+            job.run().unwrap();
+            assert!((job.result().length() - 1.0).abs() < 1e-5);
+            assert!(job.result().abs_diff_eq(q0.slerp(q1, ratio * 2.0), 1e-2));
+        }
+    }
 }
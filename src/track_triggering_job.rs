@@ -2,12 +2,14 @@
 //! Track Triggering Job.
 //!
 
-use std::fmt::Debug;
-use std::rc::Rc;
+use alloc::rc::Rc;
+use core::fmt::Debug;
+#[cfg(feature = "std")]
 use std::sync::Arc;
 
 use crate::base::{OzzError, OzzObj};
-
+#[cfg(not(feature = "std"))]
+use crate::math::F32Ext;
 use crate::track::Track;
 
 /// Structure of an edge as detected by the job.
@@ -29,6 +31,18 @@ impl Edge {
             rising: rising,
         };
     }
+
+    /// Ratio at which track value crossed threshold.
+    #[inline]
+    pub fn ratio(&self) -> f32 {
+        return self.ratio;
+    }
+
+    /// true is edge is rising (getting higher than threshold).
+    #[inline]
+    pub fn rising(&self) -> bool {
+        return self.rising;
+    }
 }
 
 /// Track edge triggering job implementation.
@@ -55,6 +69,7 @@ where
 
 pub type TrackTriggeringJobRef<'t> = TrackTriggeringJob<&'t Track<f32>>;
 pub type TrackTriggeringJobRc = TrackTriggeringJob<Rc<Track<f32>>>;
+#[cfg(feature = "std")]
 pub type TrackTriggeringJobArc = TrackTriggeringJob<Arc<Track<f32>>>;
 
 impl<T> Default for TrackTriggeringJob<T>
@@ -685,6 +700,53 @@ mod track_triggering_tests {
         assert_edges(track.clone(), 1.0, &edges);
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_square_wave_loop() {
+        let track = Track::<f32>::from_path("./resource/track/track.ozz").unwrap();
+        let mut job: TrackTriggeringJobRef = TrackTriggeringJob::default();
+        job.set_track(&track);
+        job.set_threshold(0.5);
+
+        // Square wave with 3 pulses, each edge is a short linear ramp around the threshold.
+        let ratios = track.ratios();
+        let rising: Vec<_> = [1, 5, 9].iter().map(|&i| (ratios[i] + ratios[i + 1]) * 0.5).collect();
+        let falling: Vec<_> = [3, 7, 11].iter().map(|&i| (ratios[i] + ratios[i + 1]) * 0.5).collect();
+
+        // Time step from 0.8 to 1.3, crossing the loop boundary.
+        job.set_from(0.8);
+        job.set_to(1.3);
+        let edges: Vec<_> = job.run().unwrap().collect();
+        assert_eq!(edges.len(), 2);
+        assert!((edges[0].ratio() - falling[2]).abs() < 1e-5);
+        assert!(!edges[0].rising());
+        assert!((edges[1].ratio() - (rising[0] + 1.0)).abs() < 1e-5);
+        assert!(edges[1].rising());
+
+        // Same time step backward, edges are reversed.
+        job.set_from(1.3);
+        job.set_to(0.8);
+        let edges: Vec<_> = job.run().unwrap().collect();
+        assert_eq!(edges.len(), 2);
+        assert!((edges[0].ratio() - (rising[0] + 1.0)).abs() < 1e-5);
+        assert!(!edges[0].rising());
+        assert!((edges[1].ratio() - falling[2]).abs() < 1e-5);
+        assert!(edges[1].rising());
+
+        // A full loop reports every edge once.
+        job.set_from(0.0);
+        job.set_to(1.0);
+        let edges: Vec<_> = job.run().unwrap().collect();
+        assert_eq!(edges.len(), 6);
+        assert_eq!(edges.iter().filter(|e| e.rising()).count(), 3);
+        for (edge, expected) in edges
+            .iter()
+            .zip([rising[0], falling[0], rising[1], falling[1], rising[2], falling[2]])
+        {
+            assert!((edge.ratio() - expected).abs() < 1e-5);
+        }
+    }
+
     #[test]
     #[wasm_bindgen_test]
     fn test_linear() {
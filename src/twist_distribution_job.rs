@@ -0,0 +1,286 @@
+//!
+//! Twist Distribution Job.
+//!
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use glam::{Quat, Vec3};
+#[cfg(feature = "std")]
+use std::sync::{Arc, RwLock};
+
+use crate::base::{OzzBuf, OzzError, OzzMutBuf};
+use crate::joint_limits::JointLimit;
+use crate::math::SoaTransform;
+
+///
+/// Distributes the twist of a source joint across twist joints, like forearm or upper-arm twist bones.
+///
+/// The local rotation of the source joint (like the hand) is decomposed into swing and twist around
+/// `twist_axis` (see `JointLimit::swing_twist`). Each twist joint is then rotated around the same axis by
+/// its weight fraction of the twist angle, on top of its input rotation. Weights usually grow from 0 to 1
+/// along the chain, like 0.33 and 0.66 for 2 twist bones.
+///
+/// Job input and output are local-space poses, ordered like skeleton's joints. Joints other than the
+/// twist joints are copied as is.
+///
+#[derive(Debug)]
+pub struct TwistDistributionJob<I = Rc<RefCell<Vec<SoaTransform>>>, O = Rc<RefCell<Vec<SoaTransform>>>>
+where
+    I: OzzBuf<SoaTransform>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    input: Option<I>,
+    source: i16,
+    twist_axis: Vec3,
+    twist_joints: Vec<(i16, f32)>,
+    output: Option<O>,
+    twist_angle: f32,
+}
+
+pub type TwistDistributionJobRef<'t> = TwistDistributionJob<&'t [SoaTransform], &'t mut [SoaTransform]>;
+pub type TwistDistributionJobRc = TwistDistributionJob<Rc<RefCell<Vec<SoaTransform>>>, Rc<RefCell<Vec<SoaTransform>>>>;
+#[cfg(feature = "std")]
+pub type TwistDistributionJobArc = TwistDistributionJob<Arc<RwLock<Vec<SoaTransform>>>, Arc<RwLock<Vec<SoaTransform>>>>;
+
+impl<I, O> Default for TwistDistributionJob<I, O>
+where
+    I: OzzBuf<SoaTransform>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    fn default() -> TwistDistributionJob<I, O> {
+        return TwistDistributionJob {
+            input: None,
+            source: 0,
+            twist_axis: Vec3::X,
+            twist_joints: Vec::new(),
+            output: None,
+            twist_angle: 0.0,
+        };
+    }
+}
+
+impl<I, O> TwistDistributionJob<I, O>
+where
+    I: OzzBuf<SoaTransform>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    /// Gets input of `TwistDistributionJob`.
+    #[inline]
+    pub fn input(&self) -> Option<&I> {
+        return self.input.as_ref();
+    }
+
+    /// Sets input of `TwistDistributionJob`.
+    ///
+    /// The local-space pose, containing the source and twist joints.
+    #[inline]
+    pub fn set_input(&mut self, input: I) {
+        self.input = Some(input);
+    }
+
+    /// Clears input of `TwistDistributionJob`.
+    #[inline]
+    pub fn clear_input(&mut self) {
+        self.input = None;
+    }
+
+    /// Gets source joint of `TwistDistributionJob`.
+    #[inline]
+    pub fn source(&self) -> i16 {
+        return self.source;
+    }
+
+    /// Sets source joint of `TwistDistributionJob`.
+    ///
+    /// Index of the joint whose local rotation twist is distributed. Default is 0.
+    #[inline]
+    pub fn set_source(&mut self, source: i16) {
+        self.source = source;
+    }
+
+    /// Gets twist axis of `TwistDistributionJob`.
+    #[inline]
+    pub fn twist_axis(&self) -> Vec3 {
+        return self.twist_axis;
+    }
+
+    /// Sets twist axis of `TwistDistributionJob`.
+    ///
+    /// Twist axis, in the local-space of the source and twist joints. Default is the X axis (ozz joints
+    /// axis).
+    #[inline]
+    pub fn set_twist_axis(&mut self, twist_axis: Vec3) {
+        self.twist_axis = twist_axis;
+    }
+
+    /// Gets twist joints of `TwistDistributionJob`, as `(joint, weight)` pairs.
+    #[inline]
+    pub fn twist_joints(&self) -> &[(i16, f32)] {
+        return &self.twist_joints;
+    }
+
+    /// Adds a twist joint to `TwistDistributionJob`.
+    ///
+    /// The joint is rotated by `weight` times the source twist angle. 1 reproduces the source twist.
+    #[inline]
+    pub fn add_twist_joint(&mut self, joint: i16, weight: f32) {
+        self.twist_joints.push((joint, weight));
+    }
+
+    /// Clears twist joints of `TwistDistributionJob`.
+    #[inline]
+    pub fn clear_twist_joints(&mut self) {
+        self.twist_joints.clear();
+    }
+
+    /// Gets output of `TwistDistributionJob`.
+    #[inline]
+    pub fn output(&self) -> Option<&O> {
+        return self.output.as_ref();
+    }
+
+    /// Sets output of `TwistDistributionJob`.
+    ///
+    /// The local-space pose with twisted joints, at least as large as the input.
+    #[inline]
+    pub fn set_output(&mut self, output: O) {
+        self.output = Some(output);
+    }
+
+    /// Clears output of `TwistDistributionJob`.
+    #[inline]
+    pub fn clear_output(&mut self) {
+        self.output = None;
+    }
+
+    /// Gets **output** twist angle of `TwistDistributionJob`.
+    ///
+    /// The source twist angle of the last run in radians, in range [-PI, PI].
+    #[inline]
+    pub fn twist_angle(&self) -> f32 {
+        return self.twist_angle;
+    }
+
+    /// Validates `TwistDistributionJob` parameters.
+    pub fn validate(&self) -> bool {
+        return (|| {
+            let input = self.input.as_ref()?.buf().ok()?;
+            let output = self.output.as_ref()?.buf().ok()?;
+            let ok = Self::validate_joints(self.source, &self.twist_joints, input.len());
+            return Some(ok && output.len() >= input.len());
+        })()
+        .unwrap_or(false);
+    }
+
+    fn validate_joints(source: i16, twist_joints: &[(i16, f32)], num_soa_joints: usize) -> bool {
+        let in_range = |joint: i16| joint >= 0 && (joint as usize) < num_soa_joints * 4;
+        return in_range(source) && twist_joints.iter().all(|(joint, _)| in_range(*joint));
+    }
+
+    /// Runs twist distribution job's task.
+    /// The validate job before any operation is performed.
+    pub fn run(&mut self) -> Result<(), OzzError> {
+        let input = self.input.as_ref().ok_or(OzzError::InvalidJob)?.buf()?;
+        let mut output = self.output.as_mut().ok_or(OzzError::InvalidJob)?.mut_buf()?;
+        if !Self::validate_joints(self.source, &self.twist_joints, input.len()) || output.len() < input.len() {
+            return Err(OzzError::InvalidJob);
+        }
+
+        let axis = self.twist_axis.normalize_or_zero();
+        let limit = JointLimit {
+            twist_axis: axis,
+            ..JointLimit::UNLIMITED
+        };
+        let source = self.source as usize;
+        let (_, twist) = limit.swing_twist(input[source / 4].rotation.col(source % 4));
+        self.twist_angle = JointLimit::signed_angle(twist, axis);
+
+        output[..input.len()].copy_from_slice(&input);
+        for (joint, weight) in self.twist_joints.iter() {
+            let joint = *joint as usize;
+            let rotation = input[joint / 4].rotation.col(joint % 4);
+            let twisted = rotation * Quat::from_axis_angle(axis, self.twist_angle * weight);
+            output[joint / 4].rotation.set_col(joint % 4, twisted);
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod twist_distribution_tests {
+    use wasm_bindgen_test::*;
+
+    use super::*;
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_validity() {
+        let input = vec![SoaTransform::default(); 2];
+        let mut output = vec![SoaTransform::default(); 1];
+
+        let mut job: TwistDistributionJobRef = TwistDistributionJob::default();
+        job.set_input(&input);
+        job.set_output(&mut output);
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        job.set_input(&input[..1]);
+        assert!(job.validate());
+        job.set_source(4);
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        job.set_source(3);
+        job.add_twist_joint(-1, 0.5);
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_twist_distribution() {
+        // The hand (joint 3) twists and bends, forearm twist joints 1 and 2 are at rest.
+        let twist = 1.2;
+        let source = Quat::from_rotation_z(0.4) * Quat::from_rotation_x(twist);
+        let mut input = vec![SoaTransform::default(); 1];
+        (0..4).for_each(|lane| input[0].rotation.set_col(lane, Quat::IDENTITY));
+        input[0].rotation.set_col(3, source);
+        input[0].translation.set_col(1, Vec3::new(1.0, 2.0, 3.0));
+        let mut output = vec![SoaTransform::default(); 1];
+
+        // Full weight reproduces the source twist.
+        let mut job: TwistDistributionJobRef = TwistDistributionJob::default();
+        job.set_input(&input);
+        job.set_output(&mut output);
+        job.set_source(3);
+        job.add_twist_joint(1, 1.0);
+        job.run().unwrap();
+        assert!((job.twist_angle() - twist).abs() < 1e-5);
+        let output = job.output().unwrap();
+        assert!(output[0]
+            .rotation
+            .col(1)
+            .abs_diff_eq(Quat::from_rotation_x(twist), 1e-5));
+        assert_eq!(output[0].translation.col(1), Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(output[0].rotation.col(0), Quat::IDENTITY);
+        assert_eq!(output[0].rotation.col(3), source);
+
+        // Partial weights distribute the twist proportionally, on top of the input rotation.
+        let bend = Quat::from_rotation_y(0.3);
+        input[0].rotation.set_col(2, bend);
+        let mut output = vec![SoaTransform::default(); 1];
+        let mut job: TwistDistributionJobRef = TwistDistributionJob::default();
+        job.set_input(&input);
+        job.set_output(&mut output);
+        job.set_source(3);
+        job.add_twist_joint(1, 0.25);
+        job.add_twist_joint(2, 0.5);
+        job.run().unwrap();
+        let output = job.output().unwrap();
+        let expected = Quat::from_rotation_x(twist * 0.25);
+        assert!(output[0].rotation.col(1).abs_diff_eq(expected, 1e-5));
+        let expected = bend * Quat::from_rotation_x(twist * 0.5);
+        assert!(output[0].rotation.col(2).abs_diff_eq(expected, 1e-5));
+    }
+}
@@ -0,0 +1,205 @@
+//!
+//! Flat f32 arrays interop, for JavaScript consumers (`Float32Array`, WebGL/WebGPU buffers).
+//!
+
+use glam::{Mat4, Quat, Vec3};
+
+use crate::base::OzzError;
+use crate::math::{AosTransform, SoaTransform};
+
+/// Number of floats per joint in flat matrices arrays.
+pub const FLAT_MATRIX_SIZE: usize = 16;
+
+/// Number of floats per joint in flat poses arrays: translation (x, y, z), rotation (x, y, z, w) and
+/// scale (x, y, z).
+pub const FLAT_TRANSFORM_SIZE: usize = 10;
+
+/// Flattens matrices (like `LocalToModelJob` or `SkinningJob` matrices) to 16 floats per joint, in
+/// column-major order. This is the layout expected by WebGL `uniformMatrix4fv` and WebGPU buffers.
+pub fn flatten_matrices(matrices: &[Mat4]) -> Vec<f32> {
+    let mut flat = Vec::with_capacity(matrices.len() * FLAT_MATRIX_SIZE);
+    for matrix in matrices.iter() {
+        flat.extend_from_slice(&matrix.to_cols_array());
+    }
+    return flat;
+}
+
+/// Reads matrices from a flat array of 16 floats per joint, in column-major order.
+///
+/// Returns `OzzError::BufferSize` if `flat` length isn't a multiple of 16.
+pub fn unflatten_matrices(flat: &[f32]) -> Result<Vec<Mat4>, OzzError> {
+    if flat.len() % FLAT_MATRIX_SIZE != 0 {
+        return Err(OzzError::BufferSize {
+            expected: flat.len().next_multiple_of(FLAT_MATRIX_SIZE),
+            actual: flat.len(),
+        });
+    }
+    return Ok(flat.chunks_exact(FLAT_MATRIX_SIZE).map(Mat4::from_cols_slice).collect());
+}
+
+/// Flattens the first `num_joints` joints of a local-space SoA pose (like `SamplingJob` or `BlendingJob`
+/// output) to 10 floats per joint, see `FLAT_TRANSFORM_SIZE`.
+///
+/// Returns `OzzError::BufferSize` if `pose` has less than `num_joints` joints.
+pub fn flatten_pose(pose: &[SoaTransform], num_joints: usize) -> Result<Vec<f32>, OzzError> {
+    if pose.len() * 4 < num_joints {
+        return Err(OzzError::BufferSize {
+            expected: num_joints.div_ceil(4),
+            actual: pose.len(),
+        });
+    }
+
+    let mut flat = Vec::with_capacity(num_joints * FLAT_TRANSFORM_SIZE);
+    for idx in 0..num_joints {
+        let transform = pose[idx / 4].col(idx % 4);
+        flat.extend_from_slice(&transform.translation.to_array());
+        flat.extend_from_slice(&transform.rotation.to_array());
+        flat.extend_from_slice(&transform.scale.to_array());
+    }
+    return Ok(flat);
+}
+
+/// Reads a local-space SoA pose from a flat array of 10 floats per joint, see `FLAT_TRANSFORM_SIZE`.
+/// Padding joints of the last SoA element are identity transforms.
+///
+/// Returns `OzzError::BufferSize` if `flat` length isn't a multiple of 10.
+pub fn unflatten_pose(flat: &[f32]) -> Result<Vec<SoaTransform>, OzzError> {
+    if flat.len() % FLAT_TRANSFORM_SIZE != 0 {
+        return Err(OzzError::BufferSize {
+            expected: flat.len().next_multiple_of(FLAT_TRANSFORM_SIZE),
+            actual: flat.len(),
+        });
+    }
+
+    let num_joints = flat.len() / FLAT_TRANSFORM_SIZE;
+    let mut pose = vec![SoaTransform::default(); num_joints.div_ceil(4)];
+    for (idx, joint) in flat.chunks_exact(FLAT_TRANSFORM_SIZE).enumerate() {
+        let transform = AosTransform::new(
+            Vec3::from_slice(&joint[0..3]),
+            Quat::from_slice(&joint[3..7]),
+            Vec3::from_slice(&joint[7..10]),
+        );
+        pose[idx / 4].set_col(idx % 4, &transform);
+    }
+    for idx in num_joints..pose.len() * 4 {
+        pose[idx / 4].set_col(idx % 4, &AosTransform::IDENTITY);
+    }
+    return Ok(pose);
+}
+
+/// JavaScript exports (`nodejs` feature), on `Float32Array` of flat poses and flat matrices.
+#[cfg(feature = "nodejs")]
+pub mod exports {
+    use wasm_bindgen::prelude::*;
+
+    use super::*;
+
+    fn js_error(err: OzzError) -> JsError {
+        return JsError::new(&err.to_string());
+    }
+
+    /// Converts a flat pose (10 floats per joint) to flat matrices (16 floats per joint), joint by joint.
+    #[wasm_bindgen(js_name = flatPoseToMatrices)]
+    pub fn flat_pose_to_matrices(flat_pose: &[f32]) -> Result<Vec<f32>, JsError> {
+        let num_joints = flat_pose.len() / FLAT_TRANSFORM_SIZE;
+        let pose = unflatten_pose(flat_pose).map_err(js_error)?;
+        let matrices: Vec<Mat4> = (0..num_joints)
+            .map(|idx| Mat4::from(pose[idx / 4].col(idx % 4)))
+            .collect();
+        return Ok(flatten_matrices(&matrices));
+    }
+
+    /// Converts flat matrices (16 floats per joint) to a flat pose (10 floats per joint), decomposing each
+    /// matrix. Shear is lost.
+    #[wasm_bindgen(js_name = flatMatricesToPose)]
+    pub fn flat_matrices_to_pose(flat_matrices: &[f32]) -> Result<Vec<f32>, JsError> {
+        let matrices = unflatten_matrices(flat_matrices).map_err(js_error)?;
+        let mut pose = vec![SoaTransform::default(); matrices.len().div_ceil(4)];
+        for (idx, matrix) in matrices.iter().enumerate() {
+            pose[idx / 4].set_col(idx % 4, &AosTransform::from(*matrix));
+        }
+        return flatten_pose(&pose, matrices.len()).map_err(js_error);
+    }
+}
+
+#[cfg(test)]
+mod wasm_tests {
+    use wasm_bindgen_test::*;
+
+    use super::*;
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_flat_matrices() {
+        let matrices = [
+            Mat4::from_translation(Vec3::new(1.0, 2.0, 3.0)),
+            Mat4::from_scale_rotation_translation(Vec3::splat(2.0), Quat::from_rotation_y(1.0), Vec3::ONE),
+        ];
+        let flat = flatten_matrices(&matrices);
+        assert_eq!(flat.len(), 32);
+        // Column-major, translation is the 4th column.
+        assert_eq!(&flat[12..16], &[1.0, 2.0, 3.0, 1.0]);
+        for (idx, matrix) in matrices.iter().enumerate() {
+            for col in 0..4 {
+                assert_eq!(
+                    &flat[idx * 16 + col * 4..idx * 16 + col * 4 + 4],
+                    &matrix.col(col).to_array()
+                );
+            }
+        }
+
+        assert_eq!(unflatten_matrices(&flat).unwrap(), matrices);
+        assert!(matches!(
+            unflatten_matrices(&flat[1..]),
+            Err(OzzError::BufferSize {
+                expected: 32,
+                actual: 31
+            })
+        ));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_flat_pose() {
+        let transform = AosTransform::new(Vec3::new(1.0, 2.0, 3.0), Quat::from_rotation_x(0.5), Vec3::splat(2.0));
+        let mut pose = vec![SoaTransform::default(); 2];
+        for idx in 0..8 {
+            pose[idx / 4].set_col(idx % 4, &AosTransform::IDENTITY);
+        }
+        pose[1].set_col(0, &transform);
+
+        let flat = flatten_pose(&pose, 5).unwrap();
+        assert_eq!(flat.len(), 50);
+        assert_eq!(&flat[0..10], &[0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(&flat[40..43], &[1.0, 2.0, 3.0]);
+        assert!(matches!(
+            flatten_pose(&pose, 9),
+            Err(OzzError::BufferSize { expected: 3, actual: 2 })
+        ));
+
+        assert_eq!(unflatten_pose(&flat).unwrap(), pose);
+        assert!(unflatten_pose(&flat[1..]).unwrap_err().is_buffer_size());
+    }
+
+    #[cfg(feature = "nodejs")]
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_exports() {
+        let transform = AosTransform::new(Vec3::new(1.0, 2.0, 3.0), Quat::from_rotation_x(0.5), Vec3::splat(2.0));
+        let mut flat_pose = Vec::new();
+        for _ in 0..5 {
+            flat_pose.extend_from_slice(&transform.translation.to_array());
+            flat_pose.extend_from_slice(&transform.rotation.to_array());
+            flat_pose.extend_from_slice(&transform.scale.to_array());
+        }
+        let flat_matrices = exports::flat_pose_to_matrices(&flat_pose).unwrap();
+        assert_eq!(flat_matrices.len(), 5 * FLAT_MATRIX_SIZE);
+        assert_eq!(&flat_matrices[0..16], &Mat4::from(transform).to_cols_array());
+
+        let flat_pose2 = exports::flat_matrices_to_pose(&flat_matrices).unwrap();
+        assert_eq!(flat_pose2.len(), flat_pose.len());
+        for (a, b) in flat_pose2.iter().zip(flat_pose.iter()) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
+}
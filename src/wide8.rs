@@ -0,0 +1,854 @@
+//!
+//! 8-wide SoA, for AVX machines batch processing (`wide8` feature).
+//!
+//! `Soa8Transform` packs 8 joints per element with `f32x8`, halving the number of elements of a
+//! 4-wide `SoaTransform` pose. Poses are converted with `soa8_from_soa4` and `soa4_from_soa8`.
+//!
+//! `Blending8Job` and `LocalToModel8Job` are the 8-wide versions of `BlendingJob` (without partial and
+//! additive layers) and `LocalToModelJob` (whole hierarchy).
+//!
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::simd::prelude::*;
+use glam::Mat4;
+#[cfg(feature = "std")]
+use std::sync::{Arc, RwLock};
+
+use crate::base::{OzzBuf, OzzError, OzzMutBuf, OzzObj, SKELETON_NO_PARENT};
+use crate::math::{AosMat4, SoaMat4, SoaQuat, SoaTransform, SoaVec3, StdFloat};
+use crate::skeleton::Skeleton;
+
+const ZERO: f32x8 = f32x8::from_array([0.0; 8]);
+const ONE: f32x8 = f32x8::from_array([1.0; 8]);
+const TWO: f32x8 = f32x8::from_array([2.0; 8]);
+const SIGN: u32x8 = u32x8::from_array([0x8000_0000; 8]);
+
+#[inline]
+fn fx8_low(v: f32x8) -> f32x4 {
+    return simd_swizzle!(v, [0, 1, 2, 3]);
+}
+
+#[inline]
+fn fx8_high(v: f32x8) -> f32x4 {
+    return simd_swizzle!(v, [4, 5, 6, 7]);
+}
+
+#[inline]
+fn fx8_join(low: f32x4, high: f32x4) -> f32x8 {
+    return simd_swizzle!(low, high, [0, 1, 2, 3, 4, 5, 6, 7]);
+}
+
+#[inline]
+fn fx8_sign(v: f32x8) -> u32x8 {
+    // Same as `fx4_sign`, the sign of NaN is always 0(+) for cross-platform deterministic.
+    return v.simd_lt(ZERO).select(SIGN, u32x8::splat(0));
+}
+
+/// Gets the number of 8-wide soa elements matching `num_joints` joints.
+#[inline]
+pub fn num_soa8_joints(num_joints: usize) -> usize {
+    return num_joints.div_ceil(8);
+}
+
+//
+// Soa8Vec3
+//
+
+/// 8-wide Structure of Arrays (SoA) for Vec3.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Soa8Vec3 {
+    pub x: f32x8,
+    pub y: f32x8,
+    pub z: f32x8,
+}
+
+impl Soa8Vec3 {
+    #[inline]
+    pub const fn splat_col(v: [f32; 3]) -> Soa8Vec3 {
+        return Soa8Vec3 {
+            x: f32x8::from_array([v[0]; 8]),
+            y: f32x8::from_array([v[1]; 8]),
+            z: f32x8::from_array([v[2]; 8]),
+        };
+    }
+
+    #[inline]
+    pub fn from_soa4(low: &SoaVec3, high: &SoaVec3) -> Soa8Vec3 {
+        return Soa8Vec3 {
+            x: fx8_join(low.x, high.x),
+            y: fx8_join(low.y, high.y),
+            z: fx8_join(low.z, high.z),
+        };
+    }
+
+    #[inline]
+    pub fn to_soa4(self) -> [SoaVec3; 2] {
+        return [
+            SoaVec3 {
+                x: fx8_low(self.x),
+                y: fx8_low(self.y),
+                z: fx8_low(self.z),
+            },
+            SoaVec3 {
+                x: fx8_high(self.x),
+                y: fx8_high(self.y),
+                z: fx8_high(self.z),
+            },
+        ];
+    }
+
+    #[inline]
+    pub fn add(&self, other: &Soa8Vec3) -> Soa8Vec3 {
+        return Soa8Vec3 {
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
+        };
+    }
+
+    #[inline]
+    pub fn mul_num(&self, f: f32x8) -> Soa8Vec3 {
+        return Soa8Vec3 {
+            x: self.x * f,
+            y: self.y * f,
+            z: self.z * f,
+        };
+    }
+}
+
+//
+// Soa8Quat
+//
+
+/// 8-wide Structure of Arrays (SoA) for Quaternions.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Soa8Quat {
+    pub x: f32x8,
+    pub y: f32x8,
+    pub z: f32x8,
+    pub w: f32x8,
+}
+
+impl Soa8Quat {
+    #[inline]
+    pub const fn splat_col(v: [f32; 4]) -> Soa8Quat {
+        return Soa8Quat {
+            x: f32x8::from_array([v[0]; 8]),
+            y: f32x8::from_array([v[1]; 8]),
+            z: f32x8::from_array([v[2]; 8]),
+            w: f32x8::from_array([v[3]; 8]),
+        };
+    }
+
+    #[inline]
+    pub fn from_soa4(low: &SoaQuat, high: &SoaQuat) -> Soa8Quat {
+        return Soa8Quat {
+            x: fx8_join(low.x, high.x),
+            y: fx8_join(low.y, high.y),
+            z: fx8_join(low.z, high.z),
+            w: fx8_join(low.w, high.w),
+        };
+    }
+
+    #[inline]
+    pub fn to_soa4(self) -> [SoaQuat; 2] {
+        return [
+            SoaQuat {
+                x: fx8_low(self.x),
+                y: fx8_low(self.y),
+                z: fx8_low(self.z),
+                w: fx8_low(self.w),
+            },
+            SoaQuat {
+                x: fx8_high(self.x),
+                y: fx8_high(self.y),
+                z: fx8_high(self.z),
+                w: fx8_high(self.w),
+            },
+        ];
+    }
+
+    #[inline]
+    pub fn add(&self, other: &Soa8Quat) -> Soa8Quat {
+        return Soa8Quat {
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
+            w: self.w + other.w,
+        };
+    }
+
+    #[inline]
+    pub fn mul_num(&self, f: f32x8) -> Soa8Quat {
+        return Soa8Quat {
+            x: self.x * f,
+            y: self.y * f,
+            z: self.z * f,
+            w: self.w * f,
+        };
+    }
+
+    #[inline]
+    pub fn normalize(&self) -> Soa8Quat {
+        let len2 = self.x * self.x + self.y * self.y + self.z * self.z + self.w * self.w;
+        let inv_len = len2.sqrt().recip();
+        return Soa8Quat {
+            x: self.x * inv_len,
+            y: self.y * inv_len,
+            z: self.z * inv_len,
+            w: self.w * inv_len,
+        };
+    }
+
+    #[inline]
+    pub fn dot(&self, other: &Soa8Quat) -> f32x8 {
+        return self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w;
+    }
+
+    #[inline]
+    fn xor_bits(&self, bits: u32x8) -> Soa8Quat {
+        return Soa8Quat {
+            x: f32x8::from_bits(self.x.to_bits() ^ bits),
+            y: f32x8::from_bits(self.y.to_bits() ^ bits),
+            z: f32x8::from_bits(self.z.to_bits() ^ bits),
+            w: f32x8::from_bits(self.w.to_bits() ^ bits),
+        };
+    }
+}
+
+//
+// Soa8Transform
+//
+
+/// 8-wide Structure of Arrays (SoA) for Transformations.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Soa8Transform {
+    pub translation: Soa8Vec3,
+    pub rotation: Soa8Quat,
+    pub scale: Soa8Vec3,
+}
+
+impl Soa8Transform {
+    pub const IDENTITY: Soa8Transform = Soa8Transform {
+        translation: Soa8Vec3::splat_col([0.0; 3]),
+        rotation: Soa8Quat::splat_col([0.0, 0.0, 0.0, 1.0]),
+        scale: Soa8Vec3::splat_col([1.0; 3]),
+    };
+
+    /// Packs 2 4-wide transforms, `low` being joints 0-3 and `high` joints 4-7.
+    #[inline]
+    pub fn from_soa4(low: &SoaTransform, high: &SoaTransform) -> Soa8Transform {
+        return Soa8Transform {
+            translation: Soa8Vec3::from_soa4(&low.translation, &high.translation),
+            rotation: Soa8Quat::from_soa4(&low.rotation, &high.rotation),
+            scale: Soa8Vec3::from_soa4(&low.scale, &high.scale),
+        };
+    }
+
+    /// Unpacks to 2 4-wide transforms, joints 0-3 and joints 4-7.
+    #[inline]
+    pub fn to_soa4(self) -> [SoaTransform; 2] {
+        let [t0, t1] = self.translation.to_soa4();
+        let [r0, r1] = self.rotation.to_soa4();
+        let [s0, s1] = self.scale.to_soa4();
+        return [SoaTransform::new(t0, r0, s0), SoaTransform::new(t1, r1, s1)];
+    }
+}
+
+/// Converts a 4-wide pose to an 8-wide pose. An odd number of soa elements is padded with identity.
+pub fn soa8_from_soa4(pose: &[SoaTransform]) -> Vec<Soa8Transform> {
+    let [_, identity] = Soa8Transform::IDENTITY.to_soa4();
+    return pose
+        .chunks(2)
+        .map(|pair| Soa8Transform::from_soa4(&pair[0], pair.get(1).unwrap_or(&identity)))
+        .collect();
+}
+
+/// Converts an 8-wide pose to a 4-wide pose, of twice as many soa elements.
+pub fn soa4_from_soa8(pose: &[Soa8Transform]) -> Vec<SoaTransform> {
+    return pose.iter().flat_map(|transform| transform.to_soa4()).collect();
+}
+
+//
+// Soa8Mat4
+//
+
+/// 8-wide Structure of Arrays (SoA) for 4x4 Matrices.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Soa8Mat4 {
+    pub cols: [f32x8; 16],
+}
+
+impl Soa8Mat4 {
+    pub fn from_affine(translation: &Soa8Vec3, rotation: &Soa8Quat, scale: &Soa8Vec3) -> Soa8Mat4 {
+        let xx = rotation.x * rotation.x;
+        let xy = rotation.x * rotation.y;
+        let xz = rotation.x * rotation.z;
+        let xw = rotation.x * rotation.w;
+        let yy = rotation.y * rotation.y;
+        let yz = rotation.y * rotation.z;
+        let yw = rotation.y * rotation.w;
+        let zz = rotation.z * rotation.z;
+        let zw = rotation.z * rotation.w;
+        return Soa8Mat4 {
+            cols: [
+                scale.x * (ONE - TWO * (yy + zz)),
+                scale.x * TWO * (xy + zw),
+                scale.x * TWO * (xz - yw),
+                ZERO,
+                scale.y * TWO * (xy - zw),
+                scale.y * (ONE - TWO * (xx + zz)),
+                scale.y * (TWO * (yz + xw)),
+                ZERO,
+                scale.z * TWO * (xz + yw),
+                scale.z * TWO * (yz - xw),
+                scale.z * (ONE - TWO * (xx + yy)),
+                ZERO,
+                translation.x,
+                translation.y,
+                translation.z,
+                ONE,
+            ],
+        };
+    }
+
+    pub(crate) fn to_aos(self) -> [AosMat4; 8] {
+        let low = SoaMat4 {
+            cols: self.cols.map(fx8_low),
+        };
+        let high = SoaMat4 {
+            cols: self.cols.map(fx8_high),
+        };
+        let (low, high) = (low.to_aos(), high.to_aos());
+        return [low[0], low[1], low[2], low[3], high[0], high[1], high[2], high[3]];
+    }
+}
+
+//
+// Blending8Job
+//
+
+/// Defines a layer of `Blending8Job` input data (8-wide local space transforms) and weight.
+#[derive(Debug, Clone)]
+pub struct Blending8Layer<I: OzzBuf<Soa8Transform>> {
+    /// Buffer to store 8-wide local space transforms.
+    pub transform: I,
+
+    /// Blending weight of this layer. Negative values are considered as 0.
+    pub weight: f32,
+}
+
+impl<I: OzzBuf<Soa8Transform>> Blending8Layer<I> {
+    pub fn with_weight(transform: I, weight: f32) -> Blending8Layer<I> {
+        return Blending8Layer { transform, weight };
+    }
+}
+
+///
+/// 8-wide version of `BlendingJob`.
+///
+/// Blends layers according to their weight, completing with the skeleton rest pose when the accumulated
+/// weight is below the threshold. Partial (joint weights) and additive layers aren't supported.
+///
+/// Buffers must have at least `num_soa8_joints(skeleton.num_joints())` elements.
+///
+#[derive(Debug)]
+pub struct Blending8Job<S = Rc<Skeleton>, I = Rc<RefCell<Vec<Soa8Transform>>>, O = Rc<RefCell<Vec<Soa8Transform>>>>
+where
+    S: OzzObj<Skeleton>,
+    I: OzzBuf<Soa8Transform>,
+    O: OzzMutBuf<Soa8Transform>,
+{
+    skeleton: Option<S>,
+    threshold: f32,
+    layers: Vec<Blending8Layer<I>>,
+    output: Option<O>,
+}
+
+pub type Blending8JobRef<'t> = Blending8Job<&'t Skeleton, &'t [Soa8Transform], &'t mut [Soa8Transform]>;
+pub type Blending8JobRc = Blending8Job<Rc<Skeleton>, Rc<RefCell<Vec<Soa8Transform>>>, Rc<RefCell<Vec<Soa8Transform>>>>;
+#[cfg(feature = "std")]
+pub type Blending8JobArc =
+    Blending8Job<Arc<Skeleton>, Arc<RwLock<Vec<Soa8Transform>>>, Arc<RwLock<Vec<Soa8Transform>>>>;
+
+impl<S, I, O> Default for Blending8Job<S, I, O>
+where
+    S: OzzObj<Skeleton>,
+    I: OzzBuf<Soa8Transform>,
+    O: OzzMutBuf<Soa8Transform>,
+{
+    fn default() -> Blending8Job<S, I, O> {
+        return Blending8Job {
+            skeleton: None,
+            threshold: 0.1,
+            layers: Vec::new(),
+            output: None,
+        };
+    }
+}
+
+impl<S, I, O> Blending8Job<S, I, O>
+where
+    S: OzzObj<Skeleton>,
+    I: OzzBuf<Soa8Transform>,
+    O: OzzMutBuf<Soa8Transform>,
+{
+    /// Gets skeleton of `Blending8Job`.
+    #[inline]
+    pub fn skeleton(&self) -> Option<&S> {
+        return self.skeleton.as_ref();
+    }
+
+    /// Sets skeleton of `Blending8Job`.
+    ///
+    /// The skeleton rest pose is used when the accumulated weight is below the threshold.
+    #[inline]
+    pub fn set_skeleton(&mut self, skeleton: S) {
+        self.skeleton = Some(skeleton);
+    }
+
+    /// Clears skeleton of `Blending8Job`.
+    #[inline]
+    pub fn clear_skeleton(&mut self) {
+        self.skeleton = None;
+    }
+
+    /// Gets threshold of `Blending8Job`.
+    #[inline]
+    pub fn threshold(&self) -> f32 {
+        return self.threshold;
+    }
+
+    /// Sets threshold of `Blending8Job`. See `BlendingJob::set_threshold`.
+    #[inline]
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold;
+    }
+
+    /// Gets layers of `Blending8Job`.
+    #[inline]
+    pub fn layers(&self) -> &[Blending8Layer<I>] {
+        return &self.layers;
+    }
+
+    /// Gets mutable layers of `Blending8Job`.
+    #[inline]
+    pub fn layers_mut(&mut self) -> &mut Vec<Blending8Layer<I>> {
+        return &mut self.layers;
+    }
+
+    /// Gets output of `Blending8Job`.
+    #[inline]
+    pub fn output(&self) -> Option<&O> {
+        return self.output.as_ref();
+    }
+
+    /// Sets output of `Blending8Job`.
+    #[inline]
+    pub fn set_output(&mut self, output: O) {
+        self.output = Some(output);
+    }
+
+    /// Clears output of `Blending8Job`.
+    #[inline]
+    pub fn clear_output(&mut self) {
+        self.output = None;
+    }
+
+    /// Validates `Blending8Job` parameters.
+    pub fn validate(&self) -> bool {
+        return (|| {
+            let skeleton = self.skeleton.as_ref()?.obj();
+            let output = self.output.as_ref()?.buf().ok()?;
+            let num_soa8_joints = num_soa8_joints(skeleton.num_joints());
+
+            let mut ok = self.threshold > 0.0;
+            ok &= output.len() >= num_soa8_joints;
+            for layer in self.layers.iter() {
+                ok &= layer.transform.buf().ok()?.len() >= num_soa8_joints;
+            }
+            return Some(ok);
+        })()
+        .unwrap_or(false);
+    }
+
+    /// Runs job's blending task.
+    /// The validate job before any operation is performed.
+    pub fn run(&mut self) -> Result<(), OzzError> {
+        let skeleton = self.skeleton.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        let mut output = self.output.as_mut().ok_or(OzzError::InvalidJob)?.mut_buf()?;
+        let num_soa8_joints = num_soa8_joints(skeleton.num_joints());
+
+        let mut ok = self.threshold > 0.0;
+        ok &= output.len() >= num_soa8_joints;
+        if !ok {
+            return Err(OzzError::InvalidJob);
+        }
+
+        let mut num_passes = 0;
+        let mut accumulated_weight = 0.0;
+        for layer in self.layers.iter() {
+            let transform = layer.transform.buf()?;
+            if transform.len() < num_soa8_joints {
+                return Err(OzzError::InvalidJob);
+            }
+            if layer.weight <= 0.0 {
+                continue;
+            }
+            accumulated_weight += layer.weight;
+            let weight = f32x8::splat(layer.weight);
+            for idx in 0..num_soa8_joints {
+                Self::blend_pass(&transform[idx], weight, num_passes == 0, &mut output[idx]);
+            }
+            num_passes += 1;
+        }
+
+        let rest_poses = skeleton.joint_rest_poses();
+        let [_, identity] = Soa8Transform::IDENTITY.to_soa4();
+        let rest_pose = |idx: usize| {
+            return Soa8Transform::from_soa4(&rest_poses[idx * 2], rest_poses.get(idx * 2 + 1).unwrap_or(&identity));
+        };
+
+        let bp_weight = self.threshold - accumulated_weight;
+        if bp_weight > 0.0 {
+            if num_passes == 0 {
+                accumulated_weight = 1.0;
+                for idx in 0..num_soa8_joints {
+                    output[idx] = rest_pose(idx);
+                }
+            } else {
+                accumulated_weight = self.threshold;
+                let weight = f32x8::splat(bp_weight);
+                for idx in 0..num_soa8_joints {
+                    Self::blend_pass(&rest_pose(idx), weight, false, &mut output[idx]);
+                }
+            }
+        }
+
+        let ratio = f32x8::splat(accumulated_weight.recip());
+        for dest in output[..num_soa8_joints].iter_mut() {
+            dest.translation = dest.translation.mul_num(ratio);
+            dest.rotation = dest.rotation.normalize();
+            dest.scale = dest.scale.mul_num(ratio);
+        }
+        return Ok(());
+    }
+
+    #[inline]
+    fn blend_pass(input: &Soa8Transform, weight: f32x8, first: bool, output: &mut Soa8Transform) {
+        if first {
+            output.translation = input.translation.mul_num(weight);
+            output.rotation = input.rotation.mul_num(weight);
+            output.scale = input.scale.mul_num(weight);
+        } else {
+            output.translation = output.translation.add(&input.translation.mul_num(weight));
+            let dot = output.rotation.dot(&input.rotation);
+            let rotation = input.rotation.xor_bits(fx8_sign(dot));
+            output.rotation = output.rotation.add(&rotation.mul_num(weight));
+            output.scale = output.scale.add(&input.scale.mul_num(weight));
+        }
+    }
+}
+
+//
+// LocalToModel8Job
+//
+
+///
+/// 8-wide version of `LocalToModelJob`.
+///
+/// Computes model-space joint matrices of the whole skeleton from 8-wide local-space transforms.
+/// Input must have at least `num_soa8_joints(skeleton.num_joints())` elements.
+///
+#[derive(Debug)]
+pub struct LocalToModel8Job<S = Rc<Skeleton>, I = Rc<RefCell<Vec<Soa8Transform>>>, O = Rc<RefCell<Vec<Mat4>>>>
+where
+    S: OzzObj<Skeleton>,
+    I: OzzBuf<Soa8Transform>,
+    O: OzzMutBuf<Mat4>,
+{
+    skeleton: Option<S>,
+    input: Option<I>,
+    root: AosMat4,
+    output: Option<O>,
+}
+
+pub type LocalToModel8JobRef<'t> = LocalToModel8Job<&'t Skeleton, &'t [Soa8Transform], &'t mut [Mat4]>;
+pub type LocalToModel8JobRc = LocalToModel8Job<Rc<Skeleton>, Rc<RefCell<Vec<Soa8Transform>>>, Rc<RefCell<Vec<Mat4>>>>;
+#[cfg(feature = "std")]
+pub type LocalToModel8JobArc = LocalToModel8Job<Arc<Skeleton>, Arc<RwLock<Vec<Soa8Transform>>>, Arc<RwLock<Vec<Mat4>>>>;
+
+impl<S, I, O> Default for LocalToModel8Job<S, I, O>
+where
+    S: OzzObj<Skeleton>,
+    I: OzzBuf<Soa8Transform>,
+    O: OzzMutBuf<Mat4>,
+{
+    fn default() -> LocalToModel8Job<S, I, O> {
+        return LocalToModel8Job {
+            skeleton: None,
+            input: None,
+            root: AosMat4::identity(),
+            output: None,
+        };
+    }
+}
+
+impl<S, I, O> LocalToModel8Job<S, I, O>
+where
+    S: OzzObj<Skeleton>,
+    I: OzzBuf<Soa8Transform>,
+    O: OzzMutBuf<Mat4>,
+{
+    /// Gets skeleton of `LocalToModel8Job`.
+    #[inline]
+    pub fn skeleton(&self) -> Option<&S> {
+        return self.skeleton.as_ref();
+    }
+
+    /// Sets skeleton of `LocalToModel8Job`.
+    #[inline]
+    pub fn set_skeleton(&mut self, skeleton: S) {
+        self.skeleton = Some(skeleton);
+    }
+
+    /// Clears skeleton of `LocalToModel8Job`.
+    #[inline]
+    pub fn clear_skeleton(&mut self) {
+        self.skeleton = None;
+    }
+
+    /// Gets input of `LocalToModel8Job`.
+    #[inline]
+    pub fn input(&self) -> Option<&I> {
+        return self.input.as_ref();
+    }
+
+    /// Sets input of `LocalToModel8Job`.
+    ///
+    /// The input range that store 8-wide local transforms.
+    #[inline]
+    pub fn set_input(&mut self, input: I) {
+        self.input = Some(input);
+    }
+
+    /// Clears input of `LocalToModel8Job`.
+    #[inline]
+    pub fn clear_input(&mut self) {
+        self.input = None;
+    }
+
+    /// Gets root of `LocalToModel8Job`.
+    #[inline]
+    pub fn root(&self) -> Mat4 {
+        return self.root.into();
+    }
+
+    /// Sets root of `LocalToModel8Job`.
+    ///
+    /// The root matrix will multiply to every model space matrices, default an identity matrix.
+    #[inline]
+    pub fn set_root(&mut self, root: &Mat4) {
+        self.root = (*root).into();
+    }
+
+    /// Gets output of `LocalToModel8Job`.
+    #[inline]
+    pub fn output(&self) -> Option<&O> {
+        return self.output.as_ref();
+    }
+
+    /// Sets output of `LocalToModel8Job`.
+    ///
+    /// The output range to be filled with model-space matrices.
+    #[inline]
+    pub fn set_output(&mut self, output: O) {
+        self.output = Some(output);
+    }
+
+    /// Clears output of `LocalToModel8Job`.
+    #[inline]
+    pub fn clear_output(&mut self) {
+        self.output = None;
+    }
+
+    /// Validates `LocalToModel8Job` parameters.
+    pub fn validate(&self) -> bool {
+        return (|| {
+            let skeleton = self.skeleton.as_ref()?.obj();
+            let input = self.input.as_ref()?.buf().ok()?;
+            let output = self.output.as_ref()?.buf().ok()?;
+
+            let mut ok = input.len() >= num_soa8_joints(skeleton.num_joints());
+            ok &= output.len() >= skeleton.num_joints();
+            return Some(ok);
+        })()
+        .unwrap_or(false);
+    }
+
+    /// Runs local to model job's task.
+    /// The validate job before any operation is performed.
+    pub fn run(&mut self) -> Result<(), OzzError> {
+        let skeleton = self.skeleton.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        let input = self.input.as_ref().ok_or(OzzError::InvalidJob)?.buf()?;
+        let mut output = self.output.as_mut().ok_or(OzzError::InvalidJob)?.mut_buf()?;
+
+        let num_joints = skeleton.num_joints();
+        let mut ok = input.len() >= num_soa8_joints(num_joints);
+        ok &= output.len() >= num_joints;
+        if !ok {
+            return Err(OzzError::InvalidJob);
+        }
+
+        for (soa_idx, transform) in input[..num_soa8_joints(num_joints)].iter().enumerate() {
+            let soa_matrices = Soa8Mat4::from_affine(&transform.translation, &transform.rotation, &transform.scale);
+            let aos_matrices = soa_matrices.to_aos();
+
+            let begin = soa_idx * 8;
+            for idx in begin..usize::min(begin + 8, num_joints) {
+                let parent = skeleton.joint_parent(idx);
+                if parent as i32 == SKELETON_NO_PARENT {
+                    output[idx] = AosMat4::mul(&self.root, &aos_matrices[idx & 7]).into();
+                } else {
+                    output[idx] = AosMat4::mul(&output[parent as usize].into(), &aos_matrices[idx & 7]).into();
+                }
+            }
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod wide8_tests {
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::animation::Animation;
+    use crate::blending_job::{BlendingJob, BlendingLayer};
+    use crate::local_to_model_job::LocalToModelJob;
+    use crate::sampling_job::{SamplingContext, SamplingJob};
+
+    fn sample(path: &str, ratio: f32) -> Vec<SoaTransform> {
+        let animation = Rc::new(Animation::from_path(path).unwrap());
+        let output = Rc::new(RefCell::new(vec![SoaTransform::default(); animation.num_soa_tracks()]));
+        let mut job = SamplingJob::default();
+        job.set_context(SamplingContext::new(animation.num_tracks()));
+        job.set_animation(animation);
+        job.set_output(output.clone());
+        job.set_ratio(ratio);
+        job.run().unwrap();
+        return output.take();
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_convert() {
+        let pose = sample("./resource/playback/animation.ozz", 0.3);
+        assert_eq!(pose.len(), 17);
+        let pose8 = soa8_from_soa4(&pose);
+        assert_eq!(pose8.len(), num_soa8_joints(67));
+        assert_eq!(pose8[2].translation.x[5], pose[5].translation.x[1]);
+        assert_eq!(pose8[8].to_soa4()[1], Soa8Transform::IDENTITY.to_soa4()[1]);
+
+        let pose4 = soa4_from_soa8(&pose8);
+        assert_eq!(pose4.len(), 18);
+        assert_eq!(&pose4[..17], &pose[..]);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_local_to_model() {
+        let skeleton = Rc::new(Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap());
+        let pose = sample("./resource/playback/animation.ozz", 0.3);
+        let root = Mat4::from_translation(glam::Vec3::new(1.0, 2.0, 3.0));
+
+        let expected = Rc::new(RefCell::new(vec![Mat4::IDENTITY; skeleton.num_joints()]));
+        let mut job = LocalToModelJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.set_input(Rc::new(RefCell::new(pose.clone())));
+        job.set_root(&root);
+        job.set_output(expected.clone());
+        job.run().unwrap();
+
+        let output = Rc::new(RefCell::new(vec![Mat4::IDENTITY; skeleton.num_joints()]));
+        let mut job8: LocalToModel8JobRc = LocalToModel8Job::default();
+        job8.set_skeleton(skeleton.clone());
+        job8.set_input(Rc::new(RefCell::new(soa8_from_soa4(&pose))));
+        job8.set_root(&root);
+        job8.set_output(output.clone());
+        assert!(job8.validate());
+        job8.run().unwrap();
+
+        for (idx, (a, b)) in output.borrow().iter().zip(expected.borrow().iter()).enumerate() {
+            assert!(a.abs_diff_eq(*b, 1e-5), "joint={} {} {}", idx, a, b);
+        }
+
+        job8.set_input(Rc::new(RefCell::new(vec![Soa8Transform::IDENTITY; 2])));
+        assert!(!job8.validate());
+        assert!(job8.run().unwrap_err().is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_blending() {
+        let skeleton = Rc::new(Skeleton::from_path("./resource/blend/skeleton.ozz").unwrap());
+        let poses = [
+            sample("./resource/blend/animation1.ozz", 0.4),
+            sample("./resource/blend/animation2.ozz", 0.4),
+            sample("./resource/blend/animation3.ozz", 0.4),
+        ];
+
+        // Weights summing below the threshold mix the rest pose in.
+        for weights in [[0.2, 0.5, 0.3], [1.0, 0.0, 2.0], [0.02, 0.0, 0.03], [0.0, 0.0, 0.0]] {
+            let expected = Rc::new(RefCell::new(vec![SoaTransform::default(); skeleton.num_soa_joints()]));
+            let mut job = BlendingJob::default();
+            job.set_skeleton(skeleton.clone());
+            job.set_output(expected.clone());
+            for (pose, weight) in poses.iter().zip(weights) {
+                let layer = BlendingLayer::with_weight(Rc::new(RefCell::new(pose.clone())), weight);
+                job.layers_mut().push(layer);
+            }
+            job.run().unwrap();
+
+            let output = Rc::new(RefCell::new(vec![
+                Soa8Transform::default();
+                num_soa8_joints(skeleton.num_joints())
+            ]));
+            let mut job8: Blending8JobRc = Blending8Job::default();
+            job8.set_skeleton(skeleton.clone());
+            job8.set_output(output.clone());
+            for (pose, weight) in poses.iter().zip(weights) {
+                let layer = Blending8Layer::with_weight(Rc::new(RefCell::new(soa8_from_soa4(pose))), weight);
+                job8.layers_mut().push(layer);
+            }
+            assert!(job8.validate());
+            job8.run().unwrap();
+
+            let output = soa4_from_soa8(&output.borrow());
+            for idx in 0..skeleton.num_joints() {
+                let a = output[idx / 4].col(idx % 4);
+                let b = expected.borrow()[idx / 4].col(idx % 4);
+                assert!(
+                    a.translation.abs_diff_eq(b.translation, 1e-5),
+                    "joint={} {:?}",
+                    idx,
+                    weights
+                );
+                assert!(a.rotation.abs_diff_eq(b.rotation, 1e-5), "joint={} {:?}", idx, weights);
+                assert!(a.scale.abs_diff_eq(b.scale, 1e-5), "joint={} {:?}", idx, weights);
+            }
+        }
+
+        let mut job8: Blending8JobRc = Blending8Job::default();
+        job8.set_skeleton(skeleton.clone());
+        job8.set_output(Rc::new(RefCell::new(vec![])));
+        assert!(!job8.validate());
+        assert!(job8.run().unwrap_err().is_invalid_job());
+    }
+}
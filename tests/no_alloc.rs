@@ -0,0 +1,50 @@
+use ozz_animation_rs::*;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+// Counts the allocations of each thread, tests run concurrently.
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        return System.alloc(layout);
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        return System.realloc(ptr, layout, new_size);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn allocations() -> usize {
+    return ALLOCATIONS.with(|count| count.get());
+}
+
+#[test]
+fn test_sample_into_no_alloc() {
+    let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+    let mut ctx = SamplingContext::from_animation(&animation);
+    let mut output = vec![SoaTransform::default(); animation.num_soa_tracks()];
+    animation.sample_into(0.0, &mut ctx, &mut output).unwrap();
+
+    let before = allocations();
+    for idx in 0..=100 {
+        let ratio = idx as f32 / 100.0;
+        animation.sample_into(ratio, &mut ctx, &mut output).unwrap();
+    }
+    // Rewinding resets the context, still without allocation.
+    animation.sample_into(0.0, &mut ctx, &mut output).unwrap();
+    assert_eq!(allocations(), before);
+}
@@ -0,0 +1,41 @@
+use ozz_animation_rs::prelude::*;
+use wasm_bindgen_test::*;
+
+// Only the prelude is imported, a full sample and blend must compile and run with it.
+#[test]
+#[wasm_bindgen_test]
+fn test_prelude_sample_and_blend() {
+    let skeleton = Skeleton::from_path("./resource/blend/skeleton.ozz").unwrap();
+    let animation1 = Animation::from_path("./resource/blend/animation1.ozz").unwrap();
+    let animation2 = Animation::from_path("./resource/blend/animation2.ozz").unwrap();
+
+    let mut sample_out1 = vec![SoaTransform::default(); skeleton.num_soa_joints()];
+    let mut sample_job1: SamplingJobRef = SamplingJob::default();
+    sample_job1.set_animation(&animation1);
+    sample_job1.set_context(SamplingContext::from_animation(&animation1));
+    sample_job1.set_output(&mut sample_out1);
+    sample_job1.set_ratio(0.3);
+    sample_job1.run().unwrap();
+
+    let mut sample_out2 = vec![SoaTransform::default(); skeleton.num_soa_joints()];
+    let mut sample_job2: SamplingJobRef = SamplingJob::default();
+    sample_job2.set_animation(&animation2);
+    sample_job2.set_context(SamplingContext::from_animation(&animation2));
+    sample_job2.set_output(&mut sample_out2);
+    sample_job2.set_ratio(0.3);
+    sample_job2.run().unwrap();
+
+    let mut blending_out = vec![SoaTransform::default(); skeleton.num_soa_joints()];
+    let mut blending_job: BlendingJobRef = BlendingJob::default();
+    blending_job.set_skeleton(&skeleton);
+    blending_job.set_output(&mut blending_out);
+    let layers = blending_job.layers_mut();
+    layers.push(BlendingLayer::with_weight(&sample_out1, 1.0));
+    layers.push(BlendingLayer::with_weight(&sample_out2, 0.0));
+    blending_job.run().unwrap();
+    assert!(blending_job.output().unwrap().approx_eq(sample_out1.as_slice(), 1e-6));
+
+    blending_job.layers_mut()[1].weight = 1.0;
+    blending_job.run().unwrap();
+    assert!(!blending_job.output().unwrap().approx_eq(sample_out1.as_slice(), 1e-6));
+}